@@ -55,6 +55,9 @@ async fn main() {
     let (handle, jh) = pathfinder_lib::cairo::ext_py::start(
         db_file.into(),
         std::num::NonZeroUsize::new(1).unwrap(),
+        std::time::Duration::from_secs(60),
+        std::num::NonZeroUsize::new(16).unwrap(),
+        pathfinder_lib::cairo::ext_py::Framing::default(),
         async move {
             // we expect the channel getting closed, but it doesn't really matter, just any
             // awaitable signal will work
@@ -85,7 +88,7 @@ async fn main() {
             .map_err(Error::from);
 
         let local = handle
-            .call(args.request, args.block_hash)
+            .call(args.request, args.block_hash, vec![])
             .map_err(Error::from);
 
         let (local, seq) = tokio::join!(local, seq);
@@ -5,7 +5,8 @@ use pathfinder_lib::{
         self,
         transport::{EthereumTransport, HttpTransport},
     },
-    rpc, sequencer, state,
+    rpc::{self, execution::ExecutionPolicy},
+    sequencer, state,
     storage::Storage,
 };
 use std::sync::Arc;
@@ -53,22 +54,35 @@ async fn main() -> anyhow::Result<()> {
         sync_state.clone(),
         state::l1::sync,
         state::l2::sync,
+        config.block_validation_mode,
     ));
 
-    // TODO: the error could be recovered, but currently it's required for startup. There should
-    // not be other reason for the start to fail than python script not firing up.
-    let (call_handle, cairo_handle) = cairo::ext_py::start(
-        storage.path().into(),
-        std::num::NonZeroUsize::new(2).unwrap(),
-        futures::future::pending(),
-    )
-    .await
-    .context(
-        "Creating python process for call handling. Have you setup our Python dependencies?",
-    )?;
-
-    let api = rpc::api::RpcApi::new(storage, sequencer, network_chain, sync_state)
-        .with_call_handling(call_handle);
+    let api = rpc::api::RpcApi::new(storage.clone(), sequencer, network_chain, sync_state)
+        .with_execution_policy(config.execution_policy)
+        .with_call_cache_capacity(config.call_cache_capacity);
+
+    let (api, cairo_handle) = match config.execution_policy {
+        // no need for the python processes if they would never be used
+        ExecutionPolicy::SequencerOnly => (api, tokio::spawn(futures::future::pending())),
+        ExecutionPolicy::LocalOnly | ExecutionPolicy::LocalWithSequencerFallback => {
+            // TODO: the error could be recovered, but currently it's required for startup. There
+            // should not be other reason for the start to fail than python script not firing up.
+            let (call_handle, cairo_handle) = cairo::ext_py::start(
+                storage.path().into(),
+                config.python.subprocesses,
+                config.python.call_timeout,
+                config.python.queue_depth,
+                config.python.framing,
+                futures::future::pending(),
+            )
+            .await
+            .context(
+                "Creating python process for call handling. Have you setup our Python dependencies?",
+            )?;
+
+            (api.with_call_handling(call_handle), cairo_handle)
+        }
+    };
 
     let (rpc_handle, local_addr) = rpc::run_server(config.http_rpc_addr, api)
         .await
@@ -1,7 +1,8 @@
 //! External python process pool for execute calls.
 //!
 //! The python processes are executing `$REPO_ROOT/py/src/call.py` and communicate over by sending
-//! and receiving json + `'\n'`. Main entry point is the [`service::start`] which manages running
+//! and receiving json in the [`Framing`] agreed on in a versioned handshake at startup, see
+//! [`sub_process`]. Main entry point is the [`service::start`] which manages running
 //! given number of N processes. The python script uses sqlite to read pathfinder's database, which
 //! should not cause issues in WAL mode.
 //!
@@ -10,20 +11,34 @@
 //! latter selectes "when" to call it on the history. None of the block or tags are resolved over
 //! at rust side, because transactions cannot carry over between processes.
 //!
+//! Similarly [`Handle::estimate_fee`] runs the [`Call`] as an invoke transaction, and returns the
+//! fee it would cost with the gas price of the selected block. [`Handle::simulate_transaction`]
+//! runs it the same way, but returns the full execution trace.
+//!
+//! Each command has to complete within the timeout given to [`service::start`], otherwise the
+//! executing subprocess is killed and a new one is spawned in its place. Commands are rejected
+//! with [`CallFailure::QueueFull`] when the queue in front of the executors is full.
+//!
 //! While the python script does attempt to resolve "latest", it probably needs fixing. To make it
 //! support "pending", a feature needs to be added which flushes the "open" pending to a
 //! global_state, and after that, calls can be made to it's `block_hash` for which we probably need
 //! to add an alternative way to use a hash directly rather as a root than assume it's a block hash.
 
-use crate::core::CallResultValue;
-use crate::rpc::types::{request::Call, BlockHashOrTag};
+use crate::core::{CallResultValue, CallSignatureElem, Fee, TransactionVersion};
+use crate::rpc::types::{
+    reply::{FeeEstimate, TransactionTrace},
+    request::{Call, StateOverride},
+    BlockHashOrTag,
+};
 use std::sync::Arc;
 use tokio::sync::{mpsc, oneshot, Mutex};
 
 mod de;
-use de::{ErrorKind, Status, Timings};
+use de::{ErrorKind, OutputValue, Status, Timings};
 
 mod ser;
+use ser::Verb;
+
 mod sub_process;
 
 mod service;
@@ -37,20 +52,134 @@ pub struct Handle {
 
 impl Handle {
     /// Execute the given call on the python cairo-lang executors.
+    ///
+    /// The `overrides` are applied on top of the state at `at_block` for this call only.
     pub async fn call(
         &self,
         call: Call,
         at_block: BlockHashOrTag,
+        overrides: Vec<StateOverride>,
     ) -> Result<Vec<CallResultValue>, CallFailure> {
         use tracing::field::Empty;
         let (tx, rx) = oneshot::channel();
 
         let continued_span = tracing::info_span!("ext_py_call", pid = Empty);
 
+        self.submit(
+            Command::Call {
+                call,
+                at_block,
+                overrides,
+                response: tx,
+            },
+            continued_span,
+            rx,
+        )
+        .await
+    }
+
+    /// Estimate the fee of the given call as an invoke transaction on the python cairo-lang
+    /// executors.
+    pub async fn estimate_fee(
+        &self,
+        call: Call,
+        signature: Vec<CallSignatureElem>,
+        max_fee: Fee,
+        version: TransactionVersion,
+        at_block: BlockHashOrTag,
+    ) -> Result<FeeEstimate, CallFailure> {
+        use tracing::field::Empty;
+        let (tx, rx) = oneshot::channel();
+
+        let continued_span = tracing::info_span!("ext_py_est_fee", pid = Empty);
+
+        self.submit(
+            Command::EstimateFee {
+                call,
+                signature,
+                max_fee,
+                version,
+                at_block,
+                response: tx,
+            },
+            continued_span,
+            rx,
+        )
+        .await
+    }
+
+    /// Run the given call as an invoke transaction on the python cairo-lang executors, returning
+    /// the execution trace.
+    pub async fn simulate_transaction(
+        &self,
+        call: Call,
+        signature: Vec<CallSignatureElem>,
+        max_fee: Fee,
+        version: TransactionVersion,
+        at_block: BlockHashOrTag,
+    ) -> Result<TransactionTrace, CallFailure> {
+        use tracing::field::Empty;
+        let (tx, rx) = oneshot::channel();
+
+        let continued_span = tracing::info_span!("ext_py_simulate", pid = Empty);
+
+        self.submit(
+            Command::Simulate {
+                call,
+                signature,
+                max_fee,
+                version,
+                at_block,
+                response: tx,
+            },
+            continued_span,
+            rx,
+        )
+        .await
+    }
+
+    /// Execute all of the given calls on the same block with a single python cairo-lang
+    /// executor.
+    ///
+    /// The outer error is for failures which concern all of the calls, such as the block not
+    /// being found.
+    pub async fn multicall(&self, calls: Vec<Call>, at_block: BlockHashOrTag) -> MulticallOutcome {
+        use tracing::field::Empty;
+        let (tx, rx) = oneshot::channel();
+
+        let continued_span = tracing::info_span!("ext_py_multicall", pid = Empty);
+
+        self.submit(
+            Command::Multicall {
+                calls,
+                at_block,
+                response: tx,
+            },
+            continued_span,
+            rx,
+        )
+        .await
+    }
+
+    /// Queues the command for the executors and waits for the response.
+    ///
+    /// Fails right away with [`CallFailure::QueueFull`] instead of waiting for room in the queue.
+    /// Dropping the returned future, for example when the RPC client disconnects, cancels the
+    /// command: it is skipped if still queued, and the executing subprocess is killed otherwise.
+    async fn submit<T>(
+        &self,
+        command: Command,
+        span: tracing::Span,
+        rx: oneshot::Receiver<Result<T, CallFailure>>,
+    ) -> Result<T, CallFailure> {
+        use mpsc::error::TrySendError;
+
         self.command_tx
-            .send(((call, at_block, tx), continued_span))
-            .await
-            .map_err(|_| CallFailure::Shutdown)?;
+            .try_send((command, span))
+            .map_err(|e| match e {
+                TrySendError::Full(_) => CallFailure::QueueFull,
+                TrySendError::Closed(_) => CallFailure::Shutdown,
+            })?;
 
         match rx.await {
             Ok(x) => x,
@@ -70,6 +199,10 @@ pub enum CallFailure {
     ExecutionFailed(String),
     /// Internal, opaque-ish failure reason, none of them signal an issue with the call.
     Internal(&'static str),
+    /// The execution did not complete within the configured time; the subprocess was killed.
+    Timeout,
+    /// There were too many commands waiting for an executor already.
+    QueueFull,
     /// Channel related issue or shutting down.
     Shutdown,
 }
@@ -90,12 +223,155 @@ impl From<ErrorKind> for CallFailure {
 /// to be.
 type SharedReceiver<T> = Arc<Mutex<mpsc::Receiver<T>>>;
 
-/// Alias for the type used to transfer commands over to executors.
-type Command = (
-    Call,
-    BlockHashOrTag,
-    oneshot::Sender<Result<Vec<CallResultValue>, CallFailure>>,
-);
+/// The commands executors process, along with the channel to send the response over.
+#[derive(Debug)]
+enum Command {
+    Call {
+        call: Call,
+        at_block: BlockHashOrTag,
+        overrides: Vec<StateOverride>,
+        response: oneshot::Sender<Result<Vec<CallResultValue>, CallFailure>>,
+    },
+    EstimateFee {
+        call: Call,
+        signature: Vec<CallSignatureElem>,
+        max_fee: Fee,
+        version: TransactionVersion,
+        at_block: BlockHashOrTag,
+        response: oneshot::Sender<Result<FeeEstimate, CallFailure>>,
+    },
+    Simulate {
+        call: Call,
+        signature: Vec<CallSignatureElem>,
+        max_fee: Fee,
+        version: TransactionVersion,
+        at_block: BlockHashOrTag,
+        response: oneshot::Sender<Result<TransactionTrace, CallFailure>>,
+    },
+    Multicall {
+        calls: Vec<Call>,
+        at_block: BlockHashOrTag,
+        response: oneshot::Sender<MulticallOutcome>,
+    },
+}
+
+/// The outcome of [`Handle::multicall`]; the outer error concerns all of the calls.
+type MulticallOutcome = Result<Vec<Result<Vec<CallResultValue>, CallFailure>>, CallFailure>;
+
+impl Command {
+    /// What the python loop should do with the command.
+    fn verb(&self) -> Verb {
+        match self {
+            Command::Call { .. } => Verb::Call,
+            Command::EstimateFee { .. } => Verb::EstimateFee,
+            Command::Simulate { .. } => Verb::Simulate,
+            Command::Multicall { .. } => Verb::Multicall,
+        }
+    }
+
+    /// True if the caller has already stopped waiting for the response.
+    fn is_closed(&self) -> bool {
+        match self {
+            Command::Call { response, .. } => response.is_closed(),
+            Command::EstimateFee { response, .. } => response.is_closed(),
+            Command::Simulate { response, .. } => response.is_closed(),
+            Command::Multicall { response, .. } => response.is_closed(),
+        }
+    }
+
+    /// Completes when the caller has stopped waiting for the response.
+    async fn closed(&mut self) {
+        match self {
+            Command::Call { response, .. } => response.closed().await,
+            Command::EstimateFee { response, .. } => response.closed().await,
+            Command::Simulate { response, .. } => response.closed().await,
+            Command::Multicall { response, .. } => response.closed().await,
+        }
+    }
+
+    /// Sends the output or failure to the caller, if they are still waiting for it.
+    ///
+    /// Output not matching the command is turned into [`CallFailure::Internal`].
+    fn send_response(self, output: Result<OutputValue, CallFailure>) {
+        match self {
+            Command::Call { response, .. } => {
+                let _ = response.send(output.and_then(|x| match x {
+                    OutputValue::Call(x) => Ok(x),
+                    _ => Err(CallFailure::Internal("Unexpected output")),
+                }));
+            }
+            Command::EstimateFee { response, .. } => {
+                let _ = response.send(output.and_then(|x| match x {
+                    OutputValue::Fee(x) => Ok(x),
+                    _ => Err(CallFailure::Internal("Unexpected output")),
+                }));
+            }
+            Command::Simulate { response, .. } => {
+                let _ = response.send(output.and_then(|x| match x {
+                    OutputValue::Trace(x) => Ok(*x),
+                    _ => Err(CallFailure::Internal("Unexpected output")),
+                }));
+            }
+            Command::Multicall { response, .. } => {
+                let _ = response.send(output.and_then(|x| match x {
+                    OutputValue::Multicall(x) => {
+                        Ok(x.into_iter().map(|x| x.into_result()).collect())
+                    }
+                    _ => Err(CallFailure::Internal("Unexpected output")),
+                }));
+            }
+        }
+    }
+
+    /// Sends the failure to the caller, if they are still waiting for it.
+    fn fail(self, failure: CallFailure) {
+        self.send_response(Err(failure))
+    }
+}
+
+/// How the json messages are delimited on the pipes between pathfinder and the python
+/// subprocesses.
+///
+/// The python script announces the framings it supports during the startup handshake, and
+/// [`Framing::JsonLines`] is used when it does not support the preferred one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Framing {
+    /// Each message is prefixed with its length as a big-endian `u32`.
+    LengthPrefixed,
+    /// Each message is terminated with `'\n'`.
+    JsonLines,
+}
+
+impl Framing {
+    /// The name used in the handshake and in the configuration.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Framing::LengthPrefixed => "length-prefixed",
+            Framing::JsonLines => "json-lines",
+        }
+    }
+}
+
+impl Default for Framing {
+    fn default() -> Self {
+        Framing::LengthPrefixed
+    }
+}
+
+impl std::str::FromStr for Framing {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "length-prefixed" => Ok(Framing::LengthPrefixed),
+            "json-lines" => Ok(Framing::JsonLines),
+            other => Err(anyhow::anyhow!(
+                "Unknown framing {:?}, expected one of: length-prefixed, json-lines",
+                other
+            )),
+        }
+    }
+}
 
 /// Informational events from python process executors.
 #[derive(Debug)]
@@ -112,6 +388,7 @@ enum SubprocessExitReason {
     Shutdown,
     Death,
     Cancellation,
+    Timeout,
 }
 
 /// Errors which can happen during an RPC alike round with the subprocess.
@@ -137,6 +414,131 @@ mod tests {
     use std::path::PathBuf;
     use tokio::sync::oneshot;
 
+    fn dummy_call() -> crate::rpc::types::request::Call {
+        crate::rpc::types::request::Call {
+            contract_address: crate::core::ContractAddress(StarkHash::from_hex_str("1").unwrap()),
+            calldata: vec![],
+            entry_point_selector: crate::core::EntryPoint(StarkHash::from_hex_str("2").unwrap()),
+        }
+    }
+
+    #[tokio::test]
+    async fn full_queue_rejects_commands() {
+        use super::{BlockHashOrTag, CallFailure, Handle};
+        use crate::rpc::types::Tag;
+
+        let (command_tx, mut command_rx) = tokio::sync::mpsc::channel(1);
+        let handle = Handle { command_tx };
+
+        let queued = tokio::spawn({
+            let handle = handle.clone();
+            async move {
+                handle
+                    .call(dummy_call(), BlockHashOrTag::Tag(Tag::Latest), vec![])
+                    .await
+            }
+        });
+
+        // wait for the first command to take the only place in the queue
+        while handle.command_tx.capacity() != 0 {
+            tokio::task::yield_now().await;
+        }
+
+        let res = handle
+            .call(dummy_call(), BlockHashOrTag::Tag(Tag::Latest), vec![])
+            .await;
+        assert!(matches!(res, Err(CallFailure::QueueFull)), "{res:?}");
+
+        let (command, _span) = command_rx.recv().await.unwrap();
+        command.fail(CallFailure::NoSuchBlock);
+        let res = queued.await.unwrap();
+        assert!(matches!(res, Err(CallFailure::NoSuchBlock)), "{res:?}");
+    }
+
+    #[tokio::test]
+    async fn dropped_caller_closes_command() {
+        use super::{BlockHashOrTag, Handle};
+        use crate::rpc::types::Tag;
+        use futures::FutureExt;
+
+        let (command_tx, mut command_rx) = tokio::sync::mpsc::channel(1);
+        let handle = Handle { command_tx };
+
+        {
+            let call = handle.call(dummy_call(), BlockHashOrTag::Tag(Tag::Latest), vec![]);
+            tokio::pin!(call);
+            // poll once to get the command queued, then drop it like a disconnected client would
+            assert!((&mut call).now_or_never().is_none());
+        }
+
+        let (mut command, _span) = command_rx.recv().await.unwrap();
+        assert!(command.is_closed());
+        // completes right away, which is what cancels the execution in the subprocess
+        command.closed().await;
+    }
+
+    #[tokio::test]
+    async fn stalled_subprocess_times_out_and_is_replaced() {
+        use super::{BlockHashOrTag, CallFailure};
+        use crate::rpc::types::Tag;
+
+        // speaks the protocol, but the first process stalls on the first command it gets; the
+        // marker file records the pid of the stalled process
+        const STALLING_SCRIPT: &str = r#"
+import os, sys, time
+print("pathfinder-ext-py 1 json-lines", flush=True)
+assert sys.stdin.readline() == "framing json-lines\n"
+print("ok json-lines", flush=True)
+for line in sys.stdin:
+    try:
+        with open(sys.argv[1], "x") as marker:
+            marker.write("0x%x" % os.getpid())
+    except FileExistsError:
+        print('{"status":"ok","output":["0x%x"]}' % os.getpid(), flush=True)
+        continue
+    time.sleep(3600)
+"#;
+
+        let dir = tempfile::tempdir().unwrap();
+        let marker = dir.path().join("stalled");
+        let (stop_tx, stop_rx) = oneshot::channel::<()>();
+
+        let (handle, jh) = super::service::start_with_script(
+            STALLING_SCRIPT,
+            marker.clone(),
+            std::num::NonZeroUsize::new(1).unwrap(),
+            std::time::Duration::from_millis(500),
+            std::num::NonZeroUsize::new(2).unwrap(),
+            super::Framing::JsonLines,
+            async move {
+                let _ = stop_rx.await;
+            },
+        )
+        .await
+        .unwrap();
+
+        let res = handle
+            .call(dummy_call(), BlockHashOrTag::Tag(Tag::Latest), vec![])
+            .await;
+        assert!(matches!(res, Err(CallFailure::Timeout)), "{res:?}");
+
+        let stalled_pid = std::fs::read_to_string(&marker).unwrap();
+
+        // the stalled process is killed and a new one answers the next call
+        let res = tokio::time::timeout(
+            std::time::Duration::from_secs(30),
+            handle.call(dummy_call(), BlockHashOrTag::Tag(Tag::Latest), vec![]),
+        )
+        .await
+        .expect("the stalled subprocess should had been replaced")
+        .unwrap();
+        assert_eq!(res.len(), 1);
+        assert_ne!(res[0].0, StarkHash::from_hex_str(&stalled_pid).unwrap());
+
+        drop(stop_tx);
+        jh.await.unwrap();
+    }
+
     #[test_log::test(tokio::test)]
     #[ignore]
     async fn start_with_wrong_database_schema_fails() {
@@ -155,7 +557,10 @@ mod tests {
         let (_shutdown_tx, shutdown_rx) = tokio::sync::broadcast::channel(1);
 
         let err = launch_python(
+            super::sub_process::PYTHON_SCRIPT_SOURCE,
             db_file.path().into(),
+            std::time::Duration::from_secs(60),
+            super::Framing::default(),
             work_rx.into(),
             status_tx,
             shutdown_rx,
@@ -188,6 +593,9 @@ mod tests {
         let (handle, jh) = super::start(
             PathBuf::from(db_file.path()),
             std::num::NonZeroUsize::new(2).unwrap(),
+            std::time::Duration::from_secs(60),
+            std::num::NonZeroUsize::new(16).unwrap(),
+            super::Framing::default(),
             async move {
                 let _ = shutdown_rx.await;
             },
@@ -217,7 +625,8 @@ mod tests {
                             },
                             super::BlockHashOrTag::Hash(crate::core::StarknetBlockHash(
                                 StarkHash::from_be_slice(&b"some blockhash somewhere"[..]).unwrap(),
-                            ))
+                            )),
+                            vec![],
                         ).await.unwrap();
                     }
                 })
@@ -233,6 +642,68 @@ mod tests {
         jh.await.unwrap();
     }
 
+    #[test_log::test(tokio::test)]
+    #[ignore] // these tests require that you've entered into python venv
+    async fn estimate_fee_like_in_python() {
+        let db_file = tempfile::NamedTempFile::new().unwrap();
+
+        let s = crate::storage::Storage::migrate(PathBuf::from(db_file.path())).unwrap();
+
+        let mut conn = s.connection().unwrap();
+        conn.execute("PRAGMA foreign_keys = off", []).unwrap();
+
+        let tx = conn.transaction().unwrap();
+
+        fill_example_state(&tx);
+
+        tx.commit().unwrap();
+
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+
+        let (handle, jh) = super::start(
+            PathBuf::from(db_file.path()),
+            std::num::NonZeroUsize::new(1).unwrap(),
+            std::time::Duration::from_secs(60),
+            std::num::NonZeroUsize::new(16).unwrap(),
+            super::Framing::default(),
+            async move {
+                let _ = shutdown_rx.await;
+            },
+        )
+        .await
+        .unwrap();
+
+        let estimate = handle
+            .estimate_fee(
+                super::Call {
+                    contract_address: crate::core::ContractAddress(
+                        StarkHash::from_hex_str(
+                            "057dde83c18c0efe7123c36a52d704cf27d5c38cdf0b1e1edc3b0dae3ee4e374",
+                        )
+                        .unwrap(),
+                    ),
+                    calldata: vec![crate::core::CallParam(
+                        StarkHash::from_hex_str("84").unwrap(),
+                    )],
+                    entry_point_selector: crate::core::EntryPoint::hashed(&b"get_value"[..]),
+                },
+                vec![],
+                crate::core::Fee(web3::types::H128::zero()),
+                crate::core::TransactionVersion(web3::types::H256::zero()),
+                super::BlockHashOrTag::Tag(crate::rpc::types::Tag::Latest),
+            )
+            .await
+            .unwrap();
+
+        // the example block has no gas price, so the fee is zero as well
+        assert_eq!(estimate.gas_price, web3::types::H256::zero());
+        assert_eq!(estimate.overall_fee, web3::types::H256::zero());
+
+        shutdown_tx.send(()).unwrap();
+
+        jh.await.unwrap();
+    }
+
     fn fill_example_state(tx: &rusqlite::Transaction) {
         let contract_definition = zstd::decode_all(std::io::Cursor::new(include_bytes!(
             "../../fixtures/contract_definition.json.zst"
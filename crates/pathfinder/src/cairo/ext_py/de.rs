@@ -2,6 +2,7 @@
 
 use super::{CallFailure, SubprocessError};
 use crate::core::CallResultValue;
+use crate::rpc::types::reply::{FeeEstimate, TransactionTrace};
 
 /// The python loop currently responds with these four possibilities. An enum would be more
 /// appropriate.
@@ -22,15 +23,50 @@ pub(crate) struct ChildResponse<'a> {
     #[serde(default)]
     timings: Timings,
     /// The real output from the contract when `status` is [`Status::Ok`].
-    #[serde(default)]
-    output: Vec<CallResultValue>,
+    output: Option<OutputValue>,
+}
+
+/// The output of a successful command; depends on the [`super::ser::Verb`] of the command.
+#[derive(serde::Deserialize, Debug)]
+#[serde(untagged)]
+pub(crate) enum OutputValue {
+    /// Retdata of a call.
+    Call(Vec<CallResultValue>),
+    /// Fee estimate of an invoke.
+    Fee(FeeEstimate),
+    /// Execution trace of an invoke.
+    Trace(Box<TransactionTrace>),
+    /// Outcomes of the calls of a multicall.
+    Multicall(Vec<MulticallOutput>),
+}
+
+/// The outcome of a single call of a multicall, similar to [`ChildResponse`] without timings.
+#[derive(serde::Deserialize, Debug)]
+pub(crate) struct MulticallOutput {
+    status: Status,
+    exception: Option<String>,
+    kind: Option<ErrorKind>,
+    output: Option<Vec<CallResultValue>>,
+}
+
+impl MulticallOutput {
+    pub fn into_result(self) -> Result<Vec<CallResultValue>, CallFailure> {
+        match (self.status, self.kind, self.exception, self.output) {
+            (Status::Ok, None, None, Some(output)) => Ok(output),
+            (Status::Error, Some(kind), None, None) => Err(CallFailure::from(kind)),
+            (Status::Failed, None, Some(exception), None) => {
+                Err(CallFailure::ExecutionFailed(exception))
+            }
+            _ => Err(CallFailure::Internal("Invalid json received")),
+        }
+    }
 }
 
 impl<'a> ChildResponse<'a> {
     pub(super) fn refine(mut self) -> Result<RefinedChildResponse<'a>, SubprocessError> {
         match (&self.status, &mut self.kind, &mut self.exception) {
-            (Status::Ok, None, None) => Ok(RefinedChildResponse {
-                status: RefinedStatus::Ok(self.output),
+            (Status::Ok, None, None) if self.output.is_some() => Ok(RefinedChildResponse {
+                status: RefinedStatus::Ok(self.output.take().unwrap()),
                 timings: self.timings,
             }),
             (Status::Error, x @ Some(_), None) => Ok(RefinedChildResponse {
@@ -48,13 +84,7 @@ impl<'a> ChildResponse<'a> {
 }
 
 impl RefinedChildResponse<'_> {
-    pub fn into_messages(
-        self,
-    ) -> (
-        Option<Timings>,
-        Status,
-        Result<Vec<CallResultValue>, CallFailure>,
-    ) {
+    pub fn into_messages(self) -> (Option<Timings>, Status, Result<OutputValue, CallFailure>) {
         match self {
             RefinedChildResponse {
                 timings,
@@ -121,7 +151,7 @@ pub struct RefinedChildResponse<'a> {
 
 /// More sensible alternative to [`Status`].
 pub enum RefinedStatus<'a> {
-    Ok(Vec<CallResultValue>),
+    Ok(OutputValue),
     Error(ErrorKind),
     Failed(std::borrow::Cow<'a, str>),
 }
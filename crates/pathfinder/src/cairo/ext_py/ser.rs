@@ -1,13 +1,42 @@
 //! The json serializable types
 
-use crate::core::{CallParam, ContractAddress, EntryPoint};
-use crate::rpc::types::BlockHashOrTag;
+use crate::core::{CallSignatureElem, Fee, TransactionVersion};
+use crate::rpc::types::{
+    request::{Call, StateOverride},
+    BlockHashOrTag,
+};
 
 /// The command we send to the python loop.
+#[serde_with::skip_serializing_none]
 #[derive(serde::Serialize, Debug)]
 pub struct ChildCommand<'a> {
-    pub contract_address: &'a ContractAddress,
-    pub calldata: &'a [CallParam],
-    pub entry_point_selector: &'a EntryPoint,
+    pub command: Verb,
+    /// The contract, entry point and calldata; flattened into the command. Absent for
+    /// [`Verb::Multicall`].
+    #[serde(flatten)]
+    pub call: Option<&'a Call>,
     pub at_block: &'a BlockHashOrTag,
+    /// The calls of [`Verb::Multicall`].
+    pub calls: Option<&'a [Call]>,
+    pub signature: Option<&'a [CallSignatureElem]>,
+    pub max_fee: Option<&'a Fee>,
+    pub version: Option<&'a TransactionVersion>,
+    pub overrides: Option<&'a [StateOverride]>,
+}
+
+/// Determines what the python loop does with the [`ChildCommand`].
+#[derive(serde::Serialize, Debug)]
+pub enum Verb {
+    /// Call the contract and return the retdata.
+    #[serde(rename = "call")]
+    Call,
+    /// Run the call as an invoke transaction, and return the fee it would cost.
+    #[serde(rename = "estimate_fee")]
+    EstimateFee,
+    /// Run the call as an invoke transaction, and return the execution trace.
+    #[serde(rename = "simulate")]
+    Simulate,
+    /// Execute all of the calls on the same block, and return the retdata or error of each.
+    #[serde(rename = "multicall")]
+    Multicall,
 }
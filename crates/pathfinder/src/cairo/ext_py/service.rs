@@ -1,6 +1,9 @@
 //! Starting and maintaining processes, and the main entry point
 
-use super::{sub_process::launch_python, Command, Handle, SharedReceiver, SubProcessEvent};
+use super::{
+    sub_process::{launch_python, PYTHON_SCRIPT_SOURCE},
+    Command, Framing, Handle, SharedReceiver, SubProcessEvent,
+};
 use anyhow::Context;
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -19,18 +22,47 @@ use tracing::{info, trace, warn};
 /// - By installing Python dependencies in a way that the _global_ `python3` interpreter can
 /// import them.
 ///
+/// Each command must complete within `call_timeout`, and at most `queue_depth` commands can be
+/// waiting for a free sub-process before new ones are rejected. Messages are exchanged in the
+/// preferred `framing` when the sub-processes support it.
+///
 /// Returns an error if executing calls in a sub-process is not supported.
 #[tracing::instrument(name = "ext_py", skip_all, fields(%count))]
 pub async fn start(
     database_path: PathBuf,
     count: std::num::NonZeroUsize,
+    call_timeout: std::time::Duration,
+    queue_depth: std::num::NonZeroUsize,
+    framing: Framing,
+    stop_flag: impl std::future::Future<Output = ()> + Send + 'static,
+) -> anyhow::Result<(Handle, tokio::task::JoinHandle<()>)> {
+    start_with_script(
+        PYTHON_SCRIPT_SOURCE,
+        database_path,
+        count,
+        call_timeout,
+        queue_depth,
+        framing,
+        stop_flag,
+    )
+    .await
+}
+
+/// Same as [`start`], but the sub-processes run the given python `script`.
+pub(super) async fn start_with_script(
+    script: &'static str,
+    database_path: PathBuf,
+    count: std::num::NonZeroUsize,
+    call_timeout: std::time::Duration,
+    queue_depth: std::num::NonZeroUsize,
+    framing: Framing,
     stop_flag: impl std::future::Future<Output = ()> + Send + 'static,
 ) -> anyhow::Result<(Handle, tokio::task::JoinHandle<()>)> {
     use futures::stream::StreamExt;
 
-    // channel sizes are conservative but probably enough for many workers; should investigate mpmc
-    // if the lock overhead on command_rx before making these deeper.
-    let (command_tx, command_rx) = mpsc::channel(1);
+    // the command channel is the queue in front of the workers, `Handle` rejects commands once
+    // it's full. should investigate mpmc if the lock overhead on command_rx becomes an issue.
+    let (command_tx, command_rx) = mpsc::channel(queue_depth.get());
     let (status_tx, mut status_rx) = mpsc::channel(1);
     // this will never need to become deeper
     let (child_shutdown_tx, _) = broadcast::channel(1);
@@ -41,7 +73,10 @@ pub async fn start(
 
     let jh = tokio::task::spawn(
         launch_python(
+            script,
             database_path.clone(),
+            call_timeout,
+            framing,
             Arc::clone(&command_rx),
             status_tx.clone(),
             child_shutdown_tx.subscribe(),
@@ -149,7 +184,10 @@ pub async fn start(
                 if spawn {
                     let jh = tokio::task::spawn(
                         launch_python(
+                            script,
                             database_path.clone(),
+                            call_timeout,
+                            framing,
                             Arc::clone(&command_rx),
                             status_tx.clone(),
                             child_shutdown_tx.subscribe(),
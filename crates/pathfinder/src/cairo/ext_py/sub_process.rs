@@ -3,11 +3,14 @@
 use super::{
     de::{ChildResponse, RefinedChildResponse, Status, Timings},
     ser::ChildCommand,
-    CallFailure, Command, SharedReceiver, SubProcessEvent, SubprocessError, SubprocessExitReason,
+    CallFailure, Command, Framing, SharedReceiver, SubProcessEvent, SubprocessError,
+    SubprocessExitReason,
 };
 use anyhow::Context;
 use std::{io::Write, path::PathBuf};
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::io::{
+    AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader,
+};
 use tokio::process::{Child, ChildStdin, ChildStdout};
 use tokio::sync::{broadcast, mpsc};
 use tracing::{debug, error, info, trace, warn, Instrument};
@@ -18,21 +21,27 @@ use tracing::{debug, error, info, trace, warn, Instrument};
 /// similarly to spawning this as a task usually handled.
 ///
 /// Launching happens in two stages, similar to the python process. Initially we only launch, then
-/// read `"ready\n"` from the subprocess and after that enter the loop where we contend for the
+/// [`handshake`] with the subprocess and after that enter the loop where we contend for the
 /// commands.
+///
+/// The `script` is the source of the python script to run, usually [`PYTHON_SCRIPT_SOURCE`].
 #[tracing::instrument(name = "subproc", skip_all, fields(pid))]
 pub(super) async fn launch_python(
+    script: &'static str,
     database_path: PathBuf,
+    call_timeout: std::time::Duration,
+    framing: Framing,
     commands: SharedReceiver<(Command, tracing::Span)>,
     status_updates: mpsc::Sender<SubProcessEvent>,
     mut shutdown_rx: broadcast::Receiver<()>,
 ) -> anyhow::Result<(u32, Option<std::process::ExitStatus>, SubprocessExitReason)> {
-    let (mut child, pid, mut stdin, mut stdout, mut buffer) = match spawn(database_path).await {
-        Ok(tuple) => tuple,
-        Err(e) => {
-            return Err(e.context("Failed to start python subprocess"));
-        }
-    };
+    let (mut child, pid, mut stdin, mut stdout, mut buffer, framing) =
+        match spawn(script, database_path, framing).await {
+            Ok(tuple) => tuple,
+            Err(e) => {
+                return Err(e.context("Failed to start python subprocess"));
+            }
+        };
 
     if status_updates
         .send(SubProcessEvent::ProcessLaunched(pid))
@@ -75,7 +84,7 @@ pub(super) async fn launch_python(
             },
         };
 
-        if command.2.is_closed() {
+        if command.is_closed() {
             // quickly loadshed, as the caller has already left.
             continue;
         }
@@ -85,6 +94,8 @@ pub(super) async fn launch_python(
         let (timings, status) = {
             let op = process(
                 command,
+                call_timeout,
+                framing,
                 &mut command_buffer,
                 &mut stdin,
                 &mut stdout,
@@ -160,16 +171,31 @@ pub(super) async fn launch_python(
     Ok((pid, exit_status, exit_reason))
 }
 
-const PYTHON_SCRIPT_SOURCE: &str = include_str!("../../../../../py/src/call.py");
+pub(super) const PYTHON_SCRIPT_SOURCE: &str = include_str!("../../../../../py/src/call.py");
+
+/// Version of the protocol spoken with `call.py`, has to match the `PROTOCOL_VERSION` there.
+const PROTOCOL_VERSION: u32 = 1;
+
+/// Responses longer than this are assumed to be garbage, for example from going out of sync.
+const MAX_FRAME_LENGTH: usize = 256 * 1024 * 1024;
 
 async fn spawn(
+    script: &str,
     database_path: PathBuf,
-) -> anyhow::Result<(Child, u32, ChildStdin, BufReader<ChildStdout>, String)> {
+    framing: Framing,
+) -> anyhow::Result<(
+    Child,
+    u32,
+    ChildStdin,
+    BufReader<ChildStdout>,
+    String,
+    Framing,
+)> {
     let script_file = tempfile::NamedTempFile::new()
         .context("Failed to create temporary file for Python script")?;
     script_file
         .as_file()
-        .write_all(PYTHON_SCRIPT_SOURCE.as_bytes())
+        .write_all(script.as_bytes())
         .context("Failed to write temporary file for Python script")?;
 
     // FIXME: use choom, add something over /proc/self/oom_score_adj ?
@@ -195,7 +221,7 @@ async fn spawn(
         span.record("pid", &pid);
     }
 
-    let stdin = child.stdin.take().expect("stdin was piped");
+    let mut stdin = child.stdin.take().expect("stdin was piped");
     let stdout = child.stdout.take().expect("stdout was piped");
 
     // spawn the stderr out, just forget it it will die down once the process has been torn down
@@ -230,20 +256,128 @@ async fn spawn(
 
     // reasons for this part to error out:
     // - invalid schema version
+    // - script of a different version
     // - some other pythonic thing happens, for example, no call.py found
-    let _read = stdout
-        .read_line(&mut buffer)
+    let (framing, pedersen) = handshake(framing, &mut stdin, &mut stdout, &mut buffer).await?;
+    buffer.clear();
+
+    info!(
+        framing = framing.as_str(),
+        pedersen = pedersen.as_deref().unwrap_or("unknown"),
+        "Handshake completed"
+    );
+
+    Ok((child, pid, stdin, stdout, buffer, framing))
+}
+
+/// Agrees on the [`Framing`] with a freshly started subprocess.
+///
+/// The python script starts with
+/// `pathfinder-ext-py <version> <framing>,<framing> pedersen=<implementation>\n`, to which we
+/// answer `framing <framing>\n`, which it acknowledges with `ok <framing>\n`. The `preferred`
+/// framing is used if the script supports it, otherwise we fall back to [`Framing::JsonLines`].
+///
+/// Returns the framing and the name of the Pedersen hash implementation the script uses, if it
+/// announced one.
+async fn handshake<W, R>(
+    preferred: Framing,
+    stdin: &mut W,
+    stdout: &mut R,
+    buffer: &mut String,
+) -> anyhow::Result<(Framing, Option<String>)>
+where
+    W: AsyncWrite + Unpin,
+    R: AsyncBufRead + Unpin,
+{
+    buffer.clear();
+    stdout
+        .read_line(buffer)
         .await
-        .context("Failed to read 'ready' from python process")?;
+        .context("Failed to read the handshake from python process")?;
+
+    let hello = parse_hello(buffer.trim())?;
+
+    let framing = if hello.framings.contains(&preferred) {
+        preferred
+    } else if hello.framings.contains(&Framing::JsonLines) {
+        Framing::JsonLines
+    } else {
+        anyhow::bail!("Python process supports none of the known framings, read: {buffer:?}");
+    };
+
+    stdin
+        .write_all(format!("framing {}\n", framing.as_str()).as_bytes())
+        .await
+        .context("Failed to write the framing to python process")?;
+    stdin.flush().await?;
 
+    buffer.clear();
+    stdout
+        .read_line(buffer)
+        .await
+        .context("Failed to read the framing acknowledgement from python process")?;
+
+    let expected = format!("ok {}", framing.as_str());
     anyhow::ensure!(
-        // buffer will contain the newline, which doesn't bother serde_json
-        buffer.trim() == "ready",
-        "Failed to read 'ready' from python process, read: {buffer:?}"
+        buffer.trim() == expected,
+        "Python process did not acknowledge the framing, read: {buffer:?}"
     );
-    buffer.clear();
 
-    Ok((child, pid, stdin, stdout, buffer))
+    Ok((framing, hello.pedersen))
+}
+
+/// What the python script announces about itself in the first line it writes.
+#[derive(Debug, PartialEq)]
+struct Hello {
+    framings: Vec<Framing>,
+    /// The Pedersen hash implementation used by the script: `cairo-lang`, or `stark_hash` when
+    /// the python bindings of this repository are installed.
+    pedersen: Option<String>,
+}
+
+/// Parses the first line the python script writes, failing if the script speaks another version
+/// of the protocol.
+fn parse_hello(line: &str) -> anyhow::Result<Hello> {
+    if line == "ready" {
+        // scripts before the handshake was introduced
+        anyhow::bail!(
+            "Python script does not support protocol version {PROTOCOL_VERSION}; \
+            the script is from an older version of pathfinder"
+        );
+    }
+
+    let mut parts = line.split(' ');
+
+    anyhow::ensure!(
+        parts.next() == Some("pathfinder-ext-py"),
+        "Unexpected handshake from python process, read: {line:?}"
+    );
+
+    let version = parts
+        .next()
+        .and_then(|v| v.parse::<u32>().ok())
+        .with_context(|| format!("Invalid protocol version in handshake, read: {line:?}"))?;
+
+    anyhow::ensure!(
+        version == PROTOCOL_VERSION,
+        "Python script speaks protocol version {version}, but version {PROTOCOL_VERSION} is \
+        required; the script is from a different version of pathfinder"
+    );
+
+    // unknown framings are skipped, as newer scripts might support more of them
+    let framings = parts
+        .next()
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|f| f.parse::<Framing>().ok())
+        .collect();
+
+    // unknown properties are skipped for the same reason
+    let pedersen = parts
+        .find_map(|p| p.strip_prefix("pedersen="))
+        .map(str::to_owned);
+
+    Ok(Hello { framings, pedersen })
 }
 
 /// Process a single command with the external process.
@@ -252,30 +386,80 @@ async fn spawn(
 /// - Ok(_) on succesful completion
 /// - Err(None) if nothing was done
 /// - Err(Some(_)) if the process can no longer be reused
+///
+/// The command fails with [`CallFailure::Timeout`] if the response has not been read within
+/// `timeout`, after which the process can no longer be reused.
 async fn process(
-    command: Command,
+    mut command: Command,
+    timeout: std::time::Duration,
+    framing: Framing,
     command_buffer: &mut Vec<u8>,
     stdin: &mut ChildStdin,
     stdout: &mut BufReader<ChildStdout>,
     buffer: &mut String,
 ) -> Result<(Option<Timings>, Status), Option<SubprocessExitReason>> {
-    let (call, at_block, mut response) = command;
     command_buffer.clear();
 
-    let cmd = ChildCommand {
-        contract_address: &call.contract_address,
-        calldata: &call.calldata,
-        entry_point_selector: &call.entry_point_selector,
-        at_block: &at_block,
+    let cmd = match &command {
+        Command::Call {
+            call,
+            at_block,
+            overrides,
+            ..
+        } => ChildCommand {
+            command: command.verb(),
+            call: Some(call),
+            at_block,
+            calls: None,
+            signature: None,
+            max_fee: None,
+            version: None,
+            overrides: Some(overrides.as_slice()).filter(|x| !x.is_empty()),
+        },
+        Command::EstimateFee {
+            call,
+            signature,
+            max_fee,
+            version,
+            at_block,
+            ..
+        }
+        | Command::Simulate {
+            call,
+            signature,
+            max_fee,
+            version,
+            at_block,
+            ..
+        } => ChildCommand {
+            command: command.verb(),
+            call: Some(call),
+            at_block,
+            calls: None,
+            signature: Some(signature),
+            max_fee: Some(max_fee),
+            version: Some(version),
+            overrides: None,
+        },
+        Command::Multicall {
+            calls, at_block, ..
+        } => ChildCommand {
+            command: command.verb(),
+            call: None,
+            at_block,
+            calls: Some(calls),
+            signature: None,
+            max_fee: None,
+            version: None,
+            overrides: None,
+        },
     };
 
     let mut cursor = std::io::Cursor::new(command_buffer);
 
     if let Err(e) = serde_json::to_writer(&mut cursor, &cmd) {
         error!(command=?cmd, error=%e, "Failed to render command as json");
-        let _ = response.send(Err(CallFailure::Internal(
-            "Failed to render command as json",
-        )));
+        command.fail(CallFailure::Internal("Failed to render command as json"));
         return Err(None);
     }
 
@@ -286,15 +470,15 @@ async fn process(
     let res = {
         // AsyncWriteExt::write_all used in the rpc_round is not cancellation safe, but
         // similar to above, if we lose the race, will kill the subprocess and get out.
-        let rpc_op = rpc_round(command_buffer, stdin, stdout, buffer);
+        let rpc_op = rpc_round(framing, command_buffer, stdin, stdout, buffer);
         tokio::pin!(rpc_op);
 
         tokio::select! {
             res = &mut rpc_op => res,
             // no need to await for child dying here, because the event would close the childs
-            // stdout and thus break our read_line and thus return a SubprocessError::IO and
+            // stdout and thus break our read and thus return a SubprocessError::IO and
             // we'd break out.
-            _ = response.closed() => {
+            _ = command.closed() => {
                 // attempt to guard against a call that essentially freezes up the python for
                 // how many minutes. by keeping our eye on this, we'll give the caller a
                 // chance to set timeouts, which will drop the futures.
@@ -304,6 +488,13 @@ async fn process(
                 // handled in a `expect Exception:` branch.
                 return Err(Some(SubprocessExitReason::Cancellation));
             }
+            _ = tokio::time::sleep(timeout) => {
+                // same as above, the python could be in the middle of anything so it will have
+                // to be killed.
+                warn!(?timeout, "Command timed out");
+                command.fail(CallFailure::Timeout);
+                return Err(Some(SubprocessExitReason::Timeout));
+            }
         }
     };
 
@@ -328,8 +519,7 @@ async fn process(
             )
         }
         Err(SubprocessError::IO) => {
-            let error = CallFailure::Internal("Input/output");
-            let _ = response.send(Err(error));
+            command.fail(CallFailure::Internal("Input/output"));
 
             // TODO: consider if we'd just retry; put this back into the queue?
             return Err(Some(SubprocessExitReason::UnrecoverableIO));
@@ -340,39 +530,212 @@ async fn process(
     // let result = response.send(sent_response).map_err(|_| ());
     // trace!(?result, "Call result sent");
 
-    let _ = response.send(sent_response);
+    command.send_response(sent_response);
 
     Ok((timings, status))
 }
 
 /// Run a round of writing out the request, and reading a sane response type.
 async fn rpc_round<'a>(
+    framing: Framing,
     cmd: &[u8],
     stdin: &mut tokio::process::ChildStdin,
     stdout: &mut tokio::io::BufReader<tokio::process::ChildStdout>,
     buffer: &'a mut String,
 ) -> Result<RefinedChildResponse<'a>, SubprocessError> {
-    // TODO: using a vectored write here would make most sense, but alas, advancing [IoSlice]'s is
-    // still unstable. it could be copied, but we'd still lack `write_vectored_all`.
-    //
     // note: write_all are not cancellation safe, and we call this from tokio::select! see callsite
     // for more discussion.
-    stdin.write_all(cmd).await?;
-    stdin.write_all(&b"\n"[..]).await?;
-    stdin.flush().await?;
+    write_message(framing, cmd, stdin).await?;
 
     // the read buffer is cleared very late to allow logging the output in case of an error.
+    read_message(framing, stdout, buffer).await?;
+
+    let resp =
+        serde_json::from_str::<ChildResponse>(buffer).map_err(SubprocessError::InvalidJson)?;
+
+    resp.refine()
+}
+
+/// Writes out and flushes a single message in the given framing.
+async fn write_message<W: AsyncWrite + Unpin>(
+    framing: Framing,
+    message: &[u8],
+    output: &mut W,
+) -> Result<(), SubprocessError> {
+    // TODO: using a vectored write here would make most sense, but alas, advancing [IoSlice]'s is
+    // still unstable. it could be copied, but we'd still lack `write_vectored_all`.
+    match framing {
+        Framing::LengthPrefixed => {
+            let len = u32::try_from(message.len()).map_err(|_| SubprocessError::IO)?;
+            output.write_all(&len.to_be_bytes()).await?;
+            output.write_all(message).await?;
+        }
+        Framing::JsonLines => {
+            output.write_all(message).await?;
+            output.write_all(&b"\n"[..]).await?;
+        }
+    }
+    output.flush().await?;
+    Ok(())
+}
+
+/// Reads a single message in the given framing into the cleared `buffer`.
+async fn read_message<R: AsyncBufRead + Unpin>(
+    framing: Framing,
+    input: &mut R,
+    buffer: &mut String,
+) -> Result<(), SubprocessError> {
     buffer.clear();
 
-    let read = stdout.read_line(buffer).await?;
+    match framing {
+        Framing::LengthPrefixed => {
+            let len = input.read_u32().await? as usize;
+            if len > MAX_FRAME_LENGTH {
+                return Err(SubprocessError::InvalidResponse);
+            }
+
+            // reuse the allocation of the buffer
+            let mut bytes = std::mem::take(buffer).into_bytes();
+            bytes.resize(len, 0);
+            input.read_exact(&mut bytes).await?;
+
+            *buffer = String::from_utf8(bytes).map_err(|_| SubprocessError::InvalidResponse)?;
+        }
+        Framing::JsonLines => {
+            // buffer will contain the newline, which doesn't bother serde_json
+            let read = input.read_line(buffer).await?;
+
+            if read == 0 {
+                // EOF
+                return Err(SubprocessError::IO);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{handshake, parse_hello, read_message, write_message, Framing, Hello};
+
+    #[tokio::test]
+    async fn message_round_trip() {
+        for framing in [Framing::LengthPrefixed, Framing::JsonLines] {
+            let mut pipe = Vec::new();
+            write_message(framing, br#"{"status":"ok"}"#, &mut pipe)
+                .await
+                .unwrap_or_else(|_| panic!("write failed with {framing:?}"));
+            write_message(framing, "\u{e4}".as_bytes(), &mut pipe)
+                .await
+                .unwrap_or_else(|_| panic!("write failed with {framing:?}"));
+
+            let mut input = &pipe[..];
+            let mut buffer = String::new();
 
-    if read == 0 {
-        // EOF
-        return Err(SubprocessError::IO);
+            assert!(read_message(framing, &mut input, &mut buffer).await.is_ok());
+            assert_eq!(buffer.trim_end(), r#"{"status":"ok"}"#);
+            assert!(read_message(framing, &mut input, &mut buffer).await.is_ok());
+            assert_eq!(buffer.trim_end(), "\u{e4}");
+            assert!(read_message(framing, &mut input, &mut buffer)
+                .await
+                .is_err());
+        }
     }
 
-    let resp =
-        serde_json::from_str::<ChildResponse>(buffer).map_err(SubprocessError::InvalidJson)?;
+    #[tokio::test]
+    async fn length_prefixed_frame() {
+        let mut pipe = Vec::new();
+        write_message(Framing::LengthPrefixed, b"{}", &mut pipe)
+            .await
+            .unwrap_or_else(|_| panic!("write failed"));
+        assert_eq!(pipe, b"\x00\x00\x00\x02{}");
+
+        // truncated frames are an error
+        let mut input = &b"\x00\x00\x00\x03{}"[..];
+        let mut buffer = String::new();
+        assert!(
+            read_message(Framing::LengthPrefixed, &mut input, &mut buffer)
+                .await
+                .is_err()
+        );
+    }
 
-    resp.refine()
+    #[test]
+    fn hello() {
+        assert_eq!(
+            parse_hello("pathfinder-ext-py 1 length-prefixed,json-lines pedersen=stark_hash")
+                .unwrap(),
+            Hello {
+                framings: vec![Framing::LengthPrefixed, Framing::JsonLines],
+                pedersen: Some("stark_hash".to_owned()),
+            }
+        );
+        assert_eq!(
+            parse_hello("pathfinder-ext-py 1 json-lines,carrier-pigeon").unwrap(),
+            Hello {
+                framings: vec![Framing::JsonLines],
+                pedersen: None,
+            }
+        );
+        assert_eq!(
+            parse_hello("pathfinder-ext-py 1 json-lines colour=blue pedersen=cairo-lang")
+                .unwrap()
+                .pedersen
+                .as_deref(),
+            Some("cairo-lang")
+        );
+
+        let e = parse_hello("ready").unwrap_err().to_string();
+        assert!(e.contains("older version of pathfinder"), "{e}");
+
+        let e = parse_hello("pathfinder-ext-py 2 length-prefixed")
+            .unwrap_err()
+            .to_string();
+        assert!(e.contains("protocol version 2"), "{e}");
+
+        let e = parse_hello("unexpected database schema version at start.")
+            .unwrap_err()
+            .to_string();
+        assert!(e.contains("Unexpected handshake"), "{e}");
+    }
+
+    #[tokio::test]
+    async fn handshake_falls_back_to_json_lines() {
+        let mut stdout =
+            &b"pathfinder-ext-py 1 json-lines pedersen=cairo-lang\nok json-lines\n"[..];
+        let mut stdin = Vec::new();
+        let mut buffer = String::new();
+
+        let (framing, pedersen) = handshake(
+            Framing::LengthPrefixed,
+            &mut stdin,
+            &mut stdout,
+            &mut buffer,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(framing, Framing::JsonLines);
+        assert_eq!(pedersen.as_deref(), Some("cairo-lang"));
+        assert_eq!(stdin, b"framing json-lines\n");
+    }
+
+    #[tokio::test]
+    async fn handshake_requires_acknowledgement() {
+        let mut stdout = &b"pathfinder-ext-py 1 length-prefixed\nunsupported framing request\n"[..];
+        let mut stdin = Vec::new();
+        let mut buffer = String::new();
+
+        handshake(
+            Framing::LengthPrefixed,
+            &mut stdin,
+            &mut stdout,
+            &mut buffer,
+        )
+        .await
+        .unwrap_err();
+
+        assert_eq!(stdin, b"framing length-prefixed\n");
+    }
 }
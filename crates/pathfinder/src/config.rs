@@ -3,12 +3,27 @@ mod builder;
 mod cli;
 mod file;
 
-use std::{fmt::Display, net::SocketAddr, path::PathBuf, str::FromStr};
+use std::{
+    fmt::Display, net::SocketAddr, num::NonZeroUsize, path::PathBuf, str::FromStr, time::Duration,
+};
 
 use enum_iterator::IntoEnumIterator;
 use reqwest::Url;
 
+use crate::cairo::ext_py::Framing;
+use crate::rpc::execution::ExecutionPolicy;
+use crate::state::block_hash::BlockValidationMode;
+
 const DEFAULT_HTTP_RPC_ADDR: &str = "127.0.0.1:9545";
+const DEFAULT_PYTHON_SUBPROCESSES: usize = 2;
+const DEFAULT_PYTHON_CALL_TIMEOUT_SECS: u64 = 60;
+const DEFAULT_PYTHON_QUEUE_DEPTH: usize = 32;
+const DEFAULT_PYTHON_FRAMING: &str = "length-prefixed";
+const DEFAULT_EXECUTION_POLICY: &str = "local-with-fallback";
+// Strict validation has not yet been run over the full history of every chain, and an
+// unverified hash variant would otherwise halt sync.
+const DEFAULT_BLOCK_VALIDATION: &str = "allow-mismatch";
+const DEFAULT_CALL_CACHE_CAPACITY: usize = crate::rpc::call_cache::DEFAULT_CAPACITY;
 
 /// Possible configuration options.
 #[derive(Debug, PartialEq, Clone, Copy, Hash, Eq, IntoEnumIterator)]
@@ -21,6 +36,20 @@ pub enum ConfigOption {
     HttpRpcAddress,
     /// Path to the node's data directory.
     DataDirectory,
+    /// The number of Python subprocesses executing calls.
+    PythonSubprocesses,
+    /// The time in seconds a single call may execute in a Python subprocess.
+    PythonCallTimeout,
+    /// The number of calls which may wait for a free Python subprocess.
+    PythonQueueDepth,
+    /// The preferred framing of messages exchanged with the Python subprocesses.
+    PythonFraming,
+    /// Where calls are executed: locally, on the sequencer or both.
+    ExecutionPolicy,
+    /// Whether L2 sync rejects blocks which fail hash verification.
+    BlockValidation,
+    /// The number of call results cached by the RPC.
+    CallCacheCapacity,
 }
 
 impl Display for ConfigOption {
@@ -30,6 +59,13 @@ impl Display for ConfigOption {
             ConfigOption::EthereumPassword => f.write_str("Ethereum password"),
             ConfigOption::DataDirectory => f.write_str("Data directory"),
             ConfigOption::HttpRpcAddress => f.write_str("HTTP-RPC socket address"),
+            ConfigOption::PythonSubprocesses => f.write_str("Number of Python subprocesses"),
+            ConfigOption::PythonCallTimeout => f.write_str("Python call timeout"),
+            ConfigOption::PythonQueueDepth => f.write_str("Python call queue depth"),
+            ConfigOption::PythonFraming => f.write_str("Python message framing"),
+            ConfigOption::ExecutionPolicy => f.write_str("Execution policy"),
+            ConfigOption::BlockValidation => f.write_str("Block validation mode"),
+            ConfigOption::CallCacheCapacity => f.write_str("Call cache capacity"),
         }
     }
 }
@@ -43,6 +79,19 @@ pub struct EthereumConfig {
     pub password: Option<String>,
 }
 
+/// Python call execution parameters.
+#[derive(Debug, PartialEq)]
+pub struct PythonConfig {
+    /// The number of subprocesses executing calls.
+    pub subprocesses: NonZeroUsize,
+    /// The time a single call may execute before its subprocess is killed.
+    pub call_timeout: Duration,
+    /// The number of calls which may wait for a free subprocess before new ones are rejected.
+    pub queue_depth: NonZeroUsize,
+    /// The preferred framing of messages, if the subprocesses support it.
+    pub framing: Framing,
+}
+
 /// Node configuration options.
 #[derive(Debug, PartialEq)]
 pub struct Configuration {
@@ -52,6 +101,14 @@ pub struct Configuration {
     pub http_rpc_addr: SocketAddr,
     /// The node's data directory.
     pub data_directory: PathBuf,
+    /// The Python call execution settings.
+    pub python: PythonConfig,
+    /// Where calls are executed.
+    pub execution_policy: ExecutionPolicy,
+    /// How L2 sync treats blocks which fail hash verification.
+    pub block_validation_mode: BlockValidationMode,
+    /// The number of call results cached, zero disables caching.
+    pub call_cache_capacity: usize,
 }
 
 impl Configuration {
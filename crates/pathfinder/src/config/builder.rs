@@ -1,9 +1,15 @@
 //! Provides [ConfigBuilder] which is a convenient and safe way of collecting
 //! configuration parameters from various sources and combining them into one.
 
-use crate::config::{ConfigOption, Configuration, EthereumConfig};
+use crate::cairo::ext_py::Framing;
+use crate::config::{ConfigOption, Configuration, EthereumConfig, PythonConfig};
+use crate::rpc::execution::ExecutionPolicy;
+use crate::state::block_hash::BlockValidationMode;
 use reqwest::Url;
-use std::{collections::HashMap, net::SocketAddr, path::PathBuf, str::FromStr};
+use std::{
+    collections::HashMap, net::SocketAddr, num::NonZeroUsize, path::PathBuf, str::FromStr,
+    time::Duration,
+};
 
 /// A convenient way of collecting and merging configuration options.
 ///
@@ -36,7 +42,11 @@ impl ConfigBuilder {
     /// and parsing as required by [Configuration] types. Also ensures that all
     /// required options are set.
     pub fn try_build(mut self) -> std::io::Result<Configuration> {
-        use super::DEFAULT_HTTP_RPC_ADDR;
+        use super::{
+            DEFAULT_BLOCK_VALIDATION, DEFAULT_CALL_CACHE_CAPACITY, DEFAULT_EXECUTION_POLICY,
+            DEFAULT_HTTP_RPC_ADDR, DEFAULT_PYTHON_CALL_TIMEOUT_SECS, DEFAULT_PYTHON_FRAMING,
+            DEFAULT_PYTHON_QUEUE_DEPTH, DEFAULT_PYTHON_SUBPROCESSES,
+        };
 
         // Required parameters.
         let eth_url = self.take_required(ConfigOption::EthereumHttpUrl)?;
@@ -72,6 +82,55 @@ impl ConfigBuilder {
             )
         })?;
 
+        let python_subprocesses = self.take_non_zero(
+            ConfigOption::PythonSubprocesses,
+            DEFAULT_PYTHON_SUBPROCESSES,
+        )?;
+        let python_queue_depth =
+            self.take_non_zero(ConfigOption::PythonQueueDepth, DEFAULT_PYTHON_QUEUE_DEPTH)?;
+        let python_call_timeout = match self.take(ConfigOption::PythonCallTimeout) {
+            Some(secs) => secs.parse::<u64>().map_err(|err| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("Invalid Python call timeout in seconds ({}): {}", secs, err),
+                )
+            })?,
+            None => DEFAULT_PYTHON_CALL_TIMEOUT_SECS,
+        };
+
+        let python_framing = self
+            .take(ConfigOption::PythonFraming)
+            .unwrap_or_else(|| DEFAULT_PYTHON_FRAMING.to_owned());
+        let python_framing = python_framing.parse::<Framing>().map_err(|err| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, err.to_string())
+        })?;
+
+        let execution_policy = self
+            .take(ConfigOption::ExecutionPolicy)
+            .unwrap_or_else(|| DEFAULT_EXECUTION_POLICY.to_owned());
+        let execution_policy = execution_policy.parse::<ExecutionPolicy>().map_err(|err| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, err.to_string())
+        })?;
+
+        let block_validation_mode = self
+            .take(ConfigOption::BlockValidation)
+            .unwrap_or_else(|| DEFAULT_BLOCK_VALIDATION.to_owned());
+        let block_validation_mode = block_validation_mode
+            .parse::<BlockValidationMode>()
+            .map_err(|err| {
+                std::io::Error::new(std::io::ErrorKind::InvalidInput, err.to_string())
+            })?;
+
+        let call_cache_capacity = match self.take(ConfigOption::CallCacheCapacity) {
+            Some(capacity) => capacity.parse::<usize>().map_err(|err| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("Invalid call cache capacity ({}): {}", capacity, err),
+                )
+            })?,
+            None => DEFAULT_CALL_CACHE_CAPACITY,
+        };
+
         Ok(Configuration {
             ethereum: EthereumConfig {
                 url: eth_url,
@@ -79,6 +138,15 @@ impl ConfigBuilder {
             },
             http_rpc_addr,
             data_directory,
+            python: PythonConfig {
+                subprocesses: python_subprocesses,
+                call_timeout: Duration::from_secs(python_call_timeout),
+                queue_depth: python_queue_depth,
+                framing: python_framing,
+            },
+            execution_policy,
+            block_validation_mode,
+            call_cache_capacity,
         })
     }
 
@@ -92,6 +160,23 @@ impl ConfigBuilder {
         })
     }
 
+    /// Returns the [ConfigOption] parsed as a [NonZeroUsize], or the default if it is not set.
+    fn take_non_zero(
+        &mut self,
+        option: ConfigOption,
+        default: usize,
+    ) -> std::io::Result<NonZeroUsize> {
+        match self.take(option) {
+            Some(value) => value.parse::<NonZeroUsize>().map_err(|err| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("Invalid {} ({}): {}", option, value, err),
+                )
+            }),
+            None => Ok(NonZeroUsize::new(default).expect("Default is not zero")),
+        }
+    }
+
     /// Returns the [ConfigOption], leaving it set to [None].
     pub fn take(&mut self, option: ConfigOption) -> Option<String> {
         self.0.remove(&option)
@@ -259,6 +344,96 @@ mod tests {
                 let config = builder_with_all_required().try_build().unwrap();
                 assert_eq!(config.http_rpc_addr, expected);
             }
+
+            #[test]
+            fn python() {
+                use crate::config::{
+                    DEFAULT_PYTHON_CALL_TIMEOUT_SECS, DEFAULT_PYTHON_QUEUE_DEPTH,
+                    DEFAULT_PYTHON_SUBPROCESSES,
+                };
+
+                let config = builder_with_all_required().try_build().unwrap();
+                assert_eq!(
+                    config.python.subprocesses.get(),
+                    DEFAULT_PYTHON_SUBPROCESSES
+                );
+                assert_eq!(
+                    config.python.call_timeout.as_secs(),
+                    DEFAULT_PYTHON_CALL_TIMEOUT_SECS
+                );
+                assert_eq!(config.python.queue_depth.get(), DEFAULT_PYTHON_QUEUE_DEPTH);
+                assert_eq!(
+                    config.python.framing,
+                    crate::cairo::ext_py::Framing::LengthPrefixed
+                );
+            }
+
+            #[test]
+            fn execution_policy() {
+                use crate::rpc::execution::ExecutionPolicy;
+
+                let config = builder_with_all_required().try_build().unwrap();
+                assert_eq!(
+                    config.execution_policy,
+                    ExecutionPolicy::LocalWithSequencerFallback
+                );
+            }
+
+            #[test]
+            fn block_validation_mode() {
+                use crate::state::block_hash::BlockValidationMode;
+
+                let config = builder_with_all_required().try_build().unwrap();
+                assert_eq!(
+                    config.block_validation_mode,
+                    BlockValidationMode::AllowMismatch
+                );
+            }
+
+            #[test]
+            fn call_cache_capacity() {
+                use crate::config::DEFAULT_CALL_CACHE_CAPACITY;
+
+                let config = builder_with_all_required().try_build().unwrap();
+                assert_eq!(config.call_cache_capacity, DEFAULT_CALL_CACHE_CAPACITY);
+            }
+        }
+
+        #[test]
+        fn zero_python_subprocesses_should_error() {
+            let builder = builder_with_all_required()
+                .with(ConfigOption::PythonSubprocesses, Some("0".to_owned()));
+            assert!(builder.try_build().is_err());
+        }
+
+        #[test]
+        fn unknown_python_framing_should_error() {
+            let builder = builder_with_all_required()
+                .with(ConfigOption::PythonFraming, Some("xml".to_owned()));
+            assert!(builder.try_build().is_err());
+        }
+
+        #[test]
+        fn unknown_block_validation_should_error() {
+            let builder = builder_with_all_required()
+                .with(ConfigOption::BlockValidation, Some("lenient".to_owned()));
+            assert!(builder.try_build().is_err());
+        }
+
+        #[test]
+        fn zero_call_cache_capacity_disables_caching() {
+            let config = builder_with_all_required()
+                .with(ConfigOption::CallCacheCapacity, Some("0".to_owned()))
+                .try_build()
+                .unwrap();
+            assert_eq!(config.call_cache_capacity, 0);
+        }
+
+        #[test]
+        fn negative_call_cache_capacity_should_error() {
+            let builder = builder_with_all_required()
+                .with(ConfigOption::CallCacheCapacity, Some("-1".to_owned()));
+            assert!(builder.try_build().is_err());
         }
     }
 }
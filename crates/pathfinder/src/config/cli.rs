@@ -11,6 +11,13 @@ const DATA_DIR_KEY: &str = "data-directory";
 const ETH_URL_KEY: &str = "ethereum.url";
 const ETH_PASS_KEY: &str = "ethereum.password";
 const HTTP_RPC_ADDR_KEY: &str = "http-rpc";
+const PYTHON_SUBPROCESSES_KEY: &str = "python-subprocesses";
+const PYTHON_CALL_TIMEOUT_KEY: &str = "python-call-timeout";
+const PYTHON_QUEUE_DEPTH_KEY: &str = "python-queue-depth";
+const PYTHON_FRAMING_KEY: &str = "python-framing";
+const EXECUTION_POLICY_KEY: &str = "execution-policy";
+const BLOCK_VALIDATION_KEY: &str = "block-validation";
+const CALL_CACHE_CAPACITY_KEY: &str = "call-cache-capacity";
 
 /// Parses the cmd line arguments and returns the optional
 /// configuration file's path and the specified configuration options.
@@ -40,12 +47,26 @@ where
     let ethereum_url = args.value_of(ETH_URL_KEY).map(|s| s.to_owned());
     let ethereum_password = args.value_of(ETH_PASS_KEY).map(|s| s.to_owned());
     let http_rpc_addr = args.value_of(HTTP_RPC_ADDR_KEY).map(|s| s.to_owned());
+    let python_subprocesses = args.value_of(PYTHON_SUBPROCESSES_KEY).map(|s| s.to_owned());
+    let python_call_timeout = args.value_of(PYTHON_CALL_TIMEOUT_KEY).map(|s| s.to_owned());
+    let python_queue_depth = args.value_of(PYTHON_QUEUE_DEPTH_KEY).map(|s| s.to_owned());
+    let python_framing = args.value_of(PYTHON_FRAMING_KEY).map(|s| s.to_owned());
+    let execution_policy = args.value_of(EXECUTION_POLICY_KEY).map(|s| s.to_owned());
+    let block_validation = args.value_of(BLOCK_VALIDATION_KEY).map(|s| s.to_owned());
+    let call_cache_capacity = args.value_of(CALL_CACHE_CAPACITY_KEY).map(|s| s.to_owned());
 
     let cfg = ConfigBuilder::default()
         .with(ConfigOption::EthereumHttpUrl, ethereum_url)
         .with(ConfigOption::EthereumPassword, ethereum_password)
         .with(ConfigOption::HttpRpcAddress, http_rpc_addr)
-        .with(ConfigOption::DataDirectory, data_directory);
+        .with(ConfigOption::DataDirectory, data_directory)
+        .with(ConfigOption::PythonSubprocesses, python_subprocesses)
+        .with(ConfigOption::PythonCallTimeout, python_call_timeout)
+        .with(ConfigOption::PythonQueueDepth, python_queue_depth)
+        .with(ConfigOption::PythonFraming, python_framing)
+        .with(ConfigOption::ExecutionPolicy, execution_policy)
+        .with(ConfigOption::BlockValidation, block_validation)
+        .with(ConfigOption::CallCacheCapacity, call_cache_capacity);
 
     Ok((config_filepath, cfg))
 }
@@ -54,10 +75,28 @@ where
 ///
 /// Sets the argument names, help strings etc.
 fn clap_app() -> clap::Command<'static> {
-    use super::DEFAULT_HTTP_RPC_ADDR;
+    use super::{
+        DEFAULT_BLOCK_VALIDATION, DEFAULT_CALL_CACHE_CAPACITY, DEFAULT_EXECUTION_POLICY,
+        DEFAULT_HTTP_RPC_ADDR, DEFAULT_PYTHON_CALL_TIMEOUT_SECS, DEFAULT_PYTHON_FRAMING,
+        DEFAULT_PYTHON_QUEUE_DEPTH, DEFAULT_PYTHON_SUBPROCESSES,
+    };
     lazy_static::lazy_static! {
         static ref HTTP_RPC_HELP: String =
             format!("HTTP-RPC listening address [default: {}]", DEFAULT_HTTP_RPC_ADDR);
+        static ref PYTHON_SUBPROCESSES_HELP: String =
+            format!("Number of Python subprocesses executing calls [default: {}]", DEFAULT_PYTHON_SUBPROCESSES);
+        static ref PYTHON_CALL_TIMEOUT_HELP: String =
+            format!("Time limit of a single call in seconds [default: {}]", DEFAULT_PYTHON_CALL_TIMEOUT_SECS);
+        static ref PYTHON_QUEUE_DEPTH_HELP: String =
+            format!("Number of calls waiting for a Python subprocess before new ones are rejected [default: {}]", DEFAULT_PYTHON_QUEUE_DEPTH);
+        static ref PYTHON_FRAMING_HELP: String =
+            format!("Preferred framing of messages exchanged with the Python subprocesses [default: {}]", DEFAULT_PYTHON_FRAMING);
+        static ref EXECUTION_POLICY_HELP: String =
+            format!("Where calls are executed [default: {}]", DEFAULT_EXECUTION_POLICY);
+        static ref BLOCK_VALIDATION_HELP: String =
+            format!("How L2 sync treats blocks which fail hash verification [default: {}]", DEFAULT_BLOCK_VALIDATION);
+        static ref CALL_CACHE_CAPACITY_HELP: String =
+            format!("Number of call results cached, 0 disables caching [default: {}]", DEFAULT_CALL_CACHE_CAPACITY);
     }
 
     let version = env!("VERGEN_GIT_SEMVER_LIGHTWEIGHT");
@@ -107,6 +146,72 @@ Examples:
                 .value_name("PATH")
                 .env("PATHFINDER_DATA_DIRECTORY")
         )
+        .arg(
+            Arg::new(PYTHON_SUBPROCESSES_KEY)
+                .long(PYTHON_SUBPROCESSES_KEY)
+                .help(PYTHON_SUBPROCESSES_HELP.as_ref())
+                .takes_value(true)
+                .value_name("COUNT")
+                .env("PATHFINDER_PYTHON_SUBPROCESSES")
+        )
+        .arg(
+            Arg::new(PYTHON_CALL_TIMEOUT_KEY)
+                .long(PYTHON_CALL_TIMEOUT_KEY)
+                .help(PYTHON_CALL_TIMEOUT_HELP.as_ref())
+                .takes_value(true)
+                .value_name("SECONDS")
+                .env("PATHFINDER_PYTHON_CALL_TIMEOUT")
+                .long_help("Calls taking longer than this are failed, and the Python subprocess executing the call is restarted.")
+        )
+        .arg(
+            Arg::new(PYTHON_QUEUE_DEPTH_KEY)
+                .long(PYTHON_QUEUE_DEPTH_KEY)
+                .help(PYTHON_QUEUE_DEPTH_HELP.as_ref())
+                .takes_value(true)
+                .value_name("COUNT")
+                .env("PATHFINDER_PYTHON_QUEUE_DEPTH")
+        )
+        .arg(
+            Arg::new(PYTHON_FRAMING_KEY)
+                .long(PYTHON_FRAMING_KEY)
+                .help(PYTHON_FRAMING_HELP.as_ref())
+                .takes_value(true)
+                .possible_values(["length-prefixed", "json-lines"])
+                .env("PATHFINDER_PYTHON_FRAMING")
+                .long_help("The Python subprocesses fall back to json-lines if they do not support the preferred framing.")
+        )
+        .arg(
+            Arg::new(EXECUTION_POLICY_KEY)
+                .long(EXECUTION_POLICY_KEY)
+                .help(EXECUTION_POLICY_HELP.as_ref())
+                .takes_value(true)
+                .possible_values(["local", "local-with-fallback", "sequencer"])
+                .env("PATHFINDER_EXECUTION_POLICY")
+                .long_help(r"Where calls, fee estimations and simulations are executed:
+    local:               only in the local Python subprocesses
+    local-with-fallback: in the local Python subprocesses, and on the sequencer what they cannot execute
+    sequencer:           only on the sequencer, which does not support fee estimations or simulations")
+        )
+        .arg(
+            Arg::new(BLOCK_VALIDATION_KEY)
+                .long(BLOCK_VALIDATION_KEY)
+                .help(BLOCK_VALIDATION_HELP.as_ref())
+                .takes_value(true)
+                .possible_values(["strict", "allow-mismatch"])
+                .env("PATHFINDER_BLOCK_VALIDATION")
+                .long_help(r"How L2 sync treats blocks whose block or transaction hashes do not match the ones computed locally:
+    strict:         the blocks are rejected
+    allow-mismatch: the mismatches are only logged")
+        )
+        .arg(
+            Arg::new(CALL_CACHE_CAPACITY_KEY)
+                .long(CALL_CACHE_CAPACITY_KEY)
+                .help(CALL_CACHE_CAPACITY_HELP.as_ref())
+                .takes_value(true)
+                .value_name("COUNT")
+                .env("PATHFINDER_CALL_CACHE_CAPACITY")
+                .long_help("Only the results of calls on a specific block are cached, the least recently used results are evicted first.")
+        )
 }
 
 #[cfg(test)]
@@ -126,6 +231,13 @@ mod tests {
         env::remove_var("PATHFINDER_ETHEREUM_API_URL");
         env::remove_var("PATHFINDER_HTTP_RPC_ADDRESS");
         env::remove_var("PATHFINDER_DATA_DIRECTORY");
+        env::remove_var("PATHFINDER_PYTHON_SUBPROCESSES");
+        env::remove_var("PATHFINDER_PYTHON_CALL_TIMEOUT");
+        env::remove_var("PATHFINDER_PYTHON_QUEUE_DEPTH");
+        env::remove_var("PATHFINDER_PYTHON_FRAMING");
+        env::remove_var("PATHFINDER_EXECUTION_POLICY");
+        env::remove_var("PATHFINDER_BLOCK_VALIDATION");
+        env::remove_var("PATHFINDER_CALL_CACHE_CAPACITY");
     }
 
     #[test]
@@ -232,6 +344,153 @@ mod tests {
         assert_eq!(cfg.take(ConfigOption::DataDirectory), Some(value));
     }
 
+    #[test]
+    fn python_subprocesses_long() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        let (_, mut cfg) = parse_args(vec!["bin name", "--python-subprocesses", &value]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::PythonSubprocesses), Some(value));
+    }
+
+    #[test]
+    fn python_subprocesses_environment_variable() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        env::set_var("PATHFINDER_PYTHON_SUBPROCESSES", &value);
+        let (_, mut cfg) = parse_args(vec!["bin name"]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::PythonSubprocesses), Some(value));
+    }
+
+    #[test]
+    fn python_call_timeout_long() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        let (_, mut cfg) = parse_args(vec!["bin name", "--python-call-timeout", &value]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::PythonCallTimeout), Some(value));
+    }
+
+    #[test]
+    fn python_call_timeout_environment_variable() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        env::set_var("PATHFINDER_PYTHON_CALL_TIMEOUT", &value);
+        let (_, mut cfg) = parse_args(vec!["bin name"]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::PythonCallTimeout), Some(value));
+    }
+
+    #[test]
+    fn python_queue_depth_long() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        let (_, mut cfg) = parse_args(vec!["bin name", "--python-queue-depth", &value]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::PythonQueueDepth), Some(value));
+    }
+
+    #[test]
+    fn python_queue_depth_environment_variable() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "value".to_owned();
+        env::set_var("PATHFINDER_PYTHON_QUEUE_DEPTH", &value);
+        let (_, mut cfg) = parse_args(vec!["bin name"]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::PythonQueueDepth), Some(value));
+    }
+
+    #[test]
+    fn python_framing_long() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "json-lines".to_owned();
+        let (_, mut cfg) = parse_args(vec!["bin name", "--python-framing", &value]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::PythonFraming), Some(value));
+    }
+
+    #[test]
+    fn python_framing_environment_variable() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "json-lines".to_owned();
+        env::set_var("PATHFINDER_PYTHON_FRAMING", &value);
+        let (_, mut cfg) = parse_args(vec!["bin name"]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::PythonFraming), Some(value));
+    }
+
+    #[test]
+    fn execution_policy_long() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "sequencer".to_owned();
+        let (_, mut cfg) = parse_args(vec!["bin name", "--execution-policy", &value]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::ExecutionPolicy), Some(value));
+    }
+
+    #[test]
+    fn execution_policy_environment_variable() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "local".to_owned();
+        env::set_var("PATHFINDER_EXECUTION_POLICY", &value);
+        let (_, mut cfg) = parse_args(vec!["bin name"]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::ExecutionPolicy), Some(value));
+    }
+
+    #[test]
+    fn block_validation_long() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "strict".to_owned();
+        let (_, mut cfg) = parse_args(vec!["bin name", "--block-validation", &value]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::BlockValidation), Some(value));
+    }
+
+    #[test]
+    fn block_validation_environment_variable() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "strict".to_owned();
+        env::set_var("PATHFINDER_BLOCK_VALIDATION", &value);
+        let (_, mut cfg) = parse_args(vec!["bin name"]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::BlockValidation), Some(value));
+    }
+
+    #[test]
+    fn call_cache_capacity_long() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "4096".to_owned();
+        let (_, mut cfg) = parse_args(vec!["bin name", "--call-cache-capacity", &value]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::CallCacheCapacity), Some(value));
+    }
+
+    #[test]
+    fn call_cache_capacity_environment_variable() {
+        let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
+        clear_environment();
+
+        let value = "4096".to_owned();
+        env::set_var("PATHFINDER_CALL_CACHE_CAPACITY", &value);
+        let (_, mut cfg) = parse_args(vec!["bin name"]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::CallCacheCapacity), Some(value));
+    }
+
     #[test]
     fn empty_config() {
         let _env_guard = ENV_VAR_MUTEX.lock().unwrap_or_else(|e| e.into_inner());
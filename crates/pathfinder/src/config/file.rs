@@ -16,6 +16,23 @@ struct FileConfig {
     http_rpc: Option<String>,
     #[serde(rename = "data-directory")]
     data_directory: Option<String>,
+    python: Option<PythonConfig>,
+    #[serde(rename = "execution-policy")]
+    execution_policy: Option<String>,
+    #[serde(rename = "block-validation")]
+    block_validation: Option<String>,
+    #[serde(rename = "call-cache-capacity")]
+    call_cache_capacity: Option<usize>,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct PythonConfig {
+    subprocesses: Option<usize>,
+    #[serde(rename = "call-timeout")]
+    call_timeout: Option<u64>,
+    #[serde(rename = "queue-depth")]
+    queue_depth: Option<usize>,
+    framing: Option<String>,
 }
 
 impl FileConfig {
@@ -29,6 +46,29 @@ impl FileConfig {
         }
         .with(ConfigOption::DataDirectory, self.data_directory)
         .with(ConfigOption::HttpRpcAddress, self.http_rpc)
+        .with(ConfigOption::ExecutionPolicy, self.execution_policy)
+        .with(ConfigOption::BlockValidation, self.block_validation)
+        .with(
+            ConfigOption::CallCacheCapacity,
+            self.call_cache_capacity.map(|x| x.to_string()),
+        )
+        .merge(match self.python {
+            Some(python) => ConfigBuilder::default()
+                .with(
+                    ConfigOption::PythonSubprocesses,
+                    python.subprocesses.map(|x| x.to_string()),
+                )
+                .with(
+                    ConfigOption::PythonCallTimeout,
+                    python.call_timeout.map(|x| x.to_string()),
+                )
+                .with(
+                    ConfigOption::PythonQueueDepth,
+                    python.queue_depth.map(|x| x.to_string()),
+                )
+                .with(ConfigOption::PythonFraming, python.framing),
+            None => ConfigBuilder::default(),
+        })
     }
 }
 
@@ -98,6 +138,59 @@ password = "{}""#,
         assert_eq!(cfg.take(ConfigOption::DataDirectory), Some(value));
     }
 
+    #[test]
+    fn execution_policy() {
+        let value = "sequencer".to_owned();
+        let toml = format!(r#"execution-policy = "{}""#, value);
+        let mut cfg = config_from_str(&toml).unwrap();
+        assert_eq!(cfg.take(ConfigOption::ExecutionPolicy), Some(value));
+    }
+
+    #[test]
+    fn block_validation() {
+        let value = "strict".to_owned();
+        let toml = format!(r#"block-validation = "{}""#, value);
+        let mut cfg = config_from_str(&toml).unwrap();
+        assert_eq!(cfg.take(ConfigOption::BlockValidation), Some(value));
+    }
+
+    #[test]
+    fn call_cache_capacity() {
+        let toml = "call-cache-capacity = 4096";
+        let mut cfg = config_from_str(toml).unwrap();
+        assert_eq!(
+            cfg.take(ConfigOption::CallCacheCapacity),
+            Some("4096".to_owned())
+        );
+    }
+
+    #[test]
+    fn python_section() {
+        let toml = r#"[python]
+subprocesses = 4
+call-timeout = 10
+queue-depth = 64
+framing = "json-lines""#;
+
+        let mut cfg = config_from_str(toml).unwrap();
+        assert_eq!(
+            cfg.take(ConfigOption::PythonSubprocesses),
+            Some("4".to_owned())
+        );
+        assert_eq!(
+            cfg.take(ConfigOption::PythonCallTimeout),
+            Some("10".to_owned())
+        );
+        assert_eq!(
+            cfg.take(ConfigOption::PythonQueueDepth),
+            Some("64".to_owned())
+        );
+        assert_eq!(
+            cfg.take(ConfigOption::PythonFraming),
+            Some("json-lines".to_owned())
+        );
+    }
+
     #[test]
     fn empty_config() {
         let cfg = config_from_str("").unwrap();
@@ -3,13 +3,38 @@
 //!
 //! This includes many trivial wrappers around [StarkHash] which help by providing additional type safety.
 use serde::{Deserialize, Serialize};
-use stark_hash::StarkHash;
+use stark_hash::{OutOfRangeError, ShortStringError, StarkHash, Uint256};
 use web3::types::{H128, H160, H256};
 
 /// The address of a StarkNet contract.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
 pub struct ContractAddress(pub StarkHash);
 
+impl ContractAddress {
+    /// Computes the address of a contract deployed by `deployer`, which is zero for `DEPLOY`
+    /// transactions.
+    ///
+    /// This is cairo-lang's `calculate_contract_address_from_hash`: a [stark_hash::HashChain] of
+    /// the `STARKNET_CONTRACT_ADDRESS` prefix, `deployer`, `salt`, `class_hash` and the hash chain
+    /// of `constructor_calldata`, reduced modulo `2^251 - 256`.
+    pub fn compute(
+        deployer: ContractAddress,
+        salt: ContractAddressSalt,
+        class_hash: ClassHash,
+        constructor_calldata: &[ConstructorParam],
+    ) -> Self {
+        use stark_hash::HashChain;
+
+        let prefix = StarkHash::from_be_slice(b"STARKNET_CONTRACT_ADDRESS")
+            .expect("prefix fits into a StarkHash");
+        let calldata_hash = HashChain::compute(constructor_calldata.iter().map(|param| param.0));
+
+        let address = HashChain::compute([prefix, deployer.0, salt.0, class_hash.0, calldata_hash]);
+
+        Self(reduce_to_address_bound(address))
+    }
+}
+
 /// The salt of a StarkNet contract address.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize, Serialize, PartialOrd, Ord)]
 pub struct ContractAddressSalt(pub StarkHash);
@@ -38,7 +63,7 @@ pub struct ContractCode {
 }
 
 /// Entry point of a StarkNet `call`.
-#[derive(Debug, Copy, Clone, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub struct EntryPoint(pub StarkHash);
 
 impl EntryPoint {
@@ -61,7 +86,7 @@ impl EntryPoint {
 pub struct ByteCodeOffset(pub StarkHash);
 
 /// A single parameter passed to a StarkNet `call`.
-#[derive(Debug, Copy, Clone, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub struct CallParam(pub StarkHash);
 
 /// A single parameter passed to a StarkNet contract constructor.
@@ -84,6 +109,47 @@ pub struct ByteCodeWord(pub StarkHash);
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize, Serialize, PartialOrd, Ord)]
 pub struct StorageAddress(pub StarkHash);
 
+impl StorageAddress {
+    /// Number of consecutive addresses a single storage variable can span, one for each member
+    /// of a struct.
+    pub const MAX_STORAGE_ITEM_SIZE: u16 = 256;
+
+    /// Computes the address of the Cairo `@storage_var` called `name`, with `keys` as the
+    /// arguments of the variable.
+    ///
+    /// This is cairo-lang's `get_storage_var_address`: `pedersen(sn_keccak(name), keys...)`
+    /// reduced modulo `2^251 - 256`, which leaves room for the consecutive addresses of the
+    /// members of a struct, see [StorageAddress::MAX_STORAGE_ITEM_SIZE].
+    pub fn from_var_name(name: &str, keys: &[StarkHash]) -> Self {
+        use sha3::Digest;
+
+        let base = crate::state::class_hash::truncated_keccak(<[u8; 32]>::from(
+            sha3::Keccak256::digest(name.as_bytes()),
+        ));
+        let address = keys
+            .iter()
+            .fold(base, |acc, key| stark_hash::stark_hash(acc, *key));
+
+        Self(reduce_to_address_bound(address))
+    }
+}
+
+/// Reduces `address` modulo `2^251 - 256`, cairo-lang's `ADDR_BOUND` for storage addresses and
+/// `L2_ADDRESS_UPPER_BOUND` for contract addresses.
+fn reduce_to_address_bound(address: StarkHash) -> StarkHash {
+    let mut bound = [0xffu8; 32];
+    bound[0] = 0x07;
+    bound[31] = 0x00;
+    let bound = StarkHash::from_be_bytes(bound).expect("less than the field modulus");
+
+    // The field modulus is less than twice the bound
+    if address >= bound {
+        address - bound
+    } else {
+        address
+    }
+}
+
 /// The value of a storage element for a StarkNet contract.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize, Serialize, PartialOrd, Ord)]
 pub struct StorageValue(pub StarkHash);
@@ -94,7 +160,7 @@ pub struct StorageValue(pub StarkHash);
 pub struct GlobalRoot(pub StarkHash);
 
 /// A StarkNet block hash.
-#[derive(Debug, Copy, Clone, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub struct StarknetBlockHash(pub StarkHash);
 
 /// A StarkNet block number.
@@ -276,3 +342,162 @@ impl From<u64> for GasPrice {
         Self(u128::from(src))
     }
 }
+
+/// Adds conversions to felt newtypes which commonly hold Cairo short strings or halves of
+/// [Uint256]s, such as token names and balances.
+macro_rules! felt_conversions {
+    ($($name:ident),* $(,)?) => {
+        $(
+            impl $name {
+                /// Encodes an ASCII string of at most 31 characters as a Cairo short string.
+                pub fn from_short_string(s: &str) -> Result<Self, ShortStringError> {
+                    StarkHash::from_short_string(s).map(Self)
+                }
+
+                /// Decodes a Cairo short string.
+                pub fn to_short_string(&self) -> Result<String, ShortStringError> {
+                    self.0.to_short_string()
+                }
+
+                /// Splits a [Uint256] into its `low` and `high` halves.
+                pub fn split_uint256(value: Uint256) -> (Self, Self) {
+                    let (low, high) = value.to_felts();
+                    (Self(low), Self(high))
+                }
+
+                /// Joins the `low` and `high` halves of a [Uint256], failing if either has
+                /// more than 128 bits.
+                pub fn join_uint256(low: Self, high: Self) -> Result<Uint256, OutOfRangeError> {
+                    Uint256::from_felts(low.0, high.0)
+                }
+            }
+
+            impl From<StarkHash> for $name {
+                fn from(value: StarkHash) -> Self {
+                    Self(value)
+                }
+            }
+
+            impl From<$name> for StarkHash {
+                fn from(value: $name) -> Self {
+                    value.0
+                }
+            }
+        )*
+    };
+}
+
+felt_conversions!(
+    CallParam,
+    CallResultValue,
+    ConstructorParam,
+    EventData,
+    EventKey,
+    L1ToL2MessagePayloadElem,
+    L2ToL1MessagePayloadElem,
+    StorageValue,
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_string() {
+        let name = CallResultValue::from_short_string("Ether").unwrap();
+        assert_eq!(name.0, StarkHash::from(0x4574686572u64));
+        assert_eq!(name.to_short_string().unwrap(), "Ether");
+    }
+
+    #[test]
+    fn storage_var_address() {
+        // cairo-lang's get_storage_var_address
+        assert_eq!(
+            StorageAddress::from_var_name("ERC20_name", &[]),
+            StorageAddress(
+                StarkHash::from_hex_str(
+                    "0x341c1bdfd89f69748aa00b5742b03adbffd79b8e80cab5c50d91cd8c2a79be1"
+                )
+                .unwrap()
+            )
+        );
+        assert_eq!(
+            StorageAddress::from_var_name("ERC20_balances", &[StarkHash::from(0x123u64)]),
+            StorageAddress(
+                StarkHash::from_hex_str(
+                    "0x709c6298f62a9011c05499b9f5ccce4ecc3e0753e48096edef484c409c25181"
+                )
+                .unwrap()
+            )
+        );
+        assert_eq!(
+            StorageAddress::from_var_name(
+                "ERC20_allowances",
+                &[StarkHash::ONE, StarkHash::from(2u64)]
+            ),
+            StorageAddress(
+                StarkHash::from_hex_str(
+                    "0x53b9f6a59ff5232c4a46837ed4161c12e653509291eb856a3433c1754cc3697"
+                )
+                .unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn contract_address() {
+        let felt = |hex| StarkHash::from_hex_str(hex).unwrap();
+
+        // DEPLOY transaction 0x3d7623443283d9a0cec946492db78b06d57642a551745ddfac8d3f1f4fcc2a8 on Goerli
+        let address = ContractAddress::compute(
+            ContractAddress(StarkHash::ZERO),
+            ContractAddressSalt(felt(
+                "0x655a594122f68f5e821834e606e1243b249a88555fac2d548f7acbee7863f62",
+            )),
+            ClassHash(felt(
+                "0x3523d31a077d891b4d888f9d3c7d33bdac2c0a06f89c08307a7f7b68f681c98",
+            )),
+            &[
+                ConstructorParam(felt(
+                    "0x734d2849eb47e10c59e5a433d425675849cb37338b1d7c4c4afb1e0ca42133",
+                )),
+                ConstructorParam(felt(
+                    "0xffad0128dbd859ef97a246a2d2c00680dedc8d850ff9b6ebcc8b94ee9625bb",
+                )),
+            ],
+        );
+        assert_eq!(
+            address,
+            ContractAddress(felt(
+                "0x54c6883e459baeac4a9052ee109b86b9f81adbcdcb1f65a05dceec4c34d5cf9"
+            ))
+        );
+    }
+
+    #[test]
+    fn storage_address_reduction() {
+        let bound = StarkHash::from_hex_str(
+            "0x7ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff00",
+        )
+        .unwrap();
+
+        assert_eq!(
+            reduce_to_address_bound(bound - StarkHash::ONE),
+            bound - StarkHash::ONE
+        );
+        assert_eq!(reduce_to_address_bound(bound), StarkHash::ZERO);
+        assert_eq!(
+            reduce_to_address_bound(-StarkHash::ONE),
+            -StarkHash::ONE - bound
+        );
+    }
+
+    #[test]
+    fn uint256() {
+        let balance = Uint256 { low: 1, high: 2 };
+        let (low, high) = StorageValue::split_uint256(balance);
+        assert_eq!(low, StorageValue(StarkHash::ONE));
+        assert_eq!(high, StorageValue(StarkHash::from(2u64)));
+        assert_eq!(StorageValue::join_uint256(low, high).unwrap(), balance);
+    }
+}
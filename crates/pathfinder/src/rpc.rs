@@ -1,11 +1,13 @@
 //! StarkNet node JSON-RPC related modules.
 pub mod api;
+pub mod call_cache;
+pub mod execution;
 pub mod serde;
 pub mod types;
 
 use crate::{
     core::{
-        CallSignatureElem, ConstructorParam, ContractAddress, ContractAddressSalt, Fee,
+        CallSignatureElem, ClassHash, ConstructorParam, ContractAddress, ContractAddressSalt, Fee,
         StarknetTransactionHash, StarknetTransactionIndex, TransactionVersion,
     },
     rpc::{
@@ -13,8 +15,11 @@ use crate::{
         serde::{CallSignatureElemAsDecimalStr, FeeAsHexStr, TransactionVersionAsHexStr},
         types::{
             request::OverflowingStorageAddress,
-            request::{BlockResponseScope, Call, EventFilter},
-            BlockHashOrTag, BlockNumberOrTag,
+            request::{
+                BlockResponseScope, Call, ContractsByClassHashFilter, EventFilter, StateOverride,
+                StorageHistoryFilter, TransactionsByAddressFilter,
+            },
+            BlockHashOrTag, BlockId, BlockNumberOrTag,
         },
     },
     sequencer::request::add_transaction::ContractDefinition,
@@ -119,7 +124,8 @@ pub async fn run_server(
             pub contract_address: ContractAddress,
             // Accept overflowing type here to report INVALID_STORAGE_KEY properly
             pub key: OverflowingStorageAddress,
-            pub block_hash: BlockHashOrTag,
+            #[serde(alias = "block_id")]
+            pub block_hash: BlockId,
         }
         let params = params.parse::<NamedArgs>()?;
         context
@@ -182,9 +188,43 @@ pub async fn run_server(
         #[derive(Debug, Deserialize)]
         pub struct NamedArgs {
             pub contract_address: ContractAddress,
+            #[serde(default)]
+            pub block_id: Option<BlockId>,
+        }
+        let params = params.parse::<NamedArgs>()?;
+        context
+            .get_code(params.contract_address, params.block_id)
+            .await
+    })?;
+    module.register_async_method("starknet_getClassHashAt", |params, context| async move {
+        #[derive(Debug, Deserialize)]
+        pub struct NamedArgs {
+            pub block_hash: BlockHashOrTag,
+            pub contract_address: ContractAddress,
+        }
+        let params = params.parse::<NamedArgs>()?;
+        context
+            .get_class_hash_at(params.block_hash, params.contract_address)
+            .await
+    })?;
+    module.register_async_method("starknet_getClass", |params, context| async move {
+        #[derive(Debug, Deserialize)]
+        pub struct NamedArgs {
+            pub class_hash: ClassHash,
+        }
+        context
+            .get_class(params.parse::<NamedArgs>()?.class_hash)
+            .await
+    })?;
+    module.register_async_method("starknet_getClassAt", |params, context| async move {
+        #[derive(Debug, Deserialize)]
+        pub struct NamedArgs {
+            pub block_hash: BlockHashOrTag,
+            pub contract_address: ContractAddress,
         }
+        let params = params.parse::<NamedArgs>()?;
         context
-            .get_code(params.parse::<NamedArgs>()?.contract_address)
+            .get_class_at(params.block_hash, params.contract_address)
             .await
     })?;
     module.register_async_method(
@@ -215,11 +255,94 @@ pub async fn run_server(
         #[derive(Debug, Deserialize)]
         pub struct NamedArgs {
             pub request: Call,
+            #[serde(alias = "block_id")]
+            pub block_hash: BlockId,
+            #[serde(default)]
+            pub overrides: Vec<StateOverride>,
+        }
+        let params = params.parse::<NamedArgs>()?;
+        context
+            .call(params.request, params.block_hash, params.overrides)
+            .await
+    })?;
+    module.register_async_method("pathfinder_getCallCacheMetrics", |_, context| async move {
+        context.call_cache_metrics().await
+    })?;
+    module.register_async_method("pathfinder_getSyncMetrics", |_, context| async move {
+        context.sync_metrics().await
+    })?;
+    module.register_async_method("pathfinder_multicall", |params, context| async move {
+        #[derive(Debug, Deserialize)]
+        pub struct NamedArgs {
+            pub requests: Vec<Call>,
+            pub block_hash: BlockHashOrTag,
+        }
+        let params = params.parse::<NamedArgs>()?;
+        context.multicall(params.requests, params.block_hash).await
+    })?;
+    module.register_async_method("starknet_estimateFee", |params, context| async move {
+        #[serde_with::serde_as]
+        #[derive(Debug, Deserialize)]
+        pub struct NamedArgs {
+            pub request: Call,
+            #[serde_as(as = "Vec<CallSignatureElemAsDecimalStr>")]
+            pub signature: Vec<CallSignatureElem>,
+            #[serde_as(as = "FeeAsHexStr")]
+            pub max_fee: Fee,
+            #[serde_as(as = "TransactionVersionAsHexStr")]
+            pub version: TransactionVersion,
             pub block_hash: BlockHashOrTag,
         }
         let params = params.parse::<NamedArgs>()?;
-        context.call(params.request, params.block_hash).await
+        context
+            .estimate_fee(
+                params.request,
+                params.signature,
+                params.max_fee,
+                params.version,
+                params.block_hash,
+            )
+            .await
     })?;
+    module.register_async_method(
+        "pathfinder_simulateTransaction",
+        |params, context| async move {
+            #[serde_with::serde_as]
+            #[derive(Debug, Deserialize)]
+            pub struct NamedArgs {
+                pub request: Call,
+                #[serde_as(as = "Vec<CallSignatureElemAsDecimalStr>")]
+                pub signature: Vec<CallSignatureElem>,
+                #[serde_as(as = "FeeAsHexStr")]
+                pub max_fee: Fee,
+                #[serde_as(as = "TransactionVersionAsHexStr")]
+                pub version: TransactionVersion,
+                pub block_hash: BlockHashOrTag,
+            }
+            let params = params.parse::<NamedArgs>()?;
+            context
+                .simulate_transaction(
+                    params.request,
+                    params.signature,
+                    params.max_fee,
+                    params.version,
+                    params.block_hash,
+                )
+                .await
+        },
+    )?;
+    module.register_async_method(
+        "pathfinder_traceTransaction",
+        |params, context| async move {
+            #[derive(Debug, Deserialize)]
+            pub struct NamedArgs {
+                pub transaction_hash: StarknetTransactionHash,
+            }
+            context
+                .trace_transaction(params.parse::<NamedArgs>()?.transaction_hash)
+                .await
+        },
+    )?;
     module.register_async_method("starknet_blockNumber", |_, context| async move {
         context.block_number().await
     })?;
@@ -243,6 +366,100 @@ pub async fn run_server(
         let request = params.parse::<NamedArgs>()?.filter;
         context.get_events(request).await
     })?;
+    module.register_async_method(
+        "pathfinder_getTransactionsByAddress",
+        |params, context| async move {
+            #[derive(Debug, Deserialize)]
+            struct NamedArgs {
+                pub filter: TransactionsByAddressFilter,
+            }
+            let request = params.parse::<NamedArgs>()?.filter;
+            context.get_transactions_by_address(request).await
+        },
+    )?;
+    module.register_async_method(
+        "pathfinder_getStorageHistory",
+        |params, context| async move {
+            #[derive(Debug, Deserialize)]
+            struct NamedArgs {
+                pub filter: StorageHistoryFilter,
+            }
+            let request = params.parse::<NamedArgs>()?.filter;
+            context.get_storage_history(request).await
+        },
+    )?;
+    module.register_async_method("pathfinder_getStorageVar", |params, context| async move {
+        #[derive(Debug, Deserialize)]
+        struct NamedArgs {
+            pub contract_address: ContractAddress,
+            pub var_name: String,
+            #[serde(default)]
+            pub keys: Vec<stark_hash::StarkHash>,
+            /// Number of consecutive storage slots of the variable, defaults to one.
+            #[serde(default)]
+            pub size: Option<u16>,
+            pub block_id: BlockId,
+        }
+        let params = params.parse::<NamedArgs>()?;
+        context
+            .get_storage_var(
+                params.contract_address,
+                params.var_name,
+                params.keys,
+                params.size.unwrap_or(1),
+                params.block_id,
+            )
+            .await
+    })?;
+    module.register_async_method(
+        "pathfinder_computeContractAddress",
+        |params, context| async move {
+            #[derive(Debug, Deserialize)]
+            struct NamedArgs {
+                pub contract_address_salt: ContractAddressSalt,
+                pub class_hash: ClassHash,
+                #[serde(default)]
+                pub constructor_calldata: Vec<ConstructorParam>,
+                /// Defaults to zero, the deployer of `DEPLOY` transactions.
+                #[serde(default)]
+                pub deployer_address: Option<ContractAddress>,
+            }
+            let params = params.parse::<NamedArgs>()?;
+            context
+                .compute_contract_address(
+                    params.contract_address_salt,
+                    params.class_hash,
+                    params.constructor_calldata,
+                    params
+                        .deployer_address
+                        .unwrap_or(ContractAddress(stark_hash::StarkHash::ZERO)),
+                )
+                .await
+        },
+    )?;
+    module.register_async_method(
+        "pathfinder_getContractDeployment",
+        |params, context| async move {
+            #[derive(Debug, Deserialize)]
+            struct NamedArgs {
+                pub contract_address: ContractAddress,
+            }
+            context
+                .get_contract_deployment(params.parse::<NamedArgs>()?.contract_address)
+                .await
+        },
+    )?;
+    module.register_async_method(
+        "pathfinder_getContractsByClassHash",
+        |params, context| async move {
+            #[derive(Debug, Deserialize)]
+            struct NamedArgs {
+                pub filter: ContractsByClassHashFilter,
+            }
+            let request = params.parse::<NamedArgs>()?.filter;
+            context.get_contracts_by_class_hash(request).await
+        },
+    )?;
     module.register_async_method(
         "starknet_addInvokeTransaction",
         |params, context| async move {
@@ -338,8 +555,8 @@ mod tests {
         },
         state::{state_tree::GlobalStateTree, SyncState},
         storage::{
-            ContractCodeTable, ContractsTable, StarknetBlock, StarknetBlocksTable,
-            StarknetTransactionsTable, Storage,
+            ContractCodeTable, ContractDeploymentsTable, ContractsTable, StarknetBlock,
+            StarknetBlocksTable, StarknetTransactionsTable, Storage,
         },
     };
     use assert_matches::assert_matches;
@@ -572,6 +789,23 @@ mod tests {
         StarknetTransactionsTable::upsert(&db_txn, block2.hash, block2.number, &transaction_data2)
             .unwrap();
 
+        ContractDeploymentsTable::insert(
+            &db_txn,
+            block0.number,
+            contract0_addr,
+            class0_hash,
+            Some(txn0_hash),
+        )
+        .unwrap();
+        ContractDeploymentsTable::insert(
+            &db_txn,
+            block1.number,
+            contract1_addr,
+            class1_hash,
+            Some(txn1_hash),
+        )
+        .unwrap();
+
         db_txn.commit().unwrap();
         storage
     }
@@ -1104,6 +1338,62 @@ mod tests {
             assert_eq!(crate::rpc::types::reply::ErrorCode::InvalidBlockHash, error);
         }
 
+        #[tokio::test]
+        async fn block_number() {
+            use crate::rpc::types::BlockId;
+
+            let storage = setup_storage();
+            let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+            let sync_state = Arc::new(SyncState::default());
+            let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
+            let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+            let params = rpc_params!(
+                ContractAddress(StarkHash::from_be_slice(b"contract 1").unwrap()),
+                StorageAddress(StarkHash::from_be_slice(b"storage addr 0").unwrap()),
+                BlockId::Number(StarknetBlockNumber(1))
+            );
+            let value = client(addr)
+                .request::<StorageValue>("starknet_getStorageAt", params)
+                .await
+                .unwrap();
+            assert_eq!(
+                value.0,
+                StarkHash::from_be_slice(b"storage value 1").unwrap()
+            );
+        }
+
+        #[tokio::test]
+        async fn non_existent_block_number() {
+            let storage = setup_storage();
+            let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+            let sync_state = Arc::new(SyncState::default());
+            let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
+            let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+            let params = by_name([
+                (
+                    "contract_address",
+                    json!(ContractAddress(
+                        StarkHash::from_be_slice(b"contract 1").unwrap()
+                    )),
+                ),
+                (
+                    "key",
+                    json!(StorageAddress(
+                        StarkHash::from_be_slice(b"storage addr 0").unwrap()
+                    )),
+                ),
+                ("block_id", json!(1000)),
+            ]);
+            let error = client(addr)
+                .request::<StorageValue>("starknet_getStorageAt", params)
+                .await
+                .unwrap_err();
+            assert_eq!(
+                crate::rpc::types::reply::ErrorCode::InvalidBlockNumber,
+                error
+            );
+        }
+
         #[tokio::test]
         async fn deployment_block() {
             let storage = setup_storage();
@@ -1604,6 +1894,68 @@ mod tests {
             assert_eq!(ErrorCode::ContractNotFound, not_found);
         }
 
+        #[tokio::test]
+        async fn not_yet_deployed_at_block() {
+            use crate::rpc::types::BlockId;
+
+            let storage = setup_storage();
+            let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+            let sync_state = Arc::new(SyncState::default());
+            let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
+            let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+            let contract_address =
+                ContractAddress(StarkHash::from_be_slice(b"contract 1").unwrap());
+
+            let genesis = BlockId::Hash(StarknetBlockHash(
+                StarkHash::from_be_slice(b"genesis").unwrap(),
+            ));
+            for block_id in [genesis, BlockId::Number(StarknetBlockNumber(0))] {
+                let error = client(addr)
+                    .request::<ContractCode>(
+                        "starknet_getCode",
+                        rpc_params!(contract_address, block_id),
+                    )
+                    .await
+                    .unwrap_err();
+                assert_eq!(ErrorCode::ContractNotFound, error);
+            }
+        }
+
+        #[tokio::test]
+        async fn non_existent_block() {
+            let storage = setup_storage();
+            let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+            let sync_state = Arc::new(SyncState::default());
+            let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
+            let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+            let contract_address =
+                ContractAddress(StarkHash::from_be_slice(b"contract 1").unwrap());
+
+            let error = client(addr)
+                .request::<ContractCode>(
+                    "starknet_getCode",
+                    by_name([
+                        ("contract_address", json!(contract_address)),
+                        ("block_id", json!(1000)),
+                    ]),
+                )
+                .await
+                .unwrap_err();
+            assert_eq!(ErrorCode::InvalidBlockNumber, error);
+
+            let error = client(addr)
+                .request::<ContractCode>(
+                    "starknet_getCode",
+                    by_name([
+                        ("contract_address", json!(contract_address)),
+                        ("block_id", json!("0x1234")),
+                    ]),
+                )
+                .await
+                .unwrap_err();
+            assert_eq!(ErrorCode::InvalidBlockHash, error);
+        }
+
         #[tokio::test]
         async fn returns_abi_and_code_for_known() {
             use crate::core::ContractCode;
@@ -1692,49 +2044,262 @@ mod tests {
         }
     }
 
-    mod get_block_transaction_count_by_hash {
+    mod get_class_hash_at {
         use super::*;
-        use crate::rpc::types::{BlockHashOrTag, Tag};
+        use crate::rpc::types::{reply::ErrorCode, Tag};
         use pretty_assertions::assert_eq;
 
         #[tokio::test]
-        async fn genesis() {
+        async fn deployed() {
             let storage = setup_storage();
             let sequencer = SeqClient::new(Chain::Goerli).unwrap();
             let sync_state = Arc::new(SyncState::default());
             let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
             let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
-            let params = rpc_params!(StarknetBlockHash(
-                StarkHash::from_be_slice(b"genesis").unwrap()
-            ));
-            let count = client(addr)
-                .request::<u64>("starknet_getBlockTransactionCountByHash", params)
+            let contract_address =
+                ContractAddress(StarkHash::from_be_slice(b"contract 1").unwrap());
+            let block_hash = StarknetBlockHash(StarkHash::from_be_slice(b"block 1").unwrap());
+
+            let params = rpc_params!(BlockHashOrTag::Hash(block_hash), contract_address);
+            let class_hash = client(addr)
+                .request::<ClassHash>("starknet_getClassHashAt", params)
                 .await
                 .unwrap();
-            assert_eq!(count, 1);
-        }
+            assert_eq!(
+                class_hash,
+                ClassHash(StarkHash::from_be_slice(b"class 1 hash").unwrap())
+            );
 
-        mod latest {
-            use super::*;
-            use pretty_assertions::assert_eq;
+            let params = rpc_params!(BlockHashOrTag::Tag(Tag::Latest), contract_address);
+            let class_hash = client(addr)
+                .request::<ClassHash>("starknet_getClassHashAt", params)
+                .await
+                .unwrap();
+            assert_eq!(
+                class_hash,
+                ClassHash(StarkHash::from_be_slice(b"class 1 hash").unwrap())
+            );
+        }
 
-            #[tokio::test]
-            async fn positional_args() {
-                let storage = setup_storage();
-                let sequencer = SeqClient::new(Chain::Goerli).unwrap();
-                let sync_state = Arc::new(SyncState::default());
-                let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
-                let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
-                let params = rpc_params!(BlockHashOrTag::Tag(Tag::Latest));
-                let count = client(addr)
-                    .request::<u64>("starknet_getBlockTransactionCountByHash", params)
-                    .await
-                    .unwrap();
-                assert_eq!(count, 3);
-            }
+        #[tokio::test]
+        async fn not_yet_deployed() {
+            let storage = setup_storage();
+            let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+            let sync_state = Arc::new(SyncState::default());
+            let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
+            let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+            let contract_address =
+                ContractAddress(StarkHash::from_be_slice(b"contract 1").unwrap());
+            let genesis_hash = StarknetBlockHash(StarkHash::from_be_slice(b"genesis").unwrap());
 
-            #[tokio::test]
-            async fn named_args() {
+            let params = rpc_params!(BlockHashOrTag::Hash(genesis_hash), contract_address);
+            let error = client(addr)
+                .request::<ClassHash>("starknet_getClassHashAt", params)
+                .await
+                .unwrap_err();
+            assert_eq!(ErrorCode::ContractNotFound, error);
+        }
+
+        #[tokio::test]
+        async fn invalid_block_hash() {
+            let storage = setup_storage();
+            let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+            let sync_state = Arc::new(SyncState::default());
+            let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
+            let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+            let contract_address =
+                ContractAddress(StarkHash::from_be_slice(b"contract 1").unwrap());
+
+            let params = rpc_params!(*INVALID_BLOCK_HASH, contract_address);
+            let error = client(addr)
+                .request::<ClassHash>("starknet_getClassHashAt", params)
+                .await
+                .unwrap_err();
+            assert_eq!(ErrorCode::InvalidBlockHash, error);
+        }
+    }
+
+    mod get_class {
+        use super::*;
+        use crate::rpc::types::reply::{ContractClass, ErrorCode};
+        use pretty_assertions::assert_eq;
+
+        /// Stores the fixture contract's class, returning its hash and definition.
+        pub(super) fn insert_fixture_class(storage: &Storage) -> (ClassHash, serde_json::Value) {
+            let contract_definition = include_bytes!("../fixtures/contract_definition.json.zst");
+            let contract_definition = zstd::decode_all(&contract_definition[..]).unwrap();
+
+            let (abi, bytecode, hash) =
+                crate::state::class_hash::extract_abi_code_hash(&contract_definition).unwrap();
+
+            let mut connection = storage.connection().unwrap();
+            let tx = connection.transaction().unwrap();
+            ContractCodeTable::insert(&tx, hash, &abi, &bytecode, &contract_definition).unwrap();
+            tx.commit().unwrap();
+
+            (hash, serde_json::from_slice(&contract_definition).unwrap())
+        }
+
+        #[tokio::test]
+        async fn returns_class_for_known() {
+            use flate2::read::GzDecoder;
+            use std::io::Read;
+
+            let storage = setup_storage();
+            let (class_hash, definition) = insert_fixture_class(&storage);
+            let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+            let sync_state = Arc::new(SyncState::default());
+            let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
+            let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+
+            let class = client(addr)
+                .request::<ContractClass>("starknet_getClass", rpc_params!(class_hash))
+                .await
+                .unwrap();
+
+            let program = base64::decode(&class.program).unwrap();
+            let mut decoder = GzDecoder::new(&program[..]);
+            let mut program = Vec::new();
+            decoder.read_to_end(&mut program).unwrap();
+            let program = serde_json::from_slice::<serde_json::Value>(&program).unwrap();
+
+            assert_eq!(program, definition["program"]);
+            assert_eq!(class.abi.as_ref(), Some(&definition["abi"]));
+            assert_eq!(
+                serde_json::to_value(&class.entry_points_by_type).unwrap(),
+                definition["entry_points_by_type"]
+            );
+        }
+
+        #[tokio::test]
+        async fn invalid_class_hash() {
+            let storage = setup_storage();
+            let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+            let sync_state = Arc::new(SyncState::default());
+            let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
+            let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+
+            let class_hash = ClassHash(StarkHash::from_be_slice(b"not a class").unwrap());
+            let error = client(addr)
+                .request::<ContractClass>("starknet_getClass", rpc_params!(class_hash))
+                .await
+                .unwrap_err();
+            assert_eq!(ErrorCode::InvalidContractClassHash, error);
+        }
+    }
+
+    mod get_class_at {
+        use super::get_class::insert_fixture_class;
+        use super::*;
+        use crate::rpc::types::{
+            reply::{ContractClass, ErrorCode},
+            Tag,
+        };
+        use pretty_assertions::assert_eq;
+
+        #[tokio::test]
+        async fn deployed() {
+            let storage = setup_storage();
+            let (class_hash, _) = insert_fixture_class(&storage);
+            let contract_address =
+                ContractAddress(StarkHash::from_be_slice(b"contract 2").unwrap());
+            ContractDeploymentsTable::insert(
+                &storage.connection().unwrap(),
+                StarknetBlockNumber(1),
+                contract_address,
+                class_hash,
+                None,
+            )
+            .unwrap();
+            let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+            let sync_state = Arc::new(SyncState::default());
+            let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
+            let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+            let client = client(addr);
+
+            let expected = client
+                .request::<ContractClass>("starknet_getClass", rpc_params!(class_hash))
+                .await
+                .unwrap();
+
+            let params = rpc_params!(BlockHashOrTag::Tag(Tag::Latest), contract_address);
+            let class = client
+                .request::<ContractClass>("starknet_getClassAt", params)
+                .await
+                .unwrap();
+            assert_eq!(class, expected);
+
+            let genesis_hash = StarknetBlockHash(StarkHash::from_be_slice(b"genesis").unwrap());
+            let params = rpc_params!(BlockHashOrTag::Hash(genesis_hash), contract_address);
+            let error = client
+                .request::<ContractClass>("starknet_getClassAt", params)
+                .await
+                .unwrap_err();
+            assert_eq!(ErrorCode::ContractNotFound, error);
+        }
+
+        #[tokio::test]
+        async fn invalid_block_hash() {
+            let storage = setup_storage();
+            let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+            let sync_state = Arc::new(SyncState::default());
+            let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
+            let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+            let contract_address =
+                ContractAddress(StarkHash::from_be_slice(b"contract 1").unwrap());
+
+            let params = rpc_params!(*INVALID_BLOCK_HASH, contract_address);
+            let error = client(addr)
+                .request::<ContractClass>("starknet_getClassAt", params)
+                .await
+                .unwrap_err();
+            assert_eq!(ErrorCode::InvalidBlockHash, error);
+        }
+    }
+
+    mod get_block_transaction_count_by_hash {
+        use super::*;
+        use crate::rpc::types::{BlockHashOrTag, Tag};
+        use pretty_assertions::assert_eq;
+
+        #[tokio::test]
+        async fn genesis() {
+            let storage = setup_storage();
+            let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+            let sync_state = Arc::new(SyncState::default());
+            let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
+            let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+            let params = rpc_params!(StarknetBlockHash(
+                StarkHash::from_be_slice(b"genesis").unwrap()
+            ));
+            let count = client(addr)
+                .request::<u64>("starknet_getBlockTransactionCountByHash", params)
+                .await
+                .unwrap();
+            assert_eq!(count, 1);
+        }
+
+        mod latest {
+            use super::*;
+            use pretty_assertions::assert_eq;
+
+            #[tokio::test]
+            async fn positional_args() {
+                let storage = setup_storage();
+                let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+                let sync_state = Arc::new(SyncState::default());
+                let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
+                let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+                let params = rpc_params!(BlockHashOrTag::Tag(Tag::Latest));
+                let count = client(addr)
+                    .request::<u64>("starknet_getBlockTransactionCountByHash", params)
+                    .await
+                    .unwrap();
+                assert_eq!(count, 3);
+            }
+
+            #[tokio::test]
+            async fn named_args() {
                 let storage = setup_storage();
                 let sequencer = SeqClient::new(Chain::Goerli).unwrap();
                 let sync_state = Arc::new(SyncState::default());
@@ -2082,333 +2647,923 @@ mod tests {
                 .unwrap_err();
             assert_eq!(crate::rpc::types::reply::ErrorCode::InvalidBlockHash, error);
         }
-    }
-
-    #[tokio::test]
-    async fn block_number() {
-        let storage = setup_storage();
-        let sequencer = SeqClient::new(Chain::Goerli).unwrap();
-        let sync_state = Arc::new(SyncState::default());
-        let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
-        let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
-        let number = client(addr)
-            .request::<u64>("starknet_blockNumber", rpc_params!())
-            .await
-            .unwrap();
-        assert_eq!(number, 2);
-    }
-
-    #[tokio::test]
-    async fn chain_id() {
-        use futures::stream::StreamExt;
-
-        assert_eq!(
-            [Chain::Goerli, Chain::Mainnet]
-                .iter()
-                .map(|set_chain| async {
-                    let storage = Storage::in_memory().unwrap();
-                    let sequencer = SeqClient::new(*set_chain).unwrap();
-                    let sync_state = Arc::new(SyncState::default());
-                    let api = RpcApi::new(storage, sequencer, *set_chain, sync_state);
-                    let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
-                    let params = rpc_params!();
-                    client(addr)
-                        .request::<String>("starknet_chainId", params)
-                        .await
-                        .unwrap()
-                })
-                .collect::<futures::stream::FuturesOrdered<_>>()
-                .collect::<Vec<_>>()
-                .await,
-            vec![
-                format!("0x{}", hex::encode("SN_GOERLI")),
-                format!("0x{}", hex::encode("SN_MAIN")),
-            ]
-        );
-    }
-
-    #[tokio::test]
-    #[should_panic]
-    async fn pending_transactions() {
-        let storage = Storage::in_memory().unwrap();
-        let sequencer = SeqClient::new(Chain::Goerli).unwrap();
-        let sync_state = Arc::new(SyncState::default());
-        let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
-        let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
-        client(addr)
-            .request::<()>("starknet_pendingTransactions", rpc_params!())
-            .await
-            .unwrap();
-    }
-
-    #[tokio::test]
-    #[should_panic]
-    async fn protocol_version() {
-        let storage = Storage::in_memory().unwrap();
-        let sequencer = SeqClient::new(Chain::Goerli).unwrap();
-        let sync_state = Arc::new(SyncState::default());
-        let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
-        let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
-        client(addr)
-            .request::<StarknetProtocolVersion>("starknet_protocolVersion", rpc_params!())
-            .await
-            .unwrap();
-    }
-
-    mod syncing {
-        use crate::rpc::types::reply::{syncing, Syncing};
-        use pretty_assertions::assert_eq;
-
-        use super::*;
 
         #[tokio::test]
-        async fn not_syncing() {
-            let storage = setup_storage();
+        async fn overrides_without_call_handling() {
+            use crate::{
+                core::{StorageAddress, StorageValue},
+                rpc::types::request::StateOverride,
+            };
+            use jsonrpsee::types::error::CallError;
+
+            let storage = Storage::in_memory().unwrap();
             let sequencer = SeqClient::new(Chain::Goerli).unwrap();
             let sync_state = Arc::new(SyncState::default());
             let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
             let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
-            let syncing = client(addr)
-                .request::<Syncing>("starknet_syncing", rpc_params!())
+            let params = by_name([
+                (
+                    "request",
+                    json!({
+                        "calldata": CALL_DATA.clone(),
+                        "contract_address": *VALID_CONTRACT_ADDR,
+                        "entry_point_selector": *VALID_ENTRY_POINT,
+                    }),
+                ),
+                ("block_hash", json!("latest")),
+                (
+                    "overrides",
+                    json!([StateOverride {
+                        contract_address: *VALID_CONTRACT_ADDR,
+                        storage: [(
+                            StorageAddress(StarkHash::from_hex_str("84").unwrap()),
+                            StorageValue(StarkHash::from_hex_str("5").unwrap()),
+                        )]
+                        .into_iter()
+                        .collect(),
+                        class_hash: None,
+                    }]),
+                ),
+            ]);
+            let error = client(addr)
+                .request::<Vec<CallResultValue>>("starknet_call", params)
                 .await
-                .unwrap();
-
-            assert_eq!(syncing, Syncing::False(false));
+                .unwrap_err();
+            assert_matches!(
+                error,
+                Error::Call(CallError::Custom(e)) => assert!(e.message().contains("State overrides are not supported"), "{e:?}")
+            );
         }
 
         #[tokio::test]
-        async fn syncing() {
-            use crate::rpc::types::reply::syncing::NumberedBlock;
-            let expected = Syncing::Status(syncing::Status {
-                starting: NumberedBlock::from(("abbacd", 1)),
-                current: NumberedBlock::from(("abbace", 2)),
-                highest: NumberedBlock::from(("abbacf", 3)),
-            });
+        async fn execution_backend() {
+            use crate::rpc::execution::{fake::FakeBackend, ExecutionPolicy};
 
-            let storage = setup_storage();
+            let expected = vec![CallResultValue(StarkHash::from_hex_str("3").unwrap())];
+            let backend = FakeBackend {
+                call: Some(expected.clone()),
+                ..Default::default()
+            };
+
+            let storage = Storage::in_memory().unwrap();
             let sequencer = SeqClient::new(Chain::Goerli).unwrap();
             let sync_state = Arc::new(SyncState::default());
-            *sync_state.status.write().await = expected.clone();
-            let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
+            let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state)
+                .with_execution_backend(Arc::new(backend))
+                .with_execution_policy(ExecutionPolicy::LocalOnly);
             let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
-            let syncing = client(addr)
-                .request::<Syncing>("starknet_syncing", rpc_params!())
+            let params = rpc_params!(
+                Call {
+                    calldata: CALL_DATA.clone(),
+                    contract_address: *VALID_CONTRACT_ADDR,
+                    entry_point_selector: *VALID_ENTRY_POINT,
+                },
+                BlockHashOrTag::Tag(Tag::Pending)
+            );
+            let result = client(addr)
+                .request::<Vec<CallResultValue>>("starknet_call", params)
                 .await
                 .unwrap();
-
-            assert_eq!(syncing, expected);
+            assert_eq!(result, expected);
         }
     }
 
-    mod events {
+    mod call_cache {
         use super::*;
+        use crate::{
+            core::CallResultValue,
+            rpc::{
+                call_cache::CallCacheMetrics,
+                execution::{fake::FakeBackend, ExecutionPolicy},
+                types::{request::Call, BlockHashOrTag, BlockId, Tag},
+            },
+        };
+        use pretty_assertions::assert_eq;
+        use std::sync::atomic::Ordering;
+
+        /// Makes the same call three times, returns the number of executions and the metrics.
+        async fn call_thrice(
+            storage: Storage,
+            block_hash: impl Into<BlockId>,
+        ) -> (usize, CallCacheMetrics) {
+            let block_hash = block_hash.into();
+            let backend = Arc::new(FakeBackend {
+                call: Some(vec![CallResultValue(StarkHash::from_hex_str("3").unwrap())]),
+                ..Default::default()
+            });
+            let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+            let sync_state = Arc::new(SyncState::default());
+            let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state)
+                .with_execution_backend(backend.clone())
+                .with_execution_policy(ExecutionPolicy::LocalOnly);
+            let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+            let client = client(addr);
 
-        use super::types::reply::{EmittedEvent, GetEventsResult};
-        use crate::sequencer::reply::transaction;
+            for _ in 0..3 {
+                let params = rpc_params!(
+                    Call {
+                        calldata: vec![],
+                        contract_address: *VALID_CONTRACT_ADDR,
+                        entry_point_selector: *VALID_ENTRY_POINT,
+                    },
+                    block_hash
+                );
+                client
+                    .request::<Vec<CallResultValue>>("starknet_call", params)
+                    .await
+                    .unwrap();
+            }
 
-        const NUM_BLOCKS: usize = 4;
-        const TRANSACTIONS_PER_BLOCK: usize = 10;
-        const EVENTS_PER_BLOCK: usize = TRANSACTIONS_PER_BLOCK;
-        const NUM_TRANSACTIONS: usize = NUM_BLOCKS * TRANSACTIONS_PER_BLOCK;
-        const NUM_EVENTS: usize = NUM_BLOCKS * EVENTS_PER_BLOCK;
+            let metrics = client
+                .request::<CallCacheMetrics>("pathfinder_getCallCacheMetrics", rpc_params!())
+                .await
+                .unwrap();
+            (backend.requests.load(Ordering::Relaxed), metrics)
+        }
 
-        fn create_transactions_and_receipts(
-        ) -> [(transaction::Transaction, transaction::Receipt); NUM_TRANSACTIONS] {
-            let transactions = (0..NUM_TRANSACTIONS).map(|i| transaction::Transaction {
-                calldata: None,
-                class_hash: None,
-                constructor_calldata: None,
-                contract_address: Some(ContractAddress(
-                    StarkHash::from_hex_str(&"2".repeat(i + 3)).unwrap(),
+        #[tokio::test]
+        async fn block_hash() {
+            let storage = Storage::in_memory().unwrap();
+            let (executions, metrics) = call_thrice(
+                storage,
+                BlockHashOrTag::Hash(StarknetBlockHash(
+                    StarkHash::from_be_slice(b"genesis").unwrap(),
                 )),
-                contract_address_salt: None,
-                entry_point_type: None,
-                entry_point_selector: None,
-                max_fee: None,
-                nonce: None,
-                sender_address: None,
-                signature: None,
-                transaction_hash: StarknetTransactionHash(
-                    StarkHash::from_hex_str(&"f".repeat(i + 3)).unwrap(),
-                ),
-                r#type: transaction::Type::InvokeFunction,
-                version: None,
-            });
-            let receipts = (0..NUM_TRANSACTIONS).map(|i| transaction::Receipt {
-                actual_fee: None,
-                events: vec![transaction::Event {
-                    from_address: ContractAddress(
-                        StarkHash::from_hex_str(&"2".repeat(i + 3)).unwrap(),
-                    ),
-                    data: vec![EventData(
-                        StarkHash::from_hex_str(&"c".repeat(i + 3)).unwrap(),
-                    )],
-                    keys: vec![
-                        EventKey(StarkHash::from_hex_str(&"d".repeat(i + 3)).unwrap()),
-                        EventKey(StarkHash::from_hex_str("deadbeef").unwrap()),
-                    ],
-                }],
-                execution_resources: transaction::ExecutionResources {
-                    builtin_instance_counter:
-                        transaction::execution_resources::BuiltinInstanceCounter::Empty(
-                            transaction::execution_resources::EmptyBuiltinInstanceCounter {},
-                        ),
-                    n_steps: i as u64 + 987,
-                    n_memory_holes: i as u64 + 1177,
-                },
-                l1_to_l2_consumed_message: None,
-                l2_to_l1_messages: Vec::new(),
-                transaction_hash: StarknetTransactionHash(
-                    StarkHash::from_hex_str(&"e".repeat(i + 3)).unwrap(),
-                ),
-                transaction_index: StarknetTransactionIndex(i as u64 + 2311),
-            });
+            )
+            .await;
+            assert_eq!(executions, 1);
+            assert_eq!((metrics.hits, metrics.misses, metrics.len), (2, 1, 1));
+        }
 
-            transactions
-                .into_iter()
-                .zip(receipts)
-                .collect::<Vec<_>>()
-                .try_into()
-                .unwrap()
+        #[tokio::test]
+        async fn latest_is_resolved() {
+            let storage = setup_storage();
+            let (executions, metrics) =
+                call_thrice(storage, BlockHashOrTag::Tag(Tag::Latest)).await;
+            assert_eq!(executions, 1);
+            assert_eq!((metrics.hits, metrics.misses), (2, 1));
         }
 
-        fn setup() -> (Storage, Vec<EmittedEvent>) {
-            let storage = Storage::in_memory().unwrap();
-            let connection = storage.connection().unwrap();
+        #[tokio::test]
+        async fn block_number() {
+            let storage = setup_storage();
+            let (executions, metrics) =
+                call_thrice(storage, BlockId::Number(StarknetBlockNumber(1))).await;
+            assert_eq!(executions, 1);
+            assert_eq!((metrics.hits, metrics.misses), (2, 1));
+        }
 
-            let blocks = crate::storage::test_utils::create_blocks::<NUM_BLOCKS>();
-            let transactions_and_receipts = create_transactions_and_receipts();
+        #[tokio::test]
+        async fn non_existent_block_number() {
+            let storage = setup_storage();
+            let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+            let sync_state = Arc::new(SyncState::default());
+            let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
+            let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+            let params = rpc_params!(
+                Call {
+                    calldata: vec![],
+                    contract_address: *VALID_CONTRACT_ADDR,
+                    entry_point_selector: *VALID_ENTRY_POINT,
+                },
+                BlockId::Number(StarknetBlockNumber(1000))
+            );
+            let error = client(addr)
+                .request::<Vec<CallResultValue>>("starknet_call", params)
+                .await
+                .unwrap_err();
+            assert_eq!(
+                crate::rpc::types::reply::ErrorCode::InvalidBlockNumber,
+                error
+            );
+        }
 
-            for (i, block) in blocks.iter().enumerate() {
-                StarknetBlocksTable::insert(&connection, block).unwrap();
-                StarknetTransactionsTable::upsert(
-                    &connection,
-                    block.hash,
-                    block.number,
-                    &transactions_and_receipts
-                        [i * TRANSACTIONS_PER_BLOCK..(i + 1) * TRANSACTIONS_PER_BLOCK],
-                )
-                .unwrap();
-            }
+        #[tokio::test]
+        async fn latest_without_blocks() {
+            let storage = Storage::in_memory().unwrap();
+            let (executions, metrics) =
+                call_thrice(storage, BlockHashOrTag::Tag(Tag::Latest)).await;
+            assert_eq!(executions, 3);
+            assert_eq!((metrics.hits, metrics.misses), (0, 0));
+        }
 
-            let events = transactions_and_receipts
-                .iter()
-                .enumerate()
-                .map(|(i, (txn, receipt))| {
-                    let event = &receipt.events[0];
-                    let block = &blocks[i / TRANSACTIONS_PER_BLOCK];
+        #[tokio::test]
+        async fn pending_is_not_cached() {
+            let storage = setup_storage();
+            let (executions, metrics) =
+                call_thrice(storage, BlockHashOrTag::Tag(Tag::Pending)).await;
+            assert_eq!(executions, 3);
+            assert_eq!((metrics.hits, metrics.misses), (0, 0));
+        }
+    }
 
-                    EmittedEvent {
-                        data: event.data.clone(),
-                        from_address: event.from_address,
-                        keys: event.keys.clone(),
-                        block_hash: block.hash,
-                        block_number: block.number,
-                        transaction_hash: txn.transaction_hash,
-                    }
-                })
-                .collect();
+    mod multicall {
+        use super::*;
+        use crate::rpc::types::{reply::MulticallResult, request::Call, BlockHashOrTag, Tag};
+        use pretty_assertions::assert_eq;
 
-            (storage, events)
+        #[tokio::test]
+        async fn no_requests() {
+            let storage = Storage::in_memory().unwrap();
+            let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+            let sync_state = Arc::new(SyncState::default());
+            let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
+            let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+            let params = rpc_params!(Vec::<Call>::new(), BlockHashOrTag::Tag(Tag::Latest));
+            let results = client(addr)
+                .request::<Vec<MulticallResult>>("pathfinder_multicall", params)
+                .await
+                .unwrap();
+            assert_eq!(results, vec![]);
         }
 
-        mod positional_args {
-            use super::*;
-
-            use pretty_assertions::assert_eq;
+        #[tokio::test]
+        async fn too_many_requests() {
+            use crate::rpc::api::MAX_MULTICALL_CALLS;
+            use jsonrpsee::types::error::CallError;
 
-            #[tokio::test]
-            async fn get_events_with_empty_filter() {
-                let (storage, events) = setup();
-                let sequencer = SeqClient::new(Chain::Goerli).unwrap();
-                let sync_state = Arc::new(SyncState::default());
-                let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
-                let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+            let storage = Storage::in_memory().unwrap();
+            let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+            let sync_state = Arc::new(SyncState::default());
+            let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
+            let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+            let call = Call {
+                calldata: vec![],
+                contract_address: *VALID_CONTRACT_ADDR,
+                entry_point_selector: *VALID_ENTRY_POINT,
+            };
+            let params = rpc_params!(
+                vec![call; MAX_MULTICALL_CALLS + 1],
+                BlockHashOrTag::Tag(Tag::Latest)
+            );
+            let error = client(addr)
+                .request::<Vec<MulticallResult>>("pathfinder_multicall", params)
+                .await
+                .unwrap_err();
+            assert_matches!(
+                error,
+                Error::Call(CallError::Custom(e)) => {
+                    assert_eq!(e.code(), jsonrpsee::types::error::ErrorCode::InvalidParams.code());
+                    assert_eq!(e.message(), format!("Too many calls, supported maximum is {}", MAX_MULTICALL_CALLS));
+                }
+            );
+        }
 
-                let params = rpc_params!(EventFilter {
-                    from_block: None,
-                    to_block: None,
-                    address: None,
-                    keys: vec![],
-                    page_size: NUM_EVENTS,
-                    page_number: 0,
-                });
-                let rpc_result = client(addr)
-                    .request::<GetEventsResult>("starknet_getEvents", params)
-                    .await
-                    .unwrap();
+        #[test]
+        fn result_serialization() {
+            use crate::{core::CallResultValue, rpc::types::reply::MulticallError};
 
-                assert_eq!(
-                    rpc_result,
-                    GetEventsResult {
-                        events,
-                        page_number: 0,
-                        is_last_page: true,
-                    }
-                );
-            }
+            let results = vec![
+                MulticallResult::Result(vec![CallResultValue(
+                    StarkHash::from_hex_str("3").unwrap(),
+                )]),
+                MulticallResult::Error(MulticallError {
+                    code: 20,
+                    message: "Contract not found".to_owned(),
+                }),
+            ];
+            assert_eq!(
+                serde_json::to_value(results).unwrap(),
+                json!([
+                    {"result": ["0x3"]},
+                    {"error": {"code": 20, "message": "Contract not found"}},
+                ])
+            );
+        }
+    }
 
-            #[tokio::test]
-            async fn get_events_with_fully_specified_filter() {
-                let (storage, events) = setup();
-                let sequencer = SeqClient::new(Chain::Goerli).unwrap();
-                let sync_state = Arc::new(SyncState::default());
-                let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
-                let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+    mod estimate_fee {
+        use super::*;
+        use crate::{
+            core::{CallParam, Fee, TransactionVersion},
+            rpc::types::{reply::FeeEstimate, request::Call, BlockHashOrTag, Tag},
+        };
+        use jsonrpsee::types::error::CallError;
+        use web3::types::{H128, H256};
 
-                let expected_event = &events[1];
-                let params = rpc_params!(EventFilter {
-                    from_block: Some(expected_event.block_number),
-                    to_block: Some(expected_event.block_number),
-                    address: Some(expected_event.from_address),
-                    // we're using a key which is present in _all_ events
-                    keys: vec![EventKey(StarkHash::from_hex_str("deadbeef").unwrap())],
-                    page_size: NUM_EVENTS,
-                    page_number: 0,
-                });
-                let rpc_result = client(addr)
-                    .request::<GetEventsResult>("starknet_getEvents", params)
-                    .await
+        #[tokio::test]
+        async fn unsupported_without_call_handling() {
+            let storage = Storage::in_memory().unwrap();
+            let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+            let sync_state = Arc::new(SyncState::default());
+            let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
+            let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+            let params = rpc_params!(
+                Call {
+                    calldata: vec![CallParam::from_hex_str("1234").unwrap()],
+                    contract_address: *VALID_CONTRACT_ADDR,
+                    entry_point_selector: *VALID_ENTRY_POINT,
+                },
+                Vec::<String>::new(),
+                Fee(H128::zero()),
+                TransactionVersion(H256::zero()),
+                BlockHashOrTag::Tag(Tag::Latest)
+            );
+            let error = client(addr)
+                .request::<FeeEstimate>("starknet_estimateFee", params)
+                .await
+                .unwrap_err();
+            assert_matches!(
+                error,
+                Error::Call(CallError::Custom(e)) => assert!(e.message().contains("Unsupported configuration"), "{e:?}")
+            );
+        }
+    }
+
+    mod simulate_transaction {
+        use super::*;
+        use crate::{
+            core::{CallParam, Fee, TransactionVersion},
+            rpc::types::{reply::TransactionTrace, request::Call, BlockHashOrTag, Tag},
+        };
+        use jsonrpsee::types::error::CallError;
+        use web3::types::{H128, H256};
+
+        #[tokio::test]
+        async fn unsupported_without_call_handling() {
+            let storage = Storage::in_memory().unwrap();
+            let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+            let sync_state = Arc::new(SyncState::default());
+            let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
+            let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+            let params = rpc_params!(
+                Call {
+                    calldata: vec![CallParam::from_hex_str("1234").unwrap()],
+                    contract_address: *VALID_CONTRACT_ADDR,
+                    entry_point_selector: *VALID_ENTRY_POINT,
+                },
+                Vec::<String>::new(),
+                Fee(H128::zero()),
+                TransactionVersion(H256::zero()),
+                BlockHashOrTag::Tag(Tag::Latest)
+            );
+            let error = client(addr)
+                .request::<TransactionTrace>("pathfinder_simulateTransaction", params)
+                .await
+                .unwrap_err();
+            assert_matches!(
+                error,
+                Error::Call(CallError::Custom(e)) => assert!(e.message().contains("Unsupported configuration"), "{e:?}")
+            );
+        }
+    }
+
+    mod trace_transaction {
+        use super::*;
+        use crate::rpc::types::reply::TransactionTrace;
+        use jsonrpsee::types::error::{CallError, ErrorCode};
+        use pretty_assertions::assert_eq;
+
+        async fn trace(hash: &[u8]) -> Error {
+            let storage = setup_storage();
+            let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+            let sync_state = Arc::new(SyncState::default());
+            let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
+            let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+            let hash = StarknetTransactionHash(StarkHash::from_be_slice(hash).unwrap());
+            let params = by_name([("transaction_hash", json!(hash))]);
+            client(addr)
+                .request::<TransactionTrace>("pathfinder_traceTransaction", params)
+                .await
+                .unwrap_err()
+        }
+
+        #[tokio::test]
+        async fn invalid_hash() {
+            let error = trace(b"not found").await;
+            assert_eq!(
+                crate::rpc::types::reply::ErrorCode::InvalidTransactionHash,
+                error
+            );
+        }
+
+        #[tokio::test]
+        async fn genesis_transaction() {
+            let error = trace(b"txn 0").await;
+            assert_matches!(
+                error,
+                Error::Call(CallError::Custom(e)) => {
+                    assert_eq!(e.code(), ErrorCode::InvalidParams.code());
+                    assert_eq!(e.message(), "Transactions of the genesis block cannot be traced");
+                }
+            );
+        }
+
+        #[tokio::test]
+        async fn deploy_transaction() {
+            let error = trace(b"txn 1").await;
+            assert_matches!(
+                error,
+                Error::Call(CallError::Custom(e)) => {
+                    assert_eq!(e.code(), ErrorCode::InvalidParams.code());
+                    assert_eq!(e.message(), "Only invoke transactions can be traced");
+                }
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn block_number() {
+        let storage = setup_storage();
+        let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+        let sync_state = Arc::new(SyncState::default());
+        let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
+        let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+        let number = client(addr)
+            .request::<u64>("starknet_blockNumber", rpc_params!())
+            .await
+            .unwrap();
+        assert_eq!(number, 2);
+    }
+
+    #[tokio::test]
+    async fn chain_id() {
+        use futures::stream::StreamExt;
+
+        assert_eq!(
+            [Chain::Goerli, Chain::Mainnet]
+                .iter()
+                .map(|set_chain| async {
+                    let storage = Storage::in_memory().unwrap();
+                    let sequencer = SeqClient::new(*set_chain).unwrap();
+                    let sync_state = Arc::new(SyncState::default());
+                    let api = RpcApi::new(storage, sequencer, *set_chain, sync_state);
+                    let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+                    let params = rpc_params!();
+                    client(addr)
+                        .request::<String>("starknet_chainId", params)
+                        .await
+                        .unwrap()
+                })
+                .collect::<futures::stream::FuturesOrdered<_>>()
+                .collect::<Vec<_>>()
+                .await,
+            vec![
+                format!("0x{}", hex::encode("SN_GOERLI")),
+                format!("0x{}", hex::encode("SN_MAIN")),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn pending_transactions() {
+        let storage = Storage::in_memory().unwrap();
+        let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+        let sync_state = Arc::new(SyncState::default());
+        let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
+        let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+        client(addr)
+            .request::<()>("starknet_pendingTransactions", rpc_params!())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn protocol_version() {
+        let storage = Storage::in_memory().unwrap();
+        let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+        let sync_state = Arc::new(SyncState::default());
+        let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
+        let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+        client(addr)
+            .request::<StarknetProtocolVersion>("starknet_protocolVersion", rpc_params!())
+            .await
+            .unwrap();
+    }
+
+    mod syncing {
+        use crate::rpc::types::reply::{syncing, Syncing};
+        use pretty_assertions::assert_eq;
+
+        use super::*;
+
+        #[tokio::test]
+        async fn not_syncing() {
+            let storage = setup_storage();
+            let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+            let sync_state = Arc::new(SyncState::default());
+            let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
+            let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+            let syncing = client(addr)
+                .request::<Syncing>("starknet_syncing", rpc_params!())
+                .await
+                .unwrap();
+
+            assert_eq!(syncing, Syncing::False(false));
+        }
+
+        #[tokio::test]
+        async fn syncing() {
+            use crate::rpc::types::reply::syncing::NumberedBlock;
+            let expected = Syncing::Status(syncing::Status {
+                starting: NumberedBlock::from(("abbacd", 1)),
+                current: NumberedBlock::from(("abbace", 2)),
+                highest: NumberedBlock::from(("abbacf", 3)),
+            });
+
+            let storage = setup_storage();
+            let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+            let sync_state = Arc::new(SyncState::default());
+            *sync_state.status.write().await = expected.clone();
+            let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
+            let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+            let syncing = client(addr)
+                .request::<Syncing>("starknet_syncing", rpc_params!())
+                .await
+                .unwrap();
+
+            assert_eq!(syncing, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn sync_metrics() {
+        use crate::state::SyncMetrics;
+        use std::sync::atomic::Ordering;
+
+        let storage = setup_storage();
+        let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+        let sync_state = Arc::new(SyncState::default());
+        sync_state.class_hash_mismatches.store(2, Ordering::Relaxed);
+        let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
+        let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+        let metrics = client(addr)
+            .request::<SyncMetrics>("pathfinder_getSyncMetrics", rpc_params!())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            metrics,
+            SyncMetrics {
+                class_hash_mismatches: 2
+            }
+        );
+    }
+
+    mod events {
+        use super::*;
+
+        use super::types::reply::{EmittedEvent, GetEventsResult};
+        use crate::sequencer::reply::transaction;
+
+        const NUM_BLOCKS: usize = 4;
+        const TRANSACTIONS_PER_BLOCK: usize = 10;
+        const EVENTS_PER_BLOCK: usize = TRANSACTIONS_PER_BLOCK;
+        const NUM_TRANSACTIONS: usize = NUM_BLOCKS * TRANSACTIONS_PER_BLOCK;
+        const NUM_EVENTS: usize = NUM_BLOCKS * EVENTS_PER_BLOCK;
+
+        fn create_transactions_and_receipts(
+        ) -> [(transaction::Transaction, transaction::Receipt); NUM_TRANSACTIONS] {
+            let transactions = (0..NUM_TRANSACTIONS).map(|i| transaction::Transaction {
+                calldata: None,
+                class_hash: None,
+                constructor_calldata: None,
+                contract_address: Some(ContractAddress(
+                    StarkHash::from_hex_str(&"2".repeat(i + 3)).unwrap(),
+                )),
+                contract_address_salt: None,
+                entry_point_type: None,
+                entry_point_selector: None,
+                max_fee: None,
+                nonce: None,
+                sender_address: None,
+                signature: None,
+                transaction_hash: StarknetTransactionHash(
+                    StarkHash::from_hex_str(&"f".repeat(i + 3)).unwrap(),
+                ),
+                r#type: transaction::Type::InvokeFunction,
+                version: None,
+            });
+            let receipts = (0..NUM_TRANSACTIONS).map(|i| transaction::Receipt {
+                actual_fee: None,
+                events: vec![transaction::Event {
+                    from_address: ContractAddress(
+                        StarkHash::from_hex_str(&"2".repeat(i + 3)).unwrap(),
+                    ),
+                    data: vec![EventData(
+                        StarkHash::from_hex_str(&"c".repeat(i + 3)).unwrap(),
+                    )],
+                    keys: vec![
+                        EventKey(StarkHash::from_hex_str(&"d".repeat(i + 3)).unwrap()),
+                        EventKey(StarkHash::from_hex_str("deadbeef").unwrap()),
+                    ],
+                }],
+                execution_resources: transaction::ExecutionResources {
+                    builtin_instance_counter:
+                        transaction::execution_resources::BuiltinInstanceCounter::Empty(
+                            transaction::execution_resources::EmptyBuiltinInstanceCounter {},
+                        ),
+                    n_steps: i as u64 + 987,
+                    n_memory_holes: i as u64 + 1177,
+                },
+                l1_to_l2_consumed_message: None,
+                l2_to_l1_messages: Vec::new(),
+                transaction_hash: StarknetTransactionHash(
+                    StarkHash::from_hex_str(&"e".repeat(i + 3)).unwrap(),
+                ),
+                transaction_index: StarknetTransactionIndex(i as u64 + 2311),
+            });
+
+            transactions
+                .into_iter()
+                .zip(receipts)
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap()
+        }
+
+        fn setup() -> (Storage, Vec<EmittedEvent>) {
+            let storage = Storage::in_memory().unwrap();
+            let connection = storage.connection().unwrap();
+
+            let blocks = crate::storage::test_utils::create_blocks::<NUM_BLOCKS>();
+            let transactions_and_receipts = create_transactions_and_receipts();
+
+            for (i, block) in blocks.iter().enumerate() {
+                StarknetBlocksTable::insert(&connection, block).unwrap();
+                StarknetTransactionsTable::upsert(
+                    &connection,
+                    block.hash,
+                    block.number,
+                    &transactions_and_receipts
+                        [i * TRANSACTIONS_PER_BLOCK..(i + 1) * TRANSACTIONS_PER_BLOCK],
+                )
+                .unwrap();
+            }
+
+            let events = transactions_and_receipts
+                .iter()
+                .enumerate()
+                .map(|(i, (txn, receipt))| {
+                    let event = &receipt.events[0];
+                    let block = &blocks[i / TRANSACTIONS_PER_BLOCK];
+
+                    EmittedEvent {
+                        data: event.data.clone(),
+                        from_address: event.from_address,
+                        keys: event.keys.clone(),
+                        block_hash: block.hash,
+                        block_number: block.number,
+                        transaction_hash: txn.transaction_hash,
+                    }
+                })
+                .collect();
+
+            (storage, events)
+        }
+
+        mod positional_args {
+            use super::*;
+
+            use pretty_assertions::assert_eq;
+
+            #[tokio::test]
+            async fn get_events_with_empty_filter() {
+                let (storage, events) = setup();
+                let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+                let sync_state = Arc::new(SyncState::default());
+                let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
+                let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+
+                let params = rpc_params!(EventFilter {
+                    from_block: None,
+                    to_block: None,
+                    address: None,
+                    keys: vec![],
+                    page_size: NUM_EVENTS,
+                    page_number: 0,
+                });
+                let rpc_result = client(addr)
+                    .request::<GetEventsResult>("starknet_getEvents", params)
+                    .await
+                    .unwrap();
+
+                assert_eq!(
+                    rpc_result,
+                    GetEventsResult {
+                        events,
+                        page_number: 0,
+                        is_last_page: true,
+                    }
+                );
+            }
+
+            #[tokio::test]
+            async fn get_events_with_fully_specified_filter() {
+                let (storage, events) = setup();
+                let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+                let sync_state = Arc::new(SyncState::default());
+                let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
+                let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+
+                let expected_event = &events[1];
+                let params = rpc_params!(EventFilter {
+                    from_block: Some(expected_event.block_number),
+                    to_block: Some(expected_event.block_number),
+                    address: Some(expected_event.from_address),
+                    // we're using a key which is present in _all_ events
+                    keys: vec![EventKey(StarkHash::from_hex_str("deadbeef").unwrap())],
+                    page_size: NUM_EVENTS,
+                    page_number: 0,
+                });
+                let rpc_result = client(addr)
+                    .request::<GetEventsResult>("starknet_getEvents", params)
+                    .await
+                    .unwrap();
+
+                assert_eq!(
+                    rpc_result,
+                    GetEventsResult {
+                        events: vec![expected_event.clone()],
+                        page_number: 0,
+                        is_last_page: true,
+                    }
+                );
+            }
+
+            #[tokio::test]
+            async fn get_events_by_block() {
+                let (storage, events) = setup();
+                let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+                let sync_state = Arc::new(SyncState::default());
+                let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
+                let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+
+                const BLOCK_NUMBER: usize = 2;
+                let params = rpc_params!(EventFilter {
+                    from_block: Some(StarknetBlockNumber(BLOCK_NUMBER as u64)),
+                    to_block: Some(StarknetBlockNumber(BLOCK_NUMBER as u64)),
+                    address: None,
+                    keys: vec![],
+                    page_size: NUM_EVENTS,
+                    page_number: 0,
+                });
+                let rpc_result = client(addr)
+                    .request::<GetEventsResult>("starknet_getEvents", params)
+                    .await
+                    .unwrap();
+
+                let expected_events =
+                    &events[EVENTS_PER_BLOCK * BLOCK_NUMBER..EVENTS_PER_BLOCK * (BLOCK_NUMBER + 1)];
+                assert_eq!(
+                    rpc_result,
+                    GetEventsResult {
+                        events: expected_events.to_vec(),
+                        page_number: 0,
+                        is_last_page: true,
+                    }
+                );
+            }
+
+            #[tokio::test]
+            async fn get_events_with_invalid_page_size() {
+                let (storage, _events) = setup();
+                let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+                let sync_state = Arc::new(SyncState::default());
+                let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
+                let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+
+                let params = rpc_params!(EventFilter {
+                    from_block: None,
+                    to_block: None,
+                    address: None,
+                    keys: vec![],
+                    page_size: crate::storage::StarknetEventsTable::PAGE_SIZE_LIMIT + 1,
+                    page_number: 0,
+                });
+                let error = client(addr)
+                    .request::<GetEventsResult>("starknet_getEvents", params)
+                    .await
+                    .unwrap_err();
+
+                assert_eq!(crate::rpc::types::reply::ErrorCode::PageSizeTooBig, error);
+            }
+
+            #[tokio::test]
+            async fn get_events_by_key_with_paging() {
+                let (storage, events) = setup();
+                let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+                let sync_state = Arc::new(SyncState::default());
+                let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
+                let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+
+                let expected_events = &events[27..32];
+                let keys_for_expected_events: Vec<_> =
+                    expected_events.iter().map(|e| e.keys[0]).collect();
+
+                let params = rpc_params!(EventFilter {
+                    from_block: None,
+                    to_block: None,
+                    address: None,
+                    keys: keys_for_expected_events.clone(),
+                    page_size: 2,
+                    page_number: 0,
+                });
+                let rpc_result = client(addr)
+                    .request::<GetEventsResult>("starknet_getEvents", params)
+                    .await
+                    .unwrap();
+                assert_eq!(
+                    rpc_result,
+                    GetEventsResult {
+                        events: expected_events[..2].to_vec(),
+                        page_number: 0,
+                        is_last_page: false,
+                    }
+                );
+
+                let params = rpc_params!(EventFilter {
+                    from_block: None,
+                    to_block: None,
+                    address: None,
+                    keys: keys_for_expected_events.clone(),
+                    page_size: 2,
+                    page_number: 1,
+                });
+                let rpc_result = client(addr)
+                    .request::<GetEventsResult>("starknet_getEvents", params)
+                    .await
                     .unwrap();
+                assert_eq!(
+                    rpc_result,
+                    GetEventsResult {
+                        events: expected_events[2..4].to_vec(),
+                        page_number: 1,
+                        is_last_page: false,
+                    }
+                );
 
+                let params = rpc_params!(EventFilter {
+                    from_block: None,
+                    to_block: None,
+                    address: None,
+                    keys: keys_for_expected_events.clone(),
+                    page_size: 2,
+                    page_number: 2,
+                });
+                let rpc_result = client(addr)
+                    .request::<GetEventsResult>("starknet_getEvents", params)
+                    .await
+                    .unwrap();
                 assert_eq!(
                     rpc_result,
                     GetEventsResult {
-                        events: vec![expected_event.clone()],
-                        page_number: 0,
+                        events: expected_events[4..].to_vec(),
+                        page_number: 2,
+                        is_last_page: true,
+                    }
+                );
+
+                // nonexistent page
+                let params = rpc_params!(EventFilter {
+                    from_block: None,
+                    to_block: None,
+                    address: None,
+                    keys: keys_for_expected_events.clone(),
+                    page_size: 2,
+                    page_number: 3,
+                });
+                let rpc_result = client(addr)
+                    .request::<GetEventsResult>("starknet_getEvents", params)
+                    .await
+                    .unwrap();
+                assert_eq!(
+                    rpc_result,
+                    GetEventsResult {
+                        events: vec![],
+                        page_number: 3,
                         is_last_page: true,
                     }
                 );
             }
+        }
+
+        mod named_args {
+            use super::*;
+
+            use pretty_assertions::assert_eq;
 
             #[tokio::test]
-            async fn get_events_by_block() {
+            async fn get_events_with_empty_filter() {
                 let (storage, events) = setup();
                 let sequencer = SeqClient::new(Chain::Goerli).unwrap();
                 let sync_state = Arc::new(SyncState::default());
                 let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
                 let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
 
-                const BLOCK_NUMBER: usize = 2;
-                let params = rpc_params!(EventFilter {
-                    from_block: Some(StarknetBlockNumber(BLOCK_NUMBER as u64)),
-                    to_block: Some(StarknetBlockNumber(BLOCK_NUMBER as u64)),
-                    address: None,
-                    keys: vec![],
-                    page_size: NUM_EVENTS,
-                    page_number: 0,
-                });
+                let params =
+                    by_name([("filter", json!({"page_size": NUM_EVENTS, "page_number": 0}))]);
                 let rpc_result = client(addr)
                     .request::<GetEventsResult>("starknet_getEvents", params)
                     .await
                     .unwrap();
 
-                let expected_events =
-                    &events[EVENTS_PER_BLOCK * BLOCK_NUMBER..EVENTS_PER_BLOCK * (BLOCK_NUMBER + 1)];
                 assert_eq!(
                     rpc_result,
                     GetEventsResult {
-                        events: expected_events.to_vec(),
+                        events,
                         page_number: 0,
                         is_last_page: true,
                     }
@@ -2416,192 +3571,609 @@ mod tests {
             }
 
             #[tokio::test]
-            async fn get_events_with_invalid_page_size() {
-                let (storage, _events) = setup();
+            async fn get_events_with_fully_specified_filter() {
+                let (storage, events) = setup();
                 let sequencer = SeqClient::new(Chain::Goerli).unwrap();
                 let sync_state = Arc::new(SyncState::default());
                 let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
                 let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
 
-                let params = rpc_params!(EventFilter {
-                    from_block: None,
-                    to_block: None,
-                    address: None,
-                    keys: vec![],
-                    page_size: crate::storage::StarknetEventsTable::PAGE_SIZE_LIMIT + 1,
-                    page_number: 0,
-                });
-                let error = client(addr)
-                    .request::<GetEventsResult>("starknet_getEvents", params)
-                    .await
-                    .unwrap_err();
+                let expected_event = &events[1];
+                let params = by_name([(
+                    "filter",
+                    json!({
+                        "fromBlock": expected_event.block_number.0,
+                        "toBlock": expected_event.block_number.0,
+                        "address": expected_event.from_address,
+                        "keys": [expected_event.keys[0]],
+                        "page_size": NUM_EVENTS,
+                        "page_number": 0,
+                    }),
+                )]);
+                let rpc_result = client(addr)
+                    .request::<GetEventsResult>("starknet_getEvents", params)
+                    .await
+                    .unwrap();
+
+                assert_eq!(
+                    rpc_result,
+                    GetEventsResult {
+                        events: vec![expected_event.clone()],
+                        page_number: 0,
+                        is_last_page: true,
+                    }
+                );
+            }
+        }
+    }
+
+    mod get_transactions_by_address {
+        use super::*;
+
+        use super::types::reply::{AddressTransaction, GetTransactionsByAddressResult};
+        use crate::storage::TransactionsContinuationToken;
+        use pretty_assertions::assert_eq;
+
+        #[tokio::test]
+        async fn paging() {
+            let storage = setup_storage();
+            let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+            let sync_state = Arc::new(SyncState::default());
+            let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
+            let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+
+            let contract1_addr = ContractAddress(StarkHash::from_be_slice(b"contract 1").unwrap());
+            let block1_hash = StarknetBlockHash(StarkHash::from_be_slice(b"block 1").unwrap());
+            let latest_hash = StarknetBlockHash(StarkHash::from_be_slice(b"latest").unwrap());
+
+            let params = by_name([(
+                "filter",
+                json!({ "address": contract1_addr, "page_size": 2 }),
+            )]);
+            let rpc_result = client(addr)
+                .request::<GetTransactionsByAddressResult>(
+                    "pathfinder_getTransactionsByAddress",
+                    params,
+                )
+                .await
+                .unwrap();
+            assert_eq!(
+                rpc_result,
+                GetTransactionsByAddressResult {
+                    transactions: vec![
+                        AddressTransaction {
+                            block_hash: block1_hash,
+                            block_number: StarknetBlockNumber(1),
+                            transaction_index: StarknetTransactionIndex(0),
+                            transaction_hash: StarknetTransactionHash(
+                                StarkHash::from_be_slice(b"txn 1").unwrap()
+                            ),
+                        },
+                        AddressTransaction {
+                            block_hash: block1_hash,
+                            block_number: StarknetBlockNumber(1),
+                            transaction_index: StarknetTransactionIndex(1),
+                            transaction_hash: StarknetTransactionHash(
+                                StarkHash::from_be_slice(b"txn 2").unwrap()
+                            ),
+                        },
+                    ],
+                    continuation_token: Some(TransactionsContinuationToken {
+                        block_number: StarknetBlockNumber(2),
+                        transaction_index: StarknetTransactionIndex(0),
+                    }),
+                }
+            );
+
+            let params = by_name([(
+                "filter",
+                json!({ "address": contract1_addr, "page_size": 2, "continuation_token": "2-0" }),
+            )]);
+            let rpc_result = client(addr)
+                .request::<GetTransactionsByAddressResult>(
+                    "pathfinder_getTransactionsByAddress",
+                    params,
+                )
+                .await
+                .unwrap();
+            assert_eq!(
+                rpc_result,
+                GetTransactionsByAddressResult {
+                    transactions: vec![AddressTransaction {
+                        block_hash: latest_hash,
+                        block_number: StarknetBlockNumber(2),
+                        transaction_index: StarknetTransactionIndex(0),
+                        transaction_hash: StarknetTransactionHash(
+                            StarkHash::from_be_slice(b"txn 3").unwrap()
+                        ),
+                    }],
+                    continuation_token: None,
+                }
+            );
+        }
+
+        #[tokio::test]
+        async fn page_size_too_big() {
+            let storage = setup_storage();
+            let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+            let sync_state = Arc::new(SyncState::default());
+            let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
+            let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+
+            let params = by_name([(
+                "filter",
+                json!({
+                    "address": "0x1",
+                    "page_size": crate::storage::PAGE_SIZE_LIMIT + 1
+                }),
+            )]);
+            let error = client(addr)
+                .request::<GetTransactionsByAddressResult>(
+                    "pathfinder_getTransactionsByAddress",
+                    params,
+                )
+                .await
+                .unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                jsonrpsee::core::Error::from(crate::storage::PageSizeTooBig(
+                    crate::storage::PAGE_SIZE_LIMIT
+                ))
+                .to_string()
+            );
+        }
+    }
+
+    mod get_storage_history {
+        use super::*;
+
+        use super::types::reply::{GetStorageHistoryResult, StorageChange};
+        use crate::{
+            core::StorageValue,
+            ethereum::state_update::{ContractUpdate, StorageUpdate},
+            storage::ContractStorageHistoryTable,
+        };
+        use pretty_assertions::assert_eq;
+
+        #[tokio::test]
+        async fn paging() {
+            let storage = setup_storage();
+            let contract_address =
+                ContractAddress(StarkHash::from_be_slice(b"contract 1").unwrap());
+            let key = StorageAddress(StarkHash::from_be_slice(b"storage addr 0").unwrap());
+            let values = (0..3u8)
+                .map(|i| StorageValue(StarkHash::from_be_slice(&[i + 1]).unwrap()))
+                .collect::<Vec<_>>();
+
+            let connection = storage.connection().unwrap();
+            for (i, value) in values.iter().enumerate() {
+                let update = ContractUpdate {
+                    address: contract_address,
+                    storage_updates: vec![StorageUpdate {
+                        address: key,
+                        value: *value,
+                    }],
+                };
+                ContractStorageHistoryTable::insert(
+                    &connection,
+                    StarknetBlockNumber(i as u64),
+                    &update,
+                )
+                .unwrap();
+            }
+
+            let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+            let sync_state = Arc::new(SyncState::default());
+            let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
+            let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+
+            let params = by_name([(
+                "filter",
+                json!({ "address": contract_address, "key": key, "fromBlock": 1, "page_size": 1 }),
+            )]);
+            let rpc_result = client(addr)
+                .request::<GetStorageHistoryResult>("pathfinder_getStorageHistory", params)
+                .await
+                .unwrap();
+            assert_eq!(
+                rpc_result,
+                GetStorageHistoryResult {
+                    changes: vec![StorageChange {
+                        block_hash: StarknetBlockHash(
+                            StarkHash::from_be_slice(b"block 1").unwrap()
+                        ),
+                        block_number: StarknetBlockNumber(1),
+                        value: values[1],
+                    }],
+                    history_start: StarknetBlockNumber(0),
+                    continuation_token: Some(StarknetBlockNumber(2)),
+                }
+            );
+
+            let params = by_name([(
+                "filter",
+                json!({
+                    "address": contract_address,
+                    "key": key,
+                    "fromBlock": 1,
+                    "page_size": 1,
+                    "continuation_token": 2
+                }),
+            )]);
+            let rpc_result = client(addr)
+                .request::<GetStorageHistoryResult>("pathfinder_getStorageHistory", params)
+                .await
+                .unwrap();
+            assert_eq!(
+                rpc_result,
+                GetStorageHistoryResult {
+                    changes: vec![StorageChange {
+                        block_hash: StarknetBlockHash(StarkHash::from_be_slice(b"latest").unwrap()),
+                        block_number: StarknetBlockNumber(2),
+                        value: values[2],
+                    }],
+                    history_start: StarknetBlockNumber(0),
+                    continuation_token: None,
+                }
+            );
+        }
+
+        #[tokio::test]
+        async fn range_before_history_start() {
+            let storage = setup_storage();
+            let contract_address =
+                ContractAddress(StarkHash::from_be_slice(b"contract 1").unwrap());
+            let key = StorageAddress(StarkHash::from_be_slice(b"storage addr 0").unwrap());
+            let value = StorageValue(StarkHash::from_be_slice(b"value").unwrap());
+
+            let connection = storage.connection().unwrap();
+            // as if the node had synced the first two blocks before the history was recorded
+            connection
+                .execute(
+                    "UPDATE contract_storage_history_start SET block_number = 2",
+                    [],
+                )
+                .unwrap();
+            let update = ContractUpdate {
+                address: contract_address,
+                storage_updates: vec![StorageUpdate {
+                    address: key,
+                    value,
+                }],
+            };
+            ContractStorageHistoryTable::insert(&connection, StarknetBlockNumber(2), &update)
+                .unwrap();
+
+            let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+            let sync_state = Arc::new(SyncState::default());
+            let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
+            let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+
+            let params = by_name([(
+                "filter",
+                json!({ "address": contract_address, "key": key, "fromBlock": 0, "toBlock": 1, "page_size": 10 }),
+            )]);
+            let rpc_result = client(addr)
+                .request::<GetStorageHistoryResult>("pathfinder_getStorageHistory", params)
+                .await
+                .unwrap();
+            // no changes are known, which is not the same as there having been none
+            assert_eq!(
+                rpc_result,
+                GetStorageHistoryResult {
+                    changes: vec![],
+                    history_start: StarknetBlockNumber(2),
+                    continuation_token: None,
+                }
+            );
+        }
+    }
+
+    mod get_storage_var {
+        use super::*;
+
+        use super::types::reply::ErrorCode;
+        use crate::core::StorageValue;
+        use jsonrpsee::types::error::CallError;
+        use pretty_assertions::assert_eq;
+
+        #[tokio::test]
+        async fn struct_members() {
+            let storage = setup_storage();
+            let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+            let sync_state = Arc::new(SyncState::default());
+            let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
+            let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+            let contract_address =
+                ContractAddress(StarkHash::from_be_slice(b"contract 1").unwrap());
+
+            let params = by_name([
+                ("contract_address", json!(contract_address)),
+                ("var_name", json!("ERC20_allowances")),
+                ("keys", json!([StarkHash::ONE, StarkHash::from(2u64)])),
+                ("size", json!(2)),
+                ("block_id", json!("latest")),
+            ]);
+            let values = client(addr)
+                .request::<Vec<StorageValue>>("pathfinder_getStorageVar", params)
+                .await
+                .unwrap();
+            assert_eq!(values, vec![StorageValue(StarkHash::ZERO); 2]);
+        }
+
+        #[tokio::test]
+        async fn invalid_size() {
+            let storage = setup_storage();
+            let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+            let sync_state = Arc::new(SyncState::default());
+            let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
+            let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+            let contract_address =
+                ContractAddress(StarkHash::from_be_slice(b"contract 1").unwrap());
+
+            for size in [0, StorageAddress::MAX_STORAGE_ITEM_SIZE + 1] {
+                let params = by_name([
+                    ("contract_address", json!(contract_address)),
+                    ("var_name", json!("ERC20_name")),
+                    ("size", json!(size)),
+                    ("block_id", json!("latest")),
+                ]);
+                let error = client(addr)
+                    .request::<Vec<StorageValue>>("pathfinder_getStorageVar", params)
+                    .await
+                    .unwrap_err();
+                assert_matches!(
+                    error,
+                    Error::Call(CallError::Custom(e)) => {
+                        assert_eq!(e.code(), jsonrpsee::types::error::ErrorCode::InvalidParams.code());
+                        assert_eq!(e.message(), "Size must be between 1 and 256");
+                    }
+                );
+            }
+        }
+
+        #[tokio::test]
+        async fn contract_not_found() {
+            let storage = setup_storage();
+            let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+            let sync_state = Arc::new(SyncState::default());
+            let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
+            let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+
+            let params = by_name([
+                ("contract_address", json!(*INVALID_CONTRACT_ADDR)),
+                ("var_name", json!("ERC20_name")),
+                ("block_id", json!("latest")),
+            ]);
+            let error = client(addr)
+                .request::<Vec<StorageValue>>("pathfinder_getStorageVar", params)
+                .await
+                .unwrap_err();
+            assert_eq!(ErrorCode::ContractNotFound, error);
+        }
+    }
+
+    mod compute_contract_address {
+        use super::*;
 
-                assert_eq!(crate::rpc::types::reply::ErrorCode::PageSizeTooBig, error);
-            }
+        use pretty_assertions::assert_eq;
 
-            #[tokio::test]
-            async fn get_events_by_key_with_paging() {
-                let (storage, events) = setup();
-                let sequencer = SeqClient::new(Chain::Goerli).unwrap();
-                let sync_state = Arc::new(SyncState::default());
-                let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
-                let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+        #[tokio::test]
+        async fn deploy_transaction() {
+            let storage = setup_storage();
+            let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+            let sync_state = Arc::new(SyncState::default());
+            let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
+            let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
 
-                let expected_events = &events[27..32];
-                let keys_for_expected_events: Vec<_> =
-                    expected_events.iter().map(|e| e.keys[0]).collect();
+            // DEPLOY transaction 0x3d7623443283d9a0cec946492db78b06d57642a551745ddfac8d3f1f4fcc2a8 on Goerli
+            let params = by_name([
+                (
+                    "contract_address_salt",
+                    json!("0x655a594122f68f5e821834e606e1243b249a88555fac2d548f7acbee7863f62"),
+                ),
+                (
+                    "class_hash",
+                    json!("0x3523d31a077d891b4d888f9d3c7d33bdac2c0a06f89c08307a7f7b68f681c98"),
+                ),
+                (
+                    "constructor_calldata",
+                    json!([
+                        "0x734d2849eb47e10c59e5a433d425675849cb37338b1d7c4c4afb1e0ca42133",
+                        "0xffad0128dbd859ef97a246a2d2c00680dedc8d850ff9b6ebcc8b94ee9625bb"
+                    ]),
+                ),
+            ]);
+            let address = client(addr)
+                .request::<ContractAddress>("pathfinder_computeContractAddress", params)
+                .await
+                .unwrap();
+            assert_eq!(
+                address,
+                ContractAddress(
+                    StarkHash::from_hex_str(
+                        "0x54c6883e459baeac4a9052ee109b86b9f81adbcdcb1f65a05dceec4c34d5cf9"
+                    )
+                    .unwrap()
+                )
+            );
+        }
 
-                let params = rpc_params!(EventFilter {
-                    from_block: None,
-                    to_block: None,
-                    address: None,
-                    keys: keys_for_expected_events.clone(),
-                    page_size: 2,
-                    page_number: 0,
-                });
-                let rpc_result = client(addr)
-                    .request::<GetEventsResult>("starknet_getEvents", params)
-                    .await
-                    .unwrap();
-                assert_eq!(
-                    rpc_result,
-                    GetEventsResult {
-                        events: expected_events[..2].to_vec(),
-                        page_number: 0,
-                        is_last_page: false,
-                    }
-                );
+        #[tokio::test]
+        async fn deployer_address() {
+            let storage = setup_storage();
+            let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+            let sync_state = Arc::new(SyncState::default());
+            let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
+            let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
 
-                let params = rpc_params!(EventFilter {
-                    from_block: None,
-                    to_block: None,
-                    address: None,
-                    keys: keys_for_expected_events.clone(),
-                    page_size: 2,
-                    page_number: 1,
-                });
-                let rpc_result = client(addr)
-                    .request::<GetEventsResult>("starknet_getEvents", params)
-                    .await
-                    .unwrap();
-                assert_eq!(
-                    rpc_result,
-                    GetEventsResult {
-                        events: expected_events[2..4].to_vec(),
-                        page_number: 1,
-                        is_last_page: false,
-                    }
-                );
+            let params = by_name([
+                (
+                    "contract_address_salt",
+                    json!("0x655a594122f68f5e821834e606e1243b249a88555fac2d548f7acbee7863f62"),
+                ),
+                (
+                    "class_hash",
+                    json!("0x3523d31a077d891b4d888f9d3c7d33bdac2c0a06f89c08307a7f7b68f681c98"),
+                ),
+                ("deployer_address", json!("0x1234")),
+            ]);
+            let address = client(addr)
+                .request::<ContractAddress>("pathfinder_computeContractAddress", params)
+                .await
+                .unwrap();
+            // cairo-lang's calculate_contract_address_from_hash
+            assert_eq!(
+                address,
+                ContractAddress(
+                    StarkHash::from_hex_str(
+                        "0x1cce67ab501af8371429f44b2e691b3434275a6a3b286ee6ba3c5a2d22de424"
+                    )
+                    .unwrap()
+                )
+            );
+        }
+    }
 
-                let params = rpc_params!(EventFilter {
-                    from_block: None,
-                    to_block: None,
-                    address: None,
-                    keys: keys_for_expected_events.clone(),
-                    page_size: 2,
-                    page_number: 2,
-                });
-                let rpc_result = client(addr)
-                    .request::<GetEventsResult>("starknet_getEvents", params)
-                    .await
-                    .unwrap();
-                assert_eq!(
-                    rpc_result,
-                    GetEventsResult {
-                        events: expected_events[4..].to_vec(),
-                        page_number: 2,
-                        is_last_page: true,
-                    }
-                );
+    mod get_contract_deployment {
+        use super::*;
 
-                // nonexistent page
-                let params = rpc_params!(EventFilter {
-                    from_block: None,
-                    to_block: None,
-                    address: None,
-                    keys: keys_for_expected_events.clone(),
-                    page_size: 2,
-                    page_number: 3,
-                });
-                let rpc_result = client(addr)
-                    .request::<GetEventsResult>("starknet_getEvents", params)
-                    .await
-                    .unwrap();
-                assert_eq!(
-                    rpc_result,
-                    GetEventsResult {
-                        events: vec![],
-                        page_number: 3,
-                        is_last_page: true,
-                    }
-                );
-            }
+        use super::types::reply::{ContractDeployment, ErrorCode};
+        use pretty_assertions::assert_eq;
+
+        #[tokio::test]
+        async fn deployed() {
+            let storage = setup_storage();
+            let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+            let sync_state = Arc::new(SyncState::default());
+            let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
+            let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+            let contract_address =
+                ContractAddress(StarkHash::from_be_slice(b"contract 1").unwrap());
+
+            let params = by_name([("contract_address", json!(contract_address))]);
+            let deployment = client(addr)
+                .request::<ContractDeployment>("pathfinder_getContractDeployment", params)
+                .await
+                .unwrap();
+            assert_eq!(
+                deployment,
+                ContractDeployment {
+                    contract_address,
+                    class_hash: ClassHash(StarkHash::from_be_slice(b"class 1 hash").unwrap()),
+                    block_hash: StarknetBlockHash(StarkHash::from_be_slice(b"block 1").unwrap()),
+                    block_number: StarknetBlockNumber(1),
+                    transaction_hash: Some(StarknetTransactionHash(
+                        StarkHash::from_be_slice(b"txn 1").unwrap()
+                    )),
+                }
+            );
         }
 
-        mod named_args {
-            use super::*;
+        #[tokio::test]
+        async fn not_found() {
+            let storage = setup_storage();
+            let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+            let sync_state = Arc::new(SyncState::default());
+            let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
+            let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
 
-            use pretty_assertions::assert_eq;
+            let params = by_name([("contract_address", json!(*INVALID_CONTRACT_ADDR))]);
+            let error = client(addr)
+                .request::<ContractDeployment>("pathfinder_getContractDeployment", params)
+                .await
+                .unwrap_err();
+            assert_eq!(ErrorCode::ContractNotFound, error);
+        }
+    }
 
-            #[tokio::test]
-            async fn get_events_with_empty_filter() {
-                let (storage, events) = setup();
-                let sequencer = SeqClient::new(Chain::Goerli).unwrap();
-                let sync_state = Arc::new(SyncState::default());
-                let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
-                let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+    mod get_contracts_by_class_hash {
+        use super::*;
 
-                let params =
-                    by_name([("filter", json!({"page_size": NUM_EVENTS, "page_number": 0}))]);
-                let rpc_result = client(addr)
-                    .request::<GetEventsResult>("starknet_getEvents", params)
-                    .await
-                    .unwrap();
+        use super::types::reply::{ContractDeployment, GetContractsByClassHashResult};
+        use pretty_assertions::assert_eq;
 
-                assert_eq!(
-                    rpc_result,
-                    GetEventsResult {
-                        events,
-                        page_number: 0,
-                        is_last_page: true,
-                    }
-                );
-            }
+        #[tokio::test]
+        async fn paging() {
+            let storage = setup_storage();
+            let class_hash = ClassHash(StarkHash::from_be_slice(b"class 0 hash").unwrap());
+            let genesis_hash = StarknetBlockHash(StarkHash::from_be_slice(b"genesis").unwrap());
+            let latest_hash = StarknetBlockHash(StarkHash::from_be_slice(b"latest").unwrap());
 
-            #[tokio::test]
-            async fn get_events_with_fully_specified_filter() {
-                let (storage, events) = setup();
-                let sequencer = SeqClient::new(Chain::Goerli).unwrap();
-                let sync_state = Arc::new(SyncState::default());
-                let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
-                let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
+            // A second contract of the same class, deployed without a deploy transaction.
+            let other_address = ContractAddress(StarkHash::from_be_slice(b"contract 2").unwrap());
+            let connection = storage.connection().unwrap();
+            ContractDeploymentsTable::insert(
+                &connection,
+                StarknetBlockNumber(2),
+                other_address,
+                class_hash,
+                None,
+            )
+            .unwrap();
 
-                let expected_event = &events[1];
-                let params = by_name([(
-                    "filter",
-                    json!({
-                        "fromBlock": expected_event.block_number.0,
-                        "toBlock": expected_event.block_number.0,
-                        "address": expected_event.from_address,
-                        "keys": [expected_event.keys[0]],
-                        "page_size": NUM_EVENTS,
-                        "page_number": 0,
-                    }),
-                )]);
-                let rpc_result = client(addr)
-                    .request::<GetEventsResult>("starknet_getEvents", params)
-                    .await
-                    .unwrap();
+            let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+            let sync_state = Arc::new(SyncState::default());
+            let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state);
+            let (__handle, addr) = run_server(*LOCALHOST, api).await.unwrap();
 
-                assert_eq!(
-                    rpc_result,
-                    GetEventsResult {
-                        events: vec![expected_event.clone()],
-                        page_number: 0,
-                        is_last_page: true,
-                    }
-                );
-            }
+            let params = by_name([(
+                "filter",
+                json!({ "class_hash": class_hash, "page_size": 1 }),
+            )]);
+            let rpc_result = client(addr)
+                .request::<GetContractsByClassHashResult>(
+                    "pathfinder_getContractsByClassHash",
+                    params,
+                )
+                .await
+                .unwrap();
+            assert_eq!(
+                rpc_result,
+                GetContractsByClassHashResult {
+                    contracts: vec![ContractDeployment {
+                        contract_address: ContractAddress(
+                            StarkHash::from_be_slice(b"contract 0").unwrap()
+                        ),
+                        class_hash,
+                        block_hash: genesis_hash,
+                        block_number: StarknetBlockNumber(0),
+                        transaction_hash: Some(StarknetTransactionHash(
+                            StarkHash::from_be_slice(b"txn 0").unwrap()
+                        )),
+                    }],
+                    continuation_token: Some(other_address),
+                }
+            );
+
+            let params = by_name([(
+                "filter",
+                json!({
+                    "class_hash": class_hash,
+                    "page_size": 1,
+                    "continuation_token": other_address
+                }),
+            )]);
+            let rpc_result = client(addr)
+                .request::<GetContractsByClassHashResult>(
+                    "pathfinder_getContractsByClassHash",
+                    params,
+                )
+                .await
+                .unwrap();
+            assert_eq!(
+                rpc_result,
+                GetContractsByClassHashResult {
+                    contracts: vec![ContractDeployment {
+                        contract_address: other_address,
+                        class_hash,
+                        block_hash: latest_hash,
+                        block_number: StarknetBlockNumber(2),
+                        transaction_hash: None,
+                    }],
+                    continuation_token: None,
+                }
+            );
         }
     }
 
@@ -2,25 +2,31 @@
 use crate::{
     cairo::ext_py,
     core::{
-        CallResultValue, CallSignatureElem, ConstructorParam, ContractAddress, ContractAddressSalt,
-        ContractCode, Fee, GasPrice, GlobalRoot, SequencerAddress, StarknetBlockHash,
-        StarknetBlockNumber, StarknetBlockTimestamp, StarknetTransactionHash,
+        CallResultValue, CallSignatureElem, ClassHash, ConstructorParam, ContractAddress,
+        ContractAddressSalt, ContractCode, Fee, GasPrice, GlobalRoot, SequencerAddress,
+        StarknetBlockHash, StarknetBlockNumber, StarknetBlockTimestamp, StarknetTransactionHash,
         StarknetTransactionIndex, StorageValue, TransactionNonce, TransactionVersion,
     },
     ethereum::Chain,
     rpc::types::{
         reply::{
-            Block, BlockStatus, ErrorCode, GetEventsResult, Syncing, Transaction,
-            TransactionReceipt,
+            Block, BlockStatus, ContractClass, ContractDeployment, ErrorCode,
+            GetContractsByClassHashResult, GetEventsResult, GetStorageHistoryResult,
+            GetTransactionsByAddressResult, Syncing, Transaction, TransactionReceipt,
         },
-        request::{BlockResponseScope, Call, EventFilter, OverflowingStorageAddress},
-        BlockHashOrTag, BlockNumberOrTag, Tag,
+        request::{
+            BlockResponseScope, Call, ContractsByClassHashFilter, EventFilter,
+            OverflowingStorageAddress, StateOverride, StorageHistoryFilter,
+            TransactionsByAddressFilter,
+        },
+        BlockHashOrTag, BlockId, BlockNumberOrTag, Tag,
     },
     sequencer::{self, request::add_transaction::ContractDefinition, ClientApi},
-    state::SyncState,
+    state::{SyncMetrics, SyncState},
     storage::{
-        EventFilterError, RefsTable, StarknetBlocksBlockId, StarknetBlocksTable,
-        StarknetEventsTable, StarknetTransactionsTable, Storage,
+        ContractDeploymentsTable, ContractStorageHistoryTable, EventFilterError, PageSizeTooBig,
+        RefsTable, StarknetBlocksBlockId, StarknetBlocksTable, StarknetEventsTable,
+        StarknetTransactionsByAddressFilter, StarknetTransactionsTable, Storage,
     },
 };
 use anyhow::Context;
@@ -32,16 +38,26 @@ use stark_hash::StarkHash;
 use std::convert::TryInto;
 use std::sync::Arc;
 
+use super::call_cache::{CallCache, CallCacheMetrics, CallKey};
+use super::execution::{ExecutionBackend, ExecutionPolicy, Executor};
 use super::types::reply::{
-    DeclareTransactionResult, DeployTransactionResult, InvokeTransactionResult,
+    DeclareTransactionResult, DeployTransactionResult, FeeEstimate, InvokeTransactionResult,
+    MulticallError, MulticallResult, TransactionTrace,
 };
 
+/// Maximum number of calls in a single [`RpcApi::multicall`].
+pub const MAX_MULTICALL_CALLS: usize = 100;
+
+/// Maximum number of concurrent sequencer requests when reading pending storage values.
+const MAX_CONCURRENT_STORAGE_REQUESTS: usize = 8;
+
 /// Implements JSON-RPC endpoints.
 pub struct RpcApi {
     storage: Storage,
     sequencer: sequencer::Client,
     chain_id: &'static str,
-    call_handle: Option<ext_py::Handle>,
+    executor: Executor,
+    call_cache: CallCache,
     sync_state: Arc<SyncState>,
 }
 
@@ -68,6 +84,8 @@ impl RpcApi {
     ) -> Self {
         Self {
             storage,
+            executor: Executor::new(Arc::new(sequencer.clone())),
+            call_cache: CallCache::default(),
             sequencer,
             chain_id: match chain {
                 // Hex str for b"SN_GOERLI"
@@ -75,14 +93,37 @@ impl RpcApi {
                 // Hex str for b"SN_MAIN"
                 Chain::Mainnet => "0x534e5f4d41494e",
             },
-            call_handle: None,
             sync_state,
         }
     }
 
+    /// Executes calls with the python executors, see [`RpcApi::with_execution_backend`].
     pub fn with_call_handling(self, call_handle: ext_py::Handle) -> Self {
+        self.with_execution_backend(Arc::new(call_handle))
+    }
+
+    /// Sets the local backend used for calls, fee estimations and simulations.
+    pub fn with_execution_backend(self, backend: Arc<dyn ExecutionBackend>) -> Self {
+        Self {
+            executor: self.executor.with_local(backend),
+            ..self
+        }
+    }
+
+    /// Sets how requests are divided between the local backend and the sequencer. Defaults to
+    /// [`ExecutionPolicy::LocalWithSequencerFallback`].
+    pub fn with_execution_policy(self, policy: ExecutionPolicy) -> Self {
         Self {
-            call_handle: Some(call_handle),
+            executor: self.executor.with_policy(policy),
+            ..self
+        }
+    }
+
+    /// Sets the number of [`RpcApi::call`] results cached. Defaults to
+    /// [`DEFAULT_CAPACITY`](super::call_cache::DEFAULT_CAPACITY), zero disables caching.
+    pub fn with_call_cache_capacity(self, capacity: usize) -> Self {
+        Self {
+            call_cache: CallCache::new(capacity),
             ..self
         }
     }
@@ -352,8 +393,8 @@ impl RpcApi {
 
     /// Get the value of the storage at the given address and key.
     /// `contract_address` is the address of the contract to read from, `key` is the key to the storage value for the given contract,
-    /// `block_hash` is the [Hash](crate::rpc::types::BlockHashOrTag::Hash) or [Tag](crate::rpc::types::BlockHashOrTag::Tag)
-    /// of the requested block.
+    /// `block_id` is the [Hash](crate::rpc::types::BlockId::Hash), [Number](crate::rpc::types::BlockId::Number)
+    /// or [Tag](crate::rpc::types::BlockId::Tag) of the requested block.
     ///
     /// We are using overflowing type for `key` to be able to correctly report `INVALID_STORAGE_KEY` as per
     /// [StarkNet RPC spec](https://github.com/starkware-libs/starknet-specs/blob/master/api/starknet_api_openrpc.json),
@@ -362,13 +403,9 @@ impl RpcApi {
         &self,
         contract_address: ContractAddress,
         key: OverflowingStorageAddress,
-        block_hash: BlockHashOrTag,
+        block_id: BlockId,
     ) -> RpcResult<StorageValue> {
-        use crate::{
-            core::StorageAddress,
-            state::state_tree::{ContractsStateTree, GlobalStateTree},
-            storage::ContractsStateTable,
-        };
+        use crate::core::StorageAddress;
         use stark_hash::OverflowError;
 
         let key = StorageAddress(StarkHash::from_be_bytes(key.0.to_fixed_bytes()).map_err(
@@ -382,14 +419,77 @@ impl RpcApi {
             return Err(Error::from(ErrorCode::InvalidStorageKey));
         }
 
-        let block_id = match block_hash {
-            BlockHashOrTag::Hash(hash) => hash.into(),
-            BlockHashOrTag::Tag(Tag::Latest) => StarknetBlocksBlockId::Latest,
-            BlockHashOrTag::Tag(Tag::Pending) => {
-                return Ok(self
-                    .sequencer
-                    .storage(contract_address, key, block_hash)
-                    .await?);
+        let mut values = self
+            .get_storage_values(contract_address, vec![key], block_id)
+            .await?;
+        Ok(values.remove(0))
+    }
+
+    /// Get the value of a Cairo `@storage_var` of a contract at the requested block.
+    ///
+    /// Returns `size` consecutive values starting at the address of the variable, one for each
+    /// member of a struct.
+    pub async fn get_storage_var(
+        &self,
+        contract_address: ContractAddress,
+        var_name: String,
+        keys: Vec<StarkHash>,
+        size: u16,
+        block_id: BlockId,
+    ) -> RpcResult<Vec<StorageValue>> {
+        use crate::core::StorageAddress;
+
+        if size == 0 || size > StorageAddress::MAX_STORAGE_ITEM_SIZE {
+            return Err(Error::Call(CallError::InvalidParams(anyhow::anyhow!(
+                "Size must be between 1 and {}",
+                StorageAddress::MAX_STORAGE_ITEM_SIZE
+            ))));
+        }
+
+        let base = StorageAddress::from_var_name(&var_name, &keys);
+        // Cannot overflow, the variable address leaves room for the largest struct
+        let addresses = (0..size)
+            .map(|offset| StorageAddress(base.0 + StarkHash::from(u64::from(offset))))
+            .collect();
+
+        self.get_storage_values(contract_address, addresses, block_id)
+            .await
+    }
+
+    /// Reads the values at `keys` of a contract's storage, all from the same block.
+    async fn get_storage_values(
+        &self,
+        contract_address: ContractAddress,
+        keys: Vec<crate::core::StorageAddress>,
+        block_id: BlockId,
+    ) -> RpcResult<Vec<StorageValue>> {
+        use crate::{
+            state::state_tree::{ContractsStateTree, GlobalStateTree},
+            storage::ContractsStateTable,
+        };
+
+        let not_found = block_not_found(&block_id);
+        let block_id = match block_id {
+            BlockId::Hash(hash) => hash.into(),
+            BlockId::Number(number) => number.into(),
+            BlockId::Tag(Tag::Latest) => StarknetBlocksBlockId::Latest,
+            BlockId::Tag(Tag::Pending) => {
+                use futures::{StreamExt, TryStreamExt};
+
+                // Up to `MAX_STORAGE_ITEM_SIZE` requests, so only a few are made at a time.
+                // The values are yielded in the order of the keys.
+                let values = futures::stream::iter(keys)
+                    .map(|key| {
+                        self.sequencer.storage(
+                            contract_address,
+                            key,
+                            BlockHashOrTag::Tag(Tag::Pending),
+                        )
+                    })
+                    .buffered(MAX_CONCURRENT_STORAGE_REQUESTS)
+                    .try_collect()
+                    .await?;
+                return Ok(values);
             }
         };
 
@@ -412,9 +512,9 @@ impl RpcApi {
             // which is not the same as being sure that the block is not in the db.
             let global_root = StarknetBlocksTable::get_root(&tx, block_id)
                 .map_err(internal_server_error)?
-                // Since the db query succeeded in execution, we can now report if the block hash was indeed not found
+                // Since the db query succeeded in execution, we can now report if the block was indeed not found
                 // by using a dedicated error code from the RPC API spec
-                .ok_or_else(|| Error::from(ErrorCode::InvalidBlockHash))?;
+                .ok_or_else(|| Error::from(not_found))?;
 
             let global_state_tree = GlobalStateTree::load(&tx, global_root)
                 .context("Global state tree")
@@ -446,12 +546,14 @@ impl RpcApi {
 
             // ContractsStateTree::get() will return zero if the value is still not found (and we know the key is valid),
             // which is consistent with the specification.
-            let storage_val = contract_state_tree
-                .get(key)
-                .context("Get value from contract state tree")
-                .map_err(internal_server_error)?;
-
-            Ok(storage_val)
+            keys.into_iter()
+                .map(|key| {
+                    contract_state_tree
+                        .get(key)
+                        .context("Get value from contract state tree")
+                        .map_err(internal_server_error)
+                })
+                .collect()
         });
 
         jh.await
@@ -700,10 +802,24 @@ impl RpcApi {
     }
 
     /// Get the code of a specific contract.
-    /// `contract_address` is the address of the contract to read from.
-    pub async fn get_code(&self, contract_address: ContractAddress) -> RpcResult<ContractCode> {
+    /// `contract_address` is the address of the contract to read from. If the optional `block_id`
+    /// is given, the contract must have been deployed by that block.
+    pub async fn get_code(
+        &self,
+        contract_address: ContractAddress,
+        block_id: Option<BlockId>,
+    ) -> RpcResult<ContractCode> {
         use crate::storage::ContractCodeTable;
 
+        // the code of a contract never changes, so a block only needs to be checked if the
+        // contract had been deployed by then. pending is not known to us, so it's like latest.
+        let not_found = block_id.as_ref().map(block_not_found);
+        let block_id = match block_id {
+            Some(BlockId::Hash(hash)) => Some(StarknetBlocksBlockId::from(hash)),
+            Some(BlockId::Number(number)) => Some(number.into()),
+            Some(BlockId::Tag(_)) | None => None,
+        };
+
         let storage = self.storage.clone();
         let span = tracing::Span::current();
 
@@ -718,6 +834,18 @@ impl RpcApi {
                 .context("Creating database transaction")
                 .map_err(internal_server_error)?;
 
+            if let (Some(block_id), Some(not_found)) = (block_id, not_found) {
+                let block = StarknetBlocksTable::get(&tx, block_id)
+                    .context("Read block from database")
+                    .map_err(internal_server_error)?
+                    .ok_or_else(|| Error::from(not_found))?;
+
+                ContractDeploymentsTable::get_class_hash_at(&tx, contract_address, block.number)
+                    .context("Fetching class hash from database")
+                    .map_err(internal_server_error)?
+                    .ok_or_else(|| Error::from(ErrorCode::ContractNotFound))?;
+            }
+
             let code = ContractCodeTable::get_code(&tx, contract_address)
                 .context("Fetching code from database")
                 .map_err(internal_server_error)?;
@@ -734,6 +862,231 @@ impl RpcApi {
             .and_then(|x| x)
     }
 
+    /// Get the class hash of a contract as of the given block.
+    /// `block_hash` is the [Hash](crate::rpc::types::BlockHashOrTag::Hash) or [Tag](crate::rpc::types::BlockHashOrTag::Tag)
+    /// of the requested block.
+    ///
+    /// Returns [`ErrorCode::ContractNotFound`] if the contract had not been deployed by that block.
+    pub async fn get_class_hash_at(
+        &self,
+        block_hash: BlockHashOrTag,
+        contract_address: ContractAddress,
+    ) -> RpcResult<ClassHash> {
+        let block_id = match block_hash {
+            BlockHashOrTag::Hash(hash) => hash.into(),
+            BlockHashOrTag::Tag(Tag::Latest) => StarknetBlocksBlockId::Latest,
+            BlockHashOrTag::Tag(Tag::Pending) => {
+                return Ok(self.sequencer.class_hash_at(contract_address).await?);
+            }
+        };
+
+        let storage = self.storage.clone();
+        let span = tracing::Span::current();
+
+        let jh = tokio::task::spawn_blocking(move || {
+            let _g = span.enter();
+            let mut db = storage
+                .connection()
+                .context("Opening database connection")
+                .map_err(internal_server_error)?;
+            let tx = db
+                .transaction()
+                .context("Creating database transaction")
+                .map_err(internal_server_error)?;
+
+            let block = StarknetBlocksTable::get(&tx, block_id)
+                .context("Read block from database")
+                .map_err(internal_server_error)?
+                .ok_or_else(|| Error::from(ErrorCode::InvalidBlockHash))?;
+
+            let class_hash =
+                ContractDeploymentsTable::get_class_hash_at(&tx, contract_address, block.number)
+                    .context("Fetching class hash from database")
+                    .map_err(internal_server_error)?;
+
+            class_hash.ok_or_else(|| Error::from(ErrorCode::ContractNotFound))
+        });
+
+        jh.await
+            .context("Database read panic or shutting down")
+            .map_err(internal_server_error)
+            .and_then(|x| x)
+    }
+
+    /// Get the definition of a class given its hash.
+    ///
+    /// Returns [`ErrorCode::InvalidContractClassHash`] if the class is not known.
+    pub async fn get_class(&self, class_hash: ClassHash) -> RpcResult<ContractClass> {
+        use crate::storage::ContractCodeTable;
+
+        let storage = self.storage.clone();
+        let span = tracing::Span::current();
+
+        let jh = tokio::task::spawn_blocking(move || {
+            let _g = span.enter();
+            let mut db = storage
+                .connection()
+                .context("Opening database connection")
+                .map_err(internal_server_error)?;
+            let tx = db
+                .transaction()
+                .context("Creating database transaction")
+                .map_err(internal_server_error)?;
+
+            let definition = ContractCodeTable::get_definition(&tx, class_hash)
+                .context("Fetching class definition from database")
+                .map_err(internal_server_error)?
+                .ok_or_else(|| Error::from(ErrorCode::InvalidContractClassHash))?;
+
+            ContractClass::from_definition_bytes(&definition).map_err(internal_server_error)
+        });
+
+        jh.await
+            .context("Database read panic or shutting down")
+            .map_err(internal_server_error)
+            .and_then(|x| x)
+    }
+
+    /// Get the definition of the class of a contract as of the given block.
+    /// `block_hash` is the [Hash](crate::rpc::types::BlockHashOrTag::Hash) or [Tag](crate::rpc::types::BlockHashOrTag::Tag)
+    /// of the requested block.
+    ///
+    /// Returns [`ErrorCode::ContractNotFound`] if the contract had not been deployed by that block.
+    pub async fn get_class_at(
+        &self,
+        block_hash: BlockHashOrTag,
+        contract_address: ContractAddress,
+    ) -> RpcResult<ContractClass> {
+        use crate::storage::ContractCodeTable;
+
+        let block_id = match block_hash {
+            BlockHashOrTag::Hash(hash) => hash.into(),
+            BlockHashOrTag::Tag(Tag::Latest) => StarknetBlocksBlockId::Latest,
+            BlockHashOrTag::Tag(Tag::Pending) => {
+                let class_hash = self.sequencer.class_hash_at(contract_address).await?;
+                let definition = self.sequencer.class_by_hash(class_hash).await?;
+                return ContractClass::from_definition_bytes(&definition)
+                    .map_err(internal_server_error);
+            }
+        };
+
+        let storage = self.storage.clone();
+        let span = tracing::Span::current();
+
+        let jh = tokio::task::spawn_blocking(move || {
+            let _g = span.enter();
+            let mut db = storage
+                .connection()
+                .context("Opening database connection")
+                .map_err(internal_server_error)?;
+            let tx = db
+                .transaction()
+                .context("Creating database transaction")
+                .map_err(internal_server_error)?;
+
+            let block = StarknetBlocksTable::get(&tx, block_id)
+                .context("Read block from database")
+                .map_err(internal_server_error)?
+                .ok_or_else(|| Error::from(ErrorCode::InvalidBlockHash))?;
+
+            let class_hash =
+                ContractDeploymentsTable::get_class_hash_at(&tx, contract_address, block.number)
+                    .context("Fetching class hash from database")
+                    .map_err(internal_server_error)?
+                    .ok_or_else(|| Error::from(ErrorCode::ContractNotFound))?;
+
+            // The class of a deployed contract is always stored along with it.
+            let definition = ContractCodeTable::get_definition(&tx, class_hash)
+                .context("Fetching class definition from database")
+                .map_err(internal_server_error)?
+                .ok_or_else(|| {
+                    internal_server_error(anyhow::anyhow!(
+                        "Class definition not found for class hash {}",
+                        class_hash.0
+                    ))
+                })?;
+
+            ContractClass::from_definition_bytes(&definition).map_err(internal_server_error)
+        });
+
+        jh.await
+            .context("Database read panic or shutting down")
+            .map_err(internal_server_error)
+            .and_then(|x| x)
+    }
+
+    /// Get the block and transaction in which a contract was deployed.
+    pub async fn get_contract_deployment(
+        &self,
+        contract_address: ContractAddress,
+    ) -> RpcResult<ContractDeployment> {
+        let storage = self.storage.clone();
+        let span = tracing::Span::current();
+
+        let jh = tokio::task::spawn_blocking(move || {
+            let _g = span.enter();
+            let connection = storage
+                .connection()
+                .context("Opening database connection")
+                .map_err(internal_server_error)?;
+
+            let deployment = ContractDeploymentsTable::get(&connection, contract_address)
+                .context("Fetching contract deployment from database")
+                .map_err(internal_server_error)?;
+
+            match deployment {
+                Some(deployment) => Ok(deployment.into()),
+                None => Err(ErrorCode::ContractNotFound.into()),
+            }
+        });
+
+        jh.await
+            .context("Database read panic or shutting down")
+            .map_err(internal_server_error)
+            .and_then(|x| x)
+    }
+
+    /// Returns the contracts deployed from a class, in pages.
+    pub async fn get_contracts_by_class_hash(
+        &self,
+        request: ContractsByClassHashFilter,
+    ) -> RpcResult<GetContractsByClassHashResult> {
+        let storage = self.storage.clone();
+        let span = tracing::Span::current();
+
+        let jh = tokio::task::spawn_blocking(move || {
+            let _g = span.enter();
+            let connection = storage
+                .connection()
+                .context("Opening database connection")
+                .map_err(internal_server_error)?;
+
+            let filter = crate::storage::ContractsByClassHashFilter {
+                class_hash: request.class_hash,
+                page_size: request.page_size,
+                continuation_token: request.continuation_token,
+            };
+            let page =
+                ContractDeploymentsTable::get_by_class_hash(&connection, &filter).map_err(|e| {
+                    if let Some(e) = e.downcast_ref::<PageSizeTooBig>() {
+                        Error::from(*e)
+                    } else {
+                        internal_server_error(e)
+                    }
+                })?;
+
+            Ok(GetContractsByClassHashResult {
+                contracts: page.deployments.into_iter().map(|d| d.into()).collect(),
+                continuation_token: page.continuation_token,
+            })
+        });
+
+        jh.await
+            .context("Database read panic or shutting down")
+            .map_err(internal_server_error)
+            .and_then(|x| x)
+    }
+
     /// Get the number of transactions in a block given a block hash.
     /// `block_hash` is the [Hash](crate::rpc::types::BlockHashOrTag::Hash) or [Tag](crate::rpc::types::BlockHashOrTag::Tag)
     /// of the requested block.
@@ -869,30 +1222,268 @@ impl RpcApi {
     }
 
     /// Call a starknet function without creating a StarkNet transaction.
-    /// `block_hash` is the [Hash](crate::rpc::types::BlockHashOrTag::Hash) or [Tag](crate::rpc::types::BlockHashOrTag::Tag)
-    /// of the requested block.
+    /// `block_id` is the [Hash](crate::rpc::types::BlockId::Hash), [Number](crate::rpc::types::BlockId::Number)
+    /// or [Tag](crate::rpc::types::BlockId::Tag) of the requested block.
+    ///
+    /// The `overrides` are applied on top of the state for this call only. Calls with overrides
+    /// are not forwarded to the sequencer, so they require the python executors.
+    ///
+    /// Block numbers and `latest` are resolved to block hashes before the call is executed.
+    /// Results of calls without overrides are cached for these, pending is never cached.
     pub async fn call(
         &self,
         request: Call,
-        block_hash: BlockHashOrTag,
+        block_id: BlockId,
+        overrides: Vec<StateOverride>,
     ) -> RpcResult<Vec<CallResultValue>> {
-        use futures::future::TryFutureExt;
+        let block_hash = match block_id {
+            BlockId::Hash(hash) => BlockHashOrTag::Hash(hash),
+            BlockId::Number(number) => self
+                .resolve_block_hash(number.into())
+                .await?
+                .map(BlockHashOrTag::Hash)
+                .ok_or_else(|| Error::from(ErrorCode::InvalidBlockNumber))?,
+            // without any blocks "latest" is left for the executors to handle
+            BlockId::Tag(Tag::Latest) => self
+                .resolve_block_hash(StarknetBlocksBlockId::Latest)
+                .await?
+                .map(BlockHashOrTag::Hash)
+                .unwrap_or(BlockHashOrTag::Tag(Tag::Latest)),
+            BlockId::Tag(Tag::Pending) => BlockHashOrTag::Tag(Tag::Pending),
+        };
 
-        match (self.call_handle.as_ref(), &block_hash) {
-            (Some(h), &BlockHashOrTag::Hash(_) | &BlockHashOrTag::Tag(Tag::Latest)) => {
-                // we don't yet handle pending at all, and latest has been decided to be whatever
-                // block we have, which is exactly how the py/src/call.py handles it.
-                h.call(request, block_hash).map_err(Error::from).await
-            }
-            (Some(_), _) | (None, _) => {
-                // just forward it to the sequencer for now.
-                self.sequencer
-                    .call(request.into(), block_hash)
-                    .map_ok(|x| x.result)
-                    .map_err(Error::from)
-                    .await
-            }
+        let hash = match block_hash {
+            BlockHashOrTag::Hash(hash) if overrides.is_empty() => hash,
+            _ => return self.executor.call(request, block_hash, overrides).await,
+        };
+
+        let key = CallKey::new(hash, &request);
+        self.call_cache
+            .get_or_execute(key, || {
+                self.executor
+                    .call(request, BlockHashOrTag::Hash(hash), overrides)
+            })
+            .await
+    }
+
+    /// The hash of the block, if we have it.
+    async fn resolve_block_hash(
+        &self,
+        block_id: StarknetBlocksBlockId,
+    ) -> RpcResult<Option<StarknetBlockHash>> {
+        let storage = self.storage.clone();
+        let span = tracing::Span::current();
+
+        tokio::task::spawn_blocking(move || {
+            let _g = span.enter();
+            let mut db = storage
+                .connection()
+                .context("Opening database connection")
+                .map_err(internal_server_error)?;
+            let db_tx = db
+                .transaction()
+                .context("Creating database transaction")
+                .map_err(internal_server_error)?;
+
+            StarknetBlocksTable::get(&db_tx, block_id)
+                .context("Reading block from database")
+                .map(|block| block.map(|block| block.hash))
+                .map_err(internal_server_error)
+        })
+        .await
+        .context("Database read panic or shutting down")
+        .map_err(internal_server_error)
+        .and_then(|x| x)
+    }
+
+    /// Returns the hit and miss counters of the [`RpcApi::call`] result cache.
+    pub async fn call_cache_metrics(&self) -> RpcResult<CallCacheMetrics> {
+        Ok(self.call_cache.metrics())
+    }
+
+    /// Returns the counters of the sync process.
+    pub async fn sync_metrics(&self) -> RpcResult<SyncMetrics> {
+        Ok(self.sync_state.metrics())
+    }
+
+    /// Call all of the given starknet functions on the same block.
+    ///
+    /// With the python executors the calls are made with a single executor, on the same state.
+    /// Otherwise they are handled like separate [`RpcApi::call`]s. Failing calls do not fail the
+    /// others.
+    ///
+    /// At most [`MAX_MULTICALL_CALLS`] calls can be made at once.
+    pub async fn multicall(
+        &self,
+        requests: Vec<Call>,
+        block_hash: BlockHashOrTag,
+    ) -> RpcResult<Vec<MulticallResult>> {
+        if requests.len() > MAX_MULTICALL_CALLS {
+            return Err(Error::Call(CallError::InvalidParams(anyhow::anyhow!(
+                "Too many calls, supported maximum is {}",
+                MAX_MULTICALL_CALLS
+            ))));
+        }
+
+        let results = self.executor.multicall(requests, block_hash).await?;
+
+        Ok(results
+            .into_iter()
+            .map(|result| match result {
+                Ok(output) => MulticallResult::Result(output),
+                Err(Error::Call(CallError::Custom(e))) => MulticallResult::Error(MulticallError {
+                    code: e.code(),
+                    message: e.message().to_owned(),
+                }),
+                Err(e) => MulticallResult::Error(MulticallError {
+                    code: jsonrpsee::types::error::ErrorCode::InternalError.code(),
+                    message: e.to_string(),
+                }),
+            })
+            .collect())
+    }
+
+    /// Estimate the fee of running the given call as an invoke transaction on top of the given
+    /// block.
+    ///
+    /// Unlike [`RpcApi::call`] this is not forwarded to the sequencer, so it is only available
+    /// when the python executors are configured, and never for the pending block.
+    pub async fn estimate_fee(
+        &self,
+        request: Call,
+        signature: Vec<CallSignatureElem>,
+        max_fee: Fee,
+        version: TransactionVersion,
+        block_hash: BlockHashOrTag,
+    ) -> RpcResult<FeeEstimate> {
+        self.executor
+            .estimate_fee(request, signature, max_fee, version, block_hash)
+            .await
+    }
+
+    /// Run the given call as an invoke transaction on top of the given block, returning the
+    /// execution trace.
+    ///
+    /// Like [`RpcApi::estimate_fee`] this is only available when the python executors are
+    /// configured, and never for the pending block.
+    pub async fn simulate_transaction(
+        &self,
+        request: Call,
+        signature: Vec<CallSignatureElem>,
+        max_fee: Fee,
+        version: TransactionVersion,
+        block_hash: BlockHashOrTag,
+    ) -> RpcResult<TransactionTrace> {
+        self.executor
+            .simulate_transaction(request, signature, max_fee, version, block_hash)
+            .await
+    }
+
+    /// Re-run an accepted invoke transaction on top of the state of its parent block, returning
+    /// the execution trace.
+    ///
+    /// The transactions preceding it in the same block are not applied first, so the trace can
+    /// differ from the original execution when they touched the same state.
+    pub async fn trace_transaction(
+        &self,
+        transaction_hash: StarknetTransactionHash,
+    ) -> RpcResult<TransactionTrace> {
+        let storage = self.storage.clone();
+        let span = tracing::Span::current();
+
+        let jh = tokio::task::spawn_blocking(move || {
+            let _g = span.enter();
+            let mut db = storage
+                .connection()
+                .context("Opening database connection")
+                .map_err(internal_server_error)?;
+            let db_tx = db
+                .transaction()
+                .context("Creating database transaction")
+                .map_err(internal_server_error)?;
+
+            let transaction = StarknetTransactionsTable::get_transaction(&db_tx, transaction_hash)
+                .context("Reading transaction from database")
+                .map_err(internal_server_error)?
+                .ok_or_else(|| Error::from(ErrorCode::InvalidTransactionHash))?;
+
+            let (_, block_hash) = StarknetTransactionsTable::get_receipt(&db_tx, transaction_hash)
+                .context("Reading transaction receipt from database")
+                .map_err(internal_server_error)?
+                .ok_or_else(|| Error::from(ErrorCode::InvalidTransactionHash))?;
+
+            let block = StarknetBlocksTable::get(&db_tx, StarknetBlocksBlockId::Hash(block_hash))
+                .context("Reading block from database")
+                .map_err(internal_server_error)?
+                .context("Block of the transaction is missing")
+                .map_err(internal_server_error)?;
+
+            let parent_number = block.number.0.checked_sub(1).ok_or_else(|| {
+                Error::Call(CallError::InvalidParams(anyhow::anyhow!(
+                    "Transactions of the genesis block cannot be traced"
+                )))
+            })?;
+
+            let parent = StarknetBlocksTable::get(
+                &db_tx,
+                StarknetBlocksBlockId::Number(StarknetBlockNumber(parent_number)),
+            )
+            .context("Reading parent block from database")
+            .map_err(internal_server_error)?
+            .context("Parent block of the transaction is missing")
+            .map_err(internal_server_error)?;
+
+            Ok((transaction, parent.hash))
+        });
+
+        let (transaction, parent_hash) = jh
+            .await
+            .context("Database read panic or shutting down")
+            .map_err(internal_server_error)
+            .and_then(|x| x)?;
+
+        let is_invoke = transaction.r#type == sequencer::reply::transaction::Type::InvokeFunction
+            && transaction.entry_point_type
+                != Some(sequencer::reply::transaction::EntryPointType::L1Handler);
+        if !is_invoke {
+            return Err(Error::Call(CallError::InvalidParams(anyhow::anyhow!(
+                "Only invoke transactions can be traced"
+            ))));
         }
+
+        let call = Call {
+            contract_address: transaction
+                .contract_address
+                .context("Invoke transaction is missing contract address")
+                .map_err(internal_server_error)?,
+            calldata: transaction.calldata.unwrap_or_default(),
+            entry_point_selector: transaction
+                .entry_point_selector
+                .context("Invoke transaction is missing entry point selector")
+                .map_err(internal_server_error)?,
+        };
+        let signature = transaction
+            .signature
+            .unwrap_or_default()
+            .into_iter()
+            .map(|elem| CallSignatureElem(elem.0))
+            .collect();
+        let max_fee = transaction
+            .max_fee
+            .unwrap_or(Fee(web3::types::H128::zero()));
+        let version = transaction
+            .version
+            .unwrap_or(TransactionVersion(web3::types::H256::zero()));
+
+        self.executor
+            .simulate_transaction(
+                call,
+                signature,
+                max_fee,
+                version,
+                BlockHashOrTag::Hash(parent_hash),
+            )
+            .await
     }
 
     /// Get the most recent accepted block number.
@@ -985,6 +1576,95 @@ impl RpcApi {
             .and_then(|x| x)
     }
 
+    /// Returns the invoke and deploy transactions which targeted a contract, one page at a time.
+    pub async fn get_transactions_by_address(
+        &self,
+        request: TransactionsByAddressFilter,
+    ) -> RpcResult<GetTransactionsByAddressResult> {
+        let storage = self.storage.clone();
+        let span = tracing::Span::current();
+
+        let jh = tokio::task::spawn_blocking(move || {
+            let _g = span.enter();
+            let connection = storage
+                .connection()
+                .context("Opening database connection")
+                .map_err(internal_server_error)?;
+
+            let filter = StarknetTransactionsByAddressFilter {
+                contract_address: request.address,
+                from_block: request.from_block,
+                to_block: request.to_block,
+                page_size: request.page_size,
+                continuation_token: request.continuation_token,
+            };
+            let page = StarknetTransactionsTable::get_transactions_by_address(&connection, &filter)
+                .map_err(|e| {
+                    if let Some(e) = e.downcast_ref::<PageSizeTooBig>() {
+                        Error::from(*e)
+                    } else {
+                        internal_server_error(e)
+                    }
+                })?;
+
+            Ok(GetTransactionsByAddressResult {
+                transactions: page.transactions.into_iter().map(|t| t.into()).collect(),
+                continuation_token: page.continuation_token,
+            })
+        });
+
+        jh.await
+            .context("Database read panic or shutting down")
+            .map_err(internal_server_error)
+            .and_then(|x| x)
+    }
+
+    /// Returns the values a contract's storage key was set to, one page at a time.
+    pub async fn get_storage_history(
+        &self,
+        request: StorageHistoryFilter,
+    ) -> RpcResult<GetStorageHistoryResult> {
+        let storage = self.storage.clone();
+        let span = tracing::Span::current();
+
+        let jh = tokio::task::spawn_blocking(move || {
+            let _g = span.enter();
+            let connection = storage
+                .connection()
+                .context("Opening database connection")
+                .map_err(internal_server_error)?;
+
+            let filter = crate::storage::StorageHistoryFilter {
+                contract_address: request.address,
+                storage_address: request.key,
+                from_block: request.from_block,
+                to_block: request.to_block,
+                page_size: request.page_size,
+                continuation_token: request.continuation_token,
+            };
+            let page = ContractStorageHistoryTable::get(&connection, &filter).map_err(|e| {
+                if let Some(e) = e.downcast_ref::<PageSizeTooBig>() {
+                    Error::from(*e)
+                } else {
+                    internal_server_error(e)
+                }
+            })?;
+            let history_start =
+                ContractStorageHistoryTable::start(&connection).map_err(internal_server_error)?;
+
+            Ok(GetStorageHistoryResult {
+                changes: page.changes.into_iter().map(|c| c.into()).collect(),
+                history_start,
+                continuation_token: page.continuation_token,
+            })
+        });
+
+        jh.await
+            .context("Database read panic or shutting down")
+            .map_err(internal_server_error)
+            .and_then(|x| x)
+    }
+
     /// Submit a new transaction to be added to the chain.
     ///
     /// This method just forwards the request received over the JSON-RPC
@@ -1052,20 +1732,96 @@ impl RpcApi {
         contract_definition: ContractDefinition,
         token: Option<String>,
     ) -> RpcResult<DeployTransactionResult> {
-        let result = self
-            .sequencer
-            .add_deploy_transaction(
-                contract_address_salt,
-                constructor_calldata,
-                contract_definition,
-                token,
-            )
-            .await?;
-        Ok(DeployTransactionResult {
-            transaction_hash: result.transaction_hash,
-            contract_address: result.address,
+        submit_deploy_transaction(
+            &self.sequencer,
+            contract_address_salt,
+            constructor_calldata,
+            contract_definition,
+            token,
+        )
+        .await
+    }
+
+    /// Computes the address of a contract deployed by `deployer_address`, which is zero for
+    /// `DEPLOY` transactions.
+    pub async fn compute_contract_address(
+        &self,
+        contract_address_salt: ContractAddressSalt,
+        class_hash: ClassHash,
+        constructor_calldata: Vec<ConstructorParam>,
+        deployer_address: ContractAddress,
+    ) -> RpcResult<ContractAddress> {
+        Ok(ContractAddress::compute(
+            deployer_address,
+            contract_address_salt,
+            class_hash,
+            &constructor_calldata,
+        ))
+    }
+}
+
+/// Submits a `DEPLOY` transaction to the sequencer.
+///
+/// The transaction has been accepted by the time the contract address returned by the sequencer
+/// can be compared to the one computed locally, so a mismatch is only logged.
+async fn submit_deploy_transaction<C: ClientApi + Sync>(
+    sequencer: &C,
+    contract_address_salt: ContractAddressSalt,
+    constructor_calldata: Vec<ConstructorParam>,
+    contract_definition: ContractDefinition,
+    token: Option<String>,
+) -> RpcResult<DeployTransactionResult> {
+    // Computing the class hash is expensive, so the expected address is computed in a blocking
+    // task while the transaction is submitted, and checked after the fact.
+    let expected_address = {
+        let contract_definition = contract_definition.clone();
+        let constructor_calldata = constructor_calldata.clone();
+        let span = tracing::Span::current();
+
+        tokio::task::spawn_blocking(move || {
+            let _g = span.enter();
+            match crate::state::class_hash::compute_submitted_class_hash(&contract_definition) {
+                Ok(class_hash) => Some(ContractAddress::compute(
+                    ContractAddress(StarkHash::ZERO),
+                    contract_address_salt,
+                    class_hash,
+                    &constructor_calldata,
+                )),
+                Err(e) => {
+                    tracing::debug!(reason=?e, "Failed to compute class hash of deployed contract");
+                    None
+                }
+            }
         })
+    };
+
+    let result = sequencer
+        .add_deploy_transaction(
+            contract_address_salt,
+            constructor_calldata,
+            contract_definition,
+            token,
+        )
+        .await?;
+
+    let expected_address = expected_address.await.unwrap_or_else(|e| {
+        tracing::debug!(reason=?e, "Failed to compute address of deployed contract");
+        None
+    });
+
+    if let Some(expected) = expected_address.filter(|expected| *expected != result.address) {
+        tracing::error!(
+            transaction_hash=%result.transaction_hash.0,
+            address=%result.address.0,
+            expected=%expected.0,
+            "Sequencer returned an unexpected address for the deployed contract"
+        );
     }
+
+    Ok(DeployTransactionResult {
+        transaction_hash: result.transaction_hash,
+        contract_address: result.address,
+    })
 }
 
 impl From<ext_py::CallFailure> for jsonrpsee::core::Error {
@@ -1074,6 +1830,10 @@ impl From<ext_py::CallFailure> for jsonrpsee::core::Error {
             ext_py::CallFailure::NoSuchBlock => Error::from(ErrorCode::InvalidBlockHash),
             ext_py::CallFailure::NoSuchContract => Error::from(ErrorCode::ContractNotFound),
             ext_py::CallFailure::ExecutionFailed(e) => internal_server_error(e),
+            ext_py::CallFailure::Timeout => internal_server_error("Execution timed out"),
+            ext_py::CallFailure::QueueFull => {
+                internal_server_error("Too many pending calls, try again later")
+            }
             // Intentionally hide the message under Internal
             ext_py::CallFailure::Internal(_) | ext_py::CallFailure::Shutdown => {
                 static_internal_server_error()
@@ -1097,6 +1857,26 @@ impl From<EventFilterError> for jsonrpsee::core::Error {
     }
 }
 
+impl From<PageSizeTooBig> for jsonrpsee::core::Error {
+    fn from(e: PageSizeTooBig) -> Self {
+        let PageSizeTooBig(max_size) = e;
+        let error = ErrorCode::PageSizeTooBig as i32;
+        Error::Call(CallError::Custom(ErrorObject::owned(
+            error,
+            ErrorCode::PageSizeTooBig.to_string(),
+            Some(serde_json::json!({ "max_page_size": max_size })),
+        )))
+    }
+}
+
+/// The error for a block which could not be found.
+fn block_not_found(block_id: &BlockId) -> ErrorCode {
+    match block_id {
+        BlockId::Number(_) => ErrorCode::InvalidBlockNumber,
+        BlockId::Hash(_) | BlockId::Tag(_) => ErrorCode::InvalidBlockHash,
+    }
+}
+
 // We cannot just return Error::Internal (-32003) in cases which are not covered by starknet RPC API spec
 // as jsonrpsee reserved it for internal subscription related errors only, so we resort to
 // CallError::Custom with the same code value and message as Error::Internal. This way we can still provide
@@ -1104,7 +1884,7 @@ impl From<EventFilterError> for jsonrpsee::core::Error {
 //
 // This error is used for all instances of operations that are not explicitly specified in the StarkNet spec.
 // See <https://github.com/starkware-libs/starknet-specs/blob/master/api/starknet_api_openrpc.json>
-fn internal_server_error(e: impl std::fmt::Display) -> jsonrpsee::core::Error {
+pub(super) fn internal_server_error(e: impl std::fmt::Display) -> jsonrpsee::core::Error {
     Error::Call(CallError::Custom(ErrorObject::owned(
         jsonrpsee::types::error::ErrorCode::InternalError.code(),
         format!("{}: {}", jsonrpsee::types::error::INTERNAL_ERROR_MSG, e),
@@ -1117,3 +1897,86 @@ fn static_internal_server_error() -> jsonrpsee::core::Error {
         jsonrpsee::types::error::ErrorCode::InternalError,
     )))
 }
+
+#[cfg(test)]
+mod tests {
+    mod add_deploy_transaction {
+        use super::super::submit_deploy_transaction;
+        use crate::core::{ContractAddress, ContractAddressSalt, StarknetTransactionHash};
+        use crate::rpc::types::reply::DeployTransactionResult;
+        use crate::sequencer::{
+            reply::add_transaction::DeployResponse, request::add_transaction::ContractDefinition,
+            MockClientApi,
+        };
+        use stark_hash::StarkHash;
+
+        fn contract_definition() -> ContractDefinition {
+            let json = include_bytes!("../../resources/deploy_transaction.json");
+            let mut json = serde_json::from_slice::<serde_json::Value>(json).unwrap();
+            serde_json::from_value(json["contract_definition"].take()).unwrap()
+        }
+
+        /// A sequencer accepting the deployment at `address`.
+        fn sequencer(address: ContractAddress) -> MockClientApi {
+            let mut sequencer = MockClientApi::new();
+            sequencer
+                .expect_add_deploy_transaction()
+                .times(1)
+                .returning(move |_, _, _, _| {
+                    Ok(DeployResponse {
+                        code: "TRANSACTION_RECEIVED".to_owned(),
+                        transaction_hash: StarknetTransactionHash(
+                            StarkHash::from_be_slice(b"deploy").unwrap(),
+                        ),
+                        address,
+                    })
+                });
+            sequencer
+        }
+
+        fn accepted(address: ContractAddress) -> DeployTransactionResult {
+            DeployTransactionResult {
+                transaction_hash: StarknetTransactionHash(
+                    StarkHash::from_be_slice(b"deploy").unwrap(),
+                ),
+                contract_address: address,
+            }
+        }
+
+        #[tokio::test]
+        async fn address_mismatch_returns_accepted_transaction() {
+            let salt = ContractAddressSalt(StarkHash::from_be_slice(b"salt").unwrap());
+            let definition = contract_definition();
+
+            let class_hash =
+                crate::state::class_hash::compute_submitted_class_hash(&definition).unwrap();
+            let expected =
+                ContractAddress::compute(ContractAddress(StarkHash::ZERO), salt, class_hash, &[]);
+            let returned = ContractAddress(StarkHash::from_be_slice(b"elsewhere").unwrap());
+            assert_ne!(expected, returned);
+
+            let result =
+                submit_deploy_transaction(&sequencer(returned), salt, vec![], definition, None)
+                    .await
+                    .unwrap();
+            assert_eq!(result, accepted(returned));
+        }
+
+        #[tokio::test]
+        async fn class_hash_cannot_be_computed() {
+            let salt = ContractAddressSalt(StarkHash::from_be_slice(b"salt").unwrap());
+            let definition = ContractDefinition {
+                program: "not base64".to_owned(),
+                ..contract_definition()
+            };
+            assert!(crate::state::class_hash::compute_submitted_class_hash(&definition).is_err());
+
+            let returned = ContractAddress(StarkHash::from_be_slice(b"address").unwrap());
+            let result =
+                submit_deploy_transaction(&sequencer(returned), salt, vec![], definition, None)
+                    .await
+                    .unwrap();
+            assert_eq!(result, accepted(returned));
+        }
+    }
+}
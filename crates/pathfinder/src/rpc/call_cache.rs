@@ -0,0 +1,325 @@
+//! Bounded cache of call results.
+//!
+//! Calls made against a concrete block hash always return the same result, so they can be served
+//! from the cache instead of being executed again. Callers are responsible for resolving `latest`
+//! to a block hash first; the pending block must never be cached.
+//!
+//! Concurrent misses on the same call are executed only once, see [`CallCache::get_or_execute`].
+use crate::core::{CallParam, CallResultValue, ContractAddress, EntryPoint, StarknetBlockHash};
+use crate::rpc::types::request::Call;
+use hashlink::LruCache;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// The number of call results cached by default, see [`RpcApi::with_call_cache_capacity`].
+///
+/// [`RpcApi::with_call_cache_capacity`]: crate::rpc::api::RpcApi::with_call_cache_capacity
+pub const DEFAULT_CAPACITY: usize = 1024;
+
+/// Identifies a call on a specific block.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CallKey {
+    block_hash: StarknetBlockHash,
+    contract_address: ContractAddress,
+    entry_point_selector: EntryPoint,
+    calldata: Vec<CallParam>,
+}
+
+impl CallKey {
+    pub fn new(block_hash: StarknetBlockHash, call: &Call) -> Self {
+        Self {
+            block_hash,
+            contract_address: call.contract_address,
+            entry_point_selector: call.entry_point_selector,
+            calldata: call.calldata.clone(),
+        }
+    }
+}
+
+/// Least recently used cache of successful call results, with hit and miss counters.
+pub struct CallCache {
+    inner: Mutex<LruCache<CallKey, Vec<CallResultValue>>>,
+    /// Calls currently being executed, which callers missing the same call wait for.
+    in_flight: Mutex<HashMap<CallKey, Arc<tokio::sync::Mutex<()>>>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl CallCache {
+    /// Creates a cache of at most `capacity` results; zero disables caching.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: Mutex::new(LruCache::new(capacity)),
+            in_flight: Mutex::new(HashMap::new()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the cached result, or executes the call with `execute` and caches a successful
+    /// result.
+    ///
+    /// Callers missing a call which is already being executed wait for that execution to
+    /// complete, and only execute it themselves if it failed. They are counted as misses.
+    pub async fn get_or_execute<F, Fut, E>(
+        &self,
+        key: CallKey,
+        execute: F,
+    ) -> Result<Vec<CallResultValue>, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Vec<CallResultValue>, E>>,
+    {
+        if let Some(result) = self.get(&key) {
+            return Ok(result);
+        }
+
+        let flight = InFlight::join(self, &key);
+        let _executing = flight.lock.lock().await;
+
+        // the call could have been executed while we were waiting
+        if let Some(result) = self.peek(&key) {
+            return Ok(result);
+        }
+
+        let result = execute().await?;
+        self.insert(key.clone(), result.clone());
+        Ok(result)
+    }
+
+    /// Returns the cached result, counting the lookup as a hit or a miss.
+    pub fn get(&self, key: &CallKey) -> Option<Vec<CallResultValue>> {
+        let cached = self
+            .inner
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(key)
+            .cloned();
+
+        let counter = match cached {
+            Some(_) => &self.hits,
+            None => &self.misses,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+
+        cached
+    }
+
+    /// Returns the cached result without counting the lookup.
+    fn peek(&self, key: &CallKey) -> Option<Vec<CallResultValue>> {
+        self.inner
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(key)
+            .cloned()
+    }
+
+    pub fn insert(&self, key: CallKey, result: Vec<CallResultValue>) {
+        self.inner
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(key, result);
+    }
+
+    pub fn metrics(&self) -> CallCacheMetrics {
+        let (len, capacity) = {
+            let inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+            (inner.len(), inner.capacity())
+        };
+        CallCacheMetrics::new(
+            self.hits.load(Ordering::Relaxed),
+            self.misses.load(Ordering::Relaxed),
+            len,
+            capacity,
+        )
+    }
+}
+
+impl Default for CallCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+/// A caller's share of the execution of a call in [`CallCache::in_flight`]; the entry is removed
+/// once the last caller is done with it, even if the caller was cancelled.
+struct InFlight<'a> {
+    cache: &'a CallCache,
+    key: &'a CallKey,
+    lock: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl<'a> InFlight<'a> {
+    fn join(cache: &'a CallCache, key: &'a CallKey) -> Self {
+        let lock = cache
+            .in_flight
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(key.clone())
+            .or_default()
+            .clone();
+        Self { cache, key, lock }
+    }
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        let mut in_flight = self
+            .cache
+            .in_flight
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        // one reference is held by the map and one by us
+        if Arc::strong_count(&self.lock) == 2 {
+            in_flight.remove(self.key);
+        }
+    }
+}
+
+/// Counters of a [`CallCache`], as returned by `pathfinder_getCallCacheMetrics`.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct CallCacheMetrics {
+    pub hits: u64,
+    pub misses: u64,
+    /// Share of lookups which were hits, zero before any lookups.
+    pub hit_ratio: f64,
+    /// The number of cached results.
+    pub len: usize,
+    pub capacity: usize,
+}
+
+impl CallCacheMetrics {
+    fn new(hits: u64, misses: u64, len: usize, capacity: usize) -> Self {
+        let lookups = hits + misses;
+        let hit_ratio = if lookups == 0 {
+            0.0
+        } else {
+            hits as f64 / lookups as f64
+        };
+        Self {
+            hits,
+            misses,
+            hit_ratio,
+            len,
+            capacity,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use stark_hash::StarkHash;
+
+    fn key(block: &str, calldata: &str) -> CallKey {
+        let call = Call {
+            contract_address: ContractAddress(StarkHash::from_hex_str("1").unwrap()),
+            calldata: vec![CallParam(StarkHash::from_hex_str(calldata).unwrap())],
+            entry_point_selector: EntryPoint(StarkHash::from_hex_str("2").unwrap()),
+        };
+        CallKey::new(
+            StarknetBlockHash(StarkHash::from_hex_str(block).unwrap()),
+            &call,
+        )
+    }
+
+    fn result(value: &str) -> Vec<CallResultValue> {
+        vec![CallResultValue(StarkHash::from_hex_str(value).unwrap())]
+    }
+
+    #[test]
+    fn hits_and_misses() {
+        let cache = CallCache::new(2);
+
+        assert_eq!(cache.get(&key("a", "1")), None);
+        cache.insert(key("a", "1"), result("10"));
+        assert_eq!(cache.get(&key("a", "1")), Some(result("10")));
+        // different block or calldata are different calls
+        assert_eq!(cache.get(&key("b", "1")), None);
+        assert_eq!(cache.get(&key("a", "2")), None);
+
+        assert_eq!(
+            cache.metrics(),
+            CallCacheMetrics {
+                hits: 1,
+                misses: 3,
+                hit_ratio: 0.25,
+                len: 1,
+                capacity: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn least_recently_used_is_evicted() {
+        let cache = CallCache::new(2);
+
+        cache.insert(key("a", "1"), result("10"));
+        cache.insert(key("a", "2"), result("20"));
+        // refreshes the first one
+        cache.get(&key("a", "1")).unwrap();
+        cache.insert(key("a", "3"), result("30"));
+
+        assert_eq!(cache.get(&key("a", "1")), Some(result("10")));
+        assert_eq!(cache.get(&key("a", "2")), None);
+        assert_eq!(cache.get(&key("a", "3")), Some(result("30")));
+        assert_eq!(cache.metrics().len, 2);
+    }
+
+    #[tokio::test]
+    async fn concurrent_misses_execute_once() {
+        use std::sync::atomic::AtomicUsize;
+
+        let cache = CallCache::new(2);
+        let executions = AtomicUsize::new(0);
+        let (release_tx, release_rx) = tokio::sync::oneshot::channel::<()>();
+
+        let first = cache.get_or_execute(key("a", "1"), || async {
+            executions.fetch_add(1, Ordering::Relaxed);
+            release_rx.await.unwrap();
+            Ok::<_, ()>(result("10"))
+        });
+        let second = cache.get_or_execute(key("a", "1"), || async {
+            executions.fetch_add(1, Ordering::Relaxed);
+            Ok::<_, ()>(result("20"))
+        });
+        let release = async {
+            // let both of the callers miss before the first execution completes
+            tokio::task::yield_now().await;
+            release_tx.send(()).unwrap();
+        };
+
+        let (first, second, ()) = tokio::join!(first, second, release);
+        assert_eq!(first, Ok(result("10")));
+        assert_eq!(second, Ok(result("10")));
+        assert_eq!(executions.load(Ordering::Relaxed), 1);
+        assert_eq!(cache.metrics().misses, 2);
+        assert!(cache.in_flight.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn failed_execution_is_not_shared() {
+        let cache = CallCache::new(2);
+
+        let failed = cache
+            .get_or_execute(key("a", "1"), || async { Err("failed") })
+            .await;
+        assert_eq!(failed, Err("failed"));
+
+        let executed = cache
+            .get_or_execute(key("a", "1"), || async { Ok::<_, &str>(result("10")) })
+            .await;
+        assert_eq!(executed, Ok(result("10")));
+        assert_eq!(cache.get(&key("a", "1")), Some(result("10")));
+        assert!(cache.in_flight.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn empty_metrics() {
+        let metrics = CallCache::default().metrics();
+        assert_eq!(metrics.hit_ratio, 0.0);
+        assert_eq!(metrics.capacity, DEFAULT_CAPACITY);
+    }
+}
@@ -0,0 +1,570 @@
+//! Execution of calls, fee estimations and simulations.
+//!
+//! The work is done by an [`ExecutionBackend`]: either the python executors ([`ext_py::Handle`])
+//! or the sequencer ([`sequencer::Client`]). The [`Executor`] picks the backends to use according
+//! to an [`ExecutionPolicy`], moving on to the next one when a backend cannot handle the request.
+use crate::{
+    cairo::ext_py,
+    core::{CallResultValue, CallSignatureElem, Fee, TransactionVersion},
+    rpc::{
+        api::internal_server_error,
+        types::{
+            reply::{FeeEstimate, TransactionTrace},
+            request::{Call, StateOverride},
+            BlockHashOrTag, Tag,
+        },
+    },
+    sequencer::{self, ClientApi},
+};
+use jsonrpsee::core::{error::Error, RpcResult};
+use std::sync::Arc;
+
+/// Reasons for an [`ExecutionBackend`] to not produce a result.
+#[derive(Debug)]
+pub enum BackendError {
+    /// The backend cannot handle the request, but another backend might.
+    Unavailable(&'static str),
+    /// The request was handled, and it failed.
+    Failed(Error),
+}
+
+impl From<Error> for BackendError {
+    fn from(e: Error) -> Self {
+        BackendError::Failed(e)
+    }
+}
+
+/// Results of the calls in a multicall, in the order of the calls.
+pub type MulticallResults = Vec<RpcResult<Vec<CallResultValue>>>;
+
+/// Something which can execute calls, and run them as invoke transactions.
+#[async_trait::async_trait]
+pub trait ExecutionBackend: Send + Sync {
+    async fn call(
+        &self,
+        request: Call,
+        block_hash: BlockHashOrTag,
+        overrides: Vec<StateOverride>,
+    ) -> Result<Vec<CallResultValue>, BackendError>;
+
+    async fn estimate_fee(
+        &self,
+        request: Call,
+        signature: Vec<CallSignatureElem>,
+        max_fee: Fee,
+        version: TransactionVersion,
+        block_hash: BlockHashOrTag,
+    ) -> Result<FeeEstimate, BackendError>;
+
+    async fn simulate_transaction(
+        &self,
+        request: Call,
+        signature: Vec<CallSignatureElem>,
+        max_fee: Fee,
+        version: TransactionVersion,
+        block_hash: BlockHashOrTag,
+    ) -> Result<TransactionTrace, BackendError>;
+
+    /// Executes all of the calls on the same block. Failing calls do not fail the others.
+    ///
+    /// By default the calls are made concurrently with [`ExecutionBackend::call`]. If any of them
+    /// is [`BackendError::Unavailable`], so is the whole multicall.
+    async fn multicall(
+        &self,
+        requests: Vec<Call>,
+        block_hash: BlockHashOrTag,
+    ) -> Result<MulticallResults, BackendError> {
+        let results = futures::future::join_all(
+            requests
+                .into_iter()
+                .map(|request| self.call(request, block_hash, vec![])),
+        )
+        .await;
+
+        results
+            .into_iter()
+            .map(|result| match result {
+                Ok(output) => Ok(Ok(output)),
+                Err(BackendError::Failed(e)) => Ok(Err(e)),
+                Err(BackendError::Unavailable(reason)) => Err(BackendError::Unavailable(reason)),
+            })
+            .collect()
+    }
+}
+
+/// The python executors do not support the pending block.
+fn ext_py_supports(block_hash: &BlockHashOrTag) -> Result<(), BackendError> {
+    match block_hash {
+        BlockHashOrTag::Hash(_) | BlockHashOrTag::Tag(Tag::Latest) => Ok(()),
+        BlockHashOrTag::Tag(Tag::Pending) => {
+            Err(BackendError::Unavailable("Unsupported block: pending"))
+        }
+    }
+}
+
+impl From<ext_py::CallFailure> for BackendError {
+    fn from(e: ext_py::CallFailure) -> Self {
+        match e {
+            ext_py::CallFailure::QueueFull => {
+                BackendError::Unavailable("Too many pending calls, try again later")
+            }
+            other => BackendError::Failed(Error::from(other)),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl ExecutionBackend for ext_py::Handle {
+    async fn call(
+        &self,
+        request: Call,
+        block_hash: BlockHashOrTag,
+        overrides: Vec<StateOverride>,
+    ) -> Result<Vec<CallResultValue>, BackendError> {
+        // latest has been decided to be whatever block we have, which is exactly how the
+        // py/src/call.py handles it.
+        ext_py_supports(&block_hash)?;
+        Ok(ext_py::Handle::call(self, request, block_hash, overrides).await?)
+    }
+
+    async fn estimate_fee(
+        &self,
+        request: Call,
+        signature: Vec<CallSignatureElem>,
+        max_fee: Fee,
+        version: TransactionVersion,
+        block_hash: BlockHashOrTag,
+    ) -> Result<FeeEstimate, BackendError> {
+        ext_py_supports(&block_hash)?;
+        Ok(
+            ext_py::Handle::estimate_fee(self, request, signature, max_fee, version, block_hash)
+                .await?,
+        )
+    }
+
+    async fn simulate_transaction(
+        &self,
+        request: Call,
+        signature: Vec<CallSignatureElem>,
+        max_fee: Fee,
+        version: TransactionVersion,
+        block_hash: BlockHashOrTag,
+    ) -> Result<TransactionTrace, BackendError> {
+        ext_py_supports(&block_hash)?;
+        Ok(ext_py::Handle::simulate_transaction(
+            self, request, signature, max_fee, version, block_hash,
+        )
+        .await?)
+    }
+
+    /// Executes all of the calls with a single executor, on the same state.
+    async fn multicall(
+        &self,
+        requests: Vec<Call>,
+        block_hash: BlockHashOrTag,
+    ) -> Result<MulticallResults, BackendError> {
+        ext_py_supports(&block_hash)?;
+        Ok(ext_py::Handle::multicall(self, requests, block_hash)
+            .await?
+            .into_iter()
+            .map(|result| result.map_err(Error::from))
+            .collect())
+    }
+}
+
+/// The sequencer only supports plain calls.
+#[async_trait::async_trait]
+impl ExecutionBackend for sequencer::Client {
+    async fn call(
+        &self,
+        request: Call,
+        block_hash: BlockHashOrTag,
+        overrides: Vec<StateOverride>,
+    ) -> Result<Vec<CallResultValue>, BackendError> {
+        if !overrides.is_empty() {
+            return Err(BackendError::Unavailable(
+                "State overrides are not supported with this configuration",
+            ));
+        }
+
+        ClientApi::call(self, request.into(), block_hash)
+            .await
+            .map(|x| x.result)
+            .map_err(|e| BackendError::Failed(Error::from(e)))
+    }
+
+    async fn estimate_fee(
+        &self,
+        _: Call,
+        _: Vec<CallSignatureElem>,
+        _: Fee,
+        _: TransactionVersion,
+        _: BlockHashOrTag,
+    ) -> Result<FeeEstimate, BackendError> {
+        Err(BackendError::Unavailable("Unsupported configuration"))
+    }
+
+    async fn simulate_transaction(
+        &self,
+        _: Call,
+        _: Vec<CallSignatureElem>,
+        _: Fee,
+        _: TransactionVersion,
+        _: BlockHashOrTag,
+    ) -> Result<TransactionTrace, BackendError> {
+        Err(BackendError::Unavailable("Unsupported configuration"))
+    }
+}
+
+/// Which backends the [`Executor`] uses, in order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecutionPolicy {
+    /// Only use the local backend.
+    LocalOnly,
+    /// Use the local backend, and the sequencer for what the local backend cannot handle.
+    LocalWithSequencerFallback,
+    /// Only use the sequencer.
+    SequencerOnly,
+}
+
+impl std::str::FromStr for ExecutionPolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "local" => Ok(ExecutionPolicy::LocalOnly),
+            "local-with-fallback" => Ok(ExecutionPolicy::LocalWithSequencerFallback),
+            "sequencer" => Ok(ExecutionPolicy::SequencerOnly),
+            other => Err(anyhow::anyhow!(
+                "Unknown execution policy {:?}, expected one of: local, local-with-fallback, sequencer",
+                other
+            )),
+        }
+    }
+}
+
+/// Routes requests to the [`ExecutionBackend`]s according to the [`ExecutionPolicy`].
+#[derive(Clone)]
+pub struct Executor {
+    local: Option<Arc<dyn ExecutionBackend>>,
+    sequencer: Arc<dyn ExecutionBackend>,
+    policy: ExecutionPolicy,
+}
+
+impl Executor {
+    /// Creates an executor without a local backend, which uses the sequencer whatever the policy.
+    pub fn new(sequencer: Arc<dyn ExecutionBackend>) -> Self {
+        Self {
+            local: None,
+            sequencer,
+            policy: ExecutionPolicy::LocalWithSequencerFallback,
+        }
+    }
+
+    pub fn with_local(self, local: Arc<dyn ExecutionBackend>) -> Self {
+        Self {
+            local: Some(local),
+            ..self
+        }
+    }
+
+    pub fn with_policy(self, policy: ExecutionPolicy) -> Self {
+        Self { policy, ..self }
+    }
+
+    /// The backends to try, in order.
+    fn backends(&self) -> impl Iterator<Item = &dyn ExecutionBackend> {
+        let (local, sequencer) = match self.policy {
+            ExecutionPolicy::LocalOnly => (self.local.as_deref(), None),
+            ExecutionPolicy::LocalWithSequencerFallback => {
+                (self.local.as_deref(), Some(self.sequencer.as_ref()))
+            }
+            ExecutionPolicy::SequencerOnly => (None, Some(self.sequencer.as_ref())),
+        };
+        local.into_iter().chain(sequencer)
+    }
+
+    /// Runs `f` with the backends until one of them handles the request.
+    ///
+    /// Fails with the reason given by the first backend if none of them could handle it.
+    async fn execute<'a, T, F, Fut>(&'a self, mut f: F) -> RpcResult<T>
+    where
+        F: FnMut(&'a dyn ExecutionBackend) -> Fut,
+        Fut: std::future::Future<Output = Result<T, BackendError>>,
+    {
+        let mut first_reason = None;
+
+        for backend in self.backends() {
+            match f(backend).await {
+                Ok(output) => return Ok(output),
+                Err(BackendError::Failed(e)) => return Err(e),
+                Err(BackendError::Unavailable(reason)) => {
+                    first_reason.get_or_insert(reason);
+                }
+            }
+        }
+
+        Err(internal_server_error(
+            first_reason.unwrap_or("Unsupported configuration"),
+        ))
+    }
+
+    pub async fn call(
+        &self,
+        request: Call,
+        block_hash: BlockHashOrTag,
+        overrides: Vec<StateOverride>,
+    ) -> RpcResult<Vec<CallResultValue>> {
+        self.execute(|backend| backend.call(request.clone(), block_hash, overrides.clone()))
+            .await
+    }
+
+    pub async fn estimate_fee(
+        &self,
+        request: Call,
+        signature: Vec<CallSignatureElem>,
+        max_fee: Fee,
+        version: TransactionVersion,
+        block_hash: BlockHashOrTag,
+    ) -> RpcResult<FeeEstimate> {
+        self.execute(|backend| {
+            backend.estimate_fee(
+                request.clone(),
+                signature.clone(),
+                max_fee,
+                version,
+                block_hash,
+            )
+        })
+        .await
+    }
+
+    pub async fn simulate_transaction(
+        &self,
+        request: Call,
+        signature: Vec<CallSignatureElem>,
+        max_fee: Fee,
+        version: TransactionVersion,
+        block_hash: BlockHashOrTag,
+    ) -> RpcResult<TransactionTrace> {
+        self.execute(|backend| {
+            backend.simulate_transaction(
+                request.clone(),
+                signature.clone(),
+                max_fee,
+                version,
+                block_hash,
+            )
+        })
+        .await
+    }
+
+    pub async fn multicall(
+        &self,
+        requests: Vec<Call>,
+        block_hash: BlockHashOrTag,
+    ) -> RpcResult<MulticallResults> {
+        self.execute(|backend| backend.multicall(requests.clone(), block_hash))
+            .await
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod fake {
+    //! A deterministic [`ExecutionBackend`] for tests.
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Returns the configured outputs, or [`BackendError::Unavailable`] when there are none.
+    #[derive(Default)]
+    pub struct FakeBackend {
+        pub call: Option<Vec<CallResultValue>>,
+        pub fee: Option<FeeEstimate>,
+        pub unavailable_reason: Option<&'static str>,
+        /// Number of requests this backend has been asked to handle.
+        pub requests: AtomicUsize,
+    }
+
+    impl FakeBackend {
+        fn output<T: Clone>(&self, output: &Option<T>) -> Result<T, BackendError> {
+            self.requests.fetch_add(1, Ordering::Relaxed);
+            output.clone().ok_or(BackendError::Unavailable(
+                self.unavailable_reason.unwrap_or("Fake backend"),
+            ))
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl ExecutionBackend for FakeBackend {
+        async fn call(
+            &self,
+            _: Call,
+            _: BlockHashOrTag,
+            _: Vec<StateOverride>,
+        ) -> Result<Vec<CallResultValue>, BackendError> {
+            self.output(&self.call)
+        }
+
+        async fn estimate_fee(
+            &self,
+            _: Call,
+            _: Vec<CallSignatureElem>,
+            _: Fee,
+            _: TransactionVersion,
+            _: BlockHashOrTag,
+        ) -> Result<FeeEstimate, BackendError> {
+            self.output(&self.fee)
+        }
+
+        async fn simulate_transaction(
+            &self,
+            _: Call,
+            _: Vec<CallSignatureElem>,
+            _: Fee,
+            _: TransactionVersion,
+            _: BlockHashOrTag,
+        ) -> Result<TransactionTrace, BackendError> {
+            self.output(&None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fake::FakeBackend;
+    use super::*;
+    use crate::core::{ContractAddress, EntryPoint};
+    use stark_hash::StarkHash;
+    use std::sync::atomic::Ordering;
+
+    fn call() -> Call {
+        Call {
+            contract_address: ContractAddress(StarkHash::from_hex_str("1").unwrap()),
+            calldata: vec![],
+            entry_point_selector: EntryPoint(StarkHash::from_hex_str("2").unwrap()),
+        }
+    }
+
+    fn output(value: &str) -> Vec<CallResultValue> {
+        vec![CallResultValue(StarkHash::from_hex_str(value).unwrap())]
+    }
+
+    fn backend(value: Option<&str>) -> Arc<FakeBackend> {
+        Arc::new(FakeBackend {
+            call: value.map(output),
+            ..Default::default()
+        })
+    }
+
+    async fn call_with(
+        local: &Arc<FakeBackend>,
+        sequencer: &Arc<FakeBackend>,
+        policy: ExecutionPolicy,
+    ) -> RpcResult<Vec<CallResultValue>> {
+        Executor::new(sequencer.clone())
+            .with_local(local.clone())
+            .with_policy(policy)
+            .call(call(), BlockHashOrTag::Tag(Tag::Latest), vec![])
+            .await
+    }
+
+    #[tokio::test]
+    async fn local_only() {
+        let local = backend(Some("1"));
+        let sequencer = backend(Some("2"));
+
+        let result = call_with(&local, &sequencer, ExecutionPolicy::LocalOnly).await;
+        assert_eq!(result.unwrap(), output("1"));
+        assert_eq!(sequencer.requests.load(Ordering::Relaxed), 0);
+
+        let local = backend(None);
+        call_with(&local, &sequencer, ExecutionPolicy::LocalOnly)
+            .await
+            .unwrap_err();
+        assert_eq!(sequencer.requests.load(Ordering::Relaxed), 0);
+    }
+
+    #[tokio::test]
+    async fn sequencer_only() {
+        let local = backend(Some("1"));
+        let sequencer = backend(Some("2"));
+
+        let result = call_with(&local, &sequencer, ExecutionPolicy::SequencerOnly).await;
+        assert_eq!(result.unwrap(), output("2"));
+        assert_eq!(local.requests.load(Ordering::Relaxed), 0);
+    }
+
+    #[tokio::test]
+    async fn fallback() {
+        let sequencer = backend(Some("2"));
+
+        let local = backend(Some("1"));
+        let result = call_with(
+            &local,
+            &sequencer,
+            ExecutionPolicy::LocalWithSequencerFallback,
+        );
+        assert_eq!(result.await.unwrap(), output("1"));
+        assert_eq!(sequencer.requests.load(Ordering::Relaxed), 0);
+
+        let local = backend(None);
+        let result = call_with(
+            &local,
+            &sequencer,
+            ExecutionPolicy::LocalWithSequencerFallback,
+        );
+        assert_eq!(result.await.unwrap(), output("2"));
+        assert_eq!(local.requests.load(Ordering::Relaxed), 1);
+        assert_eq!(sequencer.requests.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn first_reason_is_reported() {
+        let local = Arc::new(FakeBackend {
+            unavailable_reason: Some("Local reason"),
+            ..Default::default()
+        });
+        let sequencer = Arc::new(FakeBackend {
+            unavailable_reason: Some("Sequencer reason"),
+            ..Default::default()
+        });
+
+        let error = call_with(
+            &local,
+            &sequencer,
+            ExecutionPolicy::LocalWithSequencerFallback,
+        )
+        .await
+        .unwrap_err();
+        assert!(error.to_string().contains("Local reason"), "{error}");
+    }
+
+    #[tokio::test]
+    async fn default_multicall_uses_call() {
+        let backend = backend(Some("1"));
+        let results = backend
+            .multicall(vec![call(), call()], BlockHashOrTag::Tag(Tag::Latest))
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 2);
+        for result in results {
+            assert_eq!(result.unwrap(), output("1"));
+        }
+        assert_eq!(backend.requests.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn parse_policy() {
+        for (s, policy) in [
+            ("local", ExecutionPolicy::LocalOnly),
+            (
+                "local-with-fallback",
+                ExecutionPolicy::LocalWithSequencerFallback,
+            ),
+            ("sequencer", ExecutionPolicy::SequencerOnly),
+        ] {
+            assert_eq!(s.parse::<ExecutionPolicy>().unwrap(), policy);
+        }
+        "remote".parse::<ExecutionPolicy>().unwrap_err();
+    }
+}
@@ -3,14 +3,13 @@
 use crate::core::{
     CallParam, CallSignatureElem, ConstructorParam, EthereumAddress, EventData, EventKey, Fee,
     GasPrice, L1ToL2MessagePayloadElem, L2ToL1MessagePayloadElem, StarknetBlockNumber,
-    TransactionSignatureElem, TransactionVersion,
+    StarknetTransactionIndex, TransactionSignatureElem, TransactionVersion,
 };
-use num_bigint::BigUint;
+use crate::storage::TransactionsContinuationToken;
 use serde::de::Visitor;
 use serde_with::{serde_conv, DeserializeAs, SerializeAs};
-use stark_hash::{HexParseError, OverflowError, StarkHash};
+use stark_hash::{DecimalParseError, HexParseError, OverflowError, StarkHash, Uint256};
 use std::borrow::Cow;
-use std::str::FromStr;
 use web3::types::{H128, H160, H256};
 
 serde_conv!(
@@ -282,6 +281,98 @@ impl<'de> DeserializeAs<'de, StarknetBlockNumber> for StarknetBlockNumberAsHexSt
     }
 }
 
+/// Serializes a [StarkHash], or one of its newtypes with short string conversions in
+/// [crate::core], as a Cairo short string, for example `"ETH"` for `0x455448`.
+pub struct ShortStringAsStr;
+
+impl<T> SerializeAs<T> for ShortStringAsStr
+where
+    T: Copy + Into<StarkHash>,
+{
+    fn serialize_as<S>(source: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let hash: StarkHash = (*source).into();
+        let s = hash.to_short_string().map_err(serde::ser::Error::custom)?;
+        serializer.serialize_str(&s)
+    }
+}
+
+impl<'de, T> DeserializeAs<'de, T> for ShortStringAsStr
+where
+    T: From<StarkHash>,
+{
+    fn deserialize_as<D>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct ShortStringVisitor;
+
+        impl<'de> Visitor<'de> for ShortStringVisitor {
+            type Value = StarkHash;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("an ASCII string of up to 31 characters")
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                StarkHash::from_short_string(v).map_err(serde::de::Error::custom)
+            }
+        }
+
+        deserializer
+            .deserialize_str(ShortStringVisitor)
+            .map(T::from)
+    }
+}
+
+pub struct Uint256AsHexStr;
+
+impl SerializeAs<Uint256> for Uint256AsHexStr {
+    fn serialize_as<S>(source: &Uint256, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        // Uint256 is "0x" + 64 digits at most
+        let mut buf = [0u8; 2 + 64];
+        let bytes = source.to_be_bytes();
+        let s = bytes_as_hex_str(&bytes, &mut buf);
+        serializer.serialize_str(s)
+    }
+}
+
+impl<'de> DeserializeAs<'de, Uint256> for Uint256AsHexStr {
+    fn deserialize_as<D>(deserializer: D) -> Result<Uint256, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct Uint256Visitor;
+
+        impl<'de> Visitor<'de> for Uint256Visitor {
+            type Value = Uint256;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a hex string of up to 64 digits with an optional '0x' prefix")
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                bytes_from_hex_str::<32>(v)
+                    .map_err(serde::de::Error::custom)
+                    .map(Uint256::from_be_bytes)
+            }
+        }
+
+        deserializer.deserialize_str(Uint256Visitor)
+    }
+}
+
 serde_with::serde_conv!(
     pub TransactionVersionAsHexStr,
     TransactionVersion,
@@ -289,26 +380,47 @@ serde_with::serde_conv!(
     |s: &str| bytes_from_hex_str::<{ H256::len_bytes() }>(s).map(|b| TransactionVersion(H256::from(b)))
 );
 
-/// A helper conversion function. Only use with __sequencer API related types__.
-fn starkhash_from_biguint(b: BigUint) -> Result<StarkHash, OverflowError> {
-    StarkHash::from_be_slice(&b.to_bytes_be())
+serde_with::serde_conv!(
+    pub TransactionsContinuationTokenAsStr,
+    TransactionsContinuationToken,
+    |serialize_me: &TransactionsContinuationToken| format!(
+        "{}-{}",
+        serialize_me.block_number.0, serialize_me.transaction_index.0
+    ),
+    |s: &str| continuation_token_from_str(s)
+);
+
+/// A continuation token is encoded as `<block number>-<transaction index>`.
+fn continuation_token_from_str(s: &str) -> anyhow::Result<TransactionsContinuationToken> {
+    use anyhow::Context;
+
+    let (block_number, transaction_index) = s
+        .split_once('-')
+        .context("Continuation token is missing the '-' separator")?;
+    let block_number = block_number
+        .parse()
+        .context("Parsing continuation token block number")?;
+    let transaction_index = transaction_index
+        .parse()
+        .context("Parsing continuation token transaction index")?;
+
+    Ok(TransactionsContinuationToken {
+        block_number: StarknetBlockNumber(block_number),
+        transaction_index: StarknetTransactionIndex(transaction_index),
+    })
 }
 
 /// A helper conversion function. Only use with __sequencer API related types__.
 pub(crate) fn starkhash_to_dec_str(h: &StarkHash) -> String {
-    let b = h.to_be_bytes();
-    let b = BigUint::from_bytes_be(&b);
-    b.to_str_radix(10)
+    h.to_dec_str()
 }
 
 /// A helper conversion function. Only use with __sequencer API related types__.
 fn starkhash_from_dec_str(s: &str) -> Result<StarkHash, anyhow::Error> {
     // TODO remove fallback to hex string representation once mainnet moves to cairo-0.8.0
-    match BigUint::from_str(s) {
-        Ok(b) => {
-            let h = starkhash_from_biguint(b)?;
-            Ok(h)
-        }
+    match StarkHash::from_dec_str(s) {
+        Ok(h) => Ok(h),
+        Err(DecimalParseError::Overflow) => Err(OverflowError.into()),
         Err(_) => {
             let h = StarkHash::from_hex_str(s)?;
             Ok(h)
@@ -437,7 +549,7 @@ mod tests {
         const ZERO_DEC_STR: &str = "0";
         const ZERO_BYTES: [u8; 1] = [0];
 
-        let a = starkhash_from_biguint(BigUint::from_bytes_be(&ZERO_BYTES)).unwrap();
+        let a = StarkHash::from_be_slice(&ZERO_BYTES).unwrap();
         let b = starkhash_from_dec_str(ZERO_DEC_STR).unwrap();
         let expected = StarkHash::ZERO;
         assert_eq!(expected, a);
@@ -457,7 +569,7 @@ mod tests {
         const ODD_DEC_STR: &str = "81985529205931230";
         const ODD_BYTES: [u8; 8] = [1, 0x23, 0x45, 0x67, 0x89, 0x0a, 0xbc, 0xde];
 
-        let a = starkhash_from_biguint(BigUint::from_bytes_be(&ODD_BYTES)).unwrap();
+        let a = StarkHash::from_be_slice(&ODD_BYTES).unwrap();
         let b = starkhash_from_dec_str(ODD_DEC_STR).unwrap();
         let expected = StarkHash::from_hex_str(ODD_HEX_STR).unwrap();
         assert_eq!(expected, a);
@@ -477,7 +589,7 @@ mod tests {
         const EVEN_DEC_STR: &str = "1311768467294899695";
         const EVEN_BYTES: [u8; 8] = [0x12, 0x34, 0x56, 0x78, 0x90, 0xab, 0xcd, 0xef];
 
-        let a = starkhash_from_biguint(BigUint::from_bytes_be(&EVEN_BYTES)).unwrap();
+        let a = StarkHash::from_be_slice(&EVEN_BYTES).unwrap();
         let b = starkhash_from_dec_str(EVEN_DEC_STR).unwrap();
         let expected = StarkHash::from_hex_str(EVEN_HEX_STR).unwrap();
         assert_eq!(expected, a);
@@ -502,7 +614,7 @@ mod tests {
             0, 0, 0,
         ];
 
-        let a = starkhash_from_biguint(BigUint::from_bytes_be(&MAX_BYTES)).unwrap();
+        let a = StarkHash::from_be_slice(&MAX_BYTES).unwrap();
         let b = starkhash_from_dec_str(MAX_DEC_STR).unwrap();
         let expected = StarkHash::from_hex_str(MAX_HEX_STR).unwrap();
         assert_eq!(expected, a);
@@ -533,7 +645,7 @@ mod tests {
         ];
 
         assert_eq!(
-            starkhash_from_biguint(BigUint::from_bytes_be(&OVERFLOW_BYTES)),
+            StarkHash::from_be_slice(&OVERFLOW_BYTES),
             Err(OverflowError)
         );
         assert_eq!(
@@ -558,7 +670,7 @@ mod tests {
 
         use stark_hash::HexParseError;
         assert_eq!(
-            starkhash_from_biguint(BigUint::from_bytes_be(&TOO_LONG_BYTES)),
+            StarkHash::from_be_slice(&TOO_LONG_BYTES),
             Err(OverflowError)
         );
         assert_eq!(
@@ -586,10 +698,10 @@ mod tests {
     #[test]
     fn invalid_digit() {
         // TODO revert when mainnet moves to cairo-0.8.0
-        // use num_bigint::ParseBigIntError;
+        // use stark_hash::DecimalParseError;
         // starkhash_from_dec_str("123z")
         //     .unwrap_err()
-        //     .downcast::<ParseBigIntError>()
+        //     .downcast::<DecimalParseError>()
         //     .unwrap();
         starkhash_from_dec_str("123a").unwrap();
         assert_eq!(
@@ -642,4 +754,66 @@ mod tests {
             });
         }
     }
+
+    mod short_string_as_str {
+        use crate::core::CallResultValue;
+        use stark_hash::StarkHash;
+
+        #[serde_with::serde_as]
+        #[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+        struct Token {
+            #[serde_as(as = "super::ShortStringAsStr")]
+            name: CallResultValue,
+            #[serde_as(as = "super::ShortStringAsStr")]
+            symbol: StarkHash,
+        }
+
+        #[test]
+        fn round_trip() {
+            let token = Token {
+                name: CallResultValue(StarkHash::from(0x4574686572u64)),
+                symbol: StarkHash::from(0x455448u64),
+            };
+            let json = r#"{"name":"Ether","symbol":"ETH"}"#;
+
+            assert_eq!(serde_json::to_string(&token).unwrap(), json);
+            assert_eq!(serde_json::from_str::<Token>(json).unwrap(), token);
+        }
+
+        #[test]
+        fn errors() {
+            let not_ascii = Token {
+                name: CallResultValue(StarkHash::from(0xffu64)),
+                symbol: StarkHash::ZERO,
+            };
+            serde_json::to_string(&not_ascii).unwrap_err();
+
+            let too_long = format!(r#"{{"name":"{}","symbol":""}}"#, "a".repeat(32));
+            serde_json::from_str::<Token>(&too_long).unwrap_err();
+        }
+    }
+
+    mod uint256_as_hex_str {
+        use stark_hash::Uint256;
+
+        #[serde_with::serde_as]
+        #[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+        struct Balance(#[serde_as(as = "super::Uint256AsHexStr")] Uint256);
+
+        #[test]
+        fn round_trip() {
+            let balance = Balance(Uint256 {
+                low: 0xabc,
+                high: 1,
+            });
+            let json = r#""0x100000000000000000000000000000abc""#;
+
+            assert_eq!(serde_json::to_string(&balance).unwrap(), json);
+            assert_eq!(serde_json::from_str::<Balance>(json).unwrap(), balance);
+            assert_eq!(
+                serde_json::from_str::<Balance>(r#""0x0""#).unwrap(),
+                Balance(Uint256::ZERO)
+            );
+        }
+    }
 }
@@ -75,14 +75,87 @@ impl std::fmt::Display for BlockNumberOrTag {
     }
 }
 
+/// Identifies a block by its [Hash](self::BlockId::Hash), [Number](self::BlockId::Number) or a
+/// [Tag](self::BlockId::Tag).
+///
+/// Accepts everything [BlockHashOrTag] and [BlockNumberOrTag] accept.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum BlockId {
+    /// Hash of a block, represented as a `0x`-prefixed hex JSON string
+    Hash(StarknetBlockHash),
+    /// Number (height) of a block, represented as a JSON number
+    Number(StarknetBlockNumber),
+    /// Special [Tag](crate::rpc::types::Tag) describing a block
+    Tag(Tag),
+}
+
+impl From<BlockHashOrTag> for BlockId {
+    fn from(block: BlockHashOrTag) -> Self {
+        match block {
+            BlockHashOrTag::Hash(hash) => BlockId::Hash(hash),
+            BlockHashOrTag::Tag(tag) => BlockId::Tag(tag),
+        }
+    }
+}
+
+impl From<BlockNumberOrTag> for BlockId {
+    fn from(block: BlockNumberOrTag) -> Self {
+        match block {
+            BlockNumberOrTag::Number(number) => BlockId::Number(number),
+            BlockNumberOrTag::Tag(tag) => BlockId::Tag(tag),
+        }
+    }
+}
+
+impl std::fmt::Display for BlockId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BlockId::Hash(StarknetBlockHash(h)) => f.write_str(&h.to_hex_str()),
+            BlockId::Number(StarknetBlockNumber(n)) => std::fmt::Display::fmt(n, f),
+            BlockId::Tag(t) => std::fmt::Display::fmt(t, f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod block_id {
+    use super::{BlockId, Tag};
+    use crate::core::{StarknetBlockHash, StarknetBlockNumber};
+    use stark_hash::StarkHash;
+
+    #[test]
+    fn deserialize() {
+        for (json, expected) in [
+            (
+                r#""0x1234""#,
+                BlockId::Hash(StarknetBlockHash(StarkHash::from_hex_str("1234").unwrap())),
+            ),
+            ("1234", BlockId::Number(StarknetBlockNumber(1234))),
+            (r#""latest""#, BlockId::Tag(Tag::Latest)),
+            (r#""pending""#, BlockId::Tag(Tag::Pending)),
+        ] {
+            assert_eq!(serde_json::from_str::<BlockId>(json).unwrap(), expected);
+        }
+
+        serde_json::from_str::<BlockId>(r#""earliest""#).unwrap_err();
+        serde_json::from_str::<BlockId>("-1").unwrap_err();
+    }
+}
+
 /// Groups all strictly input types of the RPC API.
 pub mod request {
     use crate::{
-        core::{CallParam, ContractAddress, EntryPoint, EventKey, StarknetBlockNumber},
-        rpc::serde::H256AsNoLeadingZerosHexStr,
+        core::{
+            CallParam, ClassHash, ContractAddress, EntryPoint, EventKey, StarknetBlockNumber,
+            StorageAddress, StorageValue,
+        },
+        rpc::serde::{H256AsNoLeadingZerosHexStr, TransactionsContinuationTokenAsStr},
+        storage::TransactionsContinuationToken,
     };
     use serde::{Deserialize, Serialize};
     use serde_with::{serde_as, skip_serializing_none};
+    use std::collections::BTreeMap;
     use web3::types::H256;
 
     /// The address of a storage element for a StarkNet contract.
@@ -102,6 +175,22 @@ pub mod request {
         pub entry_point_selector: EntryPoint,
     }
 
+    /// Temporary changes to the state of a contract, applied for the duration of a
+    /// `starknet_call` only.
+    #[skip_serializing_none]
+    #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+    #[serde(deny_unknown_fields)]
+    pub struct StateOverride {
+        pub contract_address: ContractAddress,
+        /// Storage values used instead of the stored ones.
+        #[serde(default)]
+        pub storage: BTreeMap<StorageAddress, StorageValue>,
+        /// Class hash used instead of the one the contract was deployed with. The class has to
+        /// be known to the node.
+        #[serde(default)]
+        pub class_hash: Option<ClassHash>,
+    }
+
     /// Determines the type of response to block related queries.
     #[derive(Copy, Clone, Debug, Deserialize, Serialize, PartialEq)]
     #[serde(deny_unknown_fields)]
@@ -139,6 +228,50 @@ pub mod request {
         pub page_size: usize,
         pub page_number: usize,
     }
+
+    /// Contains filter parameters passed to `pathfinder_getTransactionsByAddress`.
+    #[serde_as]
+    #[skip_serializing_none]
+    #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+    #[serde(deny_unknown_fields)]
+    pub struct TransactionsByAddressFilter {
+        pub address: ContractAddress,
+        #[serde(default, rename = "fromBlock")]
+        pub from_block: Option<StarknetBlockNumber>,
+        #[serde(default, rename = "toBlock")]
+        pub to_block: Option<StarknetBlockNumber>,
+        pub page_size: usize,
+        #[serde_as(as = "Option<TransactionsContinuationTokenAsStr>")]
+        #[serde(default)]
+        pub continuation_token: Option<TransactionsContinuationToken>,
+    }
+
+    /// Contains filter parameters passed to `pathfinder_getStorageHistory`.
+    #[skip_serializing_none]
+    #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+    #[serde(deny_unknown_fields)]
+    pub struct StorageHistoryFilter {
+        pub address: ContractAddress,
+        pub key: StorageAddress,
+        #[serde(default, rename = "fromBlock")]
+        pub from_block: Option<StarknetBlockNumber>,
+        #[serde(default, rename = "toBlock")]
+        pub to_block: Option<StarknetBlockNumber>,
+        pub page_size: usize,
+        #[serde(default)]
+        pub continuation_token: Option<StarknetBlockNumber>,
+    }
+
+    /// Contains filter parameters passed to `pathfinder_getContractsByClassHash`.
+    #[skip_serializing_none]
+    #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+    #[serde(deny_unknown_fields)]
+    pub struct ContractsByClassHashFilter {
+        pub class_hash: ClassHash,
+        pub page_size: usize,
+        #[serde(default)]
+        pub continuation_token: Option<ContractAddress>,
+    }
 }
 
 /// Groups all strictly output types of the RPC API.
@@ -147,20 +280,26 @@ pub mod reply {
     use super::request::BlockResponseScope;
     use crate::{
         core::{
-            CallParam, ClassHash, ContractAddress, EntryPoint, EventData, EventKey, Fee, GasPrice,
-            GlobalRoot, SequencerAddress, StarknetBlockHash, StarknetBlockNumber,
-            StarknetBlockTimestamp, StarknetTransactionHash,
+            CallParam, CallResultValue, ClassHash, ContractAddress, EntryPoint, EventData,
+            EventKey, Fee, GasPrice, GlobalRoot, SequencerAddress, StarknetBlockHash,
+            StarknetBlockNumber, StarknetBlockTimestamp, StarknetTransactionHash,
+            StarknetTransactionIndex, StorageValue,
         },
         rpc::{
             api::RawBlock,
-            serde::{FeeAsHexStr, GasPriceAsHexStr},
+            serde::{
+                FeeAsHexStr, GasPriceAsHexStr, H256AsNoLeadingZerosHexStr,
+                TransactionsContinuationTokenAsStr,
+            },
         },
         sequencer,
+        storage::TransactionsContinuationToken,
     };
     use serde::{Deserialize, Serialize};
     use serde_with::{serde_as, skip_serializing_none};
     use stark_hash::StarkHash;
     use std::convert::From;
+    use web3::types::H256;
 
     /// L2 Block status as returned by the RPC API.
     #[derive(Copy, Clone, Debug, Deserialize, Serialize, PartialEq)]
@@ -942,6 +1081,163 @@ pub mod reply {
         pub is_last_page: bool,
     }
 
+    /// Describes a transaction returned by `pathfinder_getTransactionsByAddress`.
+    #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+    #[serde(deny_unknown_fields)]
+    pub struct AddressTransaction {
+        pub block_hash: StarknetBlockHash,
+        pub block_number: StarknetBlockNumber,
+        pub transaction_index: StarknetTransactionIndex,
+        pub transaction_hash: StarknetTransactionHash,
+    }
+
+    impl From<crate::storage::StarknetAddressTransaction> for AddressTransaction {
+        fn from(transaction: crate::storage::StarknetAddressTransaction) -> Self {
+            Self {
+                block_hash: transaction.block_hash,
+                block_number: transaction.block_number,
+                transaction_index: transaction.transaction_index,
+                transaction_hash: transaction.transaction_hash,
+            }
+        }
+    }
+
+    // Result type for pathfinder_getTransactionsByAddress
+    #[serde_as]
+    #[skip_serializing_none]
+    #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+    #[serde(deny_unknown_fields)]
+    pub struct GetTransactionsByAddressResult {
+        pub transactions: Vec<AddressTransaction>,
+        /// Present if there are more transactions, pass it in the next request's filter.
+        #[serde_as(as = "Option<TransactionsContinuationTokenAsStr>")]
+        #[serde(default)]
+        pub continuation_token: Option<TransactionsContinuationToken>,
+    }
+
+    /// Describes a storage change returned by `pathfinder_getStorageHistory`.
+    #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+    #[serde(deny_unknown_fields)]
+    pub struct StorageChange {
+        pub block_hash: StarknetBlockHash,
+        pub block_number: StarknetBlockNumber,
+        pub value: StorageValue,
+    }
+
+    impl From<crate::storage::StorageChange> for StorageChange {
+        fn from(change: crate::storage::StorageChange) -> Self {
+            Self {
+                block_hash: change.block_hash,
+                block_number: change.block_number,
+                value: change.value,
+            }
+        }
+    }
+
+    // Result type for pathfinder_getStorageHistory
+    #[skip_serializing_none]
+    #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+    #[serde(deny_unknown_fields)]
+    pub struct GetStorageHistoryResult {
+        pub changes: Vec<StorageChange>,
+        /// The first block for which changes have been recorded; the changes made in earlier
+        /// blocks are not available from this node.
+        pub history_start: StarknetBlockNumber,
+        /// Present if there are more changes, pass it in the next request's filter.
+        #[serde(default)]
+        pub continuation_token: Option<StarknetBlockNumber>,
+    }
+
+    /// Describes the deployment of a contract.
+    #[skip_serializing_none]
+    #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+    #[serde(deny_unknown_fields)]
+    pub struct ContractDeployment {
+        pub contract_address: ContractAddress,
+        pub class_hash: ClassHash,
+        pub block_hash: StarknetBlockHash,
+        pub block_number: StarknetBlockNumber,
+        /// Absent if the contract was not deployed by a deploy transaction.
+        #[serde(default)]
+        pub transaction_hash: Option<StarknetTransactionHash>,
+    }
+
+    impl From<crate::storage::ContractDeployment> for ContractDeployment {
+        fn from(deployment: crate::storage::ContractDeployment) -> Self {
+            Self {
+                contract_address: deployment.address,
+                class_hash: deployment.class_hash,
+                block_hash: deployment.block_hash,
+                block_number: deployment.block_number,
+                transaction_hash: deployment.transaction_hash,
+            }
+        }
+    }
+
+    // Result type for pathfinder_getContractsByClassHash
+    #[skip_serializing_none]
+    #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+    #[serde(deny_unknown_fields)]
+    pub struct GetContractsByClassHashResult {
+        pub contracts: Vec<ContractDeployment>,
+        /// Present if there are more contracts, pass it in the next request's filter.
+        #[serde(default)]
+        pub continuation_token: Option<ContractAddress>,
+    }
+
+    /// A contract class, as returned by `starknet_getClass` and `starknet_getClassAt`.
+    #[skip_serializing_none]
+    #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+    #[serde(deny_unknown_fields)]
+    pub struct ContractClass {
+        /// gzip + base64 encoded JSON of the compiled program.
+        pub program: String,
+        pub entry_points_by_type: std::collections::HashMap<
+            sequencer::request::contract::EntryPointType,
+            Vec<sequencer::request::contract::SelectorAndOffset>,
+        >,
+        #[serde(default)]
+        pub abi: Option<serde_json::Value>,
+    }
+
+    impl ContractClass {
+        /// Builds the class from a full contract definition, as stored in
+        /// [ContractCodeTable](crate::storage::ContractCodeTable) and served by the sequencer.
+        pub fn from_definition_bytes(definition: &[u8]) -> anyhow::Result<Self> {
+            use anyhow::Context;
+            use std::io::Write;
+
+            #[derive(Deserialize)]
+            struct Definition<'a> {
+                #[serde(borrow)]
+                program: &'a serde_json::value::RawValue,
+                entry_points_by_type: std::collections::HashMap<
+                    sequencer::request::contract::EntryPointType,
+                    Vec<sequencer::request::contract::SelectorAndOffset>,
+                >,
+                #[serde(default)]
+                abi: Option<serde_json::Value>,
+            }
+
+            let definition = serde_json::from_slice::<Definition<'_>>(definition)
+                .context("Parsing contract definition")?;
+
+            let mut gzip_encoder =
+                flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+            gzip_encoder
+                .write_all(definition.program.get().as_bytes())
+                .context("Compressing program")?;
+            let program = gzip_encoder.finish().context("Compressing program")?;
+            let program = base64::encode(program);
+
+            Ok(Self {
+                program,
+                entry_points_by_type: definition.entry_points_by_type,
+                abi: definition.abi,
+            })
+        }
+    }
+
     // Result type for starknet_addInvokeTransaction
     #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
     #[serde(deny_unknown_fields)]
@@ -964,4 +1260,149 @@ pub mod reply {
         pub transaction_hash: StarknetTransactionHash,
         pub contract_address: ContractAddress,
     }
+
+    /// Result type for starknet_estimateFee
+    #[serde_as]
+    #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+    #[serde(deny_unknown_fields)]
+    pub struct FeeEstimate {
+        /// The Ethereum gas cost of the transaction
+        #[serde_as(as = "H256AsNoLeadingZerosHexStr")]
+        pub gas_consumed: H256,
+        /// The gas price of the block the estimation was made on
+        #[serde_as(as = "H256AsNoLeadingZerosHexStr")]
+        pub gas_price: H256,
+        /// The estimated fee for the transaction, product of gas_consumed and gas_price
+        #[serde_as(as = "H256AsNoLeadingZerosHexStr")]
+        pub overall_fee: H256,
+    }
+
+    /// Result type of a single call of pathfinder_multicall
+    #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+    #[serde(deny_unknown_fields)]
+    pub enum MulticallResult {
+        /// The call succeeded, same as the result of starknet_call.
+        #[serde(rename = "result")]
+        Result(Vec<CallResultValue>),
+        /// The call failed, same as the error of starknet_call.
+        #[serde(rename = "error")]
+        Error(MulticallError),
+    }
+
+    /// Error of a single call of pathfinder_multicall
+    #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+    #[serde(deny_unknown_fields)]
+    pub struct MulticallError {
+        pub code: i32,
+        pub message: String,
+    }
+
+    /// Result type for pathfinder_simulateTransaction and pathfinder_traceTransaction
+    #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+    #[serde(deny_unknown_fields)]
+    pub struct TransactionTrace {
+        /// The top-level call of the transaction, including all of the internal calls.
+        pub function_invocation: trace::FunctionInvocation,
+        /// Storage values written by the transaction.
+        pub storage_writes: Vec<trace::StorageWrite>,
+    }
+
+    /// Transaction trace related substructures.
+    pub mod trace {
+        use crate::{
+            core::{
+                CallParam, CallResultValue, ClassHash, ContractAddress, EntryPoint,
+                EthereumAddress, EventData, EventKey, L2ToL1MessagePayloadElem, StorageAddress,
+                StorageValue,
+            },
+            rpc::serde::EthereumAddressAsHexStr,
+        };
+        use serde::{Deserialize, Serialize};
+        use serde_with::{serde_as, skip_serializing_none};
+        use std::collections::BTreeMap;
+
+        /// A single call made during the execution of a transaction.
+        #[skip_serializing_none]
+        #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+        #[serde(deny_unknown_fields)]
+        pub struct FunctionInvocation {
+            pub caller_address: ContractAddress,
+            pub contract_address: ContractAddress,
+            pub class_hash: Option<ClassHash>,
+            pub call_type: Option<CallType>,
+            pub entry_point_type: Option<EntryPointType>,
+            pub entry_point_selector: Option<EntryPoint>,
+            pub calldata: Vec<CallParam>,
+            pub result: Vec<CallResultValue>,
+            pub execution_resources: ExecutionResources,
+            pub events: Vec<OrderedEvent>,
+            pub messages: Vec<OrderedMessageToL1>,
+            /// Values read from storage, in the order they were read.
+            pub storage_read_values: Vec<StorageValue>,
+            pub accessed_storage_keys: Vec<StorageAddress>,
+            pub internal_calls: Vec<FunctionInvocation>,
+        }
+
+        /// How the called contract was invoked.
+        #[derive(Copy, Clone, Debug, Deserialize, Serialize, PartialEq)]
+        #[serde(deny_unknown_fields)]
+        pub enum CallType {
+            #[serde(rename = "CALL")]
+            Call,
+            #[serde(rename = "DELEGATE")]
+            Delegate,
+        }
+
+        /// The kind of the called entry point.
+        #[derive(Copy, Clone, Debug, Deserialize, Serialize, PartialEq)]
+        #[serde(deny_unknown_fields)]
+        pub enum EntryPointType {
+            #[serde(rename = "EXTERNAL")]
+            External,
+            #[serde(rename = "L1_HANDLER")]
+            L1Handler,
+            #[serde(rename = "CONSTRUCTOR")]
+            Constructor,
+        }
+
+        /// Resources used by a call, including the resources of its internal calls.
+        #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+        #[serde(deny_unknown_fields)]
+        pub struct ExecutionResources {
+            pub n_steps: u64,
+            pub n_memory_holes: u64,
+            /// Builtin name to the number of instances used.
+            pub builtin_instance_counter: BTreeMap<String, u64>,
+        }
+
+        /// Event emitted by a call, `order` is the position among all events of the transaction.
+        #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+        #[serde(deny_unknown_fields)]
+        pub struct OrderedEvent {
+            pub order: u64,
+            pub keys: Vec<EventKey>,
+            pub data: Vec<EventData>,
+        }
+
+        /// Message sent from L2 to L1 by a call, `order` is the position among all messages of
+        /// the transaction.
+        #[serde_as]
+        #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+        #[serde(deny_unknown_fields)]
+        pub struct OrderedMessageToL1 {
+            pub order: u64,
+            #[serde_as(as = "EthereumAddressAsHexStr")]
+            pub to_address: EthereumAddress,
+            pub payload: Vec<L2ToL1MessagePayloadElem>,
+        }
+
+        /// Final value written to a storage address by the transaction.
+        #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+        #[serde(deny_unknown_fields)]
+        pub struct StorageWrite {
+            pub contract_address: ContractAddress,
+            pub key: StorageAddress,
+            pub value: StorageValue,
+        }
+    }
 }
@@ -61,7 +61,7 @@ pub mod contract {
         }
     }
 
-    #[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+    #[derive(Clone, Debug, serde::Deserialize, serde::Serialize, PartialEq)]
     #[serde(deny_unknown_fields)]
     pub struct SelectorAndOffset {
         pub selector: EntryPoint,
@@ -9,14 +9,16 @@ use crate::{
     storage::{ContractsStateTable, ContractsTable},
 };
 
+pub mod block_hash;
 pub(crate) mod class_hash;
 mod merkle_node;
 pub(crate) mod merkle_tree;
 pub(crate) mod state_tree;
 mod sync;
+pub mod transaction_hash;
 
 pub use class_hash::compute_class_hash;
-pub use sync::{l1, l2, sync, State as SyncState};
+pub use sync::{l1, l2, sync, Metrics as SyncMetrics, State as SyncState};
 
 #[derive(Clone, PartialEq)]
 pub struct CompressedContract {
@@ -475,6 +477,7 @@ mod tests {
             state,
             sync::l1::sync,
             sync::l2::sync,
+            super::block_hash::BlockValidationMode::Strict,
         )
         .await
         .unwrap();
@@ -0,0 +1,321 @@
+//! Computes the StarkNet block hash and the transaction and event commitments it includes.
+//!
+//! The block hash is a hash chain (see [HashChain]) of:
+//!
+//! ```text
+//! block_number, state_root, sequencer_address, timestamp, transaction_count,
+//! transaction_commitment, event_count, event_commitment, 0, 0, parent_block_hash
+//! ```
+//!
+//! Blocks before StarkNet 0.7 did not commit to the sequencer address, the timestamp or the
+//! events, but include the chain id instead.
+//!
+//! The commitments are the roots of height 64 Merkle-Patricia trees, where the leaves are indexed
+//! by the position of the transaction (or event) in the block.
+use std::ops::Range;
+
+use anyhow::Context;
+use stark_hash::{stark_hash, HashChain, StarkHash};
+
+use crate::{
+    core::{SequencerAddress, StarknetBlockHash},
+    ethereum::Chain,
+    sequencer::reply::{
+        transaction::{Receipt, Transaction, Type},
+        Block,
+    },
+    state::{
+        merkle_tree::MerkleTree,
+        transaction_hash::{chain_id, VerifyResult},
+    },
+};
+
+/// Determines how L2 sync treats blocks which fail verification.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BlockValidationMode {
+    /// Blocks with mismatching block or transaction hashes are rejected.
+    Strict,
+    /// Mismatching block or transaction hashes are only logged.
+    AllowMismatch,
+}
+
+impl std::str::FromStr for BlockValidationMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "strict" => Ok(BlockValidationMode::Strict),
+            "allow-mismatch" => Ok(BlockValidationMode::AllowMismatch),
+            other => Err(anyhow::anyhow!(
+                "Unknown block validation mode {:?}, expected one of: strict, allow-mismatch",
+                other
+            )),
+        }
+    }
+}
+
+/// Chain specific details required to verify historical block hashes.
+struct ChainMeta {
+    /// The first block which uses the StarkNet 0.7 block hash.
+    first_0_7_block: u64,
+    /// Blocks whose hash cannot be computed locally.
+    not_verifiable_range: Option<Range<u64>>,
+    /// The sequencer address of blocks which do not include it.
+    fallback_sequencer_address: SequencerAddress,
+}
+
+impl ChainMeta {
+    fn for_chain(chain: Chain) -> Self {
+        match chain {
+            Chain::Mainnet => ChainMeta {
+                first_0_7_block: 833,
+                not_verifiable_range: None,
+                fallback_sequencer_address: SequencerAddress(
+                    StarkHash::from_hex_str(
+                        "0x021f4b90b0377c82bf330b7b5295820769e72d79d8acd0effa0ebde6e9988bc5",
+                    )
+                    .unwrap(),
+                ),
+            },
+            Chain::Goerli => ChainMeta {
+                first_0_7_block: 47028,
+                not_verifiable_range: Some(119802..148428),
+                fallback_sequencer_address: SequencerAddress(
+                    StarkHash::from_hex_str(
+                        "0x046a89ae102987331d369645031b49c27738ed096f2789c24449966da4c6de6b",
+                    )
+                    .unwrap(),
+                ),
+            },
+        }
+    }
+}
+
+/// Verifies the `block_hash` of the block by computing it locally.
+///
+/// The transaction commitment is computed from the transaction hashes as received, these should
+/// be checked separately using [verify_transaction_hash](super::transaction_hash::verify_transaction_hash).
+pub fn verify_block_hash(
+    block: &Block,
+    chain: Chain,
+) -> anyhow::Result<VerifyResult<StarknetBlockHash>> {
+    let expected = block.block_hash.context("Block hash is missing")?;
+    let block_number = block.block_number.context("Block number is missing")?;
+    let state_root = block.state_root.context("State root is missing")?;
+
+    let meta = ChainMeta::for_chain(chain);
+    if matches!(&meta.not_verifiable_range, Some(range) if range.contains(&block_number.0)) {
+        return Ok(VerifyResult::NotVerifiable);
+    }
+
+    let transaction_count = StarkHash::from(block.transactions.len() as u64);
+    let transaction_commitment = calculate_transaction_commitment(&block.transactions)?;
+
+    let mut hash = HashChain::default();
+    hash.update(StarkHash::from(block_number.0));
+    hash.update(state_root.0);
+
+    if block_number.0 < meta.first_0_7_block {
+        // sequencer address and timestamp
+        hash.update(StarkHash::ZERO);
+        hash.update(StarkHash::ZERO);
+        hash.update(transaction_count);
+        hash.update(transaction_commitment);
+        // event count and event commitment
+        hash.update(StarkHash::ZERO);
+        hash.update(StarkHash::ZERO);
+        // reserved: protocol version and extra data
+        hash.update(StarkHash::ZERO);
+        hash.update(StarkHash::ZERO);
+        hash.update(chain_id(chain));
+    } else {
+        let sequencer_address = block
+            .sequencer_address
+            .unwrap_or(meta.fallback_sequencer_address);
+        let event_count = block
+            .transaction_receipts
+            .iter()
+            .map(|receipt| receipt.events.len() as u64)
+            .sum::<u64>();
+        let event_commitment = calculate_event_commitment(&block.transaction_receipts)?;
+
+        hash.update(sequencer_address.0);
+        hash.update(StarkHash::from(block.timestamp.0));
+        hash.update(transaction_count);
+        hash.update(transaction_commitment);
+        hash.update(StarkHash::from(event_count));
+        hash.update(event_commitment);
+        // reserved: protocol version and extra data
+        hash.update(StarkHash::ZERO);
+        hash.update(StarkHash::ZERO);
+    }
+
+    hash.update(block.parent_block_hash.0);
+
+    let computed = StarknetBlockHash(hash.finalize());
+    match computed == expected {
+        true => Ok(VerifyResult::Match),
+        false => Ok(VerifyResult::Mismatch(computed)),
+    }
+}
+
+/// Calculates the transaction commitment of a block.
+///
+/// The leaves are `h(transaction_hash, h(signature))`, where the signature of deploy transactions
+/// is considered empty.
+pub fn calculate_transaction_commitment(transactions: &[Transaction]) -> anyhow::Result<StarkHash> {
+    let mut tree = CommitmentTree::default();
+
+    transactions
+        .iter()
+        .enumerate()
+        .try_for_each(|(index, transaction)| {
+            let signature_hash = match transaction.r#type {
+                Type::Deploy => HashChain::default().finalize(),
+                Type::InvokeFunction | Type::Declare => HashChain::compute(
+                    transaction
+                        .signature
+                        .iter()
+                        .flatten()
+                        .map(|element| element.0),
+                ),
+            };
+            let leaf = stark_hash(transaction.transaction_hash.0, signature_hash);
+            tree.set(index as u64, leaf)
+        })
+        .context("Failed to build transaction commitment tree")?;
+
+    tree.commit()
+}
+
+/// Calculates the event commitment of a block.
+///
+/// The leaves are `h(from_address, h(keys), h(data))`, in the order the events are emitted in.
+pub fn calculate_event_commitment(receipts: &[Receipt]) -> anyhow::Result<StarkHash> {
+    let mut tree = CommitmentTree::default();
+
+    receipts
+        .iter()
+        .flat_map(|receipt| receipt.events.iter())
+        .enumerate()
+        .try_for_each(|(index, event)| {
+            let leaf = HashChain::compute([
+                event.from_address.0,
+                HashChain::compute(event.keys.iter().map(|key| key.0)),
+                HashChain::compute(event.data.iter().map(|data| data.0)),
+            ]);
+            tree.set(index as u64, leaf)
+        })
+        .context("Failed to build event commitment tree")?;
+
+    tree.commit()
+}
+
+/// An in-memory Merkle-Patricia tree of height 64, used for the block commitments.
+struct CommitmentTree {
+    tree: MerkleTree<()>,
+}
+
+impl Default for CommitmentTree {
+    fn default() -> Self {
+        Self {
+            tree: MerkleTree::empty((), 64),
+        }
+    }
+}
+
+impl CommitmentTree {
+    fn set(&mut self, index: u64, value: StarkHash) -> anyhow::Result<()> {
+        self.tree.set(StarkHash::from(index), value)
+    }
+
+    fn commit(self) -> anyhow::Result<StarkHash> {
+        self.tree.commit()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sequencer::reply;
+
+    fn block(fixture: &str) -> reply::Block {
+        serde_json::from_str::<reply::Block>(fixture).unwrap()
+    }
+
+    #[test]
+    fn pre_0_7_blocks() {
+        let genesis = block(include_str!(
+            "../../fixtures/sequencer/0.9.0/block/genesis.json"
+        ));
+        assert_eq!(
+            verify_block_hash(&genesis, Chain::Goerli).unwrap(),
+            VerifyResult::Match
+        );
+
+        let block = block(include_str!(
+            "../../fixtures/sequencer/0.9.0/block/1716.json"
+        ));
+        assert_eq!(
+            verify_block_hash(&block, Chain::Goerli).unwrap(),
+            VerifyResult::Match
+        );
+    }
+
+    #[test]
+    fn block_with_events() {
+        let block = block(include_str!(
+            "../../fixtures/sequencer/0.9.0/block/231579.json"
+        ));
+        assert!(block
+            .transaction_receipts
+            .iter()
+            .any(|receipt| !receipt.events.is_empty()));
+
+        assert_eq!(
+            verify_block_hash(&block, Chain::Goerli).unwrap(),
+            VerifyResult::Match
+        );
+    }
+
+    #[test]
+    fn mismatch() {
+        let mut block = block(include_str!(
+            "../../fixtures/sequencer/0.9.0/block/231579.json"
+        ));
+        block
+            .transaction_receipts
+            .iter_mut()
+            .find(|receipt| !receipt.events.is_empty())
+            .unwrap()
+            .events
+            .pop();
+
+        assert_matches::assert_matches!(
+            verify_block_hash(&block, Chain::Goerli).unwrap(),
+            VerifyResult::Mismatch(_)
+        );
+    }
+
+    #[test]
+    fn not_verifiable() {
+        let mut block = block(include_str!(
+            "../../fixtures/sequencer/0.9.0/block/231579.json"
+        ));
+        block.block_number = Some(crate::core::StarknetBlockNumber(119802));
+
+        assert_eq!(
+            verify_block_hash(&block, Chain::Goerli).unwrap(),
+            VerifyResult::NotVerifiable
+        );
+    }
+
+    #[test]
+    fn empty_commitments() {
+        assert_eq!(
+            calculate_transaction_commitment(&[]).unwrap(),
+            StarkHash::ZERO
+        );
+        assert_eq!(calculate_event_commitment(&[]).unwrap(), StarkHash::ZERO);
+    }
+}
@@ -1,7 +1,7 @@
 use anyhow::{Context, Error, Result};
 use serde::Serialize;
 use sha3::Digest;
-use stark_hash::{stark_hash, StarkHash};
+use stark_hash::{HashChain, StarkHash};
 
 use crate::core::ClassHash;
 use crate::sequencer::request::contract::EntryPointType;
@@ -56,6 +56,43 @@ pub(crate) fn extract_abi_code_hash(
     Ok((abi, code, hash))
 }
 
+/// Computes the class hash of a contract definition in the form it is submitted to the gateway,
+/// which is the class hash the gateway uses for the address of a deployed contract.
+///
+/// The program is gzip + base64 encoded and there is no ABI, which the gateway hashes as `null`.
+pub(crate) fn compute_submitted_class_hash(
+    contract_definition: &crate::sequencer::request::add_transaction::ContractDefinition,
+) -> Result<ClassHash> {
+    use crate::sequencer::request::contract::SelectorAndOffset;
+    use serde_json::value::RawValue;
+    use std::collections::HashMap;
+    use std::io::Read;
+
+    #[derive(Serialize)]
+    struct Submitted<'a> {
+        abi: Option<()>,
+        entry_points_by_type: &'a HashMap<EntryPointType, Vec<SelectorAndOffset>>,
+        program: &'a RawValue,
+    }
+
+    let gzipped_program =
+        base64::decode(&contract_definition.program).context("Decoding program")?;
+    let mut program = Vec::new();
+    flate2::read::GzDecoder::new(gzipped_program.as_slice())
+        .read_to_end(&mut program)
+        .context("Decompressing program")?;
+    let program = serde_json::from_slice::<&RawValue>(&program).context("Parsing program")?;
+
+    let contract_definition_dump = serde_json::to_vec(&Submitted {
+        abi: None,
+        entry_points_by_type: &contract_definition.entry_points_by_type,
+        program,
+    })
+    .context("Serializing contract_definition")?;
+
+    compute_class_hash(&contract_definition_dump)
+}
+
 fn compute_class_hash0(mut contract_definition: json::ContractDefinition<'_>) -> Result<ClassHash> {
     use EntryPointType::*;
 
@@ -181,43 +218,6 @@ fn compute_class_hash0(mut contract_definition: json::ContractDefinition<'_>) ->
     Ok(ClassHash(outer.finalize()))
 }
 
-/// HashChain is the structure used over at cairo side to represent the hash construction needed
-/// for computing the class hash.
-///
-/// Empty hash chained value equals `H(0, 0)` where `H` is the [`stark_hash()`] function, and the
-/// second value is the number of values hashed together in this chain. For other values, the
-/// accumulator is on each update replaced with the `H(hash, value)` and the number of count
-/// incremented by one.
-struct HashChain {
-    hash: StarkHash,
-    count: usize,
-}
-
-impl Default for HashChain {
-    fn default() -> Self {
-        HashChain {
-            hash: StarkHash::ZERO,
-            count: 0,
-        }
-    }
-}
-
-impl HashChain {
-    fn update(&mut self, value: StarkHash) {
-        self.hash = stark_hash(self.hash, value);
-        self.count = self
-            .count
-            .checked_add(1)
-            .expect("could not have deserialized larger than usize Vecs");
-    }
-
-    fn finalize(self) -> StarkHash {
-        let count = StarkHash::from_be_slice(&self.count.to_be_bytes())
-            .expect("usize is smaller than 251-bits");
-        stark_hash(self.hash, count)
-    }
-}
-
 /// See:
 /// <https://github.com/starkware-libs/cairo-lang/blob/64a7f6aed9757d3d8d6c28bd972df73272b0cb0a/src/starkware/starknet/public/abi.py#L21-L26>
 pub(crate) fn truncated_keccak(mut plain: [u8; 32]) -> StarkHash {
@@ -420,6 +420,28 @@ mod json {
             );
         }
 
+        #[test]
+        fn submitted() {
+            use crate::sequencer::request::add_transaction::ContractDefinition;
+
+            let json = include_bytes!("../../resources/deploy_transaction.json");
+            let mut json = serde_json::from_slice::<serde_json::Value>(json).unwrap();
+            let contract_definition =
+                serde_json::from_value::<ContractDefinition>(json["contract_definition"].take())
+                    .unwrap();
+
+            let hash = super::super::compute_submitted_class_hash(&contract_definition).unwrap();
+
+            // as returned by the gateway for a declare transaction of the same definition
+            assert_eq!(
+                hash.0,
+                stark_hash::StarkHash::from_hex_str(
+                    "0x0371b5f7c5517d84205365a87f02dcef230efa7b4dd91a9e4ba7e04c5b69d69b"
+                )
+                .unwrap()
+            );
+        }
+
         #[tokio::test]
         async fn genesis_contract() {
             use crate::sequencer::ClientApi;
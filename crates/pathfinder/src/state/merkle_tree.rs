@@ -13,7 +13,9 @@
 //! #### Tree definition
 //!
 //! It is important to understand that since all keys are [StarkHashes](StarkHash), this means
-//! all paths to a key are equally long - 251 bits.
+//! all paths to a key are equally long - 251 bits. Trees with a lower height, such as the
+//! 64 bit high transaction and event commitment trees, use only the lowest bits of the key
+//! as its path (see [`MerkleTree::empty`]).
 //!
 //! Starknet defines three node types for a tree.
 //!
@@ -45,7 +47,7 @@
 //! The in-memory tree is built using a graph of `Rc<RefCell<Node>>` which is a bit painful.
 
 use anyhow::Context;
-use bitvec::prelude::BitVec;
+use bitvec::{order::Msb0, prelude::BitVec, slice::BitSlice};
 use rusqlite::Transaction;
 use std::{cell::RefCell, rc::Rc};
 
@@ -89,6 +91,8 @@ pub trait NodeStorage {
 pub struct MerkleTree<T> {
     storage: T,
     root: Rc<RefCell<Node>>,
+    /// The length of the path from the root to any leaf.
+    max_height: u8,
 }
 
 impl<'a> MerkleTree<RcNodeStorage<'a>> {
@@ -112,20 +116,39 @@ impl<'a> MerkleTree<RcNodeStorage<'a>> {
         root: StarkHash,
     ) -> anyhow::Result<Self> {
         let storage = RcNodeStorage::open(table, transaction)?;
-        Self::new(storage, root)
+        Self::new(storage, root, Self::MAX_HEIGHT)
     }
 }
 
 impl<T: NodeStorage + Default> Default for MerkleTree<T> {
     /// Initializes a fresh empty MerkleTree on the defined storage implementation.
     fn default() -> Self {
-        Self::new(Default::default(), StarkHash::ZERO).expect(
+        Self::new(Default::default(), StarkHash::ZERO, Self::MAX_HEIGHT).expect(
             "Since called with ZERO as root, there should not have been a query, and therefore no error",
         )
     }
 }
 
 impl<T: NodeStorage> MerkleTree<T> {
+    /// The height of the global state and contract state trees, i.e. the full bit length of a
+    /// [StarkHash].
+    pub const MAX_HEIGHT: u8 = 251;
+
+    /// Initializes a fresh empty tree of the given height on the given storage.
+    ///
+    /// Only the lowest `max_height` bits of the keys are used as their path, which means that
+    /// keys must not exceed `2^max_height - 1`. Accessing a larger key is an error.
+    pub fn empty(storage: T, max_height: u8) -> Self {
+        assert!(
+            max_height <= Self::MAX_HEIGHT,
+            "Tree height cannot exceed {}",
+            Self::MAX_HEIGHT
+        );
+        Self::new(storage, StarkHash::ZERO, max_height).expect(
+            "Since called with ZERO as root, there should not have been a query, and therefore no error",
+        )
+    }
+
     /// Removes one instance of the tree and its root from persistent storage.
     ///
     /// This implies decrementing the root's reference count. The root will
@@ -148,11 +171,12 @@ impl<T: NodeStorage> MerkleTree<T> {
     /// Less visible initialization for `MerkleTree<T>` as the main entry points should be
     /// [`MerkleTree::<RcNodeStorage>::load`] for persistent trees and [`MerkleTree::default`] for
     /// transient ones.
-    fn new(storage: T, root: StarkHash) -> anyhow::Result<Self> {
+    fn new(storage: T, root: StarkHash, max_height: u8) -> anyhow::Result<Self> {
         let root_node = Rc::new(RefCell::new(Node::Unresolved(root)));
         let mut tree = Self {
             storage,
             root: root_node,
+            max_height,
         };
         if root != StarkHash::ZERO {
             // Resolve non-zero root node to check that it does exist.
@@ -164,6 +188,21 @@ impl<T: NodeStorage> MerkleTree<T> {
         Ok(tree)
     }
 
+    /// Returns the path of `key` within this tree, which are its lowest `max_height` bits.
+    ///
+    /// Errors if any of the higher bits are set, as the key would not fit in the tree.
+    fn key_path<'k>(&self, key: &'k StarkHash) -> anyhow::Result<&'k BitSlice<Msb0, u8>> {
+        let bits = key.view_bits();
+        let (overflow, path) = bits.split_at(bits.len() - self.max_height as usize);
+        anyhow::ensure!(
+            overflow.not_any(),
+            "Key {} does not fit in a tree of height {}",
+            key,
+            self.max_height
+        );
+        Ok(path)
+    }
+
     /// Persists all changes to storage and returns the new root hash.
     ///
     /// Note that the root is reference counted in storage. Committing the
@@ -242,6 +281,8 @@ impl<T: NodeStorage> MerkleTree<T> {
             return self.delete_leaf(key);
         }
 
+        let key = self.key_path(&key)?;
+
         // Changing or inserting a new leaf into the tree will change the hashes
         // of all nodes along the path to the leaf.
         let path = self.traverse(key)?;
@@ -278,7 +319,7 @@ impl<T: NodeStorage> MerkleTree<T> {
                         let child_height = branch_height + 1;
 
                         // Path from binary node to new leaf
-                        let new_path = key.view_bits()[child_height..].to_bitvec();
+                        let new_path = key[child_height..].to_bitvec();
                         // Path from binary node to existing child
                         let old_path = edge.path[common.len() + 1..].to_bitvec();
 
@@ -312,7 +353,7 @@ impl<T: NodeStorage> MerkleTree<T> {
                             }
                         };
 
-                        let new_direction = Direction::from(key.view_bits()[branch_height]);
+                        let new_direction = Direction::from(key[branch_height]);
                         let (left, right) = match new_direction {
                             Direction::Left => (new, old),
                             Direction::Right => (old, new),
@@ -354,7 +395,7 @@ impl<T: NodeStorage> MerkleTree<T> {
                 let edge = Node::Edge(EdgeNode {
                     hash: None,
                     height: 0,
-                    path: key.view_bits().to_bitvec(),
+                    path: key.to_bitvec(),
                     child: Rc::new(RefCell::new(leaf)),
                 });
 
@@ -383,6 +424,7 @@ impl<T: NodeStorage> MerkleTree<T> {
         // and other remaining child node -- if they're also edges.
         //
         // Then we are done.
+        let key = self.key_path(&key)?;
         let path = self.traverse(key)?;
 
         // Do nothing if the leaf does not exist.
@@ -451,6 +493,7 @@ impl<T: NodeStorage> MerkleTree<T> {
 
     /// Returns the value stored at key, or [StarkHash::ZERO] if it does not exist.
     pub fn get(&self, key: StarkHash) -> anyhow::Result<StarkHash> {
+        let key = self.key_path(&key)?;
         let val = match self.traverse(key)?.last() {
             Some(node) => match &*node.borrow() {
                 Node::Leaf(value) => *value,
@@ -472,7 +515,7 @@ impl<T: NodeStorage> MerkleTree<T> {
     /// The final node can __not__ be a [Binary](Node::Binary) node since it would always be possible to continue
     /// on towards the destination. Nor can it be an [Unresolved](Node::Unresolved) node since this would be
     /// resolved to check if we can travel further.
-    fn traverse(&self, dst: StarkHash) -> anyhow::Result<Vec<Rc<RefCell<Node>>>> {
+    fn traverse(&self, dst: &BitSlice<Msb0, u8>) -> anyhow::Result<Vec<Rc<RefCell<Node>>>> {
         if self.root.borrow().is_empty() {
             return Ok(Vec::new());
         }
@@ -559,11 +602,10 @@ impl<T: NodeStorage> MerkleTree<T> {
     }
 }
 
-#[cfg(any(test, fuzzing))]
 impl NodeStorage for () {
     fn get(&self, _key: StarkHash) -> anyhow::Result<Option<PersistedNode>> {
-        // the rc<refcell> impl will do just fine by without any backing for transaction tree
-        // building
+        // the rc<refcell> impl will do just fine by without any backing for transaction and
+        // event commitment tree building
         Ok(None)
     }
 
@@ -660,6 +702,21 @@ mod tests {
         }
     }
 
+    #[test]
+    fn key_wider_than_height() {
+        let mut uut = MerkleTree::empty((), 64);
+
+        let key = StarkHash::from_hex_str("10000000000000000").unwrap();
+        let value = StarkHash::from_hex_str("1").unwrap();
+        uut.set(key, value).unwrap_err();
+        uut.get(key).unwrap_err();
+
+        // The largest key which fits is accepted.
+        let key = StarkHash::from_hex_str("ffffffffffffffff").unwrap();
+        uut.set(key, value).unwrap();
+        assert_eq!(uut.get(key).unwrap(), value);
+    }
+
     mod tree_state {
         use super::*;
 
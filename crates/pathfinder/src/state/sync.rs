@@ -2,6 +2,7 @@ pub mod l1;
 pub mod l2;
 
 use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 use crate::{
@@ -17,11 +18,15 @@ use crate::{
     },
     rpc::types::reply::{syncing, syncing::NumberedBlock, Syncing as SyncStatus},
     sequencer::{self, reply::Block},
-    state::{calculate_contract_state_hash, state_tree::GlobalStateTree, update_contract_state},
+    state::{
+        block_hash::BlockValidationMode, calculate_contract_state_hash,
+        state_tree::GlobalStateTree, update_contract_state,
+    },
     storage::{
-        ContractCodeTable, ContractsStateTable, ContractsTable, L1StateTable, L1TableBlockId,
-        RefsTable, StarknetBlock, StarknetBlocksBlockId, StarknetBlocksTable,
-        StarknetTransactionsTable, Storage,
+        ContractCodeTable, ContractDeploymentsTable, ContractStorageHistoryTable,
+        ContractsStateTable, ContractsTable, L1StateTable, L1TableBlockId, RefsTable,
+        StarknetBlock, StarknetBlocksBlockId, StarknetBlocksTable, StarknetTransactionsTable,
+        Storage,
     },
 };
 
@@ -32,17 +37,38 @@ use tokio::sync::{mpsc, RwLock};
 
 pub struct State {
     pub status: RwLock<SyncStatus>,
+    /// Number of downloaded contract definitions which were rejected because they
+    /// did not hash to their class hash.
+    pub class_hash_mismatches: AtomicU64,
 }
 
 impl Default for State {
     fn default() -> Self {
         Self {
             status: RwLock::new(SyncStatus::False(false)),
+            class_hash_mismatches: AtomicU64::new(0),
+        }
+    }
+}
+
+impl State {
+    pub fn metrics(&self) -> Metrics {
+        Metrics {
+            class_hash_mismatches: self.class_hash_mismatches.load(Ordering::Relaxed),
         }
     }
 }
 
+/// Counters of the sync process, as returned by `pathfinder_getSyncMetrics`.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Metrics {
+    /// Number of downloaded contract definitions which were rejected because they
+    /// did not hash to their class hash.
+    pub class_hash_mismatches: u64,
+}
+
 /// Implements the main sync loop, where L1 and L2 sync results are combined.
+#[allow(clippy::too_many_arguments)]
 pub async fn sync<Transport, SequencerClient, F1, F2, L1Sync, L2Sync>(
     storage: Storage,
     transport: Transport,
@@ -51,6 +77,7 @@ pub async fn sync<Transport, SequencerClient, F1, F2, L1Sync, L2Sync>(
     state: Arc<State>,
     l1_sync: L1Sync,
     l2_sync: L2Sync,
+    block_validation_mode: BlockValidationMode,
 ) -> anyhow::Result<()>
 where
     Transport: EthereumTransport + Clone,
@@ -63,6 +90,7 @@ where
             SequencerClient,
             Option<(StarknetBlockNumber, StarknetBlockHash)>,
             crate::ethereum::Chain,
+            BlockValidationMode,
         ) -> F2
         + Copy,
 {
@@ -99,7 +127,13 @@ where
 
     // Start L1 and L2 sync processes.
     let mut l1_handle = tokio::spawn(l1_sync(tx_l1, transport.clone(), chain, l1_head));
-    let mut l2_handle = tokio::spawn(l2_sync(tx_l2, sequencer.clone(), l2_head, chain));
+    let mut l2_handle = tokio::spawn(l2_sync(
+        tx_l2,
+        sequencer.clone(),
+        l2_head,
+        chain,
+        block_validation_mode,
+    ));
 
     let mut existed = (0, 0);
 
@@ -265,6 +299,15 @@ where
 
                     tracing::trace!("Inserted new contract {}", contract.hash.0.to_hex_str());
                 }
+                Some(l2::Event::ClassHashMismatch(hash)) => {
+                    let mismatches = state.class_hash_mismatches.fetch_add(1, Ordering::Relaxed) + 1;
+
+                    tracing::debug!(
+                        class_hash=%hash.0,
+                        total=%mismatches,
+                        "Rejected contract definition with mismatching class hash"
+                    );
+                }
                 Some(l2::Event::QueryHash(block, tx)) => {
                     let hash = tokio::task::block_in_place(|| {
                         StarknetBlocksTable::get(&db_conn, block.into())
@@ -309,7 +352,13 @@ where
                     let (new_tx, new_rx) = mpsc::channel(1);
                     rx_l2 = new_rx;
 
-                    l2_handle = tokio::spawn(l2_sync(new_tx, sequencer.clone(), l2_head, chain));
+                    l2_handle = tokio::spawn(l2_sync(
+                        new_tx,
+                        sequencer.clone(),
+                        l2_head,
+                        chain,
+                        block_validation_mode,
+                    ));
                     tracing::info!("L2 sync process restarted.");
                 }
             }
@@ -459,8 +508,14 @@ async fn l2_update(
             .transaction()
             .context("Create database transaction")?;
 
-        let new_root =
-            update_starknet_state(&transaction, state_diff).context("Updating Starknet state")?;
+        let deployed_contracts = state_diff
+            .deployed_contracts
+            .iter()
+            .map(|contract| (contract.address, contract.hash))
+            .collect::<Vec<_>>();
+
+        let new_root = update_starknet_state(&transaction, state_diff, block.block_number.unwrap())
+            .context("Updating Starknet state")?;
 
         // Ensure that roots match.. what should we do if it doesn't? For now the whole sync process ends..
         anyhow::ensure!(new_root == block.state_root.unwrap(), "State root mismatch");
@@ -502,6 +557,26 @@ async fn l2_update(
         )
         .context("Insert transaction data into database")?;
 
+        // Record the contract deployments along with their deploy transactions.
+        for (address, class_hash) in deployed_contracts {
+            let deploy_transaction = transaction_data
+                .iter()
+                .find(|(tx, _)| {
+                    tx.r#type == sequencer::reply::transaction::Type::Deploy
+                        && tx.contract_address == Some(address)
+                })
+                .map(|(tx, _)| tx.transaction_hash);
+
+            ContractDeploymentsTable::insert(
+                &transaction,
+                starknet_block.number,
+                address,
+                class_hash,
+                deploy_transaction,
+            )
+            .context("Insert contract deployment into database")?;
+        }
+
         // Track combined L1 and L2 state.
         let l1_l2_head = RefsTable::get_l1_l2_head(&transaction).context("Query L1-L2 head")?;
         let expected_next = l1_l2_head
@@ -555,6 +630,7 @@ async fn l2_reorg(
 fn update_starknet_state(
     transaction: &Transaction,
     diff: StateUpdate,
+    block_number: StarknetBlockNumber,
 ) -> anyhow::Result<GlobalRoot> {
     let global_root = StarknetBlocksTable::get(transaction, StarknetBlocksBlockId::Latest)
         .context("Query latest state root")?
@@ -575,6 +651,9 @@ fn update_starknet_state(
         global_tree
             .set(update.address, contract_state_hash)
             .context("Updating global state tree")?;
+
+        ContractStorageHistoryTable::insert(transaction, block_number, &update)
+            .context("Recording contract storage history")?;
     }
 
     // Apply all global tree changes.
@@ -620,7 +699,7 @@ pub fn head_poll_interval(chain: crate::ethereum::Chain) -> std::time::Duration
 
 #[cfg(test)]
 mod tests {
-    use super::{l1, l2};
+    use super::{l1, l2, BlockValidationMode};
     use crate::{
         core::{
             CallSignatureElem, ClassHash, ConstructorParam, ContractAddress, ContractAddressSalt,
@@ -813,6 +892,7 @@ mod tests {
         _: impl sequencer::ClientApi,
         _: Option<(StarknetBlockNumber, StarknetBlockHash)>,
         _: crate::ethereum::Chain,
+        _: BlockValidationMode,
     ) -> anyhow::Result<()> {
         // Avoid being restarted all the time by the outer sync() loop
         let () = std::future::pending().await;
@@ -943,6 +1023,7 @@ mod tests {
                 sync_state.clone(),
                 l1,
                 l2_noop,
+                BlockValidationMode::Strict,
             ));
 
             // TODO Find a better way to figure out that the DB update has already been performed
@@ -1008,6 +1089,7 @@ mod tests {
                 Arc::new(state::SyncState::default()),
                 l1,
                 l2_noop,
+                BlockValidationMode::Strict,
             ));
 
             // TODO Find a better way to figure out that the DB update has already been performed
@@ -1068,6 +1150,7 @@ mod tests {
             Arc::new(state::SyncState::default()),
             l1,
             l2_noop,
+            BlockValidationMode::Strict,
         ));
 
         tokio::time::sleep(Duration::from_millis(10)).await;
@@ -1096,6 +1179,7 @@ mod tests {
             Arc::new(state::SyncState::default()),
             l1,
             l2_noop,
+            BlockValidationMode::Strict,
         ));
 
         tokio::time::sleep(Duration::from_millis(5)).await;
@@ -1118,7 +1202,7 @@ mod tests {
         };
 
         // A simple L2 sync task
-        let l2 = move |tx: mpsc::Sender<l2::Event>, _, _, _| async move {
+        let l2 = move |tx: mpsc::Sender<l2::Event>, _, _, _, _| async move {
             tx.send(l2::Event::Update(
                 Box::new(block()),
                 state_update(),
@@ -1154,6 +1238,7 @@ mod tests {
                 sync_state.clone(),
                 l1_noop,
                 l2,
+                BlockValidationMode::Strict,
             ));
 
             // TODO Find a better way to figure out that the DB update has already been performed
@@ -1191,7 +1276,7 @@ mod tests {
             let connection = storage.connection().unwrap();
 
             // A simple L2 sync task
-            let l2 = move |tx: mpsc::Sender<l2::Event>, _, _, _| async move {
+            let l2 = move |tx: mpsc::Sender<l2::Event>, _, _, _, _| async move {
                 tx.send(l2::Event::Reorg(StarknetBlockNumber(reorg_on_block)))
                     .await
                     .unwrap();
@@ -1214,6 +1299,7 @@ mod tests {
                 Arc::new(state::SyncState::default()),
                 l1_noop,
                 l2,
+                BlockValidationMode::Strict,
             ));
 
             // TODO Find a better way to figure out that the DB update has already been performed
@@ -1247,7 +1333,7 @@ mod tests {
         let connection = storage.connection().unwrap();
 
         // A simple L2 sync task
-        let l2 = |tx: mpsc::Sender<l2::Event>, _, _, _| async move {
+        let l2 = |tx: mpsc::Sender<l2::Event>, _, _, _, _| async move {
             let zstd_magic = vec![0x28, 0xb5, 0x2f, 0xfd];
             tx.send(l2::Event::NewContract(state::CompressedContract {
                 abi: zstd_magic.clone(),
@@ -1271,6 +1357,7 @@ mod tests {
             Arc::new(state::SyncState::default()),
             l1_noop,
             l2,
+            BlockValidationMode::Strict,
         ));
 
         // TODO Find a better way to figure out that the DB update has already been performed
@@ -1291,7 +1378,7 @@ mod tests {
         StarknetBlocksTable::insert(&connection, &STORAGE_BLOCK0).unwrap();
 
         // A simple L2 sync task which does the request and checks he result
-        let l2 = |tx: mpsc::Sender<l2::Event>, _, _, _| async move {
+        let l2 = |tx: mpsc::Sender<l2::Event>, _, _, _, _| async move {
             let (tx1, rx1) = tokio::sync::oneshot::channel::<Option<StarknetBlockHash>>();
 
             tx.send(l2::Event::QueryHash(StarknetBlockNumber(0), tx1))
@@ -1314,6 +1401,7 @@ mod tests {
             Arc::new(state::SyncState::default()),
             l1_noop,
             l2,
+            BlockValidationMode::Strict,
         ));
     }
 
@@ -1336,7 +1424,7 @@ mod tests {
         .unwrap();
 
         // A simple L2 sync task which does the request and checks he result
-        let l2 = |tx: mpsc::Sender<l2::Event>, _, _, _| async move {
+        let l2 = |tx: mpsc::Sender<l2::Event>, _, _, _, _| async move {
             let (tx1, rx1) = tokio::sync::oneshot::channel::<Vec<bool>>();
 
             tx.send(l2::Event::QueryContractExistance(vec![ClassHash(*A)], tx1))
@@ -1359,6 +1447,7 @@ mod tests {
             Arc::new(state::SyncState::default()),
             l1_noop,
             l2,
+            BlockValidationMode::Strict,
         ));
     }
 
@@ -1371,7 +1460,7 @@ mod tests {
         static CNT: AtomicUsize = AtomicUsize::new(0);
 
         // A simple L2 sync task
-        let l2 = move |_, _, _, _| async move {
+        let l2 = move |_, _, _, _, _| async move {
             CNT.fetch_add(1, Ordering::Relaxed);
             Ok(())
         };
@@ -1385,6 +1474,7 @@ mod tests {
             Arc::new(state::SyncState::default()),
             l1_noop,
             l2,
+            BlockValidationMode::Strict,
         ));
 
         tokio::time::sleep(Duration::from_millis(5)).await;
@@ -1,8 +1,10 @@
 use std::collections::HashSet;
+use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::Context;
 use tokio::sync::{mpsc, oneshot};
+use tracing::Instrument;
 
 use crate::core::{ClassHash, StarknetBlockHash, StarknetBlockNumber};
 use crate::ethereum::state_update::{ContractUpdate, DeployedContract, StateUpdate, StorageUpdate};
@@ -11,7 +13,9 @@ use crate::sequencer::error::SequencerError;
 use crate::sequencer::reply::state_update::{Contract, StateDiff};
 use crate::sequencer::reply::Block;
 use crate::sequencer::{self};
+use crate::state::block_hash::{verify_block_hash, BlockValidationMode};
 use crate::state::class_hash::extract_abi_code_hash;
+use crate::state::transaction_hash::{verify_transaction_hash, VerifyResult};
 use crate::state::CompressedContract;
 
 #[derive(Debug, Clone, Copy)]
@@ -32,6 +36,9 @@ pub enum Event {
     Reorg(StarknetBlockNumber),
     /// A new unique L2 [contract](CompressedContract) was found.
     NewContract(CompressedContract),
+    /// A downloaded contract definition did not hash to the expected [class hash](ClassHash)
+    /// and was rejected. The definition will be downloaded again.
+    ClassHashMismatch(ClassHash),
     /// Query for the [block hash](StarknetBlockHash) of the given block.
     ///
     /// The receiver should return the [block hash](StarknetBlockHash) using the
@@ -49,12 +56,18 @@ pub enum Event {
 
 pub async fn sync(
     tx_event: mpsc::Sender<Event>,
-    sequencer: impl sequencer::ClientApi,
+    sequencer: impl sequencer::ClientApi + Send + Sync + 'static,
     mut head: Option<(StarknetBlockNumber, StarknetBlockHash)>,
     chain: crate::ethereum::Chain,
+    block_validation_mode: BlockValidationMode,
 ) -> anyhow::Result<()> {
     use crate::state::sync::head_poll_interval;
 
+    // Shared with the tasks which download rejected contract definitions again.
+    let sequencer = Arc::new(sequencer);
+    // The last block, if it is still waiting on such a task.
+    let mut pending = None;
+
     'outer: loop {
         // Get the next block from L2.
         let (next, head_hash) = match head {
@@ -64,16 +77,20 @@ pub async fn sync(
 
         let t_block = std::time::Instant::now();
         let block = loop {
-            match download_block(next, head_hash, &sequencer).await? {
+            match download_block(next, head_hash, &*sequencer).await? {
                 DownloadBlock::Block(block) => break block,
                 DownloadBlock::AtHead => {
+                    emit_pending_update(&mut pending, &tx_event).await?;
+
                     let poll_interval = head_poll_interval(chain);
                     tracing::info!(poll_interval=?poll_interval, "At head of chain");
                     tokio::time::sleep(poll_interval).await;
                 }
                 DownloadBlock::Reorg => {
+                    emit_pending_update(&mut pending, &tx_event).await?;
+
                     let some_head = head.unwrap();
-                    head = reorg(some_head, &tx_event, &sequencer)
+                    head = reorg(some_head, &tx_event, &*sequencer)
                         .await
                         .context("L2 reorg")?;
 
@@ -85,7 +102,9 @@ pub async fn sync(
 
         if let Some(some_head) = head {
             if some_head.1 != block.parent_block_hash {
-                head = reorg(some_head, &tx_event, &sequencer)
+                emit_pending_update(&mut pending, &tx_event).await?;
+
+                head = reorg(some_head, &tx_event, &*sequencer)
                     .await
                     .context("L2 reorg")?;
 
@@ -93,6 +112,10 @@ pub async fn sync(
             }
         }
 
+        let block = verify_block(block, chain, block_validation_mode)
+            .await
+            .with_context(|| format!("Verify block {}", next.0))?;
+
         // Unwrap in both block and state update is safe as the block hash always exists (unless we query for pending).
         let block_hash = block.block_hash.unwrap();
         let t_update = std::time::Instant::now();
@@ -110,8 +133,11 @@ pub async fn sync(
         );
         let t_update = t_update.elapsed();
 
+        // The previous block's contracts must be stored before this block's are queried.
+        emit_pending_update(&mut pending, &tx_event).await?;
+
         let t_deploy = std::time::Instant::now();
-        deploy_contracts(&tx_event, &sequencer, &state_update.state_diff)
+        let retries = deploy_contracts(&tx_event, &sequencer, &state_update.state_diff)
             .await
             .with_context(|| format!("Deploying new contracts for block {:?}", next))?;
         let t_deploy = t_deploy.elapsed();
@@ -162,13 +188,144 @@ pub async fn sync(
             contract_deployment: t_deploy,
         };
 
-        tx_event
-            .send(Event::Update(block, update, timings))
-            .await
-            .context("Event channel closed")?;
+        match retries {
+            Some(retries) => {
+                pending = Some(PendingUpdate {
+                    block,
+                    update,
+                    timings,
+                    retries,
+                })
+            }
+            None => tx_event
+                .send(Event::Update(block, update, timings))
+                .await
+                .context("Event channel closed")?,
+        }
+    }
+}
+
+/// A block update which is held back until its rejected contract definitions have been
+/// downloaded again.
+struct PendingUpdate {
+    block: Box<Block>,
+    update: StateUpdate,
+    timings: Timings,
+    retries: DefinitionRetries,
+}
+
+/// Task downloading rejected contract definitions again, see [deploy_contracts].
+///
+/// The task is aborted when dropped, so that it does not outlive a failed sync process.
+struct DefinitionRetries(tokio::task::JoinHandle<anyhow::Result<()>>);
+
+impl Drop for DefinitionRetries {
+    fn drop(&mut self) {
+        self.0.abort();
     }
 }
 
+/// Waits for the definitions of the pending block update and then emits the update.
+async fn emit_pending_update(
+    pending: &mut Option<PendingUpdate>,
+    tx_event: &mpsc::Sender<Event>,
+) -> anyhow::Result<()> {
+    let PendingUpdate {
+        block,
+        update,
+        mut timings,
+        mut retries,
+    } = match pending.take() {
+        Some(pending) => pending,
+        None => return Ok(()),
+    };
+
+    let t_deploy = std::time::Instant::now();
+    (&mut retries.0)
+        .await
+        .context("Join definition download task")?
+        .with_context(|| format!("Deploying new contracts for block {:?}", block.block_number))?;
+    timings.contract_deployment += t_deploy.elapsed();
+
+    tx_event
+        .send(Event::Update(block, update, timings))
+        .await
+        .context("Event channel closed")
+}
+
+/// Verifies the block hash and the transaction hashes of the block.
+///
+/// Failures are always logged, but the block is only rejected in [BlockValidationMode::Strict].
+async fn verify_block(
+    block: Box<Block>,
+    chain: crate::ethereum::Chain,
+    mode: BlockValidationMode,
+) -> anyhow::Result<Box<Block>> {
+    // Hashing every transaction is expensive, so perform in a blocking task.
+    tokio::task::spawn_blocking(move || -> anyhow::Result<_> {
+        let block_number = block.block_number.context("Block number is missing")?;
+
+        for (transaction, receipt) in block
+            .transactions
+            .iter()
+            .zip(block.transaction_receipts.iter())
+        {
+            match verify_transaction_hash(transaction, receipt, chain)
+                .context("Compute transaction hash")?
+            {
+                VerifyResult::Match => {}
+                VerifyResult::Mismatch(actual) => {
+                    tracing::warn!(
+                        block_number=%block_number.0,
+                        expected=%transaction.transaction_hash.0,
+                        actual=%actual.0,
+                        "Transaction hash mismatch"
+                    );
+                    anyhow::ensure!(
+                        mode != BlockValidationMode::Strict,
+                        "Transaction hash mismatch, actual {:x}, expected {:x}",
+                        actual.0,
+                        transaction.transaction_hash.0
+                    );
+                }
+                VerifyResult::NotVerifiable => {
+                    tracing::debug!(
+                        block_number=%block_number.0,
+                        transaction_hash=%transaction.transaction_hash.0,
+                        "Transaction hash is not verifiable"
+                    );
+                }
+            }
+        }
+
+        let expected = block.block_hash.context("Block hash is missing")?;
+        match verify_block_hash(&block, chain).context("Compute block hash")? {
+            VerifyResult::Match => {}
+            VerifyResult::Mismatch(actual) => {
+                tracing::warn!(
+                    block_number=%block_number.0,
+                    expected=%expected.0,
+                    actual=%actual.0,
+                    "Block hash mismatch"
+                );
+                anyhow::ensure!(
+                    mode != BlockValidationMode::Strict,
+                    "Block hash mismatch, actual {:x}, expected {:x}",
+                    actual.0,
+                    expected.0
+                );
+            }
+            VerifyResult::NotVerifiable => {
+                tracing::debug!(block_number=%block_number.0, "Block hash is not verifiable");
+            }
+        }
+
+        Ok(block)
+    })
+    .await
+    .context("Verify block")?
+}
+
 enum DownloadBlock {
     Block(Box<Block>),
     AtHead,
@@ -270,11 +427,15 @@ async fn reorg(
     Ok(new_head)
 }
 
-async fn deploy_contracts(
+/// Downloads the new contracts of the state diff and emits them with [Event::NewContract].
+///
+/// Definitions which do not hash to their class hash are downloaded again after a backoff.
+/// So as to not stall the sync loop, this happens in the returned task.
+async fn deploy_contracts<S: sequencer::ClientApi + Send + Sync + 'static>(
     tx_event: &mpsc::Sender<Event>,
-    sequencer: &impl sequencer::ClientApi,
+    sequencer: &Arc<S>,
     state_diff: &StateDiff,
-) -> anyhow::Result<()> {
+) -> anyhow::Result<Option<DefinitionRetries>> {
     let unique_contracts = state_diff
         .deployed_contracts
         .iter()
@@ -284,7 +445,7 @@ async fn deploy_contracts(
         .collect::<Vec<_>>();
 
     if unique_contracts.is_empty() {
-        return Ok(());
+        return Ok(None);
     }
 
     // Query database to see which of these contracts still needs downloading.
@@ -311,6 +472,7 @@ async fn deploy_contracts(
         .collect::<Vec<_>>();
 
     // Download each contract and push it to storage.
+    let mut rejected = Vec::new();
     for contract_hash in require_downloading {
         // Find the relevant contract address.
         let contract = state_diff
@@ -319,23 +481,116 @@ async fn deploy_contracts(
             .find(|contract| contract.contract_hash == contract_hash)
             .unwrap();
 
-        let contract = download_and_compress_contract(contract, sequencer)
+        match download_and_compress_contract(contract, sequencer.as_ref())
             .await
-            .with_context(|| format!("Download and compress contract {:?}", contract.address))?;
+            .with_context(|| format!("Download and compress contract {:?}", contract.address))?
+        {
+            DownloadContract::Verified(contract) => tx_event
+                .send(Event::NewContract(contract))
+                .await
+                .context("Event channel closed")?,
+            DownloadContract::HashMismatch(actual) => {
+                reject_definition(contract, actual, 1, tx_event).await?;
+                rejected.push(contract.clone());
+            }
+        }
+    }
 
-        tx_event
-            .send(Event::NewContract(contract))
-            .await
-            .context("Event channel closed")?;
+    if rejected.is_empty() {
+        return Ok(None);
     }
 
-    Ok(())
+    let tx_event = tx_event.clone();
+    let sequencer = Arc::clone(sequencer);
+    let retries = tokio::spawn(
+        async move {
+            for contract in rejected {
+                let definition =
+                    download_verified_contract(&contract, &tx_event, sequencer.as_ref())
+                        .await
+                        .with_context(|| {
+                            format!("Download and compress contract {:?}", contract.address)
+                        })?;
+
+                tx_event
+                    .send(Event::NewContract(definition))
+                    .await
+                    .context("Event channel closed")?;
+            }
+
+            Ok(())
+        }
+        .in_current_span(),
+    );
+
+    Ok(Some(DefinitionRetries(retries)))
 }
 
-async fn download_and_compress_contract(
+/// Number of times a contract definition is downloaded before a class hash mismatch
+/// fails the sync process.
+const MAX_DEFINITION_DOWNLOADS: usize = 3;
+
+/// Delay before the second download of a contract definition, doubled for each further one.
+const DEFINITION_DOWNLOAD_BACKOFF: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Downloads the contract's definition again until it hashes to the expected class hash,
+/// after its first download was rejected.
+async fn download_verified_contract(
     contract: &Contract,
+    tx_event: &mpsc::Sender<Event>,
     sequencer: &impl sequencer::ClientApi,
 ) -> anyhow::Result<CompressedContract> {
+    let mut backoff = DEFINITION_DOWNLOAD_BACKOFF;
+    for attempt in 2..=MAX_DEFINITION_DOWNLOADS {
+        tokio::time::sleep(backoff).await;
+        backoff *= 2;
+
+        match download_and_compress_contract(contract, sequencer).await? {
+            DownloadContract::Verified(contract) => return Ok(contract),
+            DownloadContract::HashMismatch(actual) => {
+                reject_definition(contract, actual, attempt, tx_event).await?
+            }
+        }
+    }
+
+    anyhow::bail!(
+        "Class hash mismatch for contract {:?} after {} downloads",
+        contract.address,
+        MAX_DEFINITION_DOWNLOADS
+    )
+}
+
+/// Logs the rejected definition and reports it with [Event::ClassHashMismatch].
+async fn reject_definition(
+    contract: &Contract,
+    actual: ClassHash,
+    attempt: usize,
+    tx_event: &mpsc::Sender<Event>,
+) -> anyhow::Result<()> {
+    tracing::warn!(
+        address=%contract.address.0,
+        expected=%contract.contract_hash.0,
+        actual=%actual.0,
+        %attempt,
+        "Class hash mismatch for downloaded contract definition"
+    );
+
+    tx_event
+        .send(Event::ClassHashMismatch(contract.contract_hash))
+        .await
+        .context("Event channel closed")
+}
+
+enum DownloadContract {
+    Verified(CompressedContract),
+    /// The definition hashed to this class hash instead of the expected one.
+    HashMismatch(ClassHash),
+}
+
+async fn download_and_compress_contract(
+    contract: &Contract,
+    sequencer: &impl sequencer::ClientApi,
+) -> anyhow::Result<DownloadContract> {
     let contract_definition = sequencer
         .full_contract(contract.address)
         .await
@@ -351,12 +606,10 @@ async fn download_and_compress_contract(
         .await
         .context("Parse contract definition and compute hash")??;
 
-    // Sanity check.
-    anyhow::ensure!(
-        contract.contract_hash == hash,
-        "Class hash mismatch for contract {:?}",
-        contract.address
-    );
+    // Never store a definition under a class hash it doesn't hash to.
+    if contract.contract_hash != hash {
+        return Ok(DownloadContract::HashMismatch(hash));
+    }
 
     let compress = tokio::task::spawn_blocking(move || -> anyhow::Result<_> {
         let mut compressor = zstd::bulk::Compressor::new(10).context("Create zstd compressor")?;
@@ -373,18 +626,20 @@ async fn download_and_compress_contract(
     });
     let (abi, bytecode, definition) = compress.await.context("Compress contract")??;
 
-    Ok(CompressedContract {
+    Ok(DownloadContract::Verified(CompressedContract {
         abi,
         bytecode,
         definition,
         hash,
-    })
+    }))
 }
 
 #[cfg(test)]
 mod tests {
     mod sync {
         use super::super::{sync, Event};
+        use crate::state::block_hash::{verify_block_hash, BlockValidationMode};
+        use crate::state::transaction_hash::VerifyResult;
         use crate::{
             core::{
                 ClassHash, ContractAddress, GasPrice, GlobalRoot, SequencerAddress,
@@ -426,14 +681,6 @@ mod tests {
         const BLOCK4_NUMBER: StarknetBlockNumber = StarknetBlockNumber(4);
 
         lazy_static::lazy_static! {
-            static ref BLOCK0_HASH: StarknetBlockHash = StarknetBlockHash(StarkHash::from_be_slice(b"block 0 hash").unwrap());
-            static ref BLOCK0_HASH_V2: StarknetBlockHash = StarknetBlockHash(StarkHash::from_be_slice(b"block 0 hash v2").unwrap());
-            static ref BLOCK1_HASH: StarknetBlockHash = StarknetBlockHash(StarkHash::from_be_slice(b"block 1 hash").unwrap());
-            static ref BLOCK1_HASH_V2: StarknetBlockHash = StarknetBlockHash(StarkHash::from_be_slice(b"block 1 hash v2").unwrap());
-            static ref BLOCK2_HASH: StarknetBlockHash = StarknetBlockHash(StarkHash::from_be_slice(b"block 2 hash").unwrap());
-            static ref BLOCK2_HASH_V2: StarknetBlockHash = StarknetBlockHash(StarkHash::from_be_slice(b"block 2 hash v2").unwrap());
-            static ref BLOCK3_HASH: StarknetBlockHash = StarknetBlockHash(StarkHash::from_be_slice(b"block 3 hash").unwrap());
-
             static ref GLOBAL_ROOT0: GlobalRoot = GlobalRoot(StarkHash::from_be_slice(b"global root 0").unwrap());
             static ref GLOBAL_ROOT0_V2: GlobalRoot = GlobalRoot(StarkHash::from_be_slice(b"global root 0 v2").unwrap());
             static ref GLOBAL_ROOT1: GlobalRoot = GlobalRoot(StarkHash::from_be_slice(b"global root 1").unwrap());
@@ -476,8 +723,8 @@ mod tests {
             static ref STORAGE_VAL0_V2: StorageValue = StorageValue(StarkHash::from_be_slice(b"contract 0 storage val 0 v2").unwrap());
             static ref STORAGE_VAL1: StorageValue = StorageValue(StarkHash::from_be_slice(b"contract 1 storage val 0").unwrap());
 
-            static ref BLOCK0: reply::Block = reply::Block {
-                block_hash: Some(*BLOCK0_HASH),
+            static ref BLOCK0: reply::Block = with_valid_hash(reply::Block {
+                block_hash: None,
                 block_number: Some(BLOCK0_NUMBER),
                 gas_price: Some(GasPrice::ZERO),
                 parent_block_hash: StarknetBlockHash(StarkHash::ZERO),
@@ -487,9 +734,10 @@ mod tests {
                 timestamp: StarknetBlockTimestamp(0),
                 transaction_receipts: vec![],
                 transactions: vec![],
-            };
-            static ref BLOCK0_V2: reply::Block = reply::Block {
-                block_hash: Some(*BLOCK0_HASH_V2),
+            });
+            static ref BLOCK0_HASH: StarknetBlockHash = BLOCK0.block_hash.unwrap();
+            static ref BLOCK0_V2: reply::Block = with_valid_hash(reply::Block {
+                block_hash: None,
                 block_number: Some(BLOCK0_NUMBER),
                 gas_price: Some(GasPrice::from_be_slice(b"gas price 0 v2").unwrap()),
                 parent_block_hash: StarknetBlockHash(StarkHash::ZERO),
@@ -499,9 +747,10 @@ mod tests {
                 timestamp: StarknetBlockTimestamp(10),
                 transaction_receipts: vec![],
                 transactions: vec![],
-            };
-            static ref BLOCK1: reply::Block = reply::Block {
-                block_hash: Some(*BLOCK1_HASH),
+            });
+            static ref BLOCK0_HASH_V2: StarknetBlockHash = BLOCK0_V2.block_hash.unwrap();
+            static ref BLOCK1: reply::Block = with_valid_hash(reply::Block {
+                block_hash: None,
                 block_number: Some(BLOCK1_NUMBER),
                 gas_price: Some(GasPrice::from(1)),
                 parent_block_hash: *BLOCK0_HASH,
@@ -511,9 +760,10 @@ mod tests {
                 timestamp: StarknetBlockTimestamp(1),
                 transaction_receipts: vec![],
                 transactions: vec![],
-            };
-            static ref BLOCK2: reply::Block = reply::Block {
-                block_hash: Some(*BLOCK2_HASH),
+            });
+            static ref BLOCK1_HASH: StarknetBlockHash = BLOCK1.block_hash.unwrap();
+            static ref BLOCK2: reply::Block = with_valid_hash(reply::Block {
+                block_hash: None,
                 block_number: Some(BLOCK2_NUMBER),
                 gas_price: Some(GasPrice::from(2)),
                 parent_block_hash: *BLOCK1_HASH,
@@ -523,7 +773,8 @@ mod tests {
                 timestamp: StarknetBlockTimestamp(2),
                 transaction_receipts: vec![],
                 transactions: vec![],
-            };
+            });
+            static ref BLOCK2_HASH: StarknetBlockHash = BLOCK2.block_hash.unwrap();
 
             static ref STATE_UPDATE0: reply::StateUpdate = reply::StateUpdate {
                 block_hash: Some(*BLOCK0_HASH),
@@ -582,8 +833,9 @@ mod tests {
                     ]),
                 },
             };
+            // The hashes of the forked blocks depend on their parents, which differ between the tests.
             static ref STATE_UPDATE1_V2: reply::StateUpdate = reply::StateUpdate {
-                block_hash: Some(*BLOCK1_HASH_V2),
+                block_hash: None,
                 new_root: *GLOBAL_ROOT1_V2,
                 old_root: *GLOBAL_ROOT0_V2,
                 state_diff: reply::state_update::StateDiff {
@@ -601,7 +853,7 @@ mod tests {
                 },
             };
             static ref STATE_UPDATE2_V2: reply::StateUpdate = reply::StateUpdate {
-                block_hash: Some(*BLOCK2_HASH_V2),
+                block_hash: None,
                 new_root: *GLOBAL_ROOT2_V2,
                 old_root: *GLOBAL_ROOT1_V2,
                 state_diff: reply::state_update::StateDiff {
@@ -610,7 +862,7 @@ mod tests {
                 },
             };
             static ref STATE_UPDATE3: reply::StateUpdate = reply::StateUpdate {
-                block_hash: Some(*BLOCK3_HASH),
+                block_hash: None,
                 new_root: *GLOBAL_ROOT3,
                 old_root: *GLOBAL_ROOT2,
                 state_diff: reply::state_update::StateDiff {
@@ -721,6 +973,16 @@ mod tests {
                 .return_once(|_| returned_result);
         }
 
+        /// Sets the block hash to the one computed from the block, so that it passes verification.
+        fn with_valid_hash(mut block: reply::Block) -> reply::Block {
+            block.block_hash = Some(StarknetBlockHash(StarkHash::ZERO));
+            match verify_block_hash(&block, crate::ethereum::Chain::Goerli).unwrap() {
+                VerifyResult::Mismatch(hash) => block.block_hash = Some(hash),
+                other => panic!("Unexpected verification result {:?}", other),
+            }
+            block
+        }
+
         /// Convenience wrapper
         fn block_not_found() -> SequencerError {
             SequencerError::StarknetError(StarknetError {
@@ -763,7 +1025,13 @@ mod tests {
                 expect_latest_block(&mut mock, &mut seq, Ok(BLOCK1.clone()));
 
                 // Let's run the UUT
-                let _jh = tokio::spawn(sync(tx_event, mock, None, Chain::Goerli));
+                let _jh = tokio::spawn(sync(
+                    tx_event,
+                    mock,
+                    None,
+                    Chain::Goerli,
+                    BlockValidationMode::Strict,
+                ));
 
                 let zstd_magic = vec![0x28, 0xb5, 0x2f, 0xfd];
 
@@ -828,6 +1096,7 @@ mod tests {
                     mock,
                     Some((BLOCK0_NUMBER, *BLOCK0_HASH)),
                     Chain::Goerli,
+                    BlockValidationMode::Strict,
                 ));
 
                 let zstd_magic = vec![0x28, 0xb5, 0x2f, 0xfd];
@@ -917,7 +1186,13 @@ mod tests {
                 expect_latest_block(&mut mock, &mut seq, Ok(BLOCK0_V2.clone()));
 
                 // Let's run the UUT
-                let _jh = tokio::spawn(sync(tx_event, mock, None, Chain::Goerli));
+                let _jh = tokio::spawn(sync(
+                    tx_event,
+                    mock,
+                    None,
+                    Chain::Goerli,
+                    BlockValidationMode::Strict,
+                ));
 
                 let zstd_magic = vec![0x28, 0xb5, 0x2f, 0xfd];
 
@@ -979,8 +1254,8 @@ mod tests {
                 let mut mock = MockClientApi::new();
                 let mut seq = mockall::Sequence::new();
 
-                let block1_v2 = reply::Block {
-                    block_hash: Some(*BLOCK1_HASH_V2),
+                let block1_v2 = with_valid_hash(reply::Block {
+                    block_hash: None,
                     block_number: Some(BLOCK1_NUMBER),
                     gas_price: Some(GasPrice::from_be_slice(b"gas price 1 v2").unwrap()),
                     parent_block_hash: *BLOCK0_HASH_V2,
@@ -992,7 +1267,7 @@ mod tests {
                     timestamp: StarknetBlockTimestamp(4),
                     transaction_receipts: vec![],
                     transactions: vec![],
-                };
+                });
 
                 // Fetch the genesis block with respective state update and contracts
                 expect_block(&mut mock, &mut seq, BLOCK0_NUMBER, Ok(BLOCK0.clone()));
@@ -1047,8 +1322,11 @@ mod tests {
                 expect_state_update(
                     &mut mock,
                     &mut seq,
-                    *BLOCK1_HASH_V2,
-                    Ok(STATE_UPDATE1_V2.clone()),
+                    block1_v2.block_hash.unwrap(),
+                    Ok(reply::StateUpdate {
+                        block_hash: block1_v2.block_hash,
+                        ..STATE_UPDATE1_V2.clone()
+                    }),
                 );
 
                 // Indicate that we are still staying at the head
@@ -1057,7 +1335,13 @@ mod tests {
                 expect_latest_block(&mut mock, &mut seq, Ok(block1_v2.clone()));
 
                 // Run the UUT
-                let _jh = tokio::spawn(sync(tx_event, mock, None, Chain::Goerli));
+                let _jh = tokio::spawn(sync(
+                    tx_event,
+                    mock,
+                    None,
+                    Chain::Goerli,
+                    BlockValidationMode::Strict,
+                ));
 
                 let zstd_magic = vec![0x28, 0xb5, 0x2f, 0xfd];
 
@@ -1161,8 +1445,8 @@ mod tests {
                 let mut mock = MockClientApi::new();
                 let mut seq = mockall::Sequence::new();
 
-                let block1_v2 = reply::Block {
-                    block_hash: Some(*BLOCK1_HASH_V2),
+                let block1_v2 = with_valid_hash(reply::Block {
+                    block_hash: None,
                     block_number: Some(BLOCK1_NUMBER),
                     gas_price: Some(GasPrice::from_be_slice(b"gas price 1 v2").unwrap()),
                     parent_block_hash: *BLOCK0_HASH,
@@ -1174,12 +1458,12 @@ mod tests {
                     timestamp: StarknetBlockTimestamp(4),
                     transaction_receipts: vec![],
                     transactions: vec![],
-                };
-                let block2_v2 = reply::Block {
-                    block_hash: Some(*BLOCK2_HASH_V2),
+                });
+                let block2_v2 = with_valid_hash(reply::Block {
+                    block_hash: None,
                     block_number: Some(BLOCK2_NUMBER),
                     gas_price: Some(GasPrice::from_be_slice(b"gas price 2 v2").unwrap()),
-                    parent_block_hash: *BLOCK1_HASH_V2,
+                    parent_block_hash: block1_v2.block_hash.unwrap(),
                     sequencer_address: Some(SequencerAddress(
                         StarkHash::from_be_slice(b"sequencer addr. 2 v2").unwrap(),
                     )),
@@ -1188,9 +1472,9 @@ mod tests {
                     timestamp: StarknetBlockTimestamp(5),
                     transaction_receipts: vec![],
                     transactions: vec![],
-                };
-                let block3 = reply::Block {
-                    block_hash: Some(*BLOCK3_HASH),
+                });
+                let block3 = with_valid_hash(reply::Block {
+                    block_hash: None,
                     block_number: Some(BLOCK3_NUMBER),
                     gas_price: Some(GasPrice::from(3)),
                     parent_block_hash: *BLOCK2_HASH,
@@ -1202,7 +1486,7 @@ mod tests {
                     timestamp: StarknetBlockTimestamp(3),
                     transaction_receipts: vec![],
                     transactions: vec![],
-                };
+                });
 
                 // Fetch the genesis block with respective state update and contracts
                 expect_block(&mut mock, &mut seq, BLOCK0_NUMBER, Ok(BLOCK0.clone()));
@@ -1227,7 +1511,15 @@ mod tests {
                 expect_state_update(&mut mock, &mut seq, *BLOCK2_HASH, Ok(STATE_UPDATE2.clone()));
                 // Fetch block #3 with respective state update and contracts
                 expect_block(&mut mock, &mut seq, BLOCK3_NUMBER, Ok(block3.clone()));
-                expect_state_update(&mut mock, &mut seq, *BLOCK3_HASH, Ok(STATE_UPDATE3.clone()));
+                expect_state_update(
+                    &mut mock,
+                    &mut seq,
+                    block3.block_hash.unwrap(),
+                    Ok(reply::StateUpdate {
+                        block_hash: block3.block_hash,
+                        ..STATE_UPDATE3.clone()
+                    }),
+                );
                 // Block #4 is not there
                 expect_block(&mut mock, &mut seq, BLOCK4_NUMBER, Err(block_not_found()));
 
@@ -1246,16 +1538,22 @@ mod tests {
                 expect_state_update(
                     &mut mock,
                     &mut seq,
-                    *BLOCK1_HASH_V2,
-                    Ok(STATE_UPDATE1_V2.clone()),
+                    block1_v2.block_hash.unwrap(),
+                    Ok(reply::StateUpdate {
+                        block_hash: block1_v2.block_hash,
+                        ..STATE_UPDATE1_V2.clone()
+                    }),
                 );
                 // Fetch the new block #2 from the fork with respective state update
                 expect_block(&mut mock, &mut seq, BLOCK2_NUMBER, Ok(block2_v2.clone()));
                 expect_state_update(
                     &mut mock,
                     &mut seq,
-                    *BLOCK2_HASH_V2,
-                    Ok(STATE_UPDATE2_V2.clone()),
+                    block2_v2.block_hash.unwrap(),
+                    Ok(reply::StateUpdate {
+                        block_hash: block2_v2.block_hash,
+                        ..STATE_UPDATE2_V2.clone()
+                    }),
                 );
 
                 // Indicate that we are still staying at the head - no new blocks and the latest block matches our head
@@ -1263,7 +1561,13 @@ mod tests {
                 expect_latest_block(&mut mock, &mut seq, Ok(block2_v2.clone()));
 
                 // Run the UUT
-                let _jh = tokio::spawn(sync(tx_event, mock, None, Chain::Goerli));
+                let _jh = tokio::spawn(sync(
+                    tx_event,
+                    mock,
+                    None,
+                    Chain::Goerli,
+                    BlockValidationMode::Strict,
+                ));
 
                 let zstd_magic = vec![0x28, 0xb5, 0x2f, 0xfd];
 
@@ -1359,8 +1663,8 @@ mod tests {
                 let mut mock = MockClientApi::new();
                 let mut seq = mockall::Sequence::new();
 
-                let block2_v2 = reply::Block {
-                    block_hash: Some(*BLOCK2_HASH_V2),
+                let block2_v2 = with_valid_hash(reply::Block {
+                    block_hash: None,
                     block_number: Some(BLOCK2_NUMBER),
                     gas_price: Some(GasPrice::from_be_slice(b"gas price 2 v2").unwrap()),
                     parent_block_hash: *BLOCK1_HASH,
@@ -1372,7 +1676,7 @@ mod tests {
                     timestamp: StarknetBlockTimestamp(5),
                     transaction_receipts: vec![],
                     transactions: vec![],
-                };
+                });
 
                 // Fetch the genesis block with respective state update and contracts
                 expect_block(&mut mock, &mut seq, BLOCK0_NUMBER, Ok(BLOCK0.clone()));
@@ -1411,8 +1715,11 @@ mod tests {
                 expect_state_update(
                     &mut mock,
                     &mut seq,
-                    *BLOCK2_HASH_V2,
-                    Ok(STATE_UPDATE2_V2.clone()),
+                    block2_v2.block_hash.unwrap(),
+                    Ok(reply::StateUpdate {
+                        block_hash: block2_v2.block_hash,
+                        ..STATE_UPDATE2_V2.clone()
+                    }),
                 );
 
                 // Indicate that we are still staying at the head - no new blocks and the latest block matches our head
@@ -1420,7 +1727,13 @@ mod tests {
                 expect_latest_block(&mut mock, &mut seq, Ok(block2_v2.clone()));
 
                 // Run the UUT
-                let _jh = tokio::spawn(sync(tx_event, mock, None, Chain::Goerli));
+                let _jh = tokio::spawn(sync(
+                    tx_event,
+                    mock,
+                    None,
+                    Chain::Goerli,
+                    BlockValidationMode::Strict,
+                ));
 
                 let zstd_magic = vec![0x28, 0xb5, 0x2f, 0xfd];
 
@@ -1496,8 +1809,8 @@ mod tests {
                 let mut mock = MockClientApi::new();
                 let mut seq = mockall::Sequence::new();
 
-                let block1_v2 = reply::Block {
-                    block_hash: Some(*BLOCK1_HASH_V2),
+                let block1_v2 = with_valid_hash(reply::Block {
+                    block_hash: None,
                     block_number: Some(BLOCK1_NUMBER),
                     gas_price: Some(GasPrice::from_be_slice(b"gas price 1 v2").unwrap()),
                     parent_block_hash: *BLOCK0_HASH,
@@ -1509,12 +1822,12 @@ mod tests {
                     timestamp: StarknetBlockTimestamp(4),
                     transaction_receipts: vec![],
                     transactions: vec![],
-                };
-                let block2 = reply::Block {
-                    block_hash: Some(*BLOCK2_HASH),
+                });
+                let block2 = with_valid_hash(reply::Block {
+                    block_hash: None,
                     block_number: Some(BLOCK2_NUMBER),
                     gas_price: Some(GasPrice::from_be_slice(b"gas price 2").unwrap()),
-                    parent_block_hash: *BLOCK1_HASH_V2,
+                    parent_block_hash: block1_v2.block_hash.unwrap(),
                     sequencer_address: Some(SequencerAddress(
                         StarkHash::from_be_slice(b"sequencer address 2").unwrap(),
                     )),
@@ -1523,7 +1836,7 @@ mod tests {
                     timestamp: StarknetBlockTimestamp(5),
                     transaction_receipts: vec![],
                     transactions: vec![],
-                };
+                });
 
                 // Fetch the genesis block with respective state update and contracts
                 expect_block(&mut mock, &mut seq, BLOCK0_NUMBER, Ok(BLOCK0.clone()));
@@ -1556,19 +1869,36 @@ mod tests {
                 expect_state_update(
                     &mut mock,
                     &mut seq,
-                    *BLOCK1_HASH_V2,
-                    Ok(STATE_UPDATE1_V2.clone()),
+                    block1_v2.block_hash.unwrap(),
+                    Ok(reply::StateUpdate {
+                        block_hash: block1_v2.block_hash,
+                        ..STATE_UPDATE1_V2.clone()
+                    }),
                 );
                 // Fetch the block #2 again, now with respective state update
                 expect_block(&mut mock, &mut seq, BLOCK2_NUMBER, Ok(block2.clone()));
-                expect_state_update(&mut mock, &mut seq, *BLOCK2_HASH, Ok(STATE_UPDATE2.clone()));
+                expect_state_update(
+                    &mut mock,
+                    &mut seq,
+                    block2.block_hash.unwrap(),
+                    Ok(reply::StateUpdate {
+                        block_hash: block2.block_hash,
+                        ..STATE_UPDATE2.clone()
+                    }),
+                );
 
                 // Indicate that we are still staying at the head - no new blocks and the latest block matches our head
                 expect_block(&mut mock, &mut seq, BLOCK3_NUMBER, Err(block_not_found()));
                 expect_latest_block(&mut mock, &mut seq, Ok(block2.clone()));
 
                 // Run the UUT
-                let _jh = tokio::spawn(sync(tx_event, mock, None, Chain::Goerli));
+                let _jh = tokio::spawn(sync(
+                    tx_event,
+                    mock,
+                    None,
+                    Chain::Goerli,
+                    BlockValidationMode::Strict,
+                ));
 
                 let zstd_magic = vec![0x28, 0xb5, 0x2f, 0xfd];
 
@@ -1644,7 +1974,13 @@ mod tests {
                 expect_state_update(&mut mock, &mut seq, *BLOCK0_HASH, Ok(STATE_UPDATE0.clone()));
 
                 // Run the UUT
-                let jh = tokio::spawn(sync(tx_event, mock, None, Chain::Goerli));
+                let jh = tokio::spawn(sync(
+                    tx_event,
+                    mock,
+                    None,
+                    Chain::Goerli,
+                    BlockValidationMode::Strict,
+                ));
 
                 // Wrap this in a timeout so we don't wait forever in case of test failure.
                 // Right now closing the channel causes an error.
@@ -1655,5 +1991,295 @@ mod tests {
                     .unwrap_err();
             }
         }
+
+        mod class_hash_mismatch {
+            use super::super::super::{DEFINITION_DOWNLOAD_BACKOFF, MAX_DEFINITION_DOWNLOADS};
+            use super::*;
+            use crate::ethereum::Chain;
+            use pretty_assertions::assert_eq;
+
+            #[tokio::test(start_paused = true)]
+            async fn definition_is_downloaded_again() {
+                let (tx_event, mut rx_event) = tokio::sync::mpsc::channel(1);
+                let mut mock = MockClientApi::new();
+                let mut seq = mockall::Sequence::new();
+
+                expect_block(&mut mock, &mut seq, BLOCK0_NUMBER, Ok(BLOCK0.clone()));
+                expect_state_update(&mut mock, &mut seq, *BLOCK0_HASH, Ok(STATE_UPDATE0.clone()));
+                // The first download is of the wrong class
+                expect_full_contract(
+                    &mut mock,
+                    &mut seq,
+                    *CONTRACT0_ADDR,
+                    Ok(CONTRACT1_DEF.clone()),
+                );
+                // Stay at head, no more blocks available
+                expect_block(&mut mock, &mut seq, BLOCK1_NUMBER, Err(block_not_found()));
+                expect_latest_block(&mut mock, &mut seq, Ok(BLOCK0.clone()));
+                expect_full_contract(
+                    &mut mock,
+                    &mut seq,
+                    *CONTRACT0_ADDR,
+                    Ok(CONTRACT0_DEF.clone()),
+                );
+
+                // Let's run the UUT
+                let _jh = tokio::spawn(sync(
+                    tx_event,
+                    mock,
+                    None,
+                    Chain::Goerli,
+                    BlockValidationMode::Strict,
+                ));
+
+                assert_matches!(rx_event.recv().await.unwrap(), Event::QueryContractExistance(contract_hashes, sender) => {
+                    assert_eq!(contract_hashes, vec![*CONTRACT0_HASH]);
+                    sender.send(vec![false]).unwrap();
+                });
+                assert_matches!(rx_event.recv().await.unwrap(), Event::ClassHashMismatch(hash) => {
+                    assert_eq!(hash, *CONTRACT0_HASH);
+                });
+                assert_matches!(rx_event.recv().await.unwrap(), Event::NewContract(compressed_contract) => {
+                    assert_eq!(compressed_contract.hash, *CONTRACT0_HASH);
+                });
+                assert_matches!(rx_event.recv().await.unwrap(), Event::Update(block, _, _) => {
+                    assert_eq!(*block, *BLOCK0);
+                });
+            }
+
+            #[tokio::test(start_paused = true)]
+            async fn gives_up() {
+                let (tx_event, mut rx_event) = tokio::sync::mpsc::channel(1);
+                let mut mock = MockClientApi::new();
+                let mut seq = mockall::Sequence::new();
+
+                expect_block(&mut mock, &mut seq, BLOCK0_NUMBER, Ok(BLOCK0.clone()));
+                expect_state_update(&mut mock, &mut seq, *BLOCK0_HASH, Ok(STATE_UPDATE0.clone()));
+                for attempt in 1..=MAX_DEFINITION_DOWNLOADS {
+                    expect_full_contract(
+                        &mut mock,
+                        &mut seq,
+                        *CONTRACT0_ADDR,
+                        Ok(CONTRACT1_DEF.clone()),
+                    );
+                    if attempt == 1 {
+                        // Stay at head, no more blocks available
+                        expect_block(&mut mock, &mut seq, BLOCK1_NUMBER, Err(block_not_found()));
+                        expect_latest_block(&mut mock, &mut seq, Ok(BLOCK0.clone()));
+                    }
+                }
+
+                // Let's run the UUT
+                let jh = tokio::spawn(sync(
+                    tx_event,
+                    mock,
+                    None,
+                    Chain::Goerli,
+                    BlockValidationMode::Strict,
+                ));
+
+                assert_matches!(rx_event.recv().await.unwrap(), Event::QueryContractExistance(_, sender) => {
+                    sender.send(vec![false]).unwrap();
+                });
+                let start = tokio::time::Instant::now();
+                for _ in 0..MAX_DEFINITION_DOWNLOADS {
+                    assert_matches!(rx_event.recv().await.unwrap(), Event::ClassHashMismatch(hash) => {
+                        assert_eq!(hash, *CONTRACT0_HASH);
+                    });
+                }
+                // The downloads are spaced out by the doubling backoff.
+                assert_eq!(
+                    start.elapsed(),
+                    DEFINITION_DOWNLOAD_BACKOFF + DEFINITION_DOWNLOAD_BACKOFF * 2
+                );
+
+                // The definition is never stored.
+                tokio::time::timeout(std::time::Duration::from_secs(2), jh)
+                    .await
+                    .unwrap()
+                    .unwrap()
+                    .unwrap_err();
+                assert!(rx_event.recv().await.is_none());
+            }
+
+            #[tokio::test(start_paused = true)]
+            async fn next_block_is_downloaded_during_retry() {
+                let (tx_event, mut rx_event) = tokio::sync::mpsc::channel(1);
+                let mut mock = MockClientApi::new();
+                let mut seq = mockall::Sequence::new();
+
+                expect_block(&mut mock, &mut seq, BLOCK0_NUMBER, Ok(BLOCK0.clone()));
+                expect_state_update(&mut mock, &mut seq, *BLOCK0_HASH, Ok(STATE_UPDATE0.clone()));
+                // The first download is of the wrong class
+                expect_full_contract(
+                    &mut mock,
+                    &mut seq,
+                    *CONTRACT0_ADDR,
+                    Ok(CONTRACT1_DEF.clone()),
+                );
+                // Block 1 is downloaded concurrently with the definition being downloaded again
+                let start = tokio::time::Instant::now();
+                let (tx_block1, rx_block1) = tokio::sync::oneshot::channel();
+                mock.expect_block_by_number()
+                    .withf(|x| x == &BlockNumberOrTag::Number(BLOCK1_NUMBER))
+                    .times(1)
+                    .in_sequence(&mut seq)
+                    .return_once(move |_| {
+                        tx_block1.send(tokio::time::Instant::now()).unwrap();
+                        Ok(BLOCK1.clone())
+                    });
+                expect_state_update(&mut mock, &mut seq, *BLOCK1_HASH, Ok(STATE_UPDATE1.clone()));
+                mock.expect_full_contract()
+                    .withf(|x| x == &*CONTRACT0_ADDR)
+                    .times(1)
+                    .return_once(|_| Ok(CONTRACT0_DEF.clone()));
+                expect_full_contract(
+                    &mut mock,
+                    &mut seq,
+                    *CONTRACT1_ADDR,
+                    Ok(CONTRACT1_DEF.clone()),
+                );
+                // Stay at head, no more blocks available
+                expect_block(&mut mock, &mut seq, BLOCK2_NUMBER, Err(block_not_found()));
+                expect_latest_block(&mut mock, &mut seq, Ok(BLOCK1.clone()));
+
+                // Let's run the UUT
+                let _jh = tokio::spawn(sync(
+                    tx_event,
+                    mock,
+                    None,
+                    Chain::Goerli,
+                    BlockValidationMode::Strict,
+                ));
+
+                assert_matches!(rx_event.recv().await.unwrap(), Event::QueryContractExistance(_, sender) => {
+                    sender.send(vec![false]).unwrap();
+                });
+                assert_matches!(rx_event.recv().await.unwrap(), Event::ClassHashMismatch(hash) => {
+                    assert_eq!(hash, *CONTRACT0_HASH);
+                });
+                assert_matches!(rx_event.recv().await.unwrap(), Event::NewContract(compressed_contract) => {
+                    assert_eq!(compressed_contract.hash, *CONTRACT0_HASH);
+                });
+                assert!(rx_block1.await.unwrap() - start < DEFINITION_DOWNLOAD_BACKOFF);
+                // Block 0 is only emitted once its definition is verified.
+                assert_matches!(rx_event.recv().await.unwrap(), Event::Update(block, _, _) => {
+                    assert_eq!(*block, *BLOCK0);
+                });
+                assert_matches!(rx_event.recv().await.unwrap(), Event::QueryContractExistance(contract_hashes, sender) => {
+                    assert_eq!(contract_hashes, vec![*CONTRACT1_HASH]);
+                    sender.send(vec![false]).unwrap();
+                });
+                assert_matches!(rx_event.recv().await.unwrap(), Event::NewContract(compressed_contract) => {
+                    assert_eq!(compressed_contract.hash, *CONTRACT1_HASH);
+                });
+                assert_matches!(rx_event.recv().await.unwrap(), Event::Update(block, _, _) => {
+                    assert_eq!(*block, *BLOCK1);
+                });
+            }
+        }
+
+        mod block_verification {
+            use super::*;
+            use crate::ethereum::Chain;
+
+            fn genesis() -> reply::Block {
+                serde_json::from_str(include_str!(
+                    "../../../fixtures/sequencer/0.9.0/block/genesis.json"
+                ))
+                .unwrap()
+            }
+
+            /// Runs the UUT until it fails and returns its error.
+            async fn sync_error(
+                mock: MockClientApi,
+                block_validation_mode: BlockValidationMode,
+            ) -> anyhow::Error {
+                let (tx_event, _rx_event) = tokio::sync::mpsc::channel(1);
+
+                let jh = tokio::spawn(sync(
+                    tx_event,
+                    mock,
+                    None,
+                    Chain::Goerli,
+                    block_validation_mode,
+                ));
+
+                tokio::time::timeout(std::time::Duration::from_secs(2), jh)
+                    .await
+                    .unwrap()
+                    .unwrap()
+                    .unwrap_err()
+            }
+
+            #[tokio::test]
+            async fn valid_block_is_accepted() {
+                let mut mock = MockClientApi::new();
+                let mut seq = mockall::Sequence::new();
+
+                let block = genesis();
+                let block_hash = block.block_hash.unwrap();
+                expect_block(&mut mock, &mut seq, BLOCK0_NUMBER, Ok(block));
+                // Failing the next step shows that the block passed verification.
+                expect_state_update(&mut mock, &mut seq, block_hash, Err(block_not_found()));
+
+                let error = sync_error(mock, BlockValidationMode::Strict).await;
+                assert!(
+                    format!("{error:#}").contains("Fetch state diff"),
+                    "{error:#}"
+                );
+            }
+
+            #[tokio::test]
+            async fn block_hash_mismatch_is_rejected() {
+                let mut mock = MockClientApi::new();
+                let mut seq = mockall::Sequence::new();
+
+                let mut block = genesis();
+                block.state_root = Some(GlobalRoot(StarkHash::from_be_slice(b"tampered").unwrap()));
+                expect_block(&mut mock, &mut seq, BLOCK0_NUMBER, Ok(block));
+
+                let error = sync_error(mock, BlockValidationMode::Strict).await;
+                assert!(
+                    format!("{error:#}").contains("Block hash mismatch"),
+                    "{error:#}"
+                );
+            }
+
+            #[tokio::test]
+            async fn transaction_hash_mismatch_is_rejected() {
+                let mut mock = MockClientApi::new();
+                let mut seq = mockall::Sequence::new();
+
+                let mut block = genesis();
+                block.transactions[0].constructor_calldata = Some(Vec::new());
+                expect_block(&mut mock, &mut seq, BLOCK0_NUMBER, Ok(block));
+
+                let error = sync_error(mock, BlockValidationMode::Strict).await;
+                assert!(
+                    format!("{error:#}").contains("Transaction hash mismatch"),
+                    "{error:#}"
+                );
+            }
+
+            #[tokio::test]
+            async fn mismatch_is_allowed() {
+                let mut mock = MockClientApi::new();
+                let mut seq = mockall::Sequence::new();
+
+                let mut block = genesis();
+                block.transactions[0].constructor_calldata = Some(Vec::new());
+                block.state_root = Some(GlobalRoot(StarkHash::from_be_slice(b"tampered").unwrap()));
+                let block_hash = block.block_hash.unwrap();
+                expect_block(&mut mock, &mut seq, BLOCK0_NUMBER, Ok(block));
+                expect_state_update(&mut mock, &mut seq, block_hash, Err(block_not_found()));
+
+                let error = sync_error(mock, BlockValidationMode::AllowMismatch).await;
+                assert!(
+                    format!("{error:#}").contains("Fetch state diff"),
+                    "{error:#}"
+                );
+            }
+        }
     }
 }
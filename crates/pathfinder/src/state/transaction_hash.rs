@@ -0,0 +1,321 @@
+//! Computes StarkNet transaction hashes.
+//!
+//! The hashes are computed as described in [the official documentation][starknet-doc], and
+//! implemented in [cairo-lang][cairo-compute]:
+//!
+//! ```text
+//! h(prefix, version, contract_address, entry_point_selector, h(calldata), max_fee, chain_id, additional_data...)
+//! ```
+//!
+//! where `h` is a hash chain of its arguments (see [HashChain]). Transactions which were accepted
+//! before StarkNet 0.8 (`version` and `max_fee` were introduced then) use the legacy form:
+//!
+//! ```text
+//! h(prefix, contract_address, entry_point_selector, h(calldata), chain_id)
+//! ```
+//!
+//! [starknet-doc]: https://docs.starknet.io/docs/Blocks/transactions/#transaction-hash
+//! [cairo-compute]: https://github.com/starkware-libs/cairo-lang/blob/v0.9.0/src/starkware/starknet/core/os/transaction_hash/transaction_hash.py
+use anyhow::Context;
+use stark_hash::{HashChain, StarkHash};
+
+use crate::{
+    core::{ContractAddress, EntryPoint, StarknetTransactionHash, TransactionNonce},
+    ethereum::Chain,
+    sequencer::reply::transaction::{EntryPointType, Receipt, Transaction, Type},
+};
+
+/// The outcome of verifying a hash which was received from the feeder gateway.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VerifyResult<T> {
+    /// The locally computed hash matches the expected one.
+    Match,
+    /// The locally computed hash (which is included) does not match the expected one.
+    Mismatch(T),
+    /// There is no known way to compute the hash locally.
+    NotVerifiable,
+}
+
+/// Returns the StarkNet chain id of the given chain, which is part of the hashes.
+pub(crate) fn chain_id(chain: Chain) -> StarkHash {
+    let id: &[u8] = match chain {
+        Chain::Mainnet => b"SN_MAIN",
+        Chain::Goerli => b"SN_GOERLI",
+    };
+    StarkHash::from_be_slice(id).expect("chain id fits into a StarkHash")
+}
+
+/// Verifies the `transaction_hash` of the transaction by computing it locally.
+///
+/// Both the current and the legacy forms of the hash are accepted. The transaction's `receipt`
+/// is required as L1 handler transactions commit to the nonce of the L1 message they consume,
+/// which the feeder gateway only includes in the receipt.
+pub fn verify_transaction_hash(
+    transaction: &Transaction,
+    receipt: &Receipt,
+    chain: Chain,
+) -> anyhow::Result<VerifyResult<StarknetTransactionHash>> {
+    let expected = transaction.transaction_hash;
+
+    let l1_handler_without_nonce = transaction.entry_point_type == Some(EntryPointType::L1Handler)
+        && transaction.nonce.is_none();
+    let with_nonce;
+    let transaction = if l1_handler_without_nonce {
+        match receipt
+            .l1_to_l2_consumed_message
+            .as_ref()
+            .and_then(|message| message.nonce)
+        {
+            Some(nonce) => {
+                with_nonce = Transaction {
+                    nonce: Some(TransactionNonce(nonce.0)),
+                    ..transaction.clone()
+                };
+                &with_nonce
+            }
+            None => return Ok(VerifyResult::NotVerifiable),
+        }
+    } else {
+        transaction
+    };
+
+    let computed = compute_transaction_hash(transaction, chain)?;
+    if computed == expected {
+        return Ok(VerifyResult::Match);
+    }
+
+    match compute_legacy_transaction_hash(transaction, chain)? {
+        Some(legacy) if legacy == expected => Ok(VerifyResult::Match),
+        _ => Ok(VerifyResult::Mismatch(computed)),
+    }
+}
+
+/// Computes the hash of a transaction using the current (StarkNet 0.8 onwards) algorithm.
+pub fn compute_transaction_hash(
+    transaction: &Transaction,
+    chain: Chain,
+) -> anyhow::Result<StarknetTransactionHash> {
+    let fields = TransactionFields::try_from(transaction)?;
+
+    let mut hash = HashChain::default();
+    hash.update(fields.prefix);
+    hash.update(fields.version);
+    hash.update(fields.contract_address.0);
+    hash.update(fields.entry_point_selector);
+    hash.update(fields.calldata_hash);
+    hash.update(fields.max_fee);
+    hash.update(chain_id(chain));
+    fields
+        .additional_data
+        .into_iter()
+        .for_each(|value| hash.update(value));
+
+    Ok(StarknetTransactionHash(hash.finalize()))
+}
+
+/// Computes the hash of a transaction using the legacy (pre StarkNet 0.8) algorithm.
+///
+/// Returns [None] for transaction types which did not exist back then.
+pub fn compute_legacy_transaction_hash(
+    transaction: &Transaction,
+    chain: Chain,
+) -> anyhow::Result<Option<StarknetTransactionHash>> {
+    if transaction.r#type == Type::Declare {
+        return Ok(None);
+    }
+
+    let fields = TransactionFields::try_from(transaction)?;
+
+    let hash = HashChain::compute([
+        fields.prefix,
+        fields.contract_address.0,
+        fields.entry_point_selector,
+        fields.calldata_hash,
+        chain_id(chain),
+    ]);
+
+    Ok(Some(StarknetTransactionHash(hash)))
+}
+
+/// The transaction type specific values which make up the transaction hash.
+struct TransactionFields {
+    prefix: StarkHash,
+    version: StarkHash,
+    contract_address: ContractAddress,
+    entry_point_selector: StarkHash,
+    calldata_hash: StarkHash,
+    max_fee: StarkHash,
+    additional_data: Vec<StarkHash>,
+}
+
+impl TryFrom<&Transaction> for TransactionFields {
+    type Error = anyhow::Error;
+
+    fn try_from(transaction: &Transaction) -> Result<Self, Self::Error> {
+        let version = match transaction.version {
+            Some(version) => StarkHash::from_be_slice(version.0.as_bytes())
+                .context("Transaction version overflows a StarkHash")?,
+            None => StarkHash::ZERO,
+        };
+        let max_fee = match transaction.max_fee {
+            Some(fee) => StarkHash::from_be_slice(fee.0.as_bytes())
+                .context("Max fee overflows a StarkHash")?,
+            None => StarkHash::ZERO,
+        };
+
+        let fields = match transaction.r#type {
+            Type::Deploy => TransactionFields {
+                prefix: prefix(b"deploy"),
+                version,
+                contract_address: transaction
+                    .contract_address
+                    .context("Deploy transaction is missing contract address")?,
+                entry_point_selector: EntryPoint::hashed(b"constructor").0,
+                calldata_hash: HashChain::compute(
+                    transaction
+                        .constructor_calldata
+                        .iter()
+                        .flatten()
+                        .map(|param| param.0),
+                ),
+                max_fee: StarkHash::ZERO,
+                additional_data: Vec::new(),
+            },
+            Type::InvokeFunction => {
+                let prefix = match transaction.entry_point_type {
+                    Some(EntryPointType::L1Handler) => prefix(b"l1_handler"),
+                    _ => prefix(b"invoke"),
+                };
+
+                TransactionFields {
+                    prefix,
+                    version,
+                    contract_address: transaction
+                        .contract_address
+                        .context("Invoke transaction is missing contract address")?,
+                    entry_point_selector: transaction
+                        .entry_point_selector
+                        .context("Invoke transaction is missing entry point selector")?
+                        .0,
+                    calldata_hash: HashChain::compute(
+                        transaction.calldata.iter().flatten().map(|param| param.0),
+                    ),
+                    max_fee,
+                    additional_data: transaction.nonce.iter().map(|nonce| nonce.0).collect(),
+                }
+            }
+            Type::Declare => TransactionFields {
+                prefix: prefix(b"declare"),
+                version,
+                contract_address: transaction
+                    .sender_address
+                    .context("Declare transaction is missing sender address")?,
+                entry_point_selector: StarkHash::ZERO,
+                calldata_hash: HashChain::default().finalize(),
+                max_fee,
+                additional_data: vec![
+                    transaction
+                        .class_hash
+                        .context("Declare transaction is missing class hash")?
+                        .0,
+                ],
+            },
+        };
+
+        Ok(fields)
+    }
+}
+
+/// Transaction hash prefixes are the ASCII encoded transaction type.
+fn prefix(prefix: &[u8]) -> StarkHash {
+    StarkHash::from_be_slice(prefix).expect("prefix fits into a StarkHash")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sequencer::reply;
+
+    fn block(fixture: &str) -> reply::Block {
+        serde_json::from_str::<reply::Block>(fixture).unwrap()
+    }
+
+    fn assert_verified(block: &reply::Block) {
+        for (transaction, receipt) in block
+            .transactions
+            .iter()
+            .zip(block.transaction_receipts.iter())
+        {
+            assert_eq!(
+                verify_transaction_hash(transaction, receipt, Chain::Goerli).unwrap(),
+                VerifyResult::Match,
+                "{transaction:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn legacy_transactions() {
+        assert_verified(&block(include_str!(
+            "../../fixtures/sequencer/0.9.0/block/genesis.json"
+        )));
+        assert_verified(&block(include_str!(
+            "../../fixtures/sequencer/0.9.0/block/1716.json"
+        )));
+    }
+
+    #[test]
+    fn deploy_invoke_declare_and_l1_handler() {
+        let block = block(include_str!(
+            "../../fixtures/sequencer/0.9.0/block/231579.json"
+        ));
+        for r#type in [Type::Deploy, Type::InvokeFunction, Type::Declare] {
+            assert!(block.transactions.iter().any(|t| t.r#type == r#type));
+        }
+        assert!(block
+            .transactions
+            .iter()
+            .any(|t| t.entry_point_type == Some(EntryPointType::L1Handler)));
+
+        assert_verified(&block);
+    }
+
+    #[test]
+    fn l1_handler_without_message_nonce_is_not_verifiable() {
+        let mut block = block(include_str!(
+            "../../fixtures/sequencer/0.9.0/block/231579.json"
+        ));
+        let (transaction, receipt) = block
+            .transactions
+            .iter()
+            .zip(block.transaction_receipts.iter_mut())
+            .find(|(t, _)| t.entry_point_type == Some(EntryPointType::L1Handler))
+            .unwrap();
+        receipt.l1_to_l2_consumed_message = None;
+
+        assert_eq!(
+            verify_transaction_hash(transaction, receipt, Chain::Goerli).unwrap(),
+            VerifyResult::NotVerifiable
+        );
+    }
+
+    #[test]
+    fn mismatch() {
+        let block = block(include_str!(
+            "../../fixtures/sequencer/0.9.0/block/231579.json"
+        ));
+        let mut transaction = block.transactions[0].clone();
+        let receipt = &block.transaction_receipts[0];
+        let expected = transaction.transaction_hash;
+        transaction.transaction_hash = StarknetTransactionHash(StarkHash::ZERO);
+
+        assert_eq!(
+            verify_transaction_hash(&transaction, receipt, Chain::Goerli).unwrap(),
+            VerifyResult::Mismatch(expected)
+        );
+        assert_eq!(
+            verify_transaction_hash(&transaction, receipt, Chain::Mainnet).unwrap(),
+            VerifyResult::Mismatch(compute_transaction_hash(&transaction, Chain::Mainnet).unwrap())
+        );
+    }
+}
@@ -15,9 +15,13 @@ use std::sync::Mutex;
 pub use contract::{ContractCodeTable, ContractsTable};
 pub use ethereum::{EthereumBlocksTable, EthereumTransactionsTable};
 pub use state::{
-    ContractsStateTable, EventFilterError, L1StateTable, L1TableBlockId, RefsTable, StarknetBlock,
-    StarknetBlocksBlockId, StarknetBlocksTable, StarknetEmittedEvent, StarknetEventFilter,
-    StarknetEventsTable, StarknetTransactionsTable,
+    ContractDeployment, ContractDeploymentsTable, ContractStorageHistoryTable,
+    ContractsByClassHashFilter, ContractsStateTable, EventFilterError, L1StateTable,
+    L1TableBlockId, PageOfAddressTransactions, PageOfContractDeployments, PageOfStorageChanges,
+    PageSizeTooBig, RefsTable, StarknetAddressTransaction, StarknetBlock, StarknetBlocksBlockId,
+    StarknetBlocksTable, StarknetEmittedEvent, StarknetEventFilter, StarknetEventsTable,
+    StarknetTransactionsByAddressFilter, StarknetTransactionsTable, StorageChange,
+    StorageHistoryFilter, TransactionsContinuationToken, PAGE_SIZE_LIMIT,
 };
 
 use anyhow::Context;
@@ -27,7 +31,7 @@ use tracing::info;
 /// Indicates database is non-existant.
 const DB_VERSION_EMPTY: u32 = 0;
 /// Current database version.
-const DB_VERSION_CURRENT: u32 = 12;
+const DB_VERSION_CURRENT: u32 = 15;
 /// Sqlite key used for the PRAGMA user version.
 const VERSION_KEY: &str = "user_version";
 
@@ -150,6 +154,9 @@ fn migrate_database(connection: &mut Connection) -> anyhow::Result<()> {
             9 => schema::revision_0010::migrate(&transaction).context("migrating from 9")?,
             10 => schema::revision_0011::migrate(&transaction).context("migrating from 10")?,
             11 => schema::revision_0012::migrate(&transaction).context("migrating from 11")?,
+            12 => schema::revision_0013::migrate(&transaction).context("migrating from 12")?,
+            13 => schema::revision_0014::migrate(&transaction).context("migrating from 13")?,
+            14 => schema::revision_0015::migrate(&transaction).context("migrating from 14")?,
             _ => unreachable!("Database version constraint was already checked!"),
         };
         // If any migration action requires vacuuming, we should vacuum.
@@ -117,6 +117,36 @@ impl ContractCodeTable {
         Ok(Some(ContractCode { bytecode, abi }))
     }
 
+    /// Gets the specified class's full definition, as served by the sequencer.
+    ///
+    /// Returns `None` if the class is missing, or if its definition was not stored.
+    pub fn get_definition(
+        transaction: &Transaction,
+        hash: ClassHash,
+    ) -> anyhow::Result<Option<Vec<u8>>> {
+        // The definition column is nullable, so the outer option is for the row and the inner one
+        // for the column.
+        let definition: Option<Option<Vec<u8>>> = transaction
+            .query_row(
+                "SELECT definition FROM contract_code WHERE hash = :hash",
+                named_params! {
+                    ":hash": &hash.0.to_be_bytes()[..]
+                },
+                |row| row.get("definition"),
+            )
+            .optional()?;
+
+        let definition = match definition.flatten() {
+            Some(definition) => definition,
+            None => return Ok(None),
+        };
+
+        let definition = zstd::decode_all(&*definition)
+            .context("Corruption: invalid compressed column (definition)")?;
+
+        Ok(Some(definition))
+    }
+
     /// Returns true for each [ClassHash] if the class definition already exists in the table.
     pub fn exists(connection: &Connection, contracts: &[ClassHash]) -> anyhow::Result<Vec<bool>> {
         let mut stmt = connection.prepare("select 1 from contract_code where hash = ?")?;
@@ -219,6 +249,43 @@ mod tests {
         assert_eq!(result, Some(hash));
     }
 
+    #[test]
+    fn get_definition() {
+        let storage = Storage::in_memory().unwrap();
+        let mut conn = storage.connection().unwrap();
+        let transaction = conn.transaction().unwrap();
+
+        let hash = ClassHash(StarkHash::from_hex_str("123").unwrap());
+        let definition = br#"{"abi":{"see":"above"},"program":{"huge":"hash"},"entry_points_by_type":{"this might be a":"hash"}}"#;
+
+        ContractCodeTable::insert(&transaction, hash, &[][..], &[][..], &definition[..]).unwrap();
+
+        let result = ContractCodeTable::get_definition(&transaction, hash).unwrap();
+        assert_eq!(result, Some(definition.to_vec()));
+
+        let missing = ClassHash(StarkHash::from_hex_str("456").unwrap());
+        let result = ContractCodeTable::get_definition(&transaction, missing).unwrap();
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn get_definition_null() {
+        let storage = Storage::in_memory().unwrap();
+        let mut conn = storage.connection().unwrap();
+        let transaction = conn.transaction().unwrap();
+
+        let hash = ClassHash(StarkHash::from_hex_str("123").unwrap());
+        transaction
+            .execute(
+                "INSERT INTO contract_code (hash, definition) VALUES (:hash, NULL)",
+                named_params! { ":hash": &hash.0.to_be_bytes()[..] },
+            )
+            .unwrap();
+
+        let result = ContractCodeTable::get_definition(&transaction, hash).unwrap();
+        assert_eq!(result, None);
+    }
+
     #[test]
     fn get_code() {
         let storage = Storage::in_memory().unwrap();
@@ -10,6 +10,9 @@ pub(crate) mod revision_0009;
 pub(crate) mod revision_0010;
 pub(crate) mod revision_0011;
 pub(crate) mod revision_0012;
+pub(crate) mod revision_0013;
+pub(crate) mod revision_0014;
+pub(crate) mod revision_0015;
 
 #[cfg(test)]
 pub(crate) mod fixtures;
@@ -24,13 +24,40 @@ pub fn setup_events(connection: &Connection) -> Vec<StarknetEmittedEvent> {
             )
             .unwrap();
 
-        crate::storage::StarknetTransactionsTable::upsert(
-            connection,
-            block.hash,
-            block.number,
-            &transactions_and_receipts[i * TXNS_PER_BLOCK..(i + 1) * TXNS_PER_BLOCK],
-        )
-        .unwrap();
+        // Transactions are inserted directly instead of through `StarknetTransactionsTable` so
+        // that the fixture only relies on tables which exist in older schema revisions.
+        for (idx, (transaction, receipt)) in transactions_and_receipts
+            [i * TXNS_PER_BLOCK..(i + 1) * TXNS_PER_BLOCK]
+            .iter()
+            .enumerate()
+        {
+            let compressed_tx =
+                zstd::encode_all(serde_json::to_vec(transaction).unwrap().as_slice(), 10).unwrap();
+            let compressed_receipt =
+                zstd::encode_all(serde_json::to_vec(receipt).unwrap().as_slice(), 10).unwrap();
+
+            connection
+                .execute(
+                    r"INSERT INTO starknet_transactions ( hash,  idx,  block_hash,  tx,  receipt)
+                                                 VALUES (:hash, :idx, :block_hash, :tx, :receipt)",
+                    rusqlite::named_params! {
+                        ":hash": transaction.transaction_hash.0.as_be_bytes(),
+                        ":idx": idx,
+                        ":block_hash": block.hash.0.as_be_bytes(),
+                        ":tx": &compressed_tx,
+                        ":receipt": &compressed_receipt,
+                    },
+                )
+                .unwrap();
+
+            crate::storage::StarknetEventsTable::insert_events(
+                connection,
+                block.number,
+                transaction,
+                &receipt.events,
+            )
+            .unwrap();
+        }
     }
 
     transactions_and_receipts
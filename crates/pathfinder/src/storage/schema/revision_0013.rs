@@ -0,0 +1,191 @@
+use crate::storage::schema::PostMigrationAction;
+
+use anyhow::Context;
+use rusqlite::{named_params, Transaction};
+
+/// This migration adds the `starknet_transactions_by_address` index table, which maps
+/// contract addresses to the invoke and deploy transactions targeting them.
+///
+/// Existing transactions are re-indexed from the `starknet_transactions` table. Transactions
+/// which no longer belong to a canonical block (left behind by a reorg) are skipped.
+pub(crate) fn migrate(transaction: &Transaction) -> anyhow::Result<PostMigrationAction> {
+    transaction
+        .execute(
+            r"CREATE TABLE starknet_transactions_by_address (
+                contract_address BLOB NOT NULL,
+                block_number INTEGER NOT NULL,
+                idx INTEGER NOT NULL,
+                transaction_hash BLOB NOT NULL,
+                PRIMARY KEY (contract_address, block_number, idx),
+                FOREIGN KEY(block_number) REFERENCES starknet_blocks(number)
+                ON DELETE CASCADE
+            ) WITHOUT ROWID",
+            [],
+        )
+        .context("Create starknet transactions by address table")?;
+
+    // The primary key index can't be used to delete rows when a block is removed.
+    transaction
+        .execute(
+            "CREATE INDEX starknet_transactions_by_address_block_number ON starknet_transactions_by_address(block_number)",
+            [],
+        )
+        .context("Create block number index")?;
+
+    let todo: usize = transaction
+        .query_row("SELECT count(1) FROM starknet_transactions", [], |r| {
+            r.get(0)
+        })
+        .context("Count rows in starknet transactions table")?;
+
+    if todo == 0 {
+        return Ok(PostMigrationAction::None);
+    }
+
+    tracing::info!(
+        num_transactions=%todo,
+        "Indexing transactions by contract address, this may take a while.",
+    );
+
+    let mut stmt = transaction
+        .prepare(
+            r"SELECT starknet_transactions.hash, starknet_transactions.idx, starknet_transactions.tx, starknet_blocks.number
+                FROM starknet_transactions
+                JOIN starknet_blocks ON starknet_transactions.block_hash = starknet_blocks.hash",
+        )
+        .context("Prepare transaction query")?;
+    let mut rows = stmt.query([])?;
+
+    let mut insert = transaction
+        .prepare(
+            r"INSERT INTO starknet_transactions_by_address ( contract_address,  block_number,  idx,  transaction_hash)
+                                                    VALUES (:contract_address, :block_number, :idx, :transaction_hash)",
+        )
+        .context("Prepare index insert statement")?;
+
+    while let Some(r) = rows.next()? {
+        let transaction_hash = r.get_ref_unwrap("hash").as_blob()?;
+        let idx = r.get_ref_unwrap("idx").as_i64()?;
+        let block_number = r.get_ref_unwrap("number").as_i64()?;
+        let tx = r.get_ref_unwrap("tx").as_blob()?;
+
+        let tx = zstd::decode_all(tx).context("Decompress transaction")?;
+        let tx: LightTransaction =
+            serde_json::de::from_slice(&tx).context("Deserializing transaction")?;
+
+        let contract_address = match (tx.r#type, tx.contract_address) {
+            (LightType::Deploy | LightType::InvokeFunction, Some(address)) => address,
+            _ => continue,
+        };
+
+        insert
+            .execute(named_params![
+                ":contract_address": &contract_address.0.as_be_bytes()[..],
+                ":block_number": block_number,
+                ":idx": idx,
+                ":transaction_hash": transaction_hash,
+            ])
+            .context("Insert transaction into index")?;
+    }
+
+    Ok(PostMigrationAction::None)
+}
+
+/// Real transaction json has a bunch of fields which we don't need
+#[derive(serde::Deserialize)]
+struct LightTransaction {
+    r#type: LightType,
+    #[serde(default)]
+    contract_address: Option<crate::core::ContractAddress>,
+}
+
+#[derive(serde::Deserialize)]
+enum LightType {
+    #[serde(rename = "DEPLOY")]
+    Deploy,
+    #[serde(rename = "INVOKE_FUNCTION")]
+    InvokeFunction,
+    #[serde(rename = "DECLARE")]
+    Declare,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::schema::{self, PostMigrationAction};
+    use rusqlite::Connection;
+
+    #[test]
+    fn empty() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        let transaction = conn.transaction().unwrap();
+
+        schema::revision_0001::migrate(&transaction).unwrap();
+        schema::revision_0002::migrate(&transaction).unwrap();
+        schema::revision_0003::migrate(&transaction).unwrap();
+        schema::revision_0004::migrate(&transaction).unwrap();
+        schema::revision_0005::migrate(&transaction).unwrap();
+        schema::revision_0006::migrate(&transaction).unwrap();
+        schema::revision_0007::migrate(&transaction).unwrap();
+        schema::revision_0008::migrate(&transaction).unwrap();
+        schema::revision_0009::migrate(&transaction).unwrap();
+        schema::revision_0010::migrate(&transaction).unwrap();
+        schema::revision_0011::migrate(&transaction).unwrap();
+        schema::revision_0012::migrate(&transaction).unwrap();
+
+        let action = super::migrate(&transaction).unwrap();
+        assert_eq!(action, PostMigrationAction::None);
+    }
+
+    #[test]
+    fn stateful() {
+        use crate::core::{StarknetBlockNumber, StarknetTransactionIndex};
+        use crate::storage::{
+            StarknetAddressTransaction, StarknetTransactionsByAddressFilter,
+            StarknetTransactionsTable,
+        };
+
+        let mut connection = Connection::open_in_memory().unwrap();
+        let transaction = connection.transaction().unwrap();
+
+        schema::revision_0001::migrate(&transaction).unwrap();
+        schema::revision_0002::migrate(&transaction).unwrap();
+        schema::revision_0003::migrate(&transaction).unwrap();
+        schema::revision_0004::migrate(&transaction).unwrap();
+        schema::revision_0005::migrate(&transaction).unwrap();
+        schema::revision_0006::migrate(&transaction).unwrap();
+        schema::revision_0007::migrate(&transaction).unwrap();
+        schema::revision_0008::migrate(&transaction).unwrap();
+        schema::revision_0009::migrate(&transaction).unwrap();
+        schema::revision_0010::migrate(&transaction).unwrap();
+        schema::revision_0011::migrate(&transaction).unwrap();
+        schema::revision_0012::migrate(&transaction).unwrap();
+
+        let emitted_events = schema::fixtures::setup_events(&transaction);
+
+        let action = super::migrate(&transaction).unwrap();
+        assert_eq!(action, PostMigrationAction::None);
+
+        // Every fixture transaction is an invoke of the contract emitting the event.
+        let expected_event = &emitted_events[schema::fixtures::TXNS_PER_BLOCK + 3];
+        let filter = StarknetTransactionsByAddressFilter {
+            contract_address: expected_event.from_address,
+            from_block: None,
+            to_block: None,
+            page_size: schema::fixtures::NUM_TXNS,
+            continuation_token: None,
+        };
+        let page =
+            StarknetTransactionsTable::get_transactions_by_address(&transaction, &filter).unwrap();
+
+        assert_eq!(page.continuation_token, None);
+        assert_eq!(
+            page.transactions,
+            vec![StarknetAddressTransaction {
+                block_hash: expected_event.block_hash,
+                block_number: StarknetBlockNumber(1),
+                transaction_index: StarknetTransactionIndex(3),
+                transaction_hash: expected_event.transaction_hash,
+            }]
+        );
+    }
+}
@@ -0,0 +1,140 @@
+use crate::storage::schema::PostMigrationAction;
+
+use anyhow::Context;
+use rusqlite::Transaction;
+
+/// This migration adds the `contract_storage_history` table, which records every storage
+/// write together with the block it happened in.
+///
+/// Past writes are not available from local data alone, so the history only covers blocks
+/// synced after this migration. The first block covered is recorded in the one row
+/// `contract_storage_history_start` table.
+pub(crate) fn migrate(transaction: &Transaction) -> anyhow::Result<PostMigrationAction> {
+    // The foreign key is deferred because the state diff of a block is applied
+    // before the block itself is inserted.
+    transaction
+        .execute_batch(
+            r"CREATE TABLE contract_storage_history (
+                contract_address BLOB NOT NULL,
+                storage_address BLOB NOT NULL,
+                block_number INTEGER NOT NULL,
+                value BLOB NOT NULL,
+                PRIMARY KEY (contract_address, storage_address, block_number),
+                FOREIGN KEY(block_number) REFERENCES starknet_blocks(number)
+                ON DELETE CASCADE
+                DEFERRABLE INITIALLY DEFERRED
+            ) WITHOUT ROWID;
+
+            CREATE INDEX contract_storage_history_block_number ON contract_storage_history(block_number);
+
+            CREATE TABLE contract_storage_history_start (
+                block_number INTEGER NOT NULL
+            );",
+        )
+        .context("Create contract storage history tables")?;
+
+    let latest: Option<u64> = transaction
+        .query_row("SELECT max(number) FROM starknet_blocks", [], |r| r.get(0))
+        .context("Query latest block number")?;
+
+    let start = latest.map_or(0, |latest| latest + 1);
+    if start > 0 {
+        tracing::info!(
+            "Contract storage history will be recorded from block {} onwards",
+            start
+        );
+    }
+
+    transaction
+        .execute(
+            "INSERT INTO contract_storage_history_start (block_number) VALUES (?)",
+            [start],
+        )
+        .context("Record the start of the contract storage history")?;
+
+    Ok(PostMigrationAction::None)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::schema::{self, PostMigrationAction};
+    use rusqlite::Connection;
+
+    #[test]
+    fn empty() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        let transaction = conn.transaction().unwrap();
+
+        schema::revision_0001::migrate(&transaction).unwrap();
+        schema::revision_0002::migrate(&transaction).unwrap();
+        schema::revision_0003::migrate(&transaction).unwrap();
+        schema::revision_0004::migrate(&transaction).unwrap();
+        schema::revision_0005::migrate(&transaction).unwrap();
+        schema::revision_0006::migrate(&transaction).unwrap();
+        schema::revision_0007::migrate(&transaction).unwrap();
+        schema::revision_0008::migrate(&transaction).unwrap();
+        schema::revision_0009::migrate(&transaction).unwrap();
+        schema::revision_0010::migrate(&transaction).unwrap();
+        schema::revision_0011::migrate(&transaction).unwrap();
+        schema::revision_0012::migrate(&transaction).unwrap();
+        schema::revision_0013::migrate(&transaction).unwrap();
+
+        let action = super::migrate(&transaction).unwrap();
+        assert_eq!(action, PostMigrationAction::None);
+
+        let start: u64 = transaction
+            .query_row(
+                "SELECT block_number FROM contract_storage_history_start",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(start, 0);
+    }
+
+    #[test]
+    fn starts_after_latest_block() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        let transaction = conn.transaction().unwrap();
+
+        schema::revision_0001::migrate(&transaction).unwrap();
+        schema::revision_0002::migrate(&transaction).unwrap();
+        schema::revision_0003::migrate(&transaction).unwrap();
+        schema::revision_0004::migrate(&transaction).unwrap();
+        schema::revision_0005::migrate(&transaction).unwrap();
+        schema::revision_0006::migrate(&transaction).unwrap();
+        schema::revision_0007::migrate(&transaction).unwrap();
+        schema::revision_0008::migrate(&transaction).unwrap();
+        schema::revision_0009::migrate(&transaction).unwrap();
+        schema::revision_0010::migrate(&transaction).unwrap();
+        schema::revision_0011::migrate(&transaction).unwrap();
+        schema::revision_0012::migrate(&transaction).unwrap();
+        schema::revision_0013::migrate(&transaction).unwrap();
+
+        for number in 0..3u64 {
+            transaction
+                .execute(
+                    r"INSERT INTO starknet_blocks ( number,  hash,  root,  timestamp)
+                                           VALUES (:number, :hash, :root, :timestamp)",
+                    rusqlite::named_params! {
+                        ":number": number,
+                        ":hash": &[number as u8][..],
+                        ":root": &[0u8][..],
+                        ":timestamp": number,
+                    },
+                )
+                .unwrap();
+        }
+
+        super::migrate(&transaction).unwrap();
+
+        let start: u64 = transaction
+            .query_row(
+                "SELECT block_number FROM contract_storage_history_start",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(start, 3);
+    }
+}
@@ -0,0 +1,268 @@
+use crate::storage::schema::PostMigrationAction;
+
+use anyhow::Context;
+use rusqlite::{named_params, Transaction};
+
+/// This migration adds the `contract_deployments` table, which records the block (and
+/// transaction) in which each contract was deployed.
+///
+/// Deployments are backfilled from the deploy transactions of canonical blocks. The class hash
+/// is taken from the `contracts` table, since older deploy transactions do not include it.
+pub(crate) fn migrate(transaction: &Transaction) -> anyhow::Result<PostMigrationAction> {
+    transaction
+        .execute(
+            r"CREATE TABLE contract_deployments (
+                address BLOB PRIMARY KEY NOT NULL,
+                class_hash BLOB NOT NULL,
+                block_number INTEGER NOT NULL,
+                transaction_hash BLOB,
+                FOREIGN KEY(block_number) REFERENCES starknet_blocks(number)
+                ON DELETE CASCADE
+            )",
+            [],
+        )
+        .context("Create contract deployments table")?;
+
+    transaction
+        .execute(
+            "CREATE INDEX contract_deployments_class_hash ON contract_deployments(class_hash)",
+            [],
+        )
+        .context("Create class hash index")?;
+
+    transaction
+        .execute(
+            "CREATE INDEX contract_deployments_block_number ON contract_deployments(block_number)",
+            [],
+        )
+        .context("Create block number index")?;
+
+    let todo: usize = transaction
+        .query_row("SELECT count(1) FROM starknet_transactions", [], |r| {
+            r.get(0)
+        })
+        .context("Count rows in starknet transactions table")?;
+
+    if todo == 0 {
+        return Ok(PostMigrationAction::None);
+    }
+
+    tracing::info!(
+        num_transactions=%todo,
+        "Recording contract deployments, this may take a while.",
+    );
+
+    let mut stmt = transaction
+        .prepare(
+            r"SELECT starknet_transactions.hash, starknet_transactions.tx, starknet_blocks.number
+                FROM starknet_transactions
+                JOIN starknet_blocks ON starknet_transactions.block_hash = starknet_blocks.hash
+                ORDER BY starknet_blocks.number, starknet_transactions.idx",
+        )
+        .context("Prepare transaction query")?;
+    let mut rows = stmt.query([])?;
+
+    // The first deployment of an address wins, anything after it would be a failed deploy.
+    let mut insert = transaction
+        .prepare(
+            r"INSERT OR IGNORE INTO contract_deployments (address, class_hash, block_number, transaction_hash)
+                SELECT address, hash, :block_number, :transaction_hash FROM contracts WHERE address = :address",
+        )
+        .context("Prepare deployment insert statement")?;
+
+    while let Some(r) = rows.next()? {
+        let transaction_hash = r.get_ref_unwrap("hash").as_blob()?;
+        let block_number = r.get_ref_unwrap("number").as_i64()?;
+        let tx = r.get_ref_unwrap("tx").as_blob()?;
+
+        let tx = zstd::decode_all(tx).context("Decompress transaction")?;
+        let tx: LightTransaction =
+            serde_json::de::from_slice(&tx).context("Deserializing transaction")?;
+
+        let address = match (tx.r#type, tx.contract_address) {
+            (LightType::Deploy, Some(address)) => address,
+            _ => continue,
+        };
+
+        insert
+            .execute(named_params![
+                ":address": &address.0.as_be_bytes()[..],
+                ":block_number": block_number,
+                ":transaction_hash": transaction_hash,
+            ])
+            .context("Insert contract deployment")?;
+    }
+
+    Ok(PostMigrationAction::None)
+}
+
+/// Real transaction json has a bunch of fields which we don't need
+#[derive(serde::Deserialize)]
+struct LightTransaction {
+    r#type: LightType,
+    #[serde(default)]
+    contract_address: Option<crate::core::ContractAddress>,
+}
+
+#[derive(serde::Deserialize)]
+enum LightType {
+    #[serde(rename = "DEPLOY")]
+    Deploy,
+    #[serde(rename = "INVOKE_FUNCTION")]
+    InvokeFunction,
+    #[serde(rename = "DECLARE")]
+    Declare,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::schema::{self, PostMigrationAction};
+    use rusqlite::Connection;
+
+    #[test]
+    fn empty() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        let transaction = conn.transaction().unwrap();
+
+        schema::revision_0001::migrate(&transaction).unwrap();
+        schema::revision_0002::migrate(&transaction).unwrap();
+        schema::revision_0003::migrate(&transaction).unwrap();
+        schema::revision_0004::migrate(&transaction).unwrap();
+        schema::revision_0005::migrate(&transaction).unwrap();
+        schema::revision_0006::migrate(&transaction).unwrap();
+        schema::revision_0007::migrate(&transaction).unwrap();
+        schema::revision_0008::migrate(&transaction).unwrap();
+        schema::revision_0009::migrate(&transaction).unwrap();
+        schema::revision_0010::migrate(&transaction).unwrap();
+        schema::revision_0011::migrate(&transaction).unwrap();
+        schema::revision_0012::migrate(&transaction).unwrap();
+        schema::revision_0013::migrate(&transaction).unwrap();
+        schema::revision_0014::migrate(&transaction).unwrap();
+
+        let action = super::migrate(&transaction).unwrap();
+        assert_eq!(action, PostMigrationAction::None);
+    }
+
+    #[test]
+    fn stateful() {
+        use crate::core::{
+            ClassHash, ContractAddress, StarknetBlockHash, StarknetBlockNumber,
+            StarknetTransactionHash,
+        };
+        use crate::storage::{ContractDeployment, ContractDeploymentsTable};
+        use stark_hash::StarkHash;
+
+        let mut connection = Connection::open_in_memory().unwrap();
+        let transaction = connection.transaction().unwrap();
+
+        schema::revision_0001::migrate(&transaction).unwrap();
+        schema::revision_0002::migrate(&transaction).unwrap();
+        schema::revision_0003::migrate(&transaction).unwrap();
+        schema::revision_0004::migrate(&transaction).unwrap();
+        schema::revision_0005::migrate(&transaction).unwrap();
+        schema::revision_0006::migrate(&transaction).unwrap();
+        schema::revision_0007::migrate(&transaction).unwrap();
+        schema::revision_0008::migrate(&transaction).unwrap();
+        schema::revision_0009::migrate(&transaction).unwrap();
+        schema::revision_0010::migrate(&transaction).unwrap();
+        schema::revision_0011::migrate(&transaction).unwrap();
+        schema::revision_0012::migrate(&transaction).unwrap();
+        schema::revision_0013::migrate(&transaction).unwrap();
+        schema::revision_0014::migrate(&transaction).unwrap();
+
+        let felt = |s: &[u8]| StarkHash::from_be_slice(s).unwrap();
+        let block_hash = |n: u64| StarknetBlockHash(felt(format!("block {n}").as_bytes()));
+        let deployed = ContractAddress(felt(b"deployed"));
+        let redeployed = ContractAddress(felt(b"redeployed"));
+        let unknown = ContractAddress(felt(b"not in contracts"));
+
+        for n in 0..2u64 {
+            transaction
+                .execute(
+                    r"INSERT INTO starknet_blocks ( number,  hash,  root,  timestamp)
+                                           VALUES (:number, :hash, :root, :timestamp)",
+                    rusqlite::named_params! {
+                        ":number": n,
+                        ":hash": block_hash(n).0.as_be_bytes(),
+                        ":root": StarkHash::ZERO.as_be_bytes(),
+                        ":timestamp": n,
+                    },
+                )
+                .unwrap();
+        }
+
+        // (block, transaction hash, type, target)
+        let transactions = [
+            (0, b"deploy 0", "DEPLOY", deployed),
+            (0, b"deploy 1", "DEPLOY", redeployed),
+            (1, b"deploy 2", "DEPLOY", redeployed),
+            (1, b"invoke 0", "INVOKE_FUNCTION", deployed),
+            (1, b"deploy 3", "DEPLOY", unknown),
+        ];
+        for (idx, (block, hash, r#type, address)) in transactions.iter().enumerate() {
+            let tx = serde_json::json!({ "type": r#type, "contract_address": address });
+            let tx = zstd::encode_all(serde_json::to_vec(&tx).unwrap().as_slice(), 10).unwrap();
+            transaction
+                .execute(
+                    r"INSERT INTO starknet_transactions ( hash,  idx,  block_hash,  tx,  receipt)
+                                                 VALUES (:hash, :idx, :block_hash, :tx, :receipt)",
+                    rusqlite::named_params! {
+                        ":hash": felt(&hash[..]).as_be_bytes(),
+                        ":idx": idx,
+                        ":block_hash": block_hash(*block).0.as_be_bytes(),
+                        ":tx": &tx,
+                        ":receipt": &[] as &[u8],
+                    },
+                )
+                .unwrap();
+        }
+
+        // The backfill takes the class hashes of the already existing contracts.
+        for (address, class_hash) in [(deployed, b"class 0"), (redeployed, b"class 1")] {
+            transaction
+                .execute(
+                    "INSERT INTO contract_code (hash) VALUES (:hash)",
+                    rusqlite::named_params! { ":hash": felt(class_hash).as_be_bytes() },
+                )
+                .unwrap();
+            transaction
+                .execute(
+                    "INSERT INTO contracts (address, hash) VALUES (:address, :hash)",
+                    rusqlite::named_params! {
+                        ":address": address.0.as_be_bytes(),
+                        ":hash": felt(class_hash).as_be_bytes(),
+                    },
+                )
+                .unwrap();
+        }
+
+        let action = super::migrate(&transaction).unwrap();
+        assert_eq!(action, PostMigrationAction::None);
+
+        assert_eq!(
+            ContractDeploymentsTable::get(&transaction, deployed).unwrap(),
+            Some(ContractDeployment {
+                address: deployed,
+                class_hash: ClassHash(felt(b"class 0")),
+                block_hash: block_hash(0),
+                block_number: StarknetBlockNumber(0),
+                transaction_hash: Some(StarknetTransactionHash(felt(b"deploy 0"))),
+            })
+        );
+        // The first deployment wins over the later failed one.
+        assert_eq!(
+            ContractDeploymentsTable::get(&transaction, redeployed).unwrap(),
+            Some(ContractDeployment {
+                address: redeployed,
+                class_hash: ClassHash(felt(b"class 1")),
+                block_hash: block_hash(0),
+                block_number: StarknetBlockNumber(0),
+                transaction_hash: Some(StarknetTransactionHash(felt(b"deploy 1"))),
+            })
+        );
+        assert_eq!(
+            ContractDeploymentsTable::get(&transaction, unknown).unwrap(),
+            None
+        );
+    }
+}
@@ -9,8 +9,12 @@ use crate::{
         EthereumBlockNumber, EthereumLogIndex, EthereumTransactionHash, EthereumTransactionIndex,
         EventData, EventKey, GasPrice, GlobalRoot, SequencerAddress, StarknetBlockHash,
         StarknetBlockNumber, StarknetBlockTimestamp, StarknetTransactionHash,
+        StarknetTransactionIndex, StorageAddress, StorageValue,
+    },
+    ethereum::{
+        log::StateUpdateLog, state_update::ContractUpdate, BlockOrigin, EthOrigin,
+        TransactionOrigin,
     },
-    ethereum::{log::StateUpdateLog, BlockOrigin, EthOrigin, TransactionOrigin},
     sequencer::reply::transaction,
 };
 
@@ -450,6 +454,22 @@ impl StarknetTransactionsTable {
                     ":receipt": &serialized_receipt,
                 ]).context("Insert transaction data into transactions table")?;
 
+            // index the transaction by the contract it targets
+            if let (
+                transaction::Type::Deploy | transaction::Type::InvokeFunction,
+                Some(contract_address),
+            ) = (transaction.r#type, transaction.contract_address)
+            {
+                connection.execute(r"INSERT OR REPLACE INTO starknet_transactions_by_address ( contract_address,  block_number,  idx,  transaction_hash)
+                                                                                      VALUES (:contract_address, :block_number, :idx, :transaction_hash)",
+                    named_params![
+                        ":contract_address": contract_address.0.as_be_bytes(),
+                        ":block_number": block_number.0,
+                        ":idx": i,
+                        ":transaction_hash": transaction.transaction_hash.0.as_be_bytes(),
+                    ]).context("Insert transaction into transactions by address table")?;
+            }
+
             // insert events from receipt
             StarknetEventsTable::insert_events(
                 connection,
@@ -658,6 +678,166 @@ impl StarknetTransactionsTable {
             }
         }
     }
+
+    /// Returns a page of the invoke and deploy transactions which targeted the filter's contract,
+    /// ordered by block number and transaction index.
+    ///
+    /// The page starts at the filter's [continuation token](TransactionsContinuationToken) if one
+    /// was given, and the returned page contains the token for the next page if there is one.
+    pub fn get_transactions_by_address(
+        connection: &Connection,
+        filter: &StarknetTransactionsByAddressFilter,
+    ) -> anyhow::Result<PageOfAddressTransactions> {
+        let mut where_statement_parts: Vec<&'static str> =
+            vec!["contract_address = :contract_address"];
+        let mut params: Vec<(&str, &dyn rusqlite::ToSql)> =
+            vec![(":contract_address", filter.contract_address.0.as_be_bytes())];
+
+        if let Some(from_block) = &filter.from_block {
+            where_statement_parts.push("block_number >= :from_block");
+            params.push((":from_block", &from_block.0));
+        }
+        if let Some(to_block) = &filter.to_block {
+            where_statement_parts.push("block_number <= :to_block");
+            params.push((":to_block", &to_block.0));
+        }
+        if let Some(token) = &filter.continuation_token {
+            where_statement_parts.push("(block_number, idx) >= (:token_block, :token_idx)");
+            params.push((":token_block", &token.block_number.0));
+            params.push((":token_idx", &token.transaction_index.0));
+        }
+
+        let limit = page_limit(filter.page_size)?;
+        params.push((":limit", &limit));
+
+        let query = format!(
+            r"SELECT block_number, starknet_blocks.hash as block_hash, idx, transaction_hash
+                FROM starknet_transactions_by_address
+                INNER JOIN starknet_blocks ON starknet_blocks.number = starknet_transactions_by_address.block_number
+                WHERE {}
+                ORDER BY block_number, idx
+                LIMIT :limit",
+            where_statement_parts.join(" AND ")
+        );
+
+        let mut statement = connection.prepare(&query).context("Preparing SQL query")?;
+        let rows = statement
+            .query(params.as_slice())
+            .context("Executing SQL query")?;
+
+        let (transactions, continuation_token) = read_page(
+            rows,
+            filter.page_size,
+            |row| {
+                let block_number = row.get_ref_unwrap("block_number").as_i64().unwrap() as u64;
+                let block_number = StarknetBlockNumber(block_number);
+
+                let transaction_index = row.get_ref_unwrap("idx").as_i64().unwrap() as u64;
+                let transaction_index = StarknetTransactionIndex(transaction_index);
+
+                TransactionsContinuationToken {
+                    block_number,
+                    transaction_index,
+                }
+            },
+            |row, token| {
+                let block_hash = row.get_ref_unwrap("block_hash").as_blob().unwrap();
+                let block_hash = StarkHash::from_be_slice(block_hash).unwrap();
+                let block_hash = StarknetBlockHash(block_hash);
+
+                let transaction_hash = row.get_ref_unwrap("transaction_hash").as_blob().unwrap();
+                let transaction_hash = StarkHash::from_be_slice(transaction_hash).unwrap();
+                let transaction_hash = StarknetTransactionHash(transaction_hash);
+
+                StarknetAddressTransaction {
+                    block_hash,
+                    block_number: token.block_number,
+                    transaction_index: token.transaction_index,
+                    transaction_hash,
+                }
+            },
+        )?;
+
+        Ok(PageOfAddressTransactions {
+            transactions,
+            continuation_token,
+        })
+    }
+}
+
+pub struct StarknetTransactionsByAddressFilter {
+    pub contract_address: ContractAddress,
+    pub from_block: Option<StarknetBlockNumber>,
+    pub to_block: Option<StarknetBlockNumber>,
+    pub page_size: usize,
+    pub continuation_token: Option<TransactionsContinuationToken>,
+}
+
+/// Identifies the first transaction of a page returned by
+/// [StarknetTransactionsTable::get_transactions_by_address].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TransactionsContinuationToken {
+    pub block_number: StarknetBlockNumber,
+    pub transaction_index: StarknetTransactionIndex,
+}
+
+/// A transaction targeting the contract requested in a [StarknetTransactionsByAddressFilter].
+#[derive(Clone, Debug, PartialEq)]
+pub struct StarknetAddressTransaction {
+    pub block_hash: StarknetBlockHash,
+    pub block_number: StarknetBlockNumber,
+    pub transaction_index: StarknetTransactionIndex,
+    pub transaction_hash: StarknetTransactionHash,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PageOfAddressTransactions {
+    pub transactions: Vec<StarknetAddressTransaction>,
+    pub continuation_token: Option<TransactionsContinuationToken>,
+}
+
+/// Maximum page size of the queries paged by continuation token.
+pub const PAGE_SIZE_LIMIT: usize = 1024;
+
+/// The requested page size is above [PAGE_SIZE_LIMIT].
+#[derive(Copy, Clone, Debug, thiserror::Error, PartialEq)]
+#[error("requested page size is too big, supported maximum is {0}")]
+pub struct PageSizeTooBig(pub usize);
+
+/// Checks the requested page size, returning the `LIMIT` of the query reading the page.
+///
+/// We request one extra row above the requested page size, which becomes the continuation
+/// token of the next page if it exists, see [read_page].
+fn page_limit(page_size: usize) -> anyhow::Result<usize> {
+    if page_size > PAGE_SIZE_LIMIT {
+        return Err(PageSizeTooBig(PAGE_SIZE_LIMIT).into());
+    }
+    if page_size < 1 {
+        anyhow::bail!("Invalid page size");
+    }
+    Ok(page_size + 1)
+}
+
+/// Reads a page of `page_size` items from the rows of a query limited by [page_limit].
+///
+/// `token` reads the continuation token of a row, which is passed on to `item` to read the rest
+/// of it. The token of the extra row past the page is returned as the start of the next page.
+fn read_page<C, T>(
+    mut rows: rusqlite::Rows<'_>,
+    page_size: usize,
+    mut token: impl FnMut(&rusqlite::Row<'_>) -> C,
+    mut item: impl FnMut(&rusqlite::Row<'_>, C) -> T,
+) -> anyhow::Result<(Vec<T>, Option<C>)> {
+    let mut items = Vec::new();
+    while let Some(row) = rows.next().context("Fetching next row")? {
+        let token = token(row);
+        if items.len() == page_size {
+            return Ok((items, Some(token)));
+        }
+        items.push(item(row, token));
+    }
+
+    Ok((items, None))
 }
 
 pub struct StarknetEventFilter {
@@ -978,6 +1158,368 @@ impl ContractsStateTable {
     }
 }
 
+/// Records the value of every contract storage write, by the block it happened in.
+///
+/// History is only available for blocks synced with schema revision 14 or later, see
+/// [ContractStorageHistoryTable::start].
+pub struct ContractStorageHistoryTable {}
+
+impl ContractStorageHistoryTable {
+    /// Returns the first block for which the history has been recorded; writes in earlier blocks
+    /// are missing.
+    pub fn start(connection: &Connection) -> anyhow::Result<StarknetBlockNumber> {
+        connection
+            .query_row(
+                "SELECT block_number FROM contract_storage_history_start",
+                [],
+                |row| row.get(0),
+            )
+            .map(StarknetBlockNumber)
+            .context("Query contract storage history start")
+    }
+
+    /// Records the storage writes of a [ContractUpdate] applied in the given block.
+    ///
+    /// Later writes to the same key in the same block overwrite earlier ones.
+    pub fn insert(
+        connection: &Connection,
+        block_number: StarknetBlockNumber,
+        update: &ContractUpdate,
+    ) -> anyhow::Result<()> {
+        let mut statement = connection
+            .prepare_cached(
+                r"INSERT OR REPLACE INTO contract_storage_history ( contract_address,  storage_address,  block_number,  value)
+                                                           VALUES (:contract_address, :storage_address, :block_number, :value)",
+            )
+            .context("Preparing statement")?;
+
+        for storage_update in &update.storage_updates {
+            statement
+                .execute(named_params! {
+                    ":contract_address": update.address.0.as_be_bytes(),
+                    ":storage_address": storage_update.address.0.as_be_bytes(),
+                    ":block_number": block_number.0,
+                    ":value": storage_update.value.0.as_be_bytes(),
+                })
+                .context("Insert storage write into contract storage history table")?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns a page of the changes made to a storage key, ordered by block number.
+    ///
+    /// The page starts at the filter's continuation token if one was given, and the returned
+    /// page contains the token for the next page if there is one.
+    pub fn get(
+        connection: &Connection,
+        filter: &StorageHistoryFilter,
+    ) -> anyhow::Result<PageOfStorageChanges> {
+        let mut where_statement_parts: Vec<&'static str> = vec![
+            "contract_address = :contract_address",
+            "storage_address = :storage_address",
+        ];
+        let mut params: Vec<(&str, &dyn rusqlite::ToSql)> = vec![
+            (":contract_address", filter.contract_address.0.as_be_bytes()),
+            (":storage_address", filter.storage_address.0.as_be_bytes()),
+        ];
+
+        if let Some(from_block) = &filter.from_block {
+            where_statement_parts.push("block_number >= :from_block");
+            params.push((":from_block", &from_block.0));
+        }
+        if let Some(to_block) = &filter.to_block {
+            where_statement_parts.push("block_number <= :to_block");
+            params.push((":to_block", &to_block.0));
+        }
+        if let Some(token) = &filter.continuation_token {
+            where_statement_parts.push("block_number >= :token_block");
+            params.push((":token_block", &token.0));
+        }
+
+        let limit = page_limit(filter.page_size)?;
+        params.push((":limit", &limit));
+
+        let query = format!(
+            r"SELECT block_number, starknet_blocks.hash as block_hash, value
+                FROM contract_storage_history
+                INNER JOIN starknet_blocks ON starknet_blocks.number = contract_storage_history.block_number
+                WHERE {}
+                ORDER BY block_number
+                LIMIT :limit",
+            where_statement_parts.join(" AND ")
+        );
+
+        let mut statement = connection.prepare(&query).context("Preparing SQL query")?;
+        let rows = statement
+            .query(params.as_slice())
+            .context("Executing SQL query")?;
+
+        let (changes, continuation_token) = read_page(
+            rows,
+            filter.page_size,
+            |row| {
+                let block_number = row.get_ref_unwrap("block_number").as_i64().unwrap() as u64;
+                StarknetBlockNumber(block_number)
+            },
+            |row, block_number| {
+                let block_hash = row.get_ref_unwrap("block_hash").as_blob().unwrap();
+                let block_hash = StarkHash::from_be_slice(block_hash).unwrap();
+                let block_hash = StarknetBlockHash(block_hash);
+
+                let value = row.get_ref_unwrap("value").as_blob().unwrap();
+                let value = StarkHash::from_be_slice(value).unwrap();
+                let value = StorageValue(value);
+
+                StorageChange {
+                    block_hash,
+                    block_number,
+                    value,
+                }
+            },
+        )?;
+
+        Ok(PageOfStorageChanges {
+            changes,
+            continuation_token,
+        })
+    }
+}
+
+pub struct StorageHistoryFilter {
+    pub contract_address: ContractAddress,
+    pub storage_address: StorageAddress,
+    pub from_block: Option<StarknetBlockNumber>,
+    pub to_block: Option<StarknetBlockNumber>,
+    pub page_size: usize,
+    /// The first block of the requested page.
+    pub continuation_token: Option<StarknetBlockNumber>,
+}
+
+/// The value a storage key was set to in a block.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StorageChange {
+    pub block_hash: StarknetBlockHash,
+    pub block_number: StarknetBlockNumber,
+    pub value: StorageValue,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PageOfStorageChanges {
+    pub changes: Vec<StorageChange>,
+    pub continuation_token: Option<StarknetBlockNumber>,
+}
+
+/// Records the block and transaction in which each contract was deployed.
+///
+/// Rows are removed together with their block on reorg.
+pub struct ContractDeploymentsTable {}
+
+impl ContractDeploymentsTable {
+    /// Records the deployment of a contract in the given block.
+    ///
+    /// `transaction_hash` is the deploy transaction, if there was one. Does nothing if the
+    /// contract's deployment has already been recorded.
+    pub fn insert(
+        connection: &Connection,
+        block_number: StarknetBlockNumber,
+        address: ContractAddress,
+        class_hash: ClassHash,
+        transaction_hash: Option<StarknetTransactionHash>,
+    ) -> anyhow::Result<()> {
+        connection
+            .execute(
+                r"INSERT OR IGNORE INTO contract_deployments ( address,  class_hash,  block_number,  transaction_hash)
+                                                      VALUES (:address, :class_hash, :block_number, :transaction_hash)",
+                named_params! {
+                    ":address": address.0.as_be_bytes(),
+                    ":class_hash": class_hash.0.as_be_bytes(),
+                    ":block_number": block_number.0,
+                    ":transaction_hash": transaction_hash.as_ref().map(|h| h.0.as_be_bytes()),
+                },
+            )
+            .context("Insert contract deployment")?;
+
+        Ok(())
+    }
+
+    /// Returns the deployment of a contract, if it has been deployed.
+    pub fn get(
+        connection: &Connection,
+        address: ContractAddress,
+    ) -> anyhow::Result<Option<ContractDeployment>> {
+        let mut statement = connection
+            .prepare(
+                r"SELECT class_hash, block_number, starknet_blocks.hash as block_hash, transaction_hash
+                    FROM contract_deployments
+                    INNER JOIN starknet_blocks ON starknet_blocks.number = contract_deployments.block_number
+                    WHERE address = :address",
+            )
+            .context("Preparing SQL query")?;
+        let mut rows = statement
+            .query(named_params! {
+                ":address": address.0.as_be_bytes(),
+            })
+            .context("Executing SQL query")?;
+
+        let row = match rows.next().context("Iterate rows")? {
+            Some(row) => row,
+            None => return Ok(None),
+        };
+
+        let class_hash = row.get_ref_unwrap("class_hash").as_blob().unwrap();
+        let class_hash = StarkHash::from_be_slice(class_hash).unwrap();
+        let class_hash = ClassHash(class_hash);
+
+        let block_number = row.get_ref_unwrap("block_number").as_i64().unwrap() as u64;
+        let block_number = StarknetBlockNumber(block_number);
+
+        let block_hash = row.get_ref_unwrap("block_hash").as_blob().unwrap();
+        let block_hash = StarkHash::from_be_slice(block_hash).unwrap();
+        let block_hash = StarknetBlockHash(block_hash);
+
+        let transaction_hash = row
+            .get_ref_unwrap("transaction_hash")
+            .as_blob_or_null()
+            .unwrap()
+            .map(|hash| StarknetTransactionHash(StarkHash::from_be_slice(hash).unwrap()));
+
+        Ok(Some(ContractDeployment {
+            address,
+            class_hash,
+            block_hash,
+            block_number,
+            transaction_hash,
+        }))
+    }
+
+    /// Returns the class hash of a contract as of the given block.
+    ///
+    /// Returns `None` if the contract had not been deployed by that block.
+    pub fn get_class_hash_at(
+        connection: &Connection,
+        address: ContractAddress,
+        block_number: StarknetBlockNumber,
+    ) -> anyhow::Result<Option<ClassHash>> {
+        let class_hash: Option<Vec<u8>> = connection
+            .query_row(
+                "SELECT class_hash FROM contract_deployments WHERE address = :address AND block_number <= :block_number",
+                named_params! {
+                    ":address": address.0.as_be_bytes(),
+                    ":block_number": block_number.0,
+                },
+                |row| row.get("class_hash"),
+            )
+            .optional()
+            .context("Query contract deployment")?;
+
+        class_hash
+            .map(|hash| {
+                StarkHash::from_be_slice(&hash)
+                    .map(ClassHash)
+                    .context("Parse class hash")
+            })
+            .transpose()
+    }
+
+    /// Returns a page of the contracts deployed from a class, ordered by address.
+    ///
+    /// The page starts at the filter's continuation token if one was given, and the returned
+    /// page contains the token for the next page if there is one.
+    pub fn get_by_class_hash(
+        connection: &Connection,
+        filter: &ContractsByClassHashFilter,
+    ) -> anyhow::Result<PageOfContractDeployments> {
+        let mut where_statement_parts: Vec<&'static str> = vec!["class_hash = :class_hash"];
+        let mut params: Vec<(&str, &dyn rusqlite::ToSql)> =
+            vec![(":class_hash", filter.class_hash.0.as_be_bytes())];
+
+        if let Some(token) = &filter.continuation_token {
+            where_statement_parts.push("address >= :token_address");
+            params.push((":token_address", token.0.as_be_bytes()));
+        }
+
+        let limit = page_limit(filter.page_size)?;
+        params.push((":limit", &limit));
+
+        let query = format!(
+            r"SELECT address, block_number, starknet_blocks.hash as block_hash, transaction_hash
+                FROM contract_deployments
+                INNER JOIN starknet_blocks ON starknet_blocks.number = contract_deployments.block_number
+                WHERE {}
+                ORDER BY address
+                LIMIT :limit",
+            where_statement_parts.join(" AND ")
+        );
+
+        let mut statement = connection.prepare(&query).context("Preparing SQL query")?;
+        let rows = statement
+            .query(params.as_slice())
+            .context("Executing SQL query")?;
+
+        let (deployments, continuation_token) = read_page(
+            rows,
+            filter.page_size,
+            |row| {
+                let address = row.get_ref_unwrap("address").as_blob().unwrap();
+                let address = StarkHash::from_be_slice(address).unwrap();
+                ContractAddress(address)
+            },
+            |row, address| {
+                let block_number = row.get_ref_unwrap("block_number").as_i64().unwrap() as u64;
+                let block_number = StarknetBlockNumber(block_number);
+
+                let block_hash = row.get_ref_unwrap("block_hash").as_blob().unwrap();
+                let block_hash = StarkHash::from_be_slice(block_hash).unwrap();
+                let block_hash = StarknetBlockHash(block_hash);
+
+                let transaction_hash = row
+                    .get_ref_unwrap("transaction_hash")
+                    .as_blob_or_null()
+                    .unwrap()
+                    .map(|hash| StarknetTransactionHash(StarkHash::from_be_slice(hash).unwrap()));
+
+                ContractDeployment {
+                    address,
+                    class_hash: filter.class_hash,
+                    block_hash,
+                    block_number,
+                    transaction_hash,
+                }
+            },
+        )?;
+
+        Ok(PageOfContractDeployments {
+            deployments,
+            continuation_token,
+        })
+    }
+}
+
+/// The deployment of a contract.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContractDeployment {
+    pub address: ContractAddress,
+    pub class_hash: ClassHash,
+    pub block_hash: StarknetBlockHash,
+    pub block_number: StarknetBlockNumber,
+    /// `None` if the contract was not deployed by a deploy transaction.
+    pub transaction_hash: Option<StarknetTransactionHash>,
+}
+
+pub struct ContractsByClassHashFilter {
+    pub class_hash: ClassHash,
+    pub page_size: usize,
+    /// The first address of the requested page.
+    pub continuation_token: Option<ContractAddress>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PageOfContractDeployments {
+    pub deployments: Vec<ContractDeployment>,
+    pub continuation_token: Option<ContractAddress>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2029,4 +2571,604 @@ mod tests {
             );
         }
     }
+
+    mod paging {
+        use super::*;
+
+        /// Reads a page of the numbers from zero to `count` (exclusive), starting at `start`.
+        fn numbers(
+            connection: &Connection,
+            count: u64,
+            start: u64,
+            page_size: usize,
+        ) -> anyhow::Result<(Vec<u64>, Option<u64>)> {
+            let limit = page_limit(page_size)?;
+            let mut statement = connection
+                .prepare(
+                    r"WITH RECURSIVE numbers(n) AS (SELECT 0 UNION ALL SELECT n + 1 FROM numbers WHERE n + 1 < :count)
+                        SELECT n FROM numbers WHERE n >= :start ORDER BY n LIMIT :limit",
+                )
+                .unwrap();
+            let rows = statement
+                .query(named_params! {":count": count, ":start": start, ":limit": limit})
+                .unwrap();
+
+            read_page(
+                rows,
+                page_size,
+                |row| row.get_ref_unwrap("n").as_i64().unwrap() as u64,
+                |_, n| n,
+            )
+        }
+
+        #[test]
+        fn continuation_token() {
+            let storage = Storage::in_memory().unwrap();
+            let connection = storage.connection().unwrap();
+
+            assert_eq!(
+                numbers(&connection, 5, 0, 2).unwrap(),
+                (vec![0, 1], Some(2))
+            );
+            assert_eq!(
+                numbers(&connection, 5, 2, 2).unwrap(),
+                (vec![2, 3], Some(4))
+            );
+            assert_eq!(numbers(&connection, 5, 4, 2).unwrap(), (vec![4], None));
+            // An exactly full last page has no next page
+            assert_eq!(numbers(&connection, 4, 2, 2).unwrap(), (vec![2, 3], None));
+        }
+
+        #[test]
+        fn invalid_page_size() {
+            let storage = Storage::in_memory().unwrap();
+            let connection = storage.connection().unwrap();
+
+            numbers(&connection, 5, 0, 0).unwrap_err();
+
+            let error = numbers(&connection, 5, 0, PAGE_SIZE_LIMIT + 1).unwrap_err();
+            assert_eq!(
+                error.downcast::<PageSizeTooBig>().unwrap(),
+                PageSizeTooBig(PAGE_SIZE_LIMIT)
+            );
+
+            numbers(&connection, 5, 0, PAGE_SIZE_LIMIT).unwrap();
+        }
+    }
+
+    mod starknet_transactions_by_address {
+        use super::*;
+
+        use crate::sequencer::reply::transaction;
+
+        const NUM_BLOCKS: usize = 3;
+        const TRANSACTIONS_PER_BLOCK: usize = 4;
+        const NUM_TRANSACTIONS: usize = NUM_BLOCKS * TRANSACTIONS_PER_BLOCK;
+
+        fn target() -> ContractAddress {
+            ContractAddress(StarkHash::from_hex_str("0xabcdef").unwrap())
+        }
+
+        /// Inserts blocks in which every other transaction targets [target].
+        ///
+        /// The last transaction of each block is a declare, which never gets indexed.
+        /// Returns the expected index content for [target].
+        fn setup(connection: &Connection) -> Vec<StarknetAddressTransaction> {
+            let blocks = crate::storage::test_utils::create_blocks::<NUM_BLOCKS>();
+            let mut transactions_and_receipts =
+                crate::storage::test_utils::create_transactions_and_receipts::<NUM_TRANSACTIONS>();
+
+            let mut expected = Vec::new();
+            for (i, (txn, receipt)) in transactions_and_receipts.iter_mut().enumerate() {
+                let block = &blocks[i / TRANSACTIONS_PER_BLOCK];
+                let idx = i % TRANSACTIONS_PER_BLOCK;
+
+                if idx == TRANSACTIONS_PER_BLOCK - 1 {
+                    txn.r#type = transaction::Type::Declare;
+                    txn.contract_address = None;
+                    receipt.events = vec![];
+                } else if idx % 2 == 0 {
+                    txn.r#type = match idx {
+                        0 => transaction::Type::Deploy,
+                        _ => transaction::Type::InvokeFunction,
+                    };
+                    txn.contract_address = Some(target());
+                    expected.push(StarknetAddressTransaction {
+                        block_hash: block.hash,
+                        block_number: block.number,
+                        transaction_index: StarknetTransactionIndex(idx as u64),
+                        transaction_hash: txn.transaction_hash,
+                    });
+                }
+            }
+
+            for (i, block) in blocks.iter().enumerate() {
+                StarknetBlocksTable::insert(connection, block).unwrap();
+                StarknetTransactionsTable::upsert(
+                    connection,
+                    block.hash,
+                    block.number,
+                    &transactions_and_receipts
+                        [i * TRANSACTIONS_PER_BLOCK..(i + 1) * TRANSACTIONS_PER_BLOCK],
+                )
+                .unwrap();
+            }
+
+            expected
+        }
+
+        fn filter(
+            page_size: usize,
+            continuation_token: Option<TransactionsContinuationToken>,
+        ) -> StarknetTransactionsByAddressFilter {
+            StarknetTransactionsByAddressFilter {
+                contract_address: target(),
+                from_block: None,
+                to_block: None,
+                page_size,
+                continuation_token,
+            }
+        }
+
+        #[test]
+        fn all() {
+            let storage = Storage::in_memory().unwrap();
+            let connection = storage.connection().unwrap();
+
+            let expected = setup(&connection);
+
+            let page = StarknetTransactionsTable::get_transactions_by_address(
+                &connection,
+                &filter(NUM_TRANSACTIONS, None),
+            )
+            .unwrap();
+            assert_eq!(
+                page,
+                PageOfAddressTransactions {
+                    transactions: expected,
+                    continuation_token: None,
+                }
+            );
+        }
+
+        #[test]
+        fn block_range() {
+            let storage = Storage::in_memory().unwrap();
+            let connection = storage.connection().unwrap();
+
+            let expected = setup(&connection);
+
+            let mut filter = filter(NUM_TRANSACTIONS, None);
+            filter.from_block = Some(StarknetBlockNumber(1));
+            filter.to_block = Some(StarknetBlockNumber(1));
+
+            let page = StarknetTransactionsTable::get_transactions_by_address(&connection, &filter)
+                .unwrap();
+            assert_eq!(
+                page,
+                PageOfAddressTransactions {
+                    transactions: expected[2..4].to_vec(),
+                    continuation_token: None,
+                }
+            );
+        }
+
+        #[test]
+        fn paging() {
+            let storage = Storage::in_memory().unwrap();
+            let connection = storage.connection().unwrap();
+
+            let expected = setup(&connection);
+
+            let mut token = None;
+            let mut transactions = Vec::new();
+            for _ in 0..3 {
+                let page = StarknetTransactionsTable::get_transactions_by_address(
+                    &connection,
+                    &filter(2, token),
+                )
+                .unwrap();
+                transactions.extend(page.transactions);
+                token = page.continuation_token;
+            }
+
+            assert_eq!(token, None);
+            assert_eq!(transactions, expected);
+        }
+
+        #[test]
+        fn reorg() {
+            let storage = Storage::in_memory().unwrap();
+            let connection = storage.connection().unwrap();
+
+            let expected = setup(&connection);
+
+            StarknetBlocksTable::reorg(&connection, StarknetBlockNumber(1)).unwrap();
+
+            let page = StarknetTransactionsTable::get_transactions_by_address(
+                &connection,
+                &filter(NUM_TRANSACTIONS, None),
+            )
+            .unwrap();
+            assert_eq!(page.transactions, expected[..2].to_vec());
+
+            let remaining: usize = connection
+                .query_row(
+                    "SELECT count(1) FROM starknet_transactions_by_address WHERE block_number >= 1",
+                    [],
+                    |r| r.get(0),
+                )
+                .unwrap();
+            assert_eq!(remaining, 0);
+        }
+    }
+
+    mod contract_storage_history {
+        use super::*;
+
+        use crate::ethereum::state_update::StorageUpdate;
+
+        const NUM_BLOCKS: usize = 4;
+
+        fn contract() -> ContractAddress {
+            ContractAddress(StarkHash::from_hex_str("0xabc").unwrap())
+        }
+
+        fn key() -> StorageAddress {
+            StorageAddress(StarkHash::from_hex_str("0x123").unwrap())
+        }
+
+        fn value(i: usize) -> StorageValue {
+            StorageValue(StarkHash::from_hex_str(&"7".repeat(i + 1)).unwrap())
+        }
+
+        /// Writes [key] in every block, along with an unrelated key. As during sync, each
+        /// block's storage writes are recorded before the block itself is inserted.
+        ///
+        /// Returns the expected history of [key].
+        fn setup(connection: &mut Connection) -> Vec<StorageChange> {
+            let blocks = crate::storage::test_utils::create_blocks::<NUM_BLOCKS>();
+            let transaction = connection.transaction().unwrap();
+
+            for (i, block) in blocks.iter().enumerate() {
+                let update = ContractUpdate {
+                    address: contract(),
+                    storage_updates: vec![
+                        StorageUpdate {
+                            address: key(),
+                            value: value(i),
+                        },
+                        StorageUpdate {
+                            address: StorageAddress(StarkHash::from_hex_str("0x456").unwrap()),
+                            value: value(i),
+                        },
+                    ],
+                };
+                ContractStorageHistoryTable::insert(&transaction, block.number, &update).unwrap();
+                StarknetBlocksTable::insert(&transaction, block).unwrap();
+            }
+
+            transaction.commit().unwrap();
+
+            blocks
+                .iter()
+                .enumerate()
+                .map(|(i, block)| StorageChange {
+                    block_hash: block.hash,
+                    block_number: block.number,
+                    value: value(i),
+                })
+                .collect()
+        }
+
+        fn filter(
+            page_size: usize,
+            continuation_token: Option<StarknetBlockNumber>,
+        ) -> StorageHistoryFilter {
+            StorageHistoryFilter {
+                contract_address: contract(),
+                storage_address: key(),
+                from_block: None,
+                to_block: None,
+                page_size,
+                continuation_token,
+            }
+        }
+
+        #[test]
+        fn all() {
+            let storage = Storage::in_memory().unwrap();
+            let mut connection = storage.connection().unwrap();
+
+            let expected = setup(&mut connection);
+
+            let page =
+                ContractStorageHistoryTable::get(&connection, &filter(NUM_BLOCKS, None)).unwrap();
+            assert_eq!(
+                page,
+                PageOfStorageChanges {
+                    changes: expected,
+                    continuation_token: None,
+                }
+            );
+        }
+
+        #[test]
+        fn block_range() {
+            let storage = Storage::in_memory().unwrap();
+            let mut connection = storage.connection().unwrap();
+
+            let expected = setup(&mut connection);
+
+            let mut filter = filter(NUM_BLOCKS, None);
+            filter.from_block = Some(StarknetBlockNumber(1));
+            filter.to_block = Some(StarknetBlockNumber(2));
+
+            let page = ContractStorageHistoryTable::get(&connection, &filter).unwrap();
+            assert_eq!(page.changes, expected[1..3].to_vec());
+        }
+
+        #[test]
+        fn paging() {
+            let storage = Storage::in_memory().unwrap();
+            let mut connection = storage.connection().unwrap();
+
+            let expected = setup(&mut connection);
+
+            let page = ContractStorageHistoryTable::get(&connection, &filter(3, None)).unwrap();
+            assert_eq!(
+                page,
+                PageOfStorageChanges {
+                    changes: expected[..3].to_vec(),
+                    continuation_token: Some(StarknetBlockNumber(3)),
+                }
+            );
+
+            let page =
+                ContractStorageHistoryTable::get(&connection, &filter(3, page.continuation_token))
+                    .unwrap();
+            assert_eq!(
+                page,
+                PageOfStorageChanges {
+                    changes: expected[3..].to_vec(),
+                    continuation_token: None,
+                }
+            );
+        }
+
+        #[test]
+        fn missing_block_is_rejected() {
+            let storage = Storage::in_memory().unwrap();
+            let mut connection = storage.connection().unwrap();
+            let transaction = connection.transaction().unwrap();
+
+            let update = ContractUpdate {
+                address: contract(),
+                storage_updates: vec![StorageUpdate {
+                    address: key(),
+                    value: value(0),
+                }],
+            };
+            ContractStorageHistoryTable::insert(&transaction, StarknetBlockNumber(0), &update)
+                .unwrap();
+
+            transaction.commit().unwrap_err();
+        }
+
+        #[test]
+        fn reorg() {
+            let storage = Storage::in_memory().unwrap();
+            let mut connection = storage.connection().unwrap();
+
+            let expected = setup(&mut connection);
+
+            StarknetBlocksTable::reorg(&connection, StarknetBlockNumber(2)).unwrap();
+
+            let page =
+                ContractStorageHistoryTable::get(&connection, &filter(NUM_BLOCKS, None)).unwrap();
+            assert_eq!(page.changes, expected[..2].to_vec());
+
+            let remaining: usize = connection
+                .query_row(
+                    "SELECT count(1) FROM contract_storage_history WHERE block_number >= 2",
+                    [],
+                    |r| r.get(0),
+                )
+                .unwrap();
+            assert_eq!(remaining, 0);
+        }
+    }
+
+    mod contract_deployments {
+        use super::*;
+
+        const NUM_BLOCKS: usize = 3;
+
+        fn class_hash() -> ClassHash {
+            ClassHash(StarkHash::from_hex_str("0xc1a55").unwrap())
+        }
+
+        fn address(i: usize) -> ContractAddress {
+            ContractAddress(StarkHash::from_hex_str(&"a".repeat(i + 1)).unwrap())
+        }
+
+        /// Deploys one contract of [class_hash] in every block, along with a contract of an
+        /// unrelated class in the first block.
+        ///
+        /// Returns the expected deployments of [class_hash].
+        fn setup(connection: &mut Connection) -> Vec<ContractDeployment> {
+            let blocks = crate::storage::test_utils::create_blocks::<NUM_BLOCKS>();
+            let transaction = connection.transaction().unwrap();
+
+            let deployments = blocks
+                .iter()
+                .enumerate()
+                .map(|(i, block)| ContractDeployment {
+                    address: address(i),
+                    class_hash: class_hash(),
+                    block_hash: block.hash,
+                    block_number: block.number,
+                    transaction_hash: Some(StarknetTransactionHash(
+                        StarkHash::from_hex_str(&"f".repeat(i + 1)).unwrap(),
+                    )),
+                })
+                .collect::<Vec<_>>();
+
+            for (block, deployment) in blocks.iter().zip(&deployments) {
+                StarknetBlocksTable::insert(&transaction, block).unwrap();
+                ContractDeploymentsTable::insert(
+                    &transaction,
+                    deployment.block_number,
+                    deployment.address,
+                    deployment.class_hash,
+                    deployment.transaction_hash,
+                )
+                .unwrap();
+            }
+
+            let unrelated = ClassHash(StarkHash::from_hex_str("0xdead").unwrap());
+            ContractDeploymentsTable::insert(
+                &transaction,
+                StarknetBlockNumber::GENESIS,
+                ContractAddress(StarkHash::from_hex_str("0xbbb").unwrap()),
+                unrelated,
+                None,
+            )
+            .unwrap();
+
+            transaction.commit().unwrap();
+
+            deployments
+        }
+
+        fn filter(
+            page_size: usize,
+            continuation_token: Option<ContractAddress>,
+        ) -> ContractsByClassHashFilter {
+            ContractsByClassHashFilter {
+                class_hash: class_hash(),
+                page_size,
+                continuation_token,
+            }
+        }
+
+        #[test]
+        fn get() {
+            let storage = Storage::in_memory().unwrap();
+            let mut connection = storage.connection().unwrap();
+
+            let expected = setup(&mut connection);
+
+            for deployment in expected {
+                let result = ContractDeploymentsTable::get(&connection, deployment.address)
+                    .unwrap()
+                    .unwrap();
+                assert_eq!(result, deployment);
+            }
+
+            let missing = ContractAddress(StarkHash::from_hex_str("0x123").unwrap());
+            let result = ContractDeploymentsTable::get(&connection, missing).unwrap();
+            assert_eq!(result, None);
+        }
+
+        #[test]
+        fn redeploy_is_ignored() {
+            let storage = Storage::in_memory().unwrap();
+            let mut connection = storage.connection().unwrap();
+
+            let expected = setup(&mut connection);
+
+            ContractDeploymentsTable::insert(
+                &connection,
+                StarknetBlockNumber(2),
+                expected[0].address,
+                ClassHash(StarkHash::from_hex_str("0x999").unwrap()),
+                None,
+            )
+            .unwrap();
+
+            let result = ContractDeploymentsTable::get(&connection, expected[0].address)
+                .unwrap()
+                .unwrap();
+            assert_eq!(result, expected[0]);
+        }
+
+        #[test]
+        fn class_hash_at() {
+            let storage = Storage::in_memory().unwrap();
+            let mut connection = storage.connection().unwrap();
+
+            let expected = setup(&mut connection);
+
+            let before = ContractDeploymentsTable::get_class_hash_at(
+                &connection,
+                expected[1].address,
+                StarknetBlockNumber(0),
+            )
+            .unwrap();
+            assert_eq!(before, None);
+
+            for block in 1..NUM_BLOCKS as u64 {
+                let at = ContractDeploymentsTable::get_class_hash_at(
+                    &connection,
+                    expected[1].address,
+                    StarknetBlockNumber(block),
+                )
+                .unwrap();
+                assert_eq!(at, Some(class_hash()));
+            }
+        }
+
+        #[test]
+        fn by_class_hash() {
+            let storage = Storage::in_memory().unwrap();
+            let mut connection = storage.connection().unwrap();
+
+            let expected = setup(&mut connection);
+
+            let page =
+                ContractDeploymentsTable::get_by_class_hash(&connection, &filter(NUM_BLOCKS, None))
+                    .unwrap();
+            assert_eq!(page.deployments, expected);
+            assert_eq!(page.continuation_token, None);
+        }
+
+        #[test]
+        fn paging() {
+            let storage = Storage::in_memory().unwrap();
+            let mut connection = storage.connection().unwrap();
+
+            let expected = setup(&mut connection);
+
+            let mut continuation_token = None;
+            for deployment in &expected {
+                let page = ContractDeploymentsTable::get_by_class_hash(
+                    &connection,
+                    &filter(1, continuation_token),
+                )
+                .unwrap();
+                assert_eq!(page.deployments, vec![deployment.clone()]);
+                continuation_token = page.continuation_token;
+            }
+            assert_eq!(continuation_token, None);
+        }
+
+        #[test]
+        fn reorg() {
+            let storage = Storage::in_memory().unwrap();
+            let mut connection = storage.connection().unwrap();
+
+            let expected = setup(&mut connection);
+
+            StarknetBlocksTable::reorg(&connection, StarknetBlockNumber(1)).unwrap();
+
+            let page =
+                ContractDeploymentsTable::get_by_class_hash(&connection, &filter(NUM_BLOCKS, None))
+                    .unwrap();
+            assert_eq!(page.deployments, expected[..1].to_vec());
+
+            let result = ContractDeploymentsTable::get(&connection, expected[1].address).unwrap();
+            assert_eq!(result, None);
+        }
+    }
 }
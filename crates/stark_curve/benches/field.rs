@@ -0,0 +1,45 @@
+use ::stark_curve::FieldElement;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use ff::{Field, PrimeField};
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    // The same inputs as used for the Pedersen hash benchmark, in decimal
+    let e0 = "1740729136829561885683894917751815192814966525555656371386868611731128807883";
+    let e1 = "919869093895560023824014392670608914007817594969197822578496829435657368346";
+
+    let e0 = FieldElement::from_str_vartime(e0).unwrap();
+    let e1 = FieldElement::from_str_vartime(e1).unwrap();
+
+    c.bench_function("field_add", |b| {
+        b.iter(|| {
+            black_box(black_box(e0) + black_box(e1));
+        });
+    });
+
+    c.bench_function("field_sub", |b| {
+        b.iter(|| {
+            black_box(black_box(e0) - black_box(e1));
+        });
+    });
+
+    c.bench_function("field_mul", |b| {
+        b.iter(|| {
+            black_box(black_box(e0) * black_box(e1));
+        });
+    });
+
+    c.bench_function("field_square", |b| {
+        b.iter(|| {
+            black_box(black_box(e0).square());
+        });
+    });
+
+    c.bench_function("field_invert", |b| {
+        b.iter(|| {
+            black_box(black_box(e0).invert());
+        });
+    });
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -1,6 +1,7 @@
 use crate::field::{FieldElement, FIELD_ONE, FIELD_THREE, FIELD_TWO};
+use alloc::vec::Vec;
 use bitvec::{order::Lsb0, slice::BitSlice};
-use ff::Field;
+use ff::{BatchInvert, Field};
 
 /// An affine point on an elliptic curve over [FieldElement].
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -24,6 +25,29 @@ impl From<&ProjectivePoint> for AffinePoint {
 }
 
 impl AffinePoint {
+    /// Converts many projective points at once, sharing a single field inversion between all of
+    /// them using Montgomery's trick.
+    pub fn batch_from(points: &[ProjectivePoint]) -> Vec<AffinePoint> {
+        let mut zinvs: Vec<FieldElement> = points.iter().map(|p| p.z).collect();
+        zinvs.iter_mut().batch_invert();
+
+        points
+            .iter()
+            .zip(zinvs)
+            .map(|(p, zinv)| {
+                if p.infinity {
+                    AffinePoint::identity()
+                } else {
+                    AffinePoint {
+                        x: p.x * zinv,
+                        y: p.y * zinv,
+                        infinity: false,
+                    }
+                }
+            })
+            .collect()
+    }
+
     pub const fn new(x: [u64; 4], y: [u64; 4]) -> Self {
         Self {
             x: FieldElement::new(x),
@@ -40,6 +64,22 @@ impl AffinePoint {
         }
     }
 
+    /// Returns one of the two points with the given `x`, or `None` if there are none on the curve.
+    /// The other point is its negation.
+    pub fn from_x(x: FieldElement) -> Option<Self> {
+        // y^2 = x^3 + ax + b with a=1 from stark curve
+        let y_squared = x * x * x + x + CURVE_BETA;
+        Option::from(y_squared.sqrt()).map(|y| AffinePoint {
+            x,
+            y,
+            infinity: false,
+        })
+    }
+
+    pub fn negate(&mut self) {
+        self.y = -self.y;
+    }
+
     pub fn double(&mut self) {
         if self.infinity {
             return;
@@ -250,8 +290,15 @@ impl ProjectivePoint {
     }
 }
 
+/// Montgomery representation of the Stark curve constant beta, `b` in `y^2 = x^3 + ax + b`.
+pub const CURVE_BETA: FieldElement = FieldElement::new([
+    3863487492851900874,
+    7432612994240712710,
+    12360725113329547591,
+    88155977965380735,
+]);
+
 /// Montgomery representation of the Stark curve generator G.
-#[allow(dead_code)]
 pub const CURVE_G: ProjectivePoint = ProjectivePoint {
     x: FieldElement::new([
         14484022957141291997,
@@ -441,6 +488,28 @@ mod tests {
         assert_eq!(ag_triple.x, result.x);
     }
 
+    #[test]
+    fn batch_from_projective() {
+        let mut points = vec![CURVE_G, PEDERSEN_P0, ProjectivePoint::identity()];
+        let mut point = CURVE_G;
+        for _ in 0..10 {
+            point.double();
+            point.add(&PEDERSEN_P1);
+            points.push(point.clone());
+        }
+
+        let result = AffinePoint::batch_from(&points);
+
+        assert_eq!(result.len(), points.len());
+        for (projective, affine) in points.iter().zip(result) {
+            if projective.infinity {
+                assert_eq!(affine, AffinePoint::identity());
+            } else {
+                assert_eq!(affine, AffinePoint::from(projective));
+            }
+        }
+    }
+
     #[test]
     fn const_generator() {
         let expected = projective_from_xy_str(
@@ -450,6 +519,28 @@ mod tests {
         assert_eq!(CURVE_G, expected);
     }
 
+    #[test]
+    fn const_beta() {
+        let expected = FieldElement::from_str_vartime(
+            "3141592653589793238462643383279502884197169399375105820974944592307816406665",
+        )
+        .unwrap();
+        assert_eq!(CURVE_BETA, expected);
+    }
+
+    #[test]
+    fn affine_from_x() {
+        let g = AffinePoint::from(&CURVE_G);
+        let mut found = AffinePoint::from_x(g.x).unwrap();
+        if found != g {
+            found.negate();
+        }
+        assert_eq!(found, g);
+
+        // there is no point with x = 0 on the curve, as beta is not a square
+        assert_eq!(AffinePoint::from_x(FieldElement::zero()), None);
+    }
+
     #[test]
     fn const_p0() {
         let expected = projective_from_xy_str(
@@ -0,0 +1,418 @@
+//! ECDSA over the Stark curve, as implemented in cairo-lang's `starkware.crypto.signature`.
+use crate::curve::{AffinePoint, ProjectivePoint, CURVE_G};
+use crate::field::FieldElement;
+use crate::fixed_base::multiply_generator;
+use crate::rfc6979::generate_k;
+use crate::scalar::Scalar;
+use ff::{Field, PrimeField};
+use sha2::{Digest, Sha256};
+
+/// `2^256` rounded down to a multiple of the curve order, the limit for [grind_key].
+const GRIND_LIMIT: [u8; 32] = [
+    0xf8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x0e, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xf7,
+    0x38, 0xa1, 0x3b, 0x4b, 0x92, 0x0e, 0x94, 0x11, 0xae, 0x6d, 0xa5, 0xf4, 0x0b, 0x03, 0x58, 0xb1,
+];
+
+/// An ECDSA signature; `s` is the one verified by [verify], not its inverse `w`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Signature {
+    pub r: FieldElement,
+    pub s: FieldElement,
+}
+
+/// Error returned by [verify] when the inputs are out of the ranges allowed by cairo-lang.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum VerifyError {
+    /// The message hash is not less than `2^251`.
+    InvalidMessageHash,
+    /// `r` is zero or not less than `2^251`.
+    InvalidR,
+    /// `s` is zero, not less than the curve order, or its inverse is not less than `2^251`.
+    InvalidS,
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for VerifyError {}
+
+impl core::fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidMessageHash => f.write_str("Message hash is out of range"),
+            Self::InvalidR => f.write_str("Signature r value is out of range"),
+            Self::InvalidS => f.write_str("Signature s value is out of range"),
+        }
+    }
+}
+
+/// Error returned by [sign] and [public_key] for inputs which cannot be used.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SignError {
+    /// The message hash is not less than `2^251`.
+    InvalidMessageHash,
+    /// The private key is zero.
+    InvalidPrivateKey,
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SignError {}
+
+impl core::fmt::Display for SignError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidMessageHash => f.write_str("Message hash is out of range"),
+            Self::InvalidPrivateKey => f.write_str("Private key is zero"),
+        }
+    }
+}
+
+/// Returns the stark key of `private_key`, the x-coordinate of its public key point.
+pub fn public_key(private_key: &Scalar) -> Result<FieldElement, SignError> {
+    if private_key.is_zero_vartime() {
+        return Err(SignError::InvalidPrivateKey);
+    }
+    let point = multiply_generator(&private_key.to_field_element().into_bits());
+    Ok(AffinePoint::from(&point).x)
+}
+
+/// Signs `msg_hash` with `private_key` like cairo-lang's `starkware.crypto.signature.sign`.
+///
+/// The nonce is derived deterministically with RFC6979, so signing the same message with the same
+/// key always gives the same signature.
+pub fn sign(private_key: &Scalar, msg_hash: &FieldElement) -> Result<Signature, SignError> {
+    if !fits_251_bits(msg_hash) {
+        return Err(SignError::InvalidMessageHash);
+    }
+    if private_key.is_zero_vartime() {
+        return Err(SignError::InvalidPrivateKey);
+    }
+
+    // there is a negligible chance of a nonce not being usable, in which case the next one is
+    // drawn with an incremented seed.
+    let mut seed = None;
+    loop {
+        let k = generate_k(msg_hash, private_key, seed);
+        seed = Some(seed.map_or(1, |seed| seed + 1));
+
+        if let Some(signature) = sign_with_k(private_key, msg_hash, &k) {
+            return Ok(signature);
+        }
+    }
+}
+
+/// Signs with the given nonce, `None` if the nonce cannot be used.
+fn sign_with_k(private_key: &Scalar, msg_hash: &FieldElement, k: &Scalar) -> Option<Signature> {
+    let r = AffinePoint::from(&multiply_generator(&k.to_field_element().into_bits())).x;
+    // unlike in the classic ECDSA, r is not reduced by the curve order
+    if r.is_zero_vartime() || !fits_251_bits(&r) {
+        return None;
+    }
+
+    // both are less than 2^251 and thus less than the curve order
+    let z = Scalar::from_field_element(msg_hash)?;
+    let r_scalar = Scalar::from_field_element(&r)?;
+
+    let divisor = z + r_scalar * private_key;
+    let w = *k * Option::<Scalar>::from(divisor.invert())?;
+    if w.is_zero_vartime() || !fits_251_bits(&w.to_field_element()) {
+        return None;
+    }
+
+    let s = Option::<Scalar>::from(w.invert())?.to_field_element();
+    Some(Signature { r, s })
+}
+
+/// Derives a private key from the big endian `key_seed` like StarkWare's `grind_key`, which
+/// hashes the seed until the result can be reduced to the curve order without bias.
+pub fn grind_key(key_seed: &[u8]) -> Scalar {
+    // the seed and the index are hashed as their shortest big endian bytes, but at least one
+    fn shortest(bytes: &[u8]) -> &[u8] {
+        let zeros = bytes.iter().take_while(|b| **b == 0).count();
+        match &bytes[zeros..] {
+            [] => &[0],
+            rest => rest,
+        }
+    }
+    let seed = shortest(key_seed);
+
+    let mut index = 0u64;
+    loop {
+        let key: [u8; 32] = Sha256::new()
+            .chain(seed)
+            .chain(shortest(&index.to_be_bytes()))
+            .finalize()
+            .into();
+
+        if key < GRIND_LIMIT {
+            let byte = Scalar::from(256);
+            return key.iter().fold(Scalar::zero(), |acc, b| {
+                acc * byte + Scalar::from(u64::from(*b))
+            });
+        }
+
+        index += 1;
+    }
+}
+
+/// Derives the private key from an Ethereum signature `r || s || v` of the key derivation
+/// message, as StarkEx wallets do.
+pub fn private_key_from_eth_signature(signature: &[u8; 65]) -> Scalar {
+    grind_key(&signature[..32])
+}
+
+/// Verifies the signature `(r, s)` of `msg_hash` by the stark key `public_key`.
+///
+/// The stark key is the x-coordinate of the public key point, both of the points with that x
+/// are accepted. A `public_key` which is not on the curve fails the verification.
+pub fn verify(
+    public_key: &FieldElement,
+    msg_hash: &FieldElement,
+    r: &FieldElement,
+    s: &FieldElement,
+) -> Result<bool, VerifyError> {
+    if !fits_251_bits(msg_hash) {
+        return Err(VerifyError::InvalidMessageHash);
+    }
+    if r.is_zero_vartime() || !fits_251_bits(r) {
+        return Err(VerifyError::InvalidR);
+    }
+    let s = Scalar::from_field_element(s)
+        .filter(|s| !s.is_zero_vartime())
+        .ok_or(VerifyError::InvalidS)?;
+    let w = s.invert().unwrap().to_field_element();
+    if !fits_251_bits(&w) {
+        return Err(VerifyError::InvalidS);
+    }
+
+    let public_key = match AffinePoint::from_x(*public_key) {
+        Some(point) => ProjectivePoint::from(&point),
+        None => return Ok(false),
+    };
+
+    // the signature is valid if x((zG + rQ) * w) == r; zG - rQ covers the other point with
+    // the same x.
+    let z_g = CURVE_G.multiply(&msg_hash.into_bits());
+    let mut r_q = public_key.multiply(&r.into_bits());
+    let w = w.into_bits();
+
+    for _ in 0..2 {
+        let mut sum = z_g.clone();
+        sum.add(&r_q);
+
+        let result = sum.multiply(&w);
+        if !result.infinity && AffinePoint::from(&result).x == *r {
+            return Ok(true);
+        }
+
+        r_q.y = -r_q.y;
+    }
+
+    Ok(false)
+}
+
+/// Returns `true` if the canonical value of `fe` is less than `2^251`.
+fn fits_251_bits(fe: &FieldElement) -> bool {
+    fe.to_repr().0[0] < 0x08
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::FieldElementRepr;
+    use pretty_assertions::assert_eq;
+
+    fn scalar(hex: &str) -> Scalar {
+        Scalar::from_field_element(&felt(hex)).unwrap()
+    }
+
+    fn be_bytes<const N: usize>(hex: &str) -> [u8; N] {
+        let hex = format!("{:0>width$}", hex.trim_start_matches("0x"), width = 2 * N);
+        let mut bytes = [0u8; N];
+        for (i, b) in bytes.iter_mut().enumerate() {
+            *b = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).unwrap();
+        }
+        bytes
+    }
+
+    fn felt(hex: &str) -> FieldElement {
+        FieldElement::from_repr_vartime(FieldElementRepr(be_bytes(hex))).unwrap()
+    }
+
+    // signature of message 2 with private key 1 and k = 3, the public key being the generator
+    const G_X: &str = "0x1ef15c18599971b7beced415a40f0c7deacfd9b0d1819e03d723d8bc943cfca";
+    const R: &str = "0x411494b501a98abd8262b0da1351e17899a0c4ef23dd2f96fec5ba847310b20";
+    const S: &str = "0x405c3191ab3883ef2b763af35bc5f5d15b3b4e99461d70e84c654a351a7c81b";
+
+    #[test]
+    fn valid_signature() {
+        assert_eq!(verify(&felt(G_X), &felt("2"), &felt(R), &felt(S)), Ok(true));
+    }
+
+    #[test]
+    fn valid_signature_with_other_key() {
+        // public key of the private key 0x3c1e9550e66958296d11b60f8e8e7a7ad990d07fa65d5f7652c4a6c87d4e3cc
+        let public_key = felt("0x77a3b314db07c45076d11f62b6f9e748a39790441823307743cf00d6597ea43");
+        let msg_hash = felt("0x397e76d1667c4454bfb83514e120583af836f8e32a516765497823eabe16a3f");
+        let r = felt("0x31b0512cf9537619a65f7edacb3d805ea86d35d7e178586b8499f4e6622064e");
+        let s = felt("0xd21f5bf8a8879d6d10f3ba5e920d415da466f39b73fff11e241677df802770");
+
+        assert_eq!(verify(&public_key, &msg_hash, &r, &s), Ok(true));
+    }
+
+    #[test]
+    fn invalid_signature() {
+        assert_eq!(
+            verify(&felt(G_X), &felt("3"), &felt(R), &felt(S)),
+            Ok(false)
+        );
+        assert_eq!(
+            verify(&felt("2"), &felt("2"), &felt(R), &felt(S)),
+            Ok(false)
+        );
+        assert_eq!(
+            verify(&felt(G_X), &felt("2"), &felt(S), &felt(R)),
+            Ok(false)
+        );
+    }
+
+    #[test]
+    fn public_key_not_on_curve() {
+        assert_eq!(
+            verify(&felt("0"), &felt("2"), &felt(R), &felt(S)),
+            Ok(false)
+        );
+    }
+
+    #[test]
+    fn out_of_range() {
+        let two_pow_251 = felt("0x800000000000000000000000000000000000000000000000000000000000000");
+        let curve_order = felt("0x800000000000010ffffffffffffffffb781126dcae7b2321e66a241adc64d2f");
+
+        assert_eq!(
+            verify(&felt(G_X), &two_pow_251, &felt(R), &felt(S)),
+            Err(VerifyError::InvalidMessageHash)
+        );
+        assert_eq!(
+            verify(&felt(G_X), &felt("2"), &felt("0"), &felt(S)),
+            Err(VerifyError::InvalidR)
+        );
+        assert_eq!(
+            verify(&felt(G_X), &felt("2"), &two_pow_251, &felt(S)),
+            Err(VerifyError::InvalidR)
+        );
+        assert_eq!(
+            verify(&felt(G_X), &felt("2"), &felt(R), &felt("0")),
+            Err(VerifyError::InvalidS)
+        );
+        assert_eq!(
+            verify(&felt(G_X), &felt("2"), &felt(R), &curve_order),
+            Err(VerifyError::InvalidS)
+        );
+    }
+
+    #[test]
+    fn sign_with_given_k() {
+        let signature = sign_with_k(&scalar("1"), &felt("2"), &scalar("3")).unwrap();
+        assert_eq!(
+            signature,
+            Signature {
+                r: felt(R),
+                s: felt(S)
+            }
+        );
+    }
+
+    #[test]
+    fn sign_like_cairo_lang() {
+        let private_key =
+            scalar("0x3c1e9550e66958296d11b60f8e8e7a7ad990d07fa65d5f7652c4a6c87d4e3cc");
+        let msg_hash = felt("0x397e76d1667c4454bfb83514e120583af836f8e32a516765497823eabe16a3f");
+
+        let signature = sign(&private_key, &msg_hash).unwrap();
+
+        // from cairo-lang's signature test data, which records w = s^-1 instead of s
+        let r = felt("0x173fd03d8b008ee7432977ac27d1e9d1a1f6c98b1a2f05fa84a21c84c44e882");
+        let w = scalar("0x1f2c44a7798f55192f153b4c48ea5c1241fbb69e6132cc8a0da9c5b62a4286e");
+        let s = w.invert().unwrap().to_field_element();
+
+        assert_eq!(signature, Signature { r, s });
+        let public_key = public_key(&private_key).unwrap();
+        assert_eq!(verify(&public_key, &msg_hash, &r, &s), Ok(true));
+    }
+
+    #[test]
+    fn sign_and_verify() {
+        let private_key =
+            scalar("0x766f11e90cd7c7b43085b56da35c781f8c067ac0d578eabdceebc4886435bda");
+        let public_key = public_key(&private_key).unwrap();
+
+        for msg_hash in [
+            "0",
+            "1",
+            "0x10ab",
+            "0x7ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+        ] {
+            let msg_hash = felt(msg_hash);
+            let signature = sign(&private_key, &msg_hash).unwrap();
+            assert_eq!(
+                verify(&public_key, &msg_hash, &signature.r, &signature.s),
+                Ok(true),
+                "{msg_hash:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn sign_rejects_invalid_input() {
+        let two_pow_251 = felt("0x800000000000000000000000000000000000000000000000000000000000000");
+        assert_eq!(
+            sign(&scalar("1"), &two_pow_251),
+            Err(SignError::InvalidMessageHash)
+        );
+        assert_eq!(
+            sign(&Scalar::zero(), &felt("2")),
+            Err(SignError::InvalidPrivateKey)
+        );
+        assert_eq!(
+            public_key(&Scalar::zero()),
+            Err(SignError::InvalidPrivateKey)
+        );
+    }
+
+    #[test]
+    fn public_key_derivation() {
+        let private_key =
+            scalar("0x3c1e9550e66958296d11b60f8e8e7a7ad990d07fa65d5f7652c4a6c87d4e3cc");
+        assert_eq!(
+            public_key(&private_key),
+            Ok(felt(
+                "0x77a3b314db07c45076d11f62b6f9e748a39790441823307743cf00d6597ea43"
+            ))
+        );
+        assert_eq!(public_key(&scalar("1")), Ok(felt(G_X)));
+    }
+
+    #[test]
+    fn grinding() {
+        let seed: [u8; 32] =
+            be_bytes("0x86F3E7293141F20A8BAFF320E8EE4ACCB9D4A4BF2B4D295E8CEE784DB46E0519");
+        assert_eq!(
+            grind_key(&seed),
+            scalar("0x5c8c8683596c732541a59e03007b2d30dbbbb873556fe65b5fb63c16688f941")
+        );
+        // leading zeros are not hashed
+        assert_eq!(grind_key(&[0x00, 0xab]), grind_key(&[0xab]));
+    }
+
+    #[test]
+    fn eth_signature_key() {
+        let signature = be_bytes(
+            "0x21fbf0696d5e0aa2ef41a2b4ffb623bcaf070461d61cf7251c74161f82fec3a4\
+            370854bc0a34b3ab487c1bc021cd318c734c51ae29374f2beb0e6f2dd49b4bf41c",
+        );
+
+        assert_eq!(
+            private_key_from_eth_signature(&signature),
+            scalar("0x766f11e90cd7c7b43085b56da35c781f8c067ac0d578eabdceebc4886435bda")
+        );
+    }
+}
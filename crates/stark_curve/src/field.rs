@@ -1,19 +1,48 @@
-// FIXME: needed because of mont_reduce generated by PrimeField derive
-// https://github.com/zkcrypto/ff/pull/83
-#![allow(clippy::too_many_arguments)]
-
 use bitvec::{array::BitArray, order::Lsb0};
-use ff::PrimeField;
+use core::cmp::Ordering;
+use core::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use ff::{Field, PrimeField};
+use rand_core::RngCore;
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq, CtOption};
 
 /// The field primitive used by Starkware's curve.
 ///
 /// It's main use is to allow `pedersen_hash`.
-#[derive(PrimeField)]
-#[PrimeFieldModulus = "3618502788666131213697322783095070105623107215331596699973092056135872020481"]
-#[PrimeFieldGenerator = "7"]
-#[PrimeFieldReprEndianness = "big"]
+///
+/// Elements are kept in Montgomery form with `R = 2^256`, the same representation `ff`'s
+/// `PrimeField` derive uses, but the arithmetic is specialised for the modulus
+/// `p = 2^251 + 17 * 2^192 + 1`: as `p = 1 mod 2^64`, the Montgomery reduction needs no
+/// multiplication to find the quotient digits, and only two limbs of `p` are non-zero.
+#[derive(Clone, Copy)]
 pub struct FieldElement([u64; 4]);
 
+/// Big-endian bytes of the canonical value of a [FieldElement].
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub struct FieldElementRepr(pub [u8; 32]);
+
+/// The modulus in little-endian limbs.
+const MODULUS: [u64; 4] = [1, 0, 0, MODULUS_TOP];
+/// The only non-trivial limb of the modulus.
+const MODULUS_TOP: u64 = 0x0800000000000011;
+/// `R^2 mod p`, used to convert into Montgomery form.
+const R2: FieldElement = FieldElement([
+    18446741271209837569,
+    5151653887,
+    18446744073700081664,
+    576413109808302096,
+]);
+/// `p - 1 = 2^S * T` with odd `T`.
+const S: u32 = 192;
+/// `(T - 1) / 2`, used by the square root.
+const T_MINUS_ONE_DIV_TWO: [u64; 1] = [288230376151711752];
+/// The generator 3 raised to the power `T`, a `2^S` root of unity.
+const ROOT_OF_UNITY: FieldElement = FieldElement([
+    4685640052668284376,
+    12298664652803292137,
+    735711535595279732,
+    514024103053294630,
+]);
+
 impl FieldElement {
     /// Construct a field element constant from montgomery representation
     pub const fn new(v: [u64; 4]) -> Self {
@@ -25,27 +54,460 @@ impl FieldElement {
     }
 
     /// Transforms [FieldElement] into little endian bit representation.
-    pub fn into_bits(mut self) -> BitArray<Lsb0, [u64; 4]> {
-        #[cfg(not(target_endian = "little"))]
-        {
-            todo!("untested and probably unimplemented: big-endian targets")
-        }
-
-        #[cfg(target_endian = "little")]
-        {
-            self.mont_reduce(
-                self.0[0usize],
-                self.0[1usize],
-                self.0[2usize],
-                self.0[3usize],
-                0,
-                0,
-                0,
-                0,
-            );
+    pub fn into_bits(self) -> BitArray<Lsb0, [u64; 4]> {
+        self.canonical().into()
+    }
+
+    /// The canonical value in little-endian limbs, taking it out of Montgomery form.
+    fn canonical(&self) -> [u64; 4] {
+        let [a0, a1, a2, a3] = self.0;
+        Self::montgomery_reduce([a0, a1, a2, a3, 0, 0, 0, 0]).0
+    }
+
+    /// Computes `t / R mod p` for `t < p * R`.
+    #[inline(always)]
+    fn montgomery_reduce(mut t: [u64; 8]) -> Self {
+        // Carry out of the top limb of the previous round
+        let mut carry2 = 0;
+        for i in 0..4 {
+            // -p^-1 mod 2^64 is -1, so the quotient digit is simply the negated limb
+            let m = t[i].wrapping_neg();
+
+            // Add m * p * 2^(64i), of which m * 1 clears limb i ...
+            let mut carry = (t[i] != 0) as u64;
+            carry = adc(&mut t[i + 1], 0, carry);
+            carry = adc(&mut t[i + 2], 0, carry);
+            // ... and m * MODULUS_TOP lands three limbs higher
+            carry = mac(&mut t[i + 3], m, MODULUS_TOP, carry);
+            carry2 = adc(&mut t[i + 4], carry2, carry);
+        }
+
+        let mut result = Self([t[4], t[5], t[6], t[7]]);
+        result.reduce_once();
+        result
+    }
+
+    /// Subtracts the modulus once if the limbs are not less than it.
+    #[inline(always)]
+    fn reduce_once(&mut self) {
+        // The modulus is MODULUS_TOP * 2^192 + 1, so the top limb almost always decides
+        let [a0, a1, a2, a3] = self.0;
+        if a3 > MODULUS_TOP || (a3 == MODULUS_TOP && (a0 | a1 | a2) != 0) {
+            self.0 = sub_limbs(&self.0, &MODULUS).0;
+        }
+    }
+
+    /// Multiplies the limbs without any reduction.
+    #[inline(always)]
+    fn mul_wide(&self, other: &Self) -> [u64; 8] {
+        let mut t = [0u64; 8];
+        for i in 0..4 {
+            let mut carry = 0;
+            for j in 0..4 {
+                carry = mac(&mut t[i + j], self.0[i], other.0[j], carry);
+            }
+            t[i + 4] = carry;
+        }
+        t
+    }
+
+    /// Squares the limbs without any reduction, computing the cross products only once.
+    #[inline(always)]
+    fn square_wide(&self) -> [u64; 8] {
+        let a = &self.0;
+        let mut t = [0u64; 8];
+        for i in 0..3 {
+            let mut carry = 0;
+            for j in i + 1..4 {
+                carry = mac(&mut t[i + j], a[i], a[j], carry);
+            }
+            t[i + 4] = carry;
+        }
+
+        // Double the cross products, the top bit is free as the values are less than 2^252
+        for i in (1..8).rev() {
+            t[i] = (t[i] << 1) | (t[i - 1] >> 63);
+        }
+        t[0] <<= 1;
 
-            self.0.into()
+        let mut carry = 0;
+        for i in 0..4 {
+            let high = mac(&mut t[2 * i], a[i], a[i], carry);
+            carry = adc(&mut t[2 * i + 1], high, 0);
         }
+        t
+    }
+}
+
+/// Sets `a` to `a + b + carry`, returning the new carry.
+#[inline(always)]
+fn adc(a: &mut u64, b: u64, carry: u64) -> u64 {
+    let ret = (*a as u128) + (b as u128) + (carry as u128);
+    *a = ret as u64;
+    (ret >> 64) as u64
+}
+
+/// Sets `a` to `a - b - borrow`, returning the new borrow.
+#[inline(always)]
+fn sbb(a: &mut u64, b: u64, borrow: u64) -> u64 {
+    let ret = (*a as u128).wrapping_sub((b as u128) + (borrow as u128));
+    *a = ret as u64;
+    (ret >> 127) as u64
+}
+
+/// Sets `a` to `a + b * c + carry`, returning the new carry.
+#[inline(always)]
+fn mac(a: &mut u64, b: u64, c: u64, carry: u64) -> u64 {
+    let ret = (*a as u128) + ((b as u128) * (c as u128)) + (carry as u128);
+    *a = ret as u64;
+    (ret >> 64) as u64
+}
+
+#[inline(always)]
+fn add_limbs(a: &[u64; 4], b: &[u64; 4]) -> [u64; 4] {
+    let mut result = *a;
+    let mut carry = 0;
+    for (limb, b) in result.iter_mut().zip(b) {
+        carry = adc(limb, *b, carry);
+    }
+    result
+}
+
+#[inline(always)]
+fn sub_limbs(a: &[u64; 4], b: &[u64; 4]) -> ([u64; 4], u64) {
+    let mut result = *a;
+    let mut borrow = 0;
+    for (limb, b) in result.iter_mut().zip(b) {
+        borrow = sbb(limb, *b, borrow);
+    }
+    (result, borrow)
+}
+
+fn cmp_limbs(a: &[u64; 4], b: &[u64; 4]) -> Ordering {
+    a.iter().rev().cmp(b.iter().rev())
+}
+
+impl Default for FieldElement {
+    fn default() -> Self {
+        Self::zero()
+    }
+}
+
+impl ConstantTimeEq for FieldElement {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        // The Montgomery form is unique, so the limbs can be compared directly
+        self.0.ct_eq(&other.0)
+    }
+}
+
+impl PartialEq for FieldElement {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl Eq for FieldElement {}
+
+impl core::fmt::Debug for FieldElement {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "FieldElement({:?})", self.to_repr())
+    }
+}
+
+/// Elements are ordered by their canonical values.
+impl Ord for FieldElement {
+    fn cmp(&self, other: &Self) -> Ordering {
+        cmp_limbs(&self.canonical(), &other.canonical())
+    }
+}
+
+impl PartialOrd for FieldElement {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl From<u64> for FieldElement {
+    fn from(value: u64) -> Self {
+        Self([value, 0, 0, 0]) * R2
+    }
+}
+
+impl From<FieldElement> for FieldElementRepr {
+    fn from(fe: FieldElement) -> Self {
+        fe.to_repr()
+    }
+}
+
+impl<'a> From<&'a FieldElement> for FieldElementRepr {
+    fn from(fe: &'a FieldElement) -> Self {
+        fe.to_repr()
+    }
+}
+
+impl ConditionallySelectable for FieldElement {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        let mut limbs = [0u64; 4];
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            *limb = u64::conditional_select(&a.0[i], &b.0[i], choice);
+        }
+        Self(limbs)
+    }
+}
+
+impl Neg for FieldElement {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        if self.is_zero_vartime() {
+            self
+        } else {
+            Self(sub_limbs(&MODULUS, &self.0).0)
+        }
+    }
+}
+
+impl AddAssign<&FieldElement> for FieldElement {
+    #[inline]
+    fn add_assign(&mut self, other: &Self) {
+        // Both are less than 2^252, so this cannot overflow
+        self.0 = add_limbs(&self.0, &other.0);
+        self.reduce_once();
+    }
+}
+
+impl SubAssign<&FieldElement> for FieldElement {
+    #[inline]
+    fn sub_assign(&mut self, other: &Self) {
+        let (result, borrow) = sub_limbs(&self.0, &other.0);
+        self.0 = if borrow == 0 {
+            result
+        } else {
+            add_limbs(&result, &MODULUS)
+        };
+    }
+}
+
+impl MulAssign<&FieldElement> for FieldElement {
+    #[inline]
+    fn mul_assign(&mut self, other: &Self) {
+        *self = Self::montgomery_reduce(self.mul_wide(other));
+    }
+}
+
+/// Implements the by-value and the remaining by-reference variants of a binary operator in
+/// terms of its `*Assign<&FieldElement>` implementation.
+macro_rules! impl_binary_op {
+    ($op:ident, $op_fn:ident, $assign:ident, $assign_fn:ident) => {
+        impl $assign for FieldElement {
+            #[inline]
+            fn $assign_fn(&mut self, other: Self) {
+                self.$assign_fn(&other);
+            }
+        }
+
+        impl $op<&FieldElement> for FieldElement {
+            type Output = Self;
+
+            #[inline]
+            fn $op_fn(mut self, other: &Self) -> Self {
+                self.$assign_fn(other);
+                self
+            }
+        }
+
+        impl $op for FieldElement {
+            type Output = Self;
+
+            #[inline]
+            fn $op_fn(mut self, other: Self) -> Self {
+                self.$assign_fn(&other);
+                self
+            }
+        }
+    };
+}
+
+impl_binary_op!(Add, add, AddAssign, add_assign);
+impl_binary_op!(Sub, sub, SubAssign, sub_assign);
+impl_binary_op!(Mul, mul, MulAssign, mul_assign);
+
+impl Field for FieldElement {
+    /// Computes a uniformly random element using rejection sampling.
+    fn random(mut rng: impl RngCore) -> Self {
+        loop {
+            let mut limbs = [0u64; 4];
+            for limb in &mut limbs {
+                *limb = rng.next_u64();
+            }
+            // Mask away the bits above the 252 bits of the modulus
+            limbs[3] &= u64::MAX >> 4;
+
+            if cmp_limbs(&limbs, &MODULUS) == Ordering::Less {
+                return Self(limbs);
+            }
+        }
+    }
+
+    #[inline]
+    fn zero() -> Self {
+        Self([0; 4])
+    }
+
+    #[inline]
+    fn one() -> Self {
+        FIELD_ONE
+    }
+
+    #[inline]
+    fn is_zero_vartime(&self) -> bool {
+        self.0 == [0; 4]
+    }
+
+    #[inline]
+    fn square(&self) -> Self {
+        Self::montgomery_reduce(self.square_wide())
+    }
+
+    #[inline]
+    fn double(&self) -> Self {
+        *self + self
+    }
+
+    fn invert(&self) -> CtOption<Self> {
+        // Raises to the power p - 2 = 2^251 + 2^196 + (2^192 - 1), building the long run of ones
+        // from blocks of 16 to need fewer multiplications than plain square-and-multiply.
+        let square_n = |mut x: Self, n: usize| {
+            for _ in 0..n {
+                x = x.square();
+            }
+            x
+        };
+
+        // x^(2^k - 1) for k = 2, 4, 8 and 16
+        let x2 = square_n(*self, 1) * self;
+        let x4 = square_n(x2, 2) * x2;
+        let x8 = square_n(x4, 4) * x4;
+        let x16 = square_n(x8, 8) * x8;
+
+        // The two single bits 251 and 196, followed by four zeros ...
+        let mut inverse = square_n(*self, 55) * self;
+        inverse = square_n(inverse, 4);
+        // ... and 192 ones
+        for _ in 0..12 {
+            inverse = square_n(inverse, 16) * x16;
+        }
+
+        CtOption::new(inverse, !self.is_zero())
+    }
+
+    /// Tonelli-Shanks, which is variable time.
+    fn sqrt(&self) -> CtOption<Self> {
+        if self.is_zero_vartime() {
+            return CtOption::new(Self::zero(), Choice::from(1));
+        }
+
+        let w = self.pow_vartime(T_MINUS_ONE_DIV_TWO);
+        let mut v = S;
+        let mut x = *self * w;
+        let mut b = x * w;
+        let mut z = ROOT_OF_UNITY;
+
+        while b != Self::one() {
+            let mut k = 0;
+            let mut b2k = b;
+            while b2k != Self::one() {
+                b2k = b2k.square();
+                k += 1;
+            }
+            if k == v {
+                // Not a quadratic residue
+                return CtOption::new(Self::zero(), Choice::from(0));
+            }
+
+            let mut w = z;
+            for _ in 0..v - k - 1 {
+                w = w.square();
+            }
+            z = w.square();
+            b *= z;
+            x *= w;
+            v = k;
+        }
+
+        CtOption::new(x, Choice::from(1))
+    }
+}
+
+impl PrimeField for FieldElement {
+    type Repr = FieldElementRepr;
+
+    fn from_repr(repr: FieldElementRepr) -> CtOption<Self> {
+        match Self::from_repr_vartime(repr) {
+            Some(fe) => CtOption::new(fe, Choice::from(1)),
+            None => CtOption::new(Self::zero(), Choice::from(0)),
+        }
+    }
+
+    fn from_repr_vartime(repr: FieldElementRepr) -> Option<Self> {
+        let mut limbs = [0u64; 4];
+        for (limb, chunk) in limbs.iter_mut().zip(repr.0.rchunks_exact(8)) {
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(chunk);
+            *limb = u64::from_be_bytes(bytes);
+        }
+
+        if cmp_limbs(&limbs, &MODULUS) == Ordering::Less {
+            Some(Self(limbs) * R2)
+        } else {
+            None
+        }
+    }
+
+    fn to_repr(&self) -> FieldElementRepr {
+        let mut repr = FieldElementRepr::default();
+        for (chunk, limb) in repr.0.rchunks_exact_mut(8).zip(self.canonical()) {
+            chunk.copy_from_slice(&limb.to_be_bytes());
+        }
+        repr
+    }
+
+    fn is_odd(&self) -> Choice {
+        Choice::from((self.canonical()[0] & 1) as u8)
+    }
+
+    const NUM_BITS: u32 = 252;
+
+    const CAPACITY: u32 = Self::NUM_BITS - 1;
+
+    fn multiplicative_generator() -> Self {
+        FIELD_THREE
+    }
+
+    const S: u32 = S;
+
+    fn root_of_unity() -> Self {
+        ROOT_OF_UNITY
+    }
+}
+
+impl AsRef<[u8]> for FieldElementRepr {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl AsMut<[u8]> for FieldElementRepr {
+    fn as_mut(&mut self) -> &mut [u8] {
+        &mut self.0
+    }
+}
+
+impl core::fmt::Debug for FieldElementRepr {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "0x")?;
+        self.0.iter().try_for_each(|b| write!(f, "{:02x}", b))
     }
 }
 
@@ -105,6 +567,156 @@ mod tests {
         assert_eq!(two, expected);
     }
 
+    #[test]
+    fn sqrt() {
+        // y^2 of the curve generator
+        let square = FieldElement::from_str_vartime(
+            "152666792071518830868575557812948353041420400780739481342941381225525861407",
+        )
+        .unwrap()
+        .square();
+        let root = square.sqrt().unwrap();
+        assert_eq!(root.square(), square);
+
+        let non_residue = FieldElement::from(3);
+        assert!(bool::from(non_residue.sqrt().is_none()));
+    }
+
+    /// The `ff` derived implementation this one replaced, to check the results stay identical.
+    mod derived {
+        // FIXME: needed because of mont_reduce generated by PrimeField derive
+        // https://github.com/zkcrypto/ff/pull/83
+        #![allow(clippy::too_many_arguments)]
+
+        #[derive(ff::PrimeField)]
+        #[PrimeFieldModulus = "3618502788666131213697322783095070105623107215331596699973092056135872020481"]
+        #[PrimeFieldGenerator = "3"]
+        #[PrimeFieldReprEndianness = "big"]
+        pub struct Derived([u64; 4]);
+
+        /// The Montgomery form of a [Derived].
+        pub fn limbs(derived: &Derived) -> [u64; 4] {
+            derived.0
+        }
+    }
+
+    use derived::{limbs, Derived, DerivedRepr};
+
+    /// Values covering the edges of the field followed by a pseudo-random sequence.
+    fn test_values() -> Vec<Derived> {
+        let mut values = vec![
+            Derived::zero(),
+            Derived::one(),
+            Derived::from(2),
+            -Derived::one(),
+            -Derived::from(2),
+            Derived::from(u64::MAX),
+            Derived::from_str_vartime(
+                "3618502788666131213697322783095070105623107215331596699973092056135872020479",
+            )
+            .unwrap(),
+        ];
+
+        let mut x = Derived::from(0x1234_5678_9abc_def0);
+        for _ in 0..200 {
+            x = x.square() + Derived::from(17);
+            values.push(x);
+        }
+        values
+    }
+
+    fn convert(derived: &Derived) -> FieldElement {
+        FieldElement::new(limbs(derived))
+    }
+
+    #[test]
+    fn same_as_derived() {
+        let values = test_values();
+        for (a, b) in values.iter().zip(values.iter().rev()) {
+            let (x, y) = (convert(a), convert(b));
+
+            assert_eq!((x + y).inner(), limbs(&(*a + b)), "{x:?} + {y:?}");
+            assert_eq!((x - y).inner(), limbs(&(*a - b)), "{x:?} - {y:?}");
+            assert_eq!((x * y).inner(), limbs(&(*a * b)), "{x:?} * {y:?}");
+            assert_eq!(x.square().inner(), limbs(&a.square()), "{x:?}^2");
+            assert_eq!(x.double().inner(), limbs(&a.double()), "2 * {x:?}");
+            assert_eq!((-x).inner(), limbs(&-*a), "-{x:?}");
+            assert_eq!(x.cmp(&y), a.cmp(b), "{x:?} cmp {y:?}");
+            assert_eq!(x.to_repr().0, a.to_repr().0, "{x:?}");
+            assert_eq!(bool::from(x.is_odd()), bool::from(a.is_odd()), "{x:?}");
+
+            let inverse: Option<FieldElement> = x.invert().into();
+            let expected: Option<Derived> = a.invert().into();
+            assert_eq!(
+                inverse.map(|i| i.inner()),
+                expected.map(|e| limbs(&e)),
+                "1 / {x:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn sqrt_same_as_derived() {
+        // Tonelli-Shanks is slow with S = 192, so only check some of the values
+        for value in test_values().iter().take(20) {
+            let x = convert(value);
+
+            let root: Option<FieldElement> = x.sqrt().into();
+            let expected: Option<Derived> = value.sqrt().into();
+            assert_eq!(root.is_some(), expected.is_some(), "sqrt {x:?}");
+            if let Some(root) = root {
+                assert_eq!(root.square(), x, "sqrt {x:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn repr_same_as_derived() {
+        for value in test_values() {
+            let repr = value.to_repr().0;
+            let fe = FieldElement::from_repr_vartime(FieldElementRepr(repr)).unwrap();
+            assert_eq!(fe.inner(), limbs(&value));
+
+            let mut canonical = [0u64; 4];
+            for (limb, chunk) in canonical.iter_mut().zip(repr.rchunks_exact(8)) {
+                *limb = u64::from_be_bytes(chunk.try_into().unwrap());
+            }
+            assert_eq!(
+                fe.into_bits(),
+                BitArray::<Lsb0, [u64; 4]>::from(canonical),
+                "{fe:?}"
+            );
+        }
+
+        let mut modulus = [0u8; 32];
+        modulus[0] = 0x08;
+        modulus[7] = 0x11;
+        modulus[31] = 0x01;
+        assert_eq!(
+            FieldElement::from_repr_vartime(FieldElementRepr(modulus)),
+            None
+        );
+        assert!(bool::from(
+            FieldElement::from_repr(FieldElementRepr(modulus)).is_none()
+        ));
+        assert!(Derived::from_repr_vartime(DerivedRepr(modulus)).is_none());
+    }
+
+    #[test]
+    fn constants() {
+        assert_eq!(
+            FieldElement::multiplicative_generator(),
+            FieldElement::from(3)
+        );
+        let root = FieldElement::root_of_unity();
+        assert_eq!(root.inner(), limbs(&Derived::root_of_unity()));
+        assert_eq!(
+            // 2^(S - 1)
+            root.pow_vartime([0, 0, 1u64 << 63]),
+            -FieldElement::one()
+        );
+    }
+
     #[test]
     fn const_one_two_three() {
         let one = FieldElement::from(1);
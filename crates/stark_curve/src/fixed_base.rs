@@ -0,0 +1,121 @@
+use crate::curve::{AffinePoint, ProjectivePoint, CURVE_G};
+use alloc::vec::Vec;
+use bitvec::{field::BitField, order::Lsb0, slice::BitSlice};
+
+/// Number of bits handled with a single table lookup.
+const WINDOW_BITS: usize = 4;
+/// Number of windows needed to cover values less than `2^252`.
+const WINDOWS: usize = 252 / WINDOW_BITS;
+/// Number of precomputed points per window; a window of zero needs no point.
+const WINDOW_SIZE: usize = (1 << WINDOW_BITS) - 1;
+
+#[cfg(feature = "std")]
+lazy_static::lazy_static! {
+    /// Precomputed multiples of the curve generator, used for signing and key derivation.
+    static ref GENERATOR_TABLE: FixedBaseTable = FixedBaseTable::new(&CURVE_G);
+}
+
+/// Multiplies the curve generator with the little endian `bits`.
+///
+/// Uses the cached `GENERATOR_TABLE` with `std`. Without it there is nowhere to cache the table
+/// (about 68kB), so this falls back to [ProjectivePoint::multiply].
+pub(crate) fn multiply_generator(bits: &BitSlice<Lsb0, u64>) -> ProjectivePoint {
+    #[cfg(feature = "std")]
+    {
+        GENERATOR_TABLE.multiply(bits)
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        CURVE_G.multiply(bits)
+    }
+}
+
+/// Precomputed multiples of a fixed point for faster multiplication with it.
+///
+/// Holds `d * 16^i * P` for every 4 bit window `i` and non-zero digit `d`, similar to the
+/// Pedersen lookup tables, so multiplying needs one addition per non-zero window and no doublings.
+pub struct FixedBaseTable {
+    points: Vec<AffinePoint>,
+}
+
+impl FixedBaseTable {
+    pub fn new(base: &ProjectivePoint) -> Self {
+        let mut points = Vec::with_capacity(WINDOWS * WINDOW_SIZE);
+
+        let mut window_base = base.clone();
+        for _ in 0..WINDOWS {
+            let mut multiple = window_base.clone();
+            for _ in 0..WINDOW_SIZE {
+                points.push(multiple.clone());
+                multiple.add(&window_base);
+            }
+
+            for _ in 0..WINDOW_BITS {
+                window_base.double();
+            }
+        }
+
+        Self {
+            points: AffinePoint::batch_from(&points),
+        }
+    }
+
+    /// Multiplies the base point with the little endian `bits`, giving the same result as
+    /// [ProjectivePoint::multiply].
+    ///
+    /// Panics if any bit above the 252 least significant bits is set.
+    pub fn multiply(&self, bits: &BitSlice<Lsb0, u64>) -> ProjectivePoint {
+        let covered = bits.len().min(WINDOWS * WINDOW_BITS);
+        assert!(
+            bits[covered..].not_any(),
+            "Only values less than 2^252 are supported"
+        );
+
+        let mut product = ProjectivePoint::identity();
+        for (i, window) in bits[..covered].chunks(WINDOW_BITS).enumerate() {
+            let digit: usize = window.load_le();
+            if digit > 0 {
+                product.add_affine(&self.points[i * WINDOW_SIZE + digit - 1]);
+            }
+        }
+        product
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::FieldElement;
+    use ff::{Field, PrimeField};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn same_as_multiply() {
+        let values = [
+            FieldElement::zero(),
+            FieldElement::one(),
+            FieldElement::from(16),
+            FieldElement::from_str_vartime(
+                "2089986280348253421170679821480865132823066470938446095505822317253594081284",
+            )
+            .unwrap(),
+            // the largest value
+            -FieldElement::one(),
+        ];
+
+        for value in values {
+            let bits = value.into_bits();
+            let expected = CURVE_G.multiply(&bits);
+            let result = GENERATOR_TABLE.multiply(&bits);
+
+            assert_eq!(result.infinity, expected.infinity, "{value:?}");
+            if !expected.infinity {
+                assert_eq!(
+                    AffinePoint::from(&result),
+                    AffinePoint::from(&expected),
+                    "{value:?}"
+                );
+            }
+        }
+    }
+}
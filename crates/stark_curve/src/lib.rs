@@ -1,7 +1,22 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
 mod curve;
+mod ecdsa;
 mod field;
+mod fixed_base;
+mod rfc6979;
+mod scalar;
 
 pub use curve::{
-    AffinePoint, ProjectivePoint, PEDERSEN_P0, PEDERSEN_P1, PEDERSEN_P2, PEDERSEN_P3, PEDERSEN_P4,
+    AffinePoint, ProjectivePoint, CURVE_BETA, CURVE_G, PEDERSEN_P0, PEDERSEN_P1, PEDERSEN_P2,
+    PEDERSEN_P3, PEDERSEN_P4,
+};
+pub use ecdsa::{
+    grind_key, private_key_from_eth_signature, public_key, sign, verify, SignError, Signature,
+    VerifyError,
 };
 pub use field::{FieldElement, FieldElementRepr};
+pub use fixed_base::FixedBaseTable;
+pub use scalar::{Scalar, ScalarRepr};
@@ -0,0 +1,97 @@
+//! Deterministic generation of the ECDSA nonce `k` following RFC6979, as done by cairo-lang's
+//! `generate_k_rfc6979` through the python `ecdsa` package.
+use crate::field::FieldElement;
+use crate::scalar::{Scalar, ScalarRepr};
+use ff::{Field, PrimeField};
+use hmac::{Hmac, Mac, NewMac};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Generates the nonce for signing `msg_hash` with `private_key`; `seed` is the extra entropy
+/// used to draw another nonce when the previous one could not be used.
+pub(crate) fn generate_k(
+    msg_hash: &FieldElement,
+    private_key: &Scalar,
+    seed: Option<u64>,
+) -> Scalar {
+    let private_key = private_key.to_repr().0;
+    // cairo-lang pads some message hashes with a nibble before handing them over as the minimal
+    // big endian bytes, which the `bits2octets` of RFC6979 then truncates to the 252 bits of the
+    // curve order. For message hashes less than 2^251 both of these cancel out, leaving the hash
+    // itself, which is also less than the curve order.
+    let msg_hash = msg_hash.to_repr().0;
+    let seed = seed.map(u64::to_be_bytes).unwrap_or_default();
+    let extra_entropy = strip_leading_zeros(&seed);
+
+    let hmac = |key: &[u8], parts: &[&[u8]]| -> [u8; 32] {
+        let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+        for part in parts {
+            mac.update(part);
+        }
+        mac.finalize().into_bytes().into()
+    };
+
+    let mut v = [0x01u8; 32];
+    let mut k = [0x00u8; 32];
+
+    k = hmac(&k, &[&v, &[0x00], &private_key, &msg_hash, extra_entropy]);
+    v = hmac(&k, &[&v]);
+    k = hmac(&k, &[&v, &[0x01], &private_key, &msg_hash, extra_entropy]);
+    v = hmac(&k, &[&v]);
+
+    loop {
+        v = hmac(&k, &[&v]);
+
+        // bits2int: keep the 252 most significant bits, the bit length of the curve order
+        let candidate = Scalar::from_repr_vartime(ScalarRepr(shift_right_4(&v)))
+            .filter(|candidate| !candidate.is_zero_vartime());
+        if let Some(candidate) = candidate {
+            return candidate;
+        }
+
+        k = hmac(&k, &[&v, &[0x00]]);
+        v = hmac(&k, &[&v]);
+    }
+}
+
+fn strip_leading_zeros(bytes: &[u8]) -> &[u8] {
+    let zeros = bytes.iter().take_while(|b| **b == 0).count();
+    &bytes[zeros..]
+}
+
+/// Shifts the big endian 256 bit value right by four bits.
+fn shift_right_4(bytes: &[u8; 32]) -> [u8; 32] {
+    let mut shifted = [0u8; 32];
+    shifted[0] = bytes[0] >> 4;
+    for (i, byte) in shifted.iter_mut().enumerate().skip(1) {
+        *byte = (bytes[i - 1] << 4) | (bytes[i] >> 4);
+    }
+    shifted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn shift() {
+        let mut bytes = [0u8; 32];
+        bytes[0] = 0xab;
+        bytes[31] = 0xcd;
+
+        let mut expected = [0u8; 32];
+        expected[0] = 0x0a;
+        expected[1] = 0xb0;
+        expected[31] = 0x0c;
+
+        assert_eq!(shift_right_4(&bytes), expected);
+    }
+
+    #[test]
+    fn leading_zeros() {
+        assert_eq!(strip_leading_zeros(&[0, 0, 1, 0]), &[1, 0]);
+        assert_eq!(strip_leading_zeros(&[0, 0]), &[] as &[u8]);
+    }
+}
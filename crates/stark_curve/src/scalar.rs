@@ -0,0 +1,57 @@
+// FIXME: needed because of mont_reduce generated by PrimeField derive
+// https://github.com/zkcrypto/ff/pull/83
+#![allow(clippy::too_many_arguments)]
+
+use crate::field::{FieldElement, FieldElementRepr};
+use ff::PrimeField;
+
+/// Integers modulo the order of Starkware's curve.
+///
+/// Used for the ECDSA scalars, which live in a different field than the curve coordinates.
+#[derive(PrimeField)]
+#[PrimeFieldModulus = "3618502788666131213697322783095070105526743751716087489154079457884512865583"]
+#[PrimeFieldGenerator = "3"]
+#[PrimeFieldReprEndianness = "big"]
+pub struct Scalar([u64; 4]);
+
+impl Scalar {
+    /// Converts the canonical value of a [FieldElement], `None` if it is not less than the curve
+    /// order.
+    pub fn from_field_element(fe: &FieldElement) -> Option<Self> {
+        Self::from_repr_vartime(ScalarRepr(fe.to_repr().0))
+    }
+
+    /// Converts to a [FieldElement] with the same canonical value.
+    pub fn to_field_element(&self) -> FieldElement {
+        // the curve order is less than the field modulus
+        FieldElement::from_repr_vartime(FieldElementRepr(self.to_repr().0))
+            .expect("Curve order is less than the field modulus")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ff::Field;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn field_element_round_trip() {
+        let fe = FieldElement::from_str_vartime("123456789").unwrap();
+        let scalar = Scalar::from_field_element(&fe).unwrap();
+        assert_eq!(scalar, Scalar::from(123456789));
+        assert_eq!(scalar.to_field_element(), fe);
+    }
+
+    #[test]
+    fn curve_order_does_not_fit() {
+        let order = FieldElement::from_str_vartime(
+            "3618502788666131213697322783095070105526743751716087489154079457884512865583",
+        )
+        .unwrap();
+        assert_eq!(Scalar::from_field_element(&order), None);
+
+        let largest = order - FieldElement::one();
+        assert_eq!(Scalar::from_field_element(&largest), Some(-Scalar::one()));
+    }
+}
@@ -0,0 +1,27 @@
+use ::stark_hash::{poseidon_hash, poseidon_hash_many, StarkHash};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    // The same inputs as used for the Pedersen hash benchmark
+    let e0 = "03d937c035c878245caf64531a5756109c53068da139362728feb561405371cb";
+    let e1 = "0208a0a10250e382e1e4bbe2880906c2791bf6275695e02fbbc6aeff9cd8b31a";
+
+    let e0 = StarkHash::from_hex_str(e0).unwrap();
+    let e1 = StarkHash::from_hex_str(e1).unwrap();
+
+    c.bench_function("poseidon_hash", |b| {
+        b.iter(|| {
+            black_box(poseidon_hash(e0, e1));
+        });
+    });
+
+    let elements = [e0, e1].repeat(5);
+    c.bench_function("poseidon_hash_many", |b| {
+        b.iter(|| {
+            black_box(poseidon_hash_many(&elements));
+        });
+    });
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
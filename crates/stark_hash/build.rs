@@ -2,6 +2,7 @@ use std::env;
 use std::fs;
 use std::path::Path;
 
+use sha2::{Digest, Sha256};
 use stark_curve::*;
 
 fn generate_consts(path: &Path, bits: u32) {
@@ -24,21 +25,22 @@ fn generate_consts(path: &Path, bits: u32) {
 }
 
 fn push_points(buf: &mut String, name: &str, base: &ProjectivePoint, max_bits: u32, bits: u32) {
-    let base = AffinePoint::from(base);
-
     let full_chunks = max_bits / bits;
     let leftover_bits = max_bits % bits;
     let table_size_full = (1 << bits) - 1;
     let table_size_leftover = (1 << leftover_bits) - 1;
     let len = full_chunks * table_size_full + table_size_leftover;
 
+    // A static instead of a const, so the tables are placed once in read-only memory instead of
+    // being inlined where used, which matters on no_std targets
     buf.push_str(&format!(
-        "pub const CURVE_CONSTS_{}: [AffinePoint; {}] = [\n",
+        "pub static CURVE_CONSTS_{}: [AffinePoint; {}] = [\n",
         name, len
     ));
 
+    let mut points = Vec::with_capacity(len as usize);
     let mut bits_left = max_bits;
-    let mut outer_point = base;
+    let mut outer_point = base.clone();
     while bits_left > 0 {
         let eat_bits = std::cmp::min(bits_left, bits);
         let table_size = (1 << eat_bits) - 1;
@@ -47,11 +49,8 @@ fn push_points(buf: &mut String, name: &str, base: &ProjectivePoint, max_bits: u
 
         // Loop through each possible bit combination except zero
         let mut inner_point = outer_point.clone();
-        for j in 1..(table_size + 1) {
-            if bits_left < max_bits || j > 1 {
-                buf.push_str(",\n");
-            }
-            push_point(buf, &inner_point);
+        for _ in 1..(table_size + 1) {
+            points.push(inner_point.clone());
             inner_point.add(&outer_point);
         }
 
@@ -62,6 +61,14 @@ fn push_points(buf: &mut String, name: &str, base: &ProjectivePoint, max_bits: u
         }
     }
 
+    // Convert them all to affine at once, needing only a single inversion
+    for (i, point) in AffinePoint::batch_from(&points).iter().enumerate() {
+        if i > 0 {
+            buf.push_str(",\n");
+        }
+        push_point(buf, point);
+    }
+
     buf.push_str("\n];");
 }
 
@@ -84,9 +91,53 @@ fn push_point(buf: &mut String, p: &AffinePoint) {
     buf.push_str("\n    )");
 }
 
+/// Number of rounds of the Poseidon permutation, 8 full and 83 partial ones.
+const POSEIDON_ROUNDS: usize = 8 + 83;
+/// Width of the Poseidon state.
+const POSEIDON_WIDTH: usize = 3;
+
+/// Generates the Poseidon round keys the way cairo-lang does: the `i`th key is
+/// `sha256("Hades{i}")` reduced modulo the field modulus.
+fn generate_poseidon_consts(path: &Path) {
+    let mut buf = String::with_capacity(64 * 1024);
+
+    buf.push_str(&format!(
+        "pub static POSEIDON_ROUND_KEYS: [[FieldElement; {}]; {}] = [\n",
+        POSEIDON_WIDTH, POSEIDON_ROUNDS
+    ));
+
+    for round in 0..POSEIDON_ROUNDS {
+        buf.push_str("    [\n");
+        for i in 0..POSEIDON_WIDTH {
+            let digest = Sha256::digest(format!("Hades{}", round * POSEIDON_WIDTH + i).as_bytes());
+            // The digest can exceed the modulus, so reduce it byte by byte
+            let key = digest.iter().fold(FieldElement::from(0), |acc, byte| {
+                acc * FieldElement::from(256) + FieldElement::from(*byte as u64)
+            });
+            push_field_element(&mut buf, &key);
+        }
+        buf.push_str("    ],\n");
+    }
+
+    buf.push_str("];\n");
+
+    fs::write(path, buf).expect("Unable to write file");
+}
+
+fn push_field_element(buf: &mut String, fe: &FieldElement) {
+    let limbs = fe.inner();
+    buf.push_str(&format!(
+        "        FieldElement::new([{}, {}, {}, {}]),\n",
+        limbs[0], limbs[1], limbs[2], limbs[3]
+    ));
+}
+
 fn main() {
     let out_dir = env::var_os("OUT_DIR").unwrap();
     let dest_path = Path::new(&out_dir).join("curve_consts.rs");
     let bits = 4;
     generate_consts(&dest_path, bits);
+
+    let dest_path = Path::new(&out_dir).join("poseidon_consts.rs");
+    generate_poseidon_consts(&dest_path);
 }
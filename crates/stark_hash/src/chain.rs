@@ -0,0 +1,81 @@
+use crate::{stark_hash, StarkHash};
+
+/// HashChain is the structure used over at cairo side to represent the hash construction needed
+/// for computing the class hash, transaction hashes and the block commitments.
+///
+/// Empty hash chained value equals `H(0, 0)` where `H` is the [`stark_hash()`] function, and the
+/// second value is the number of values hashed together in this chain. For other values, the
+/// accumulator is on each update replaced with the `H(hash, value)` and the number of count
+/// incremented by one.
+///
+/// This is the equivalent of `compute_hash_on_elements` in cairo-lang.
+#[derive(Clone, Debug)]
+pub struct HashChain {
+    hash: StarkHash,
+    count: usize,
+}
+
+impl Default for HashChain {
+    fn default() -> Self {
+        HashChain {
+            hash: StarkHash::ZERO,
+            count: 0,
+        }
+    }
+}
+
+impl HashChain {
+    pub fn update(&mut self, value: StarkHash) {
+        self.hash = stark_hash(self.hash, value);
+        self.count = self
+            .count
+            .checked_add(1)
+            .expect("could not have deserialized larger than usize Vecs");
+    }
+
+    pub fn finalize(self) -> StarkHash {
+        let count = StarkHash::from_be_slice(&self.count.to_be_bytes())
+            .expect("usize is smaller than 251-bits");
+        stark_hash(self.hash, count)
+    }
+
+    /// Hash chains all of the `values` together, returning the final hash.
+    pub fn compute<I: IntoIterator<Item = StarkHash>>(values: I) -> StarkHash {
+        values
+            .into_iter()
+            .fold(HashChain::default(), |mut chain, value| {
+                chain.update(value);
+                chain
+            })
+            .finalize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn empty() {
+        let expected = stark_hash(StarkHash::ZERO, StarkHash::ZERO);
+
+        assert_eq!(HashChain::default().finalize(), expected);
+        assert_eq!(HashChain::compute([]), expected);
+    }
+
+    #[test]
+    fn update_and_finalize() {
+        let one = StarkHash::from_hex_str("1").unwrap();
+        let two = StarkHash::from_hex_str("2").unwrap();
+
+        let expected = stark_hash(stark_hash(stark_hash(StarkHash::ZERO, one), two), two);
+
+        let mut chain = HashChain::default();
+        chain.update(one);
+        chain.update(two);
+        assert_eq!(chain.finalize(), expected);
+
+        assert_eq!(HashChain::compute([one, two]), expected);
+    }
+}
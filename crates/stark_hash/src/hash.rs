@@ -1,46 +1,50 @@
-use std::borrow::Cow;
-use std::error::Error;
-use std::fmt::Display;
+use alloc::borrow::Cow;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Display;
 
 use stark_curve::{AffinePoint, FieldElement, FieldElementRepr, ProjectivePoint, PEDERSEN_P0};
 
 use bitvec::{field::BitField, order::Msb0, slice::BitSlice, view::BitView};
-use ff::PrimeField;
+use ff::{Field, PrimeField};
 
 include!(concat!(env!("OUT_DIR"), "/curve_consts.rs"));
 
 /// The Starknet elliptic curve Field Element.
 ///
 /// Forms the basic building block of most Starknet interactions.
+///
+/// Arithmetic is done modulo the field modulus, and the ordering is that of the
+/// canonical integer values, as the bytes are kept in big-endian.
 #[derive(Clone, Copy, PartialEq, Hash, Eq, PartialOrd, Ord)]
 pub struct StarkHash([u8; 32]);
 
-impl std::fmt::Debug for StarkHash {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Debug for StarkHash {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "StarkHash({})", self)
     }
 }
 
-impl std::fmt::Display for StarkHash {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for StarkHash {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         // 0xABCDEF1234567890
         write!(f, "0x{:X}", self)
     }
 }
 
-impl std::fmt::LowerHex for StarkHash {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::LowerHex for StarkHash {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         self.0.iter().try_for_each(|&b| write!(f, "{:02x}", b))
     }
 }
 
-impl std::fmt::UpperHex for StarkHash {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::UpperHex for StarkHash {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         self.0.iter().try_for_each(|&b| write!(f, "{:02X}", b))
     }
 }
 
-impl std::default::Default for StarkHash {
+impl Default for StarkHash {
     fn default() -> Self {
         StarkHash::ZERO
     }
@@ -51,18 +55,24 @@ impl std::default::Default for StarkHash {
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub struct OverflowError;
 
-impl Error for OverflowError {}
+#[cfg(feature = "std")]
+impl std::error::Error for OverflowError {}
 
 const OVERFLOW_MSG: &str = "The StarkHash maximum value was exceeded.";
 
-impl std::fmt::Display for OverflowError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for OverflowError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.write_str(OVERFLOW_MSG)
     }
 }
 
 impl StarkHash {
     pub const ZERO: StarkHash = StarkHash([0u8; 32]);
+    pub const ONE: StarkHash = {
+        let mut bytes = [0u8; 32];
+        bytes[31] = 1;
+        StarkHash(bytes)
+    };
 
     /// Returns the big-endian representation of this [StarkHash].
     pub fn to_be_bytes(self) -> [u8; 32] {
@@ -123,7 +133,63 @@ impl StarkHash {
     }
 }
 
-impl std::ops::Add for StarkHash {
+impl From<u64> for StarkHash {
+    fn from(value: u64) -> Self {
+        let mut bytes = [0u8; 32];
+        bytes[24..].copy_from_slice(&value.to_be_bytes());
+        StarkHash(bytes)
+    }
+}
+
+impl From<u128> for StarkHash {
+    fn from(value: u128) -> Self {
+        let mut bytes = [0u8; 32];
+        bytes[16..].copy_from_slice(&value.to_be_bytes());
+        StarkHash(bytes)
+    }
+}
+
+/// Error returned when converting a [StarkHash] into an integer type too small
+/// to hold its value.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct OutOfRangeError;
+
+#[cfg(feature = "std")]
+impl std::error::Error for OutOfRangeError {}
+
+impl core::fmt::Display for OutOfRangeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("The StarkHash value does not fit into the integer type.")
+    }
+}
+
+impl TryFrom<StarkHash> for u64 {
+    type Error = OutOfRangeError;
+
+    fn try_from(value: StarkHash) -> Result<Self, Self::Error> {
+        if value.0[..24].iter().any(|b| *b != 0) {
+            return Err(OutOfRangeError);
+        }
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&value.0[24..]);
+        Ok(u64::from_be_bytes(bytes))
+    }
+}
+
+impl TryFrom<StarkHash> for u128 {
+    type Error = OutOfRangeError;
+
+    fn try_from(value: StarkHash) -> Result<Self, Self::Error> {
+        if value.0[..16].iter().any(|b| *b != 0) {
+            return Err(OutOfRangeError);
+        }
+        let mut bytes = [0u8; 16];
+        bytes.copy_from_slice(&value.0[16..]);
+        Ok(u128::from_be_bytes(bytes))
+    }
+}
+
+impl core::ops::Add for StarkHash {
     type Output = StarkHash;
 
     fn add(self, rhs: Self) -> Self::Output {
@@ -132,6 +198,40 @@ impl std::ops::Add for StarkHash {
     }
 }
 
+impl core::ops::Sub for StarkHash {
+    type Output = StarkHash;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        let result = FieldElement::from(self) - FieldElement::from(rhs);
+        StarkHash::from(result)
+    }
+}
+
+impl core::ops::Mul for StarkHash {
+    type Output = StarkHash;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        let result = FieldElement::from(self) * FieldElement::from(rhs);
+        StarkHash::from(result)
+    }
+}
+
+impl core::ops::Neg for StarkHash {
+    type Output = StarkHash;
+
+    fn neg(self) -> Self::Output {
+        StarkHash::from(-FieldElement::from(self))
+    }
+}
+
+impl StarkHash {
+    /// Returns the multiplicative inverse modulo the field modulus, `None` for zero.
+    pub fn inverse(&self) -> Option<StarkHash> {
+        let inverse: Option<FieldElement> = FieldElement::from(*self).invert().into();
+        inverse.map(StarkHash::from)
+    }
+}
+
 /// Computes the [Starknet Pedersen hash] on `a` and `b` using precomputed points.
 ///
 /// [Starknet Pedersen hash]: https://docs.starkware.co/starkex-v3/crypto/pedersen-hash-function
@@ -170,8 +270,8 @@ pub fn stark_hash(a: StarkHash, b: StarkHash) -> StarkHash {
 impl From<StarkHash> for FieldElement {
     fn from(hash: StarkHash) -> Self {
         debug_assert_eq!(
-            std::mem::size_of::<FieldElement>(),
-            std::mem::size_of::<StarkHash>()
+            core::mem::size_of::<FieldElement>(),
+            core::mem::size_of::<StarkHash>()
         );
         Self::from_repr(FieldElementRepr(hash.to_be_bytes())).unwrap()
     }
@@ -180,8 +280,8 @@ impl From<StarkHash> for FieldElement {
 impl From<FieldElement> for StarkHash {
     fn from(fp: FieldElement) -> Self {
         debug_assert_eq!(
-            std::mem::size_of::<FieldElement>(),
-            std::mem::size_of::<StarkHash>()
+            core::mem::size_of::<FieldElement>(),
+            core::mem::size_of::<StarkHash>()
         );
         // unwrap is safe because the FieldElement and StarkHash
         // should both be smaller than the field modulus.
@@ -196,7 +296,7 @@ pub(crate) struct InvalidBufferSizeError {
 }
 
 impl Display for InvalidBufferSizeError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.write_fmt(format_args!(
             "Expected buffer size {}, got {}",
             self.expected, self.actual,
@@ -300,7 +400,7 @@ impl StarkHash {
         let (it, start, len) = self.skip_zeros();
         let res = Self::it_to_hex_str(it, start, len, buf);
         // Unwrap is safe because `buf` holds valid UTF8 characters.
-        std::str::from_utf8(res).unwrap()
+        core::str::from_utf8(res).unwrap()
     }
 
     /// A convenience function which produces a "0x" prefixed hex string from a [StarkHash].
@@ -309,13 +409,146 @@ impl StarkHash {
             return Cow::from("0x0");
         }
         let (it, start, len) = self.skip_zeros();
-        let mut buf = vec![0u8; len];
+        let mut buf = alloc::vec![0u8; len];
         Self::it_to_hex_str(it, start, len, &mut buf);
         // Unwrap is safe as the buffer contains valid utf8
         String::from_utf8(buf).unwrap().into()
     }
 }
 
+impl StarkHash {
+    /// Parses a decimal string into a [StarkHash].
+    ///
+    /// Leading zeros are allowed, but signs and prefixes are not.
+    pub fn from_dec_str(dec_str: &str) -> Result<Self, DecimalParseError> {
+        if dec_str.is_empty() {
+            return Err(DecimalParseError::Empty);
+        }
+
+        // Little-endian u64 limbs
+        let mut limbs = [0u64; 4];
+        for digit in dec_str.bytes() {
+            let digit = match digit {
+                b'0'..=b'9' => digit - b'0',
+                other => return Err(DecimalParseError::InvalidDigit(other)),
+            };
+
+            let mut carry = digit as u128;
+            for limb in limbs.iter_mut() {
+                let product = *limb as u128 * 10 + carry;
+                *limb = product as u64;
+                carry = product >> 64;
+            }
+            if carry != 0 {
+                return Err(DecimalParseError::Overflow);
+            }
+        }
+
+        let mut bytes = [0u8; 32];
+        for (chunk, limb) in bytes.chunks_exact_mut(8).rev().zip(limbs) {
+            chunk.copy_from_slice(&limb.to_be_bytes());
+        }
+
+        let hash = StarkHash::from_be_bytes(bytes)?;
+        Ok(hash)
+    }
+
+    /// Produces the decimal string of a [StarkHash], without leading zeros.
+    pub fn to_dec_str(&self) -> String {
+        // The largest power of ten fitting into a u64
+        const CHUNK: u64 = 10_000_000_000_000_000_000;
+        const CHUNK_DIGITS: usize = 19;
+
+        // Big-endian u64 limbs
+        let mut limbs = [0u64; 4];
+        for (limb, chunk) in limbs.iter_mut().zip(self.0.chunks_exact(8)) {
+            let mut buf = [0u8; 8];
+            buf.copy_from_slice(chunk);
+            *limb = u64::from_be_bytes(buf);
+        }
+
+        // Repeatedly divide by CHUNK, collecting the remainders least significant first
+        let mut chunks = Vec::with_capacity(5);
+        while limbs.iter().any(|limb| *limb != 0) {
+            let mut remainder = 0u128;
+            for limb in limbs.iter_mut() {
+                let dividend = (remainder << 64) | *limb as u128;
+                *limb = (dividend / CHUNK as u128) as u64;
+                remainder = dividend % CHUNK as u128;
+            }
+            chunks.push(remainder as u64);
+        }
+
+        let mut chunks = chunks.into_iter().rev();
+        let mut result = chunks.next().unwrap_or_default().to_string();
+        for chunk in chunks {
+            result.push_str(&alloc::format!("{:0width$}", chunk, width = CHUNK_DIGITS));
+        }
+        result
+    }
+}
+
+impl StarkHash {
+    /// Encodes an ASCII string of at most 31 characters as a Cairo short string, the big-endian
+    /// bytes of the characters.
+    pub fn from_short_string(s: &str) -> Result<Self, ShortStringError> {
+        if let Some(non_ascii) = s.bytes().find(|b| !b.is_ascii()) {
+            return Err(ShortStringError::NonAscii(non_ascii));
+        }
+        if s.len() > MAX_SHORT_STRING_LEN {
+            return Err(ShortStringError::TooLong(s.len()));
+        }
+
+        // At most 31 bytes always fit
+        Ok(StarkHash::from_be_slice(s.as_bytes()).unwrap())
+    }
+
+    /// Decodes a Cairo short string, ignoring the leading zero bytes.
+    pub fn to_short_string(&self) -> Result<String, ShortStringError> {
+        let zeros = self.0.iter().take_while(|b| **b == 0).count();
+        let bytes = &self.0[zeros..];
+
+        if let Some(non_ascii) = bytes.iter().find(|b| !b.is_ascii()) {
+            return Err(ShortStringError::NonAscii(*non_ascii));
+        }
+        if bytes.len() > MAX_SHORT_STRING_LEN {
+            return Err(ShortStringError::TooLong(bytes.len()));
+        }
+
+        // Unwrap is safe as ASCII is valid utf8
+        Ok(String::from_utf8(bytes.to_vec()).unwrap())
+    }
+}
+
+/// The longest Cairo short string, as a [StarkHash] cannot hold all 32 byte values.
+const MAX_SHORT_STRING_LEN: usize = 31;
+
+#[derive(Debug, PartialEq)]
+pub enum DecimalParseError {
+    InvalidDigit(u8),
+    Empty,
+    Overflow,
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DecimalParseError {}
+
+impl From<OverflowError> for DecimalParseError {
+    fn from(_: OverflowError) -> Self {
+        Self::Overflow
+    }
+}
+
+impl core::fmt::Display for DecimalParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidDigit(d) => f.write_fmt(format_args!("Invalid digit found: 0x{:x}", *d)),
+            Self::Empty => f.write_str("No digits found"),
+            Self::Overflow => f.write_str(OVERFLOW_MSG),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub enum HexParseError {
     InvalidNibble(u8),
@@ -323,7 +556,8 @@ pub enum HexParseError {
     Overflow,
 }
 
-impl Error for HexParseError {}
+#[cfg(feature = "std")]
+impl std::error::Error for HexParseError {}
 
 impl From<OverflowError> for HexParseError {
     fn from(_: OverflowError) -> Self {
@@ -331,8 +565,8 @@ impl From<OverflowError> for HexParseError {
     }
 }
 
-impl std::fmt::Display for HexParseError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for HexParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             Self::InvalidNibble(n) => f.write_fmt(format_args!("Invalid nibble found: 0x{:x}", *n)),
             Self::InvalidLength { max, actual } => {
@@ -343,6 +577,30 @@ impl std::fmt::Display for HexParseError {
     }
 }
 
+/// Error returned when a value cannot be converted to or from a Cairo short string.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ShortStringError {
+    /// The string has more than 31 characters.
+    TooLong(usize),
+    /// The string contains a byte outside of ASCII.
+    NonAscii(u8),
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ShortStringError {}
+
+impl core::fmt::Display for ShortStringError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::TooLong(len) => f.write_fmt(format_args!(
+                "More than {} characters found: {}",
+                MAX_SHORT_STRING_LEN, *len
+            )),
+            Self::NonAscii(b) => f.write_fmt(format_args!("Non-ASCII byte found: 0x{:x}", *b)),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -360,6 +618,15 @@ mod tests {
         assert_eq!(one, expected);
     }
 
+    #[test]
+    fn from_u64() {
+        assert_eq!(StarkHash::from(0u64), StarkHash::ZERO);
+        assert_eq!(
+            StarkHash::from(u64::MAX),
+            StarkHash::from_hex_str("ffffffffffffffff").unwrap()
+        );
+    }
+
     #[test]
     fn bits_round_trip() {
         let mut bits = bitvec![Msb0, u8; 1; 251];
@@ -666,6 +933,240 @@ mod tests {
         }
     }
 
+    mod arithmetic {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        fn a() -> StarkHash {
+            StarkHash::from_hex_str(
+                "0x3d937c035c878245caf64531a5756109c53068da139362728feb561405371cb",
+            )
+            .unwrap()
+        }
+
+        fn b() -> StarkHash {
+            StarkHash::from_hex_str(
+                "0x208a0a10250e382e1e4bbe2880906c2791bf6275695e02fbbc6aeff9cd8b31a",
+            )
+            .unwrap()
+        }
+
+        const MAX: &str = "0x800000000000011000000000000000000000000000000000000000000000000";
+
+        #[test]
+        fn sub() {
+            let expected = StarkHash::from_hex_str(
+                "0x1d0971f337794a17acaa870924e4f4e233710664aa355f76d380661a37abeb1",
+            )
+            .unwrap();
+            assert_eq!(a() - b(), expected);
+
+            // wraps around the modulus
+            let expected = StarkHash::from_hex_str(
+                "0x62f68e0cc886b6f8535578f6db1b0b1dcc8ef99b55caa0892c7f99e5c854150",
+            )
+            .unwrap();
+            assert_eq!(b() - a(), expected);
+            assert_eq!(
+                StarkHash::ZERO - StarkHash::ONE,
+                StarkHash::from_hex_str(MAX).unwrap()
+            );
+        }
+
+        #[test]
+        fn mul() {
+            let expected = StarkHash::from_hex_str(
+                "0x2b450c0822ebf75b867cac31b03b95ba7f4e6939bb10337564c70e10be808cb",
+            )
+            .unwrap();
+            assert_eq!(a() * b(), expected);
+            assert_eq!(a() * StarkHash::ONE, a());
+            assert_eq!(a() * StarkHash::ZERO, StarkHash::ZERO);
+        }
+
+        #[test]
+        fn neg() {
+            assert_eq!(-StarkHash::ONE, StarkHash::from_hex_str(MAX).unwrap());
+            assert_eq!(-StarkHash::ZERO, StarkHash::ZERO);
+            assert_eq!(-a() + a(), StarkHash::ZERO);
+        }
+
+        #[test]
+        fn inverse() {
+            let expected = StarkHash::from_hex_str(
+                "0x29a479bb244ed3bdf7c581cefd9d987860769d1da4655be0b20974bc8b43371",
+            )
+            .unwrap();
+            assert_eq!(a().inverse(), Some(expected));
+            assert_eq!(a() * expected, StarkHash::ONE);
+            assert_eq!(StarkHash::ZERO.inverse(), None);
+        }
+
+        #[test]
+        fn ordering() {
+            assert!(StarkHash::ZERO < StarkHash::ONE);
+            assert!(StarkHash::from(0x100u64) > StarkHash::from(0xffu64));
+            assert!(b() < a());
+            assert!(a() < StarkHash::from_hex_str(MAX).unwrap());
+        }
+    }
+
+    mod integer_conversions {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn u128_round_trip() {
+            for value in [0, 1, u64::MAX as u128 + 1, u128::MAX] {
+                let hash = StarkHash::from(value);
+                assert_eq!(u128::try_from(hash), Ok(value));
+            }
+            assert_eq!(
+                StarkHash::from(u128::MAX),
+                StarkHash::from_hex_str("ffffffffffffffffffffffffffffffff").unwrap()
+            );
+        }
+
+        #[test]
+        fn u64_round_trip() {
+            for value in [0, 1, u64::MAX] {
+                let hash = StarkHash::from(value);
+                assert_eq!(u64::try_from(hash), Ok(value));
+            }
+        }
+
+        #[test]
+        fn out_of_range() {
+            let hash = StarkHash::from(u64::MAX as u128 + 1);
+            assert_eq!(u64::try_from(hash), Err(OutOfRangeError));
+
+            let hash = StarkHash::from_hex_str("1ffffffffffffffffffffffffffffffff").unwrap();
+            assert_eq!(u128::try_from(hash), Err(OutOfRangeError));
+        }
+    }
+
+    mod dec_str {
+        use super::*;
+        use assert_matches::assert_matches;
+        use pretty_assertions::assert_eq;
+
+        const MAX: &str =
+            "3618502788666131213697322783095070105623107215331596699973092056135872020480";
+
+        #[test]
+        fn round_trip() {
+            for dec_str in [
+                "0",
+                "1",
+                "10000000000000000000",
+                "18446744073709551616",
+                MAX,
+            ] {
+                let hash = StarkHash::from_dec_str(dec_str).unwrap();
+                assert_eq!(hash.to_dec_str(), dec_str);
+            }
+        }
+
+        #[test]
+        fn same_as_hex() {
+            assert_eq!(
+                StarkHash::from_dec_str(MAX).unwrap(),
+                StarkHash::from_hex_str(
+                    "0x800000000000011000000000000000000000000000000000000000000000000"
+                )
+                .unwrap()
+            );
+            assert_eq!(
+                StarkHash::from_dec_str("340282366920938463463374607431768211455").unwrap(),
+                StarkHash::from(u128::MAX)
+            );
+        }
+
+        #[test]
+        fn leading_zeros() {
+            assert_eq!(
+                StarkHash::from_dec_str("000123").unwrap(),
+                StarkHash::from(123u64)
+            );
+            assert_eq!(StarkHash::from_dec_str("000").unwrap(), StarkHash::ZERO);
+        }
+
+        #[test]
+        fn invalid_digit() {
+            assert_matches!(StarkHash::from_dec_str("123a").unwrap_err(), DecimalParseError::InvalidDigit(d) => assert_eq!(d, b'a'));
+            assert_matches!(StarkHash::from_dec_str("0x1").unwrap_err(), DecimalParseError::InvalidDigit(d) => assert_eq!(d, b'x'));
+        }
+
+        #[test]
+        fn empty() {
+            assert_eq!(StarkHash::from_dec_str(""), Err(DecimalParseError::Empty));
+        }
+
+        #[test]
+        fn overflow() {
+            // Field modulus
+            assert_eq!(
+                StarkHash::from_dec_str(
+                    "3618502788666131213697322783095070105623107215331596699973092056135872020481"
+                ),
+                Err(DecimalParseError::Overflow)
+            );
+            // Does not even fit into 256 bits
+            assert_eq!(
+                StarkHash::from_dec_str(&"9".repeat(80)),
+                Err(DecimalParseError::Overflow)
+            );
+        }
+    }
+
+    mod short_string {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn round_trip() {
+            let longest = "a".repeat(31);
+            for short_string in ["", "ETH", "Ether", "with space and \0 NUL", &longest] {
+                let hash = StarkHash::from_short_string(short_string).unwrap();
+                assert_eq!(hash.to_short_string().unwrap(), short_string);
+            }
+        }
+
+        #[test]
+        fn big_endian() {
+            assert_eq!(
+                StarkHash::from_short_string("ETH").unwrap(),
+                StarkHash::from(0x455448u64)
+            );
+            assert_eq!(StarkHash::from_short_string("").unwrap(), StarkHash::ZERO);
+        }
+
+        #[test]
+        fn too_long() {
+            assert_eq!(
+                StarkHash::from_short_string(&"a".repeat(32)),
+                Err(ShortStringError::TooLong(32))
+            );
+
+            let mut bytes = [b'a'; 32];
+            bytes[0] = 0x01;
+            let hash = StarkHash::from_be_bytes(bytes).unwrap();
+            assert_eq!(hash.to_short_string(), Err(ShortStringError::TooLong(32)));
+        }
+
+        #[test]
+        fn non_ascii() {
+            assert_eq!(
+                StarkHash::from_short_string("\u{e4}"),
+                Err(ShortStringError::NonAscii(0xc3))
+            );
+            assert_eq!(
+                StarkHash::from(0x4180u64).to_short_string(),
+                Err(ShortStringError::NonAscii(0x80))
+            );
+        }
+    }
+
     mod has_more_than_251_bits {
         use super::*;
 
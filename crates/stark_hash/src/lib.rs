@@ -1,4 +1,19 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+mod chain;
 mod hash;
-mod serde;
+mod poseidon;
+pub mod serde;
+mod signature;
+mod uint256;
 
-pub use hash::{stark_hash, HexParseError, OverflowError, StarkHash};
+pub use chain::HashChain;
+pub use hash::{
+    stark_hash, DecimalParseError, HexParseError, OutOfRangeError, OverflowError, ShortStringError,
+    StarkHash,
+};
+pub use poseidon::{poseidon_hash, poseidon_hash_many};
+pub use signature::{verify_signature, VerifyError};
+pub use uint256::Uint256;
@@ -0,0 +1,183 @@
+use crate::StarkHash;
+
+use ff::Field;
+use stark_curve::FieldElement;
+
+include!(concat!(env!("OUT_DIR"), "/poseidon_consts.rs"));
+
+/// Number of rounds applying the S-box to the whole state, half of them before and half of them
+/// after the partial rounds.
+const FULL_ROUNDS: usize = 8;
+/// Number of rounds applying the S-box only to the last element of the state.
+const PARTIAL_ROUNDS: usize = 83;
+
+/// Computes the Starknet Poseidon hash of `a` and `b`.
+///
+/// This is cairo-lang's `poseidon_hash`, which differs from [poseidon_hash_many] over the same
+/// two elements.
+pub fn poseidon_hash(a: StarkHash, b: StarkHash) -> StarkHash {
+    let mut state = [a.into(), b.into(), FieldElement::from(2)];
+    permute(&mut state);
+
+    StarkHash::from(state[0])
+}
+
+/// Computes the Starknet Poseidon hash of any number of elements.
+///
+/// The elements are absorbed two at a time after padding them with a one, and a zero if that
+/// leaves an odd count, the same as cairo-lang's `poseidon_hash_many`.
+pub fn poseidon_hash_many(elements: &[StarkHash]) -> StarkHash {
+    let mut state = [FieldElement::zero(); 3];
+
+    let mut pairs = elements.chunks_exact(2);
+    for pair in pairs.by_ref() {
+        state[0] += FieldElement::from(pair[0]);
+        state[1] += FieldElement::from(pair[1]);
+        permute(&mut state);
+    }
+
+    match pairs.remainder() {
+        [last] => {
+            state[0] += FieldElement::from(*last);
+            state[1] += FieldElement::one();
+        }
+        _ => state[0] += FieldElement::one(),
+    }
+    permute(&mut state);
+
+    StarkHash::from(state[0])
+}
+
+/// The Hades permutation used by Starknet's Poseidon.
+fn permute(state: &mut [FieldElement; 3]) {
+    let mut round_keys = POSEIDON_ROUND_KEYS.iter();
+
+    for keys in round_keys.by_ref().take(FULL_ROUNDS / 2) {
+        add_round_keys(state, keys);
+        state.iter_mut().for_each(sbox);
+        mix(state);
+    }
+
+    for keys in round_keys.by_ref().take(PARTIAL_ROUNDS) {
+        add_round_keys(state, keys);
+        sbox(&mut state[2]);
+        mix(state);
+    }
+
+    for keys in round_keys {
+        add_round_keys(state, keys);
+        state.iter_mut().for_each(sbox);
+        mix(state);
+    }
+}
+
+fn add_round_keys(state: &mut [FieldElement; 3], keys: &[FieldElement; 3]) {
+    state
+        .iter_mut()
+        .zip(keys)
+        .for_each(|(element, key)| *element += key);
+}
+
+fn sbox(element: &mut FieldElement) {
+    *element *= element.square();
+}
+
+/// Multiplies the state with the MDS matrix `[[3, 1, 1], [1, -1, 1], [1, 1, -2]]`.
+fn mix(state: &mut [FieldElement; 3]) {
+    let sum = state[0] + state[1] + state[2];
+    state[0] = sum + state[0].double();
+    state[1] = sum - state[1].double();
+    state[2] = sum - state[2].double() - state[2];
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn felt(hex: &str) -> StarkHash {
+        StarkHash::from_hex_str(hex).unwrap()
+    }
+
+    #[test]
+    fn round_keys() {
+        assert_eq!(POSEIDON_ROUND_KEYS.len(), FULL_ROUNDS + PARTIAL_ROUNDS);
+        assert_eq!(
+            StarkHash::from(POSEIDON_ROUND_KEYS[0][0]),
+            felt("0x6861759ea556a2339dd92f9562a30b9e58e2ad98109ae4780b7fd8eac77fe6f")
+        );
+        assert_eq!(
+            StarkHash::from(POSEIDON_ROUND_KEYS[90][2]),
+            felt("0x61fc552b8eb75e17ad0fb7aaa4ca528f415e14f0d9cdbed861a8db0bfff0c5b")
+        );
+    }
+
+    #[test]
+    fn permutation() {
+        // cairo-lang's hades_permutation([0, 0, 0])
+        let mut state = [FieldElement::zero(); 3];
+        permute(&mut state);
+
+        let expected = [
+            felt("0x79e8d1e78258000a28fc9d49e233bc6852357968577b1e386550ed6a9086133"),
+            felt("0x3840d003d0f3f96dbb796ff6aa6a63be5b5404b91ccaabca256154cbb6fb984"),
+            felt("0x1eb39da3f7d3b04142d0ac83d9da00c9325a61fb2ef326e50b70eaa8a3c7cc7"),
+        ];
+        assert_eq!(state.map(StarkHash::from), expected);
+    }
+
+    #[test]
+    fn hash() {
+        let test_data = [
+            (
+                felt("0xb662f9017fa7956fd70e26129b1833e10ad000fd37b4d9f4e0ce6884b7bbe"),
+                felt("0x1fe356bf76102cdae1bfbdc173602ead228b12904c00dad9cf16e035468bea"),
+                felt("0x75540825a6ecc5dc7d7c2f5f868164182742227f1367d66c43ee51ec7937a81"),
+            ),
+            (
+                StarkHash::from(1u64),
+                StarkHash::from(2u64),
+                felt("0x5d44a3decb2b2e0cc71071f7b802f45dd792d064f0fc7316c46514f70f9891a"),
+            ),
+            (
+                -StarkHash::ONE,
+                -StarkHash::ONE,
+                felt("0x8240c823e0ce7f8300da42d6a28931c23f7e2eec7dd8d7e4caae97f1fd28cf"),
+            ),
+        ];
+
+        for (a, b, expected) in test_data {
+            assert_eq!(poseidon_hash(a, b), expected, "{a} {b}");
+        }
+    }
+
+    #[test]
+    fn hash_many() {
+        let test_data = [
+            (
+                vec![],
+                felt("0x2272be0f580fd156823304800919530eaa97430e972d7213ee13f4fbf7a5dbc"),
+            ),
+            (
+                vec![StarkHash::from(1u64)],
+                felt("0x579e8877c7755365d5ec1ec7d3a94a457eff5d1f40482bbe9729c064cdead2"),
+            ),
+            (
+                vec![StarkHash::from(1u64), StarkHash::from(2u64)],
+                felt("0x371cb6995ea5e7effcd2e174de264b5b407027a75a231a70c2c8d196107f0e7"),
+            ),
+            (
+                vec![
+                    StarkHash::from(1u64),
+                    StarkHash::from(2u64),
+                    StarkHash::from(3u64),
+                ],
+                felt("0x2f0d8840bcf3bc629598d8a6cc80cb7c0d9e52d93dab244bbf9cd0dca0ad082"),
+            ),
+        ];
+
+        for (elements, expected) in test_data {
+            assert_eq!(poseidon_hash_many(&elements), expected, "{elements:?}");
+        }
+    }
+}
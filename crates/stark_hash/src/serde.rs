@@ -1,3 +1,5 @@
+//! [StarkHash] is serialized as a "0x" prefixed hex string by default, the [decimal]
+//! module can be used with `#[serde(with = "...")]` for decimal strings instead.
 use super::StarkHash;
 use serde::{de::Visitor, Deserialize, Serialize};
 
@@ -23,7 +25,7 @@ impl<'de> Deserialize<'de> for StarkHash {
         impl<'de> Visitor<'de> for StarkHashVisitor {
             type Value = StarkHash;
 
-            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
                 formatter.write_str("a hex string of up to 64 digits with an optional '0x' prefix")
             }
 
@@ -39,6 +41,43 @@ impl<'de> Deserialize<'de> for StarkHash {
     }
 }
 
+/// Serializes a [StarkHash] as a decimal string, for use with `#[serde(with = "...")]`.
+pub mod decimal {
+    use crate::StarkHash;
+    use serde::de::Visitor;
+
+    pub fn serialize<S>(value: &StarkHash, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&value.to_dec_str())
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<StarkHash, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct DecimalVisitor;
+
+        impl<'de> Visitor<'de> for DecimalVisitor {
+            type Value = StarkHash;
+
+            fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+                formatter.write_str("a decimal string less than the field modulus")
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                StarkHash::from_dec_str(v).map_err(serde::de::Error::custom)
+            }
+        }
+
+        deserializer.deserialize_str(DecimalVisitor)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -99,4 +138,33 @@ mod tests {
             original
         );
     }
+
+    mod decimal {
+        use crate::serde::decimal;
+        use crate::StarkHash;
+        use pretty_assertions::assert_eq;
+
+        fn to_json(value: &StarkHash) -> String {
+            let mut buf = Vec::new();
+            decimal::serialize(value, &mut serde_json::Serializer::new(&mut buf)).unwrap();
+            String::from_utf8(buf).unwrap()
+        }
+
+        fn from_json(json: &str) -> Result<StarkHash, serde_json::Error> {
+            decimal::deserialize(&mut serde_json::Deserializer::from_str(json))
+        }
+
+        #[test]
+        fn round_trip() {
+            let original = StarkHash::from(1234567890u64);
+            let json = to_json(&original);
+            assert_eq!(json, r#""1234567890""#);
+            assert_eq!(from_json(&json).unwrap(), original);
+        }
+
+        #[test]
+        fn hex_is_rejected() {
+            from_json(r#""0x1""#).unwrap_err();
+        }
+    }
 }
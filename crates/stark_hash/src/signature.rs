@@ -0,0 +1,59 @@
+use crate::StarkHash;
+use stark_curve::FieldElement;
+
+pub use stark_curve::VerifyError;
+
+/// Verifies the ECDSA signature `(r, s)` of `msg_hash` by the stark key `public_key`, as done by
+/// cairo-lang's `starkware.crypto.signature.verify`.
+///
+/// Returns `Ok(false)` for signatures which do not match, including a `public_key` which is not
+/// on the curve, and an error for values outside of the ranges cairo-lang allows.
+pub fn verify_signature(
+    public_key: StarkHash,
+    msg_hash: StarkHash,
+    r: StarkHash,
+    s: StarkHash,
+) -> Result<bool, VerifyError> {
+    stark_curve::verify(
+        &FieldElement::from(public_key),
+        &FieldElement::from(msg_hash),
+        &FieldElement::from(r),
+        &FieldElement::from(s),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn verify() {
+        // signature of message 2 with private key 1, the public key being the curve generator
+        let public_key = StarkHash::from_hex_str(
+            "0x1ef15c18599971b7beced415a40f0c7deacfd9b0d1819e03d723d8bc943cfca",
+        )
+        .unwrap();
+        let r = StarkHash::from_hex_str(
+            "0x411494b501a98abd8262b0da1351e17899a0c4ef23dd2f96fec5ba847310b20",
+        )
+        .unwrap();
+        let s = StarkHash::from_hex_str(
+            "0x405c3191ab3883ef2b763af35bc5f5d15b3b4e99461d70e84c654a351a7c81b",
+        )
+        .unwrap();
+
+        assert_eq!(
+            verify_signature(public_key, StarkHash::from(2u64), r, s),
+            Ok(true)
+        );
+        assert_eq!(
+            verify_signature(public_key, StarkHash::from(3u64), r, s),
+            Ok(false)
+        );
+        assert_eq!(
+            verify_signature(public_key, StarkHash::from(2u64), StarkHash::ZERO, s),
+            Err(VerifyError::InvalidR)
+        );
+    }
+}
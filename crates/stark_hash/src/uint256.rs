@@ -0,0 +1,173 @@
+use crate::{OutOfRangeError, OverflowError, StarkHash};
+
+/// Cairo's `Uint256`, an unsigned 256-bit integer stored as two felts of 128 bits each.
+///
+/// Used by contracts for values which might not fit into a [StarkHash], such as token balances.
+/// The ordering is that of the integer values.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Uint256 {
+    /// The 128 least significant bits.
+    pub low: u128,
+    /// The 128 most significant bits.
+    pub high: u128,
+}
+
+impl Uint256 {
+    pub const ZERO: Uint256 = Uint256 { low: 0, high: 0 };
+    pub const MAX: Uint256 = Uint256 {
+        low: u128::MAX,
+        high: u128::MAX,
+    };
+
+    /// Joins the `low` and `high` felts of a Cairo `Uint256`.
+    ///
+    /// Returns [OutOfRangeError] if either of them has more than 128 bits.
+    pub fn from_felts(low: StarkHash, high: StarkHash) -> Result<Self, OutOfRangeError> {
+        Ok(Self {
+            low: low.try_into()?,
+            high: high.try_into()?,
+        })
+    }
+
+    /// Splits into the `low` and `high` felts of a Cairo `Uint256`.
+    pub fn to_felts(self) -> (StarkHash, StarkHash) {
+        (StarkHash::from(self.low), StarkHash::from(self.high))
+    }
+
+    /// Creates a [Uint256] from big-endian bytes.
+    pub fn from_be_bytes(bytes: [u8; 32]) -> Self {
+        let mut high = [0u8; 16];
+        let mut low = [0u8; 16];
+        high.copy_from_slice(&bytes[..16]);
+        low.copy_from_slice(&bytes[16..]);
+
+        Self {
+            low: u128::from_be_bytes(low),
+            high: u128::from_be_bytes(high),
+        }
+    }
+
+    /// Returns the big-endian representation of this [Uint256].
+    pub fn to_be_bytes(self) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        bytes[..16].copy_from_slice(&self.high.to_be_bytes());
+        bytes[16..].copy_from_slice(&self.low.to_be_bytes());
+        bytes
+    }
+}
+
+impl PartialOrd for Uint256 {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Uint256 {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        (self.high, self.low).cmp(&(other.high, other.low))
+    }
+}
+
+impl From<u128> for Uint256 {
+    fn from(value: u128) -> Self {
+        Self {
+            low: value,
+            high: 0,
+        }
+    }
+}
+
+impl From<StarkHash> for Uint256 {
+    fn from(value: StarkHash) -> Self {
+        Self::from_be_bytes(value.to_be_bytes())
+    }
+}
+
+impl TryFrom<Uint256> for StarkHash {
+    type Error = OverflowError;
+
+    /// Fails if the value is not less than the field modulus.
+    fn try_from(value: Uint256) -> Result<Self, Self::Error> {
+        StarkHash::from_be_bytes(value.to_be_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn felts_round_trip() {
+        let value = Uint256 {
+            low: 1,
+            high: u128::MAX,
+        };
+        let (low, high) = value.to_felts();
+
+        assert_eq!(low, StarkHash::ONE);
+        assert_eq!(high, StarkHash::from(u128::MAX));
+        assert_eq!(Uint256::from_felts(low, high).unwrap(), value);
+    }
+
+    #[test]
+    fn felts_out_of_range() {
+        let too_big = StarkHash::from(u128::MAX) + StarkHash::ONE;
+
+        assert_eq!(
+            Uint256::from_felts(too_big, StarkHash::ZERO),
+            Err(OutOfRangeError)
+        );
+        assert_eq!(
+            Uint256::from_felts(StarkHash::ZERO, too_big),
+            Err(OutOfRangeError)
+        );
+    }
+
+    #[test]
+    fn stark_hash_round_trip() {
+        let max = -StarkHash::ONE;
+        let value = Uint256::from(max);
+
+        assert_eq!(value.high, 0x0800000000000011 << 64);
+        assert_eq!(value.low, 0);
+        assert_eq!(StarkHash::try_from(value), Ok(max));
+    }
+
+    #[test]
+    fn stark_hash_overflow() {
+        // The field modulus
+        let modulus = Uint256 {
+            low: 1,
+            high: 0x0800000000000011 << 64,
+        };
+
+        assert_eq!(StarkHash::try_from(modulus), Err(OverflowError));
+        assert_eq!(StarkHash::try_from(Uint256::MAX), Err(OverflowError));
+    }
+
+    #[test]
+    fn be_bytes_round_trip() {
+        let mut bytes = [0u8; 32];
+        bytes[0] = 0xab;
+        bytes[31] = 0xcd;
+        let value = Uint256::from_be_bytes(bytes);
+
+        assert_eq!(value.high, 0xab << 120);
+        assert_eq!(value.low, 0xcd);
+        assert_eq!(value.to_be_bytes(), bytes);
+    }
+
+    #[test]
+    fn ordering() {
+        let high = Uint256 { low: 0, high: 1 };
+        let low = Uint256 {
+            low: u128::MAX,
+            high: 0,
+        };
+
+        assert!(low < high);
+        assert!(Uint256::ZERO < low);
+        assert!(high < Uint256::MAX);
+    }
+}
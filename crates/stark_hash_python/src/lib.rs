@@ -0,0 +1,62 @@
+//! Python bindings for [stark_hash], used by `py/src/call.py` in place of cairo-lang's pure python
+//! Pedersen hash when installed.
+//!
+//! The functions mirror the cairo-lang ones they replace, with the values being python `int`s less
+//! than the field modulus. Build and install it into the virtual environment of `py/` with
+//! `maturin develop --release`.
+//!
+//! This crate is not a member of the workspace, as it can only be linked as a python extension.
+use num_bigint::BigUint;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use stark_hash::{stark_hash, HashChain, StarkHash};
+
+/// Computes the Pedersen hash of `a` and `b`, like cairo-lang's `pedersen_hash`.
+#[pyfunction]
+fn pedersen_hash(a: BigUint, b: BigUint) -> PyResult<BigUint> {
+    let hash = stark_hash(to_stark_hash(&a)?, to_stark_hash(&b)?);
+    Ok(to_int(hash))
+}
+
+/// Variant of [pedersen_hash] for 32 big-endian bytes, like cairo-lang's `pedersen_hash_func`.
+#[pyfunction]
+fn pedersen_hash_func<'py>(py: Python<'py>, a: &[u8], b: &[u8]) -> PyResult<&'py PyBytes> {
+    if a.len() != 32 || b.len() != 32 {
+        return Err(PyValueError::new_err("Unexpected element length."));
+    }
+
+    let a = StarkHash::from_be_slice(a).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    let b = StarkHash::from_be_slice(b).map_err(|e| PyValueError::new_err(e.to_string()))?;
+
+    Ok(PyBytes::new(py, stark_hash(a, b).as_be_bytes()))
+}
+
+/// Hash chains all of the `data` together using [HashChain], like cairo-lang's
+/// `compute_hash_on_elements` with the default hash function.
+#[pyfunction]
+fn compute_hash_on_elements(data: Vec<BigUint>) -> PyResult<BigUint> {
+    let data = data
+        .iter()
+        .map(to_stark_hash)
+        .collect::<PyResult<Vec<_>>>()?;
+
+    Ok(to_int(HashChain::compute(data)))
+}
+
+fn to_stark_hash(value: &BigUint) -> PyResult<StarkHash> {
+    StarkHash::from_be_slice(&value.to_bytes_be())
+        .map_err(|_| PyValueError::new_err(format!("{} is not less than the field modulus", value)))
+}
+
+fn to_int(hash: StarkHash) -> BigUint {
+    BigUint::from_bytes_be(hash.as_be_bytes())
+}
+
+#[pymodule]
+fn stark_hash_rust(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(pedersen_hash, m)?)?;
+    m.add_function(wrap_pyfunction!(pedersen_hash_func, m)?)?;
+    m.add_function(wrap_pyfunction!(compute_hash_on_elements, m)?)?;
+    Ok(())
+}
@@ -1,20 +1,28 @@
 import sys
 import json
 import time
+import struct
 import sqlite3
 import asyncio
 from starkware.starkware_utils.error_handling import WebFriendlyException
 from starkware.storage.storage import Storage
 
 # used from tests, and the query which asserts that the schema is of expected version.
-EXPECTED_SCHEMA_REVISION = 12
+EXPECTED_SCHEMA_REVISION = 15
 EXPECTED_CAIRO_VERSION = "0.9.0"
 
+# version of the protocol spoken with pathfinder, announced in the handshake.
+PROTOCOL_VERSION = 1
+# framings of the messages after the handshake, in the order of preference.
+SUPPORTED_FRAMINGS = ["length-prefixed", "json-lines"]
+# the only invoke transaction version cairo-lang 0.9.0 executes.
+SUPPORTED_TRANSACTION_VERSION = 0
+
 
 def main():
     """
-    Loops on stdin, reads json commands from lines, outputs single json as a response.
-    Starts by outputting "ready"
+    Loops on stdin, reads json commands from frames, outputs single json as a response.
+    Starts with the handshake, see `handshake`.
     """
     if len(sys.argv) != 2:
         print("usage: call.py [sqlite.db]")
@@ -45,8 +53,17 @@ def main():
         # whenever communicating with the other process, it's important to flush manually
         # even though "the general wisdom" is to flush on '\n', python seems to only do it
         # if it didn't add the newline to the written out string.
-        print("ready", flush=True)
-        do_loop(connection, sys.stdin, sys.stdout)
+        pedersen = use_fast_pedersen()
+        framing = handshake(sys.stdin.buffer, sys.stdout.buffer, pedersen)
+        if framing == "length-prefixed":
+            do_loop(
+                connection,
+                read_frames(sys.stdin.buffer),
+                sys.stdout.buffer,
+                write_frame,
+            )
+        else:
+            do_loop(connection, sys.stdin, sys.stdout)
 
 
 def check_cairolang_version():
@@ -56,18 +73,115 @@ def check_cairolang_version():
     return version == EXPECTED_CAIRO_VERSION
 
 
-def do_loop(connection, input_gen, output_file):
+def use_fast_pedersen():
+    """
+    Replaces cairo-lang's pure python Pedersen hash with the one from the `stark_hash_rust`
+    extension module, if it has been installed, see `crates/stark_hash_python`. Has to be called
+    before the rest of cairo-lang is imported, as many modules import the functions by name.
+    Returns the name of the implementation in use.
+    """
+    try:
+        import stark_hash_rust
+    except ImportError:
+        return "cairo-lang"
+
+    import starkware.crypto.signature.fast_pedersen_hash as fast_pedersen_hash
+
+    fast_pedersen_hash.pedersen_hash = stark_hash_rust.pedersen_hash
+    fast_pedersen_hash.pedersen_hash_func = stark_hash_rust.pedersen_hash_func
+
+    # these pick up the replaced functions when imported, but are patched just in case they had
+    # already been imported
+    import starkware.cairo.lang.vm.crypto as crypto
+    import starkware.cairo.common.hash_state as hash_state
+
+    crypto.pedersen_hash = stark_hash_rust.pedersen_hash
+    crypto.pedersen_hash_func = stark_hash_rust.pedersen_hash_func
+
+    compute_hash_on_elements = hash_state.compute_hash_on_elements
+
+    def fast_compute_hash_on_elements(data, hash_func=stark_hash_rust.pedersen_hash):
+        if hash_func is stark_hash_rust.pedersen_hash:
+            return stark_hash_rust.compute_hash_on_elements(data)
+        return compute_hash_on_elements(data, hash_func)
+
+    hash_state.compute_hash_on_elements = fast_compute_hash_on_elements
+
+    return "stark_hash"
+
+
+def handshake(input_file, output_file, pedersen="cairo-lang"):
+    """
+    Announces the protocol version, the supported framings and the Pedersen hash implementation
+    in use with "pathfinder-ext-py <version> <framing>,<framing> pedersen=<implementation>",
+    reads pathfinder's choice as "framing <framing>" and acknowledges it with "ok <framing>".
+    All lines are utf-8 and newline terminated. Returns the chosen framing.
+    """
+    framings = ",".join(SUPPORTED_FRAMINGS)
+    hello = f"pathfinder-ext-py {PROTOCOL_VERSION} {framings} pedersen={pedersen}\n"
+    output_file.write(hello.encode())
+    output_file.flush()
+
+    line = input_file.readline().decode("utf-8").strip()
+    verb, _, framing = line.partition(" ")
+    if verb != "framing" or framing not in SUPPORTED_FRAMINGS:
+        output_file.write(f"unsupported framing request: {line!r}\n".encode())
+        output_file.flush()
+        sys.exit(1)
+
+    output_file.write(f"ok {framing}\n".encode())
+    output_file.flush()
+    return framing
+
+
+def read_frames(input_file):
+    """
+    Yields the utf-8 payloads of frames made of a big-endian u32 length and the payload,
+    until the input is closed.
+    """
+    while True:
+        header = input_file.read(4)
+        if len(header) < 4:
+            return
+        (length,) = struct.unpack(">I", header)
+        payload = input_file.read(length)
+        if len(payload) < length:
+            return
+        yield payload.decode("utf-8")
+
+
+def write_frame(message, output_file):
+    payload = message.encode("utf-8")
+    output_file.write(struct.pack(">I", len(payload)))
+    output_file.write(payload)
+    output_file.flush()
+
+
+def write_line(message, output_file):
+    print(message, file=output_file, flush=True)
+
+
+def do_loop(connection, input_gen, output_file, write_output=write_line):
 
     required = {
         # FIXME: this should be hash_or_latest
         "at_block": int_hash_or_latest,
+    }
+
+    optional = {
+        "command": verb,
+        # required for all but multicall, see check_command
         "contract_address": hash_or_int,
         "entry_point_selector": string_or_int,
         "calldata": list_of_hash_or_int,
+        "calls": list_of_calls,
+        "caller_address": hash_or_int,
+        "signature": list_of_hash_or_int,
+        "max_fee": hash_or_int,
+        "version": hash_or_int,
+        "overrides": list_of_overrides,
     }
 
-    optional = {"caller_address": hash_or_int, "signature": hash_or_int}
-
     for line in input_gen:
         if line == "" or line.startswith("#"):
             continue
@@ -79,6 +193,7 @@ def do_loop(connection, input_gen, output_file):
 
         try:
             command = parse_command(json.loads(line), required, optional)
+            check_command(command)
 
             parsed_at = time.time()
 
@@ -86,26 +201,17 @@ def do_loop(connection, input_gen, output_file):
 
             output = loop_inner(connection, command)
 
-            # we need to render the retdata as hex strings, so we can just deserialize it easily
-            out["output"] = list(
-                map(lambda x: "0x" + x.to_bytes(32, "big").hex(), output)
-            )
-        except NoSuchBlock:
-            out = {"status": "error", "kind": "NO_SUCH_BLOCK"}
-        except NoSuchContract:
-            out = {"status": "error", "kind": "NO_SUCH_CONTRACT"}
-        except UnexpectedSchemaVersion:
-            out = {"status": "error", "kind": "INVALID_SCHEMA_VERSION"}
-        except InvalidInput:
-            out = {"status": "error", "kind": "INVALID_INPUT"}
-        except WebFriendlyException as e:
-            # this is hopefully something we can give to the user
-            out = {"status": "failed", "exception": str(e.code)}
+            verb = command.get("command", "call")
+            if verb == "estimate_fee":
+                # the values can be larger than fit into json numbers on the rust side
+                out["output"] = {k: hex(v) for (k, v) in output.items()}
+            elif verb in ["simulate", "multicall"]:
+                # these are already rendered with hex strings
+                out["output"] = output
+            else:
+                out["output"] = render_retdata(output)
         except Exception as e:
-            stringified = str(e)
-            if len(stringified) > 200:
-                stringified = stringified[:197] + "..."
-            out = {"status": "failed", "exception": stringified}
+            out = error_response(e)
         finally:
             connection.rollback()
 
@@ -120,7 +226,34 @@ def do_loop(connection, input_gen, output_file):
 
             out["timings"] = timings
 
-            print(json.dumps(out), file=output_file, flush=True)
+            write_output(json.dumps(out), output_file)
+
+
+def render_retdata(retdata):
+    # we need to render the retdata as hex strings, so we can just deserialize it easily
+    return list(map(lambda x: "0x" + x.to_bytes(32, "big").hex(), retdata))
+
+
+def error_response(e):
+    """
+    Renders the exception as the response to a command, or to a single call of a multicall.
+    """
+    if isinstance(e, NoSuchBlock):
+        return {"status": "error", "kind": "NO_SUCH_BLOCK"}
+    if isinstance(e, NoSuchContract):
+        return {"status": "error", "kind": "NO_SUCH_CONTRACT"}
+    if isinstance(e, UnexpectedSchemaVersion):
+        return {"status": "error", "kind": "INVALID_SCHEMA_VERSION"}
+    if isinstance(e, InvalidInput):
+        return {"status": "error", "kind": "INVALID_INPUT"}
+    if isinstance(e, WebFriendlyException):
+        # this is hopefully something we can give to the user
+        return {"status": "failed", "exception": str(e.code)}
+
+    stringified = str(e)
+    if len(stringified) > 200:
+        stringified = stringified[:197] + "..."
+    return {"status": "failed", "exception": stringified}
 
 
 def loop_inner(connection, command):
@@ -129,6 +262,35 @@ def loop_inner(connection, command):
 
     (block_info, global_root) = resolve_block(connection, command["at_block"])
 
+    verb = command.get("command", "call")
+
+    if verb == "multicall":
+        return asyncio.run(
+            do_multicall(
+                SqliteAdapter(connection), global_root, command["calls"], block_info
+            )
+        )
+
+    if verb in ["estimate_fee", "simulate"]:
+        version = command.get("version", SUPPORTED_TRANSACTION_VERSION)
+        if version != SUPPORTED_TRANSACTION_VERSION:
+            raise UnsupportedTransactionVersion(version)
+
+        do_invoke_verb = do_estimate_fee if verb == "estimate_fee" else do_simulate
+        return asyncio.run(
+            do_invoke_verb(
+                SqliteAdapter(connection),
+                global_root,
+                command["contract_address"],
+                command["entry_point_selector"],
+                command["calldata"],
+                command.get("caller_address", 0),
+                command.get("signature", None),
+                command.get("max_fee", 0),
+                block_info,
+            )
+        )
+
     return asyncio.run(
         do_call(
             SqliteAdapter(connection),
@@ -139,6 +301,7 @@ def loop_inner(connection, command):
             command.get("caller_address", 0),
             command.get("signature", None),
             block_info,
+            command.get("overrides", []),
         )
     )
 
@@ -171,6 +334,32 @@ def parse_command(command, required, optional):
     return converted
 
 
+def check_command(command):
+    if command.get("command", "call") == "multicall":
+        assert "calls" in command, "missing keys from command: {'calls'}"
+        return
+
+    missing = {"contract_address", "entry_point_selector", "calldata"} - command.keys()
+    assert len(missing) == 0, f"missing keys from command: {missing}"
+
+
+def verb(s):
+    assert s in ["call", "estimate_fee", "simulate", "multicall"], f"unknown command: {s}"
+    return s
+
+
+def list_of_calls(s):
+    assert type(s) == list, f"Expected list, got {type(s)}"
+
+    required = {
+        "contract_address": hash_or_int,
+        "entry_point_selector": string_or_int,
+        "calldata": list_of_hash_or_int,
+    }
+
+    return list(map(lambda call: parse_command(call, required, {}), s))
+
+
 def int_hash_or_latest(s):
     if type(s) == int:
         return s
@@ -225,6 +414,31 @@ def list_of_hash_or_int(s):
     return list(map(hash_or_int, s))
 
 
+def list_of_overrides(s):
+    assert type(s) == list, f"Expected list, got {type(s)}"
+    return list(map(state_override, s))
+
+
+def state_override(s):
+    assert type(s) == dict, f"Expected dict, got {type(s)}"
+
+    extra = s.keys() - {"contract_address", "storage", "class_hash"}
+    assert len(extra) == 0, f"extra keys in override: {extra}"
+
+    class_hash = s.get("class_hash", None)
+    if class_hash is not None:
+        # class hashes are bytes over at cairo-lang
+        class_hash = hash_or_int(class_hash).to_bytes(32, "big")
+
+    return {
+        "contract_address": hash_or_int(s["contract_address"]),
+        "storage": {
+            hash_or_int(k): hash_or_int(v) for (k, v) in s.get("storage", {}).items()
+        },
+        "class_hash": class_hash,
+    }
+
+
 def check_schema(connection):
     global first
     assert connection.in_transaction
@@ -295,6 +509,11 @@ class InvalidInput(Exception):
         super().__init__(f"Invalid input for key: {key}")
 
 
+class UnsupportedTransactionVersion(Exception):
+    def __init__(self, version):
+        super().__init__(f"Unsupported transaction version: {hex(version)}")
+
+
 class SqliteAdapter(Storage):
     """
     Reads from pathfinders' database to give cairo-lang call implementation the nodes as needed
@@ -421,6 +640,7 @@ async def do_call(
     caller_address,
     signature,
     block_info,
+    overrides,
 ):
     """
     Loads all of the cairo-lang parts needed for the call. Dirties the internal
@@ -429,6 +649,226 @@ async def do_call(
 
     Returns the retdata from the call, which is the only property needed by the RPC api.
     """
+    (_, output) = await do_invoke(
+        adapter,
+        root,
+        contract_address,
+        selector,
+        calldata,
+        caller_address,
+        signature,
+        0,
+        block_info,
+        overrides,
+    )
+
+    # this is everything we need, at least so far for the "call".
+    return output.call_info.retdata
+
+
+async def do_multicall(adapter, root, calls, block_info):
+    """
+    Executes the calls one after another on the same root, and within the same
+    database transaction.
+
+    Returns the rendered response of each call; a failing call does not stop
+    the others from being executed.
+    """
+    responses = []
+
+    for call in calls:
+        try:
+            output = await do_call(
+                adapter,
+                root,
+                call["contract_address"],
+                call["entry_point_selector"],
+                call["calldata"],
+                0,
+                None,
+                block_info,
+                [],
+            )
+            responses.append({"status": "ok", "output": render_retdata(output)})
+        except Exception as e:
+            responses.append(error_response(e))
+
+    return responses
+
+
+async def do_estimate_fee(
+    adapter,
+    root,
+    contract_address,
+    selector,
+    calldata,
+    caller_address,
+    signature,
+    max_fee,
+    block_info,
+):
+    """
+    Runs the call like an invoke transaction on top of the given block, and
+    calculates the fee it would had cost with the gas price of that block.
+
+    Returns a dict with the fee estimate, with integer values.
+    """
+    import dataclasses
+    from starkware.starknet.business_logic.transaction_fee import calculate_tx_fee
+
+    (state, output) = await do_invoke(
+        adapter,
+        root,
+        contract_address,
+        selector,
+        calldata,
+        caller_address,
+        signature,
+        max_fee,
+        block_info,
+        [],
+    )
+
+    overall_fee = calculate_tx_fee(
+        state=state.state,
+        call_info=output.call_info,
+        general_config=state.general_config,
+    )
+
+    # the gas usage does not depend on the gas price, so it is the fee at a gas
+    # price of one. it cannot be derived from the overall fee, as older blocks
+    # have zero gas price and the fee is zero as well.
+    state.state.block_info = dataclasses.replace(block_info, gas_price=1)
+    gas_consumed = calculate_tx_fee(
+        state=state.state,
+        call_info=output.call_info,
+        general_config=state.general_config,
+    )
+
+    gas_price = block_info.gas_price
+
+    return {
+        "gas_consumed": gas_consumed,
+        "gas_price": gas_price,
+        "overall_fee": overall_fee,
+    }
+
+
+async def do_simulate(
+    adapter,
+    root,
+    contract_address,
+    selector,
+    calldata,
+    caller_address,
+    signature,
+    max_fee,
+    block_info,
+):
+    """
+    Runs the call like an invoke transaction on top of the given block.
+
+    Returns the execution trace with all of the internal calls and the storage
+    writes, rendered with hex strings.
+    """
+    (state, output) = await do_invoke(
+        adapter,
+        root,
+        contract_address,
+        selector,
+        calldata,
+        caller_address,
+        signature,
+        max_fee,
+        block_info,
+        [],
+    )
+
+    return {
+        "function_invocation": render_call_info(output.call_info),
+        "storage_writes": render_storage_writes(state.state),
+    }
+
+
+def render_call_info(call_info):
+    """
+    Renders the cairo-lang CallInfo and the internal calls it made.
+    """
+
+    def felt(x):
+        if type(x) == bytes:
+            x = int.from_bytes(x, "big")
+        return hex(x)
+
+    def felts(xs):
+        return list(map(felt, xs))
+
+    def maybe(f, x):
+        return None if x is None else f(x)
+
+    resources = call_info.execution_resources
+
+    return {
+        "caller_address": felt(call_info.caller_address),
+        "contract_address": felt(call_info.contract_address),
+        "class_hash": maybe(felt, call_info.class_hash),
+        "call_type": maybe(lambda x: x.name.upper(), call_info.call_type),
+        "entry_point_type": maybe(lambda x: x.name, call_info.entry_point_type),
+        "entry_point_selector": maybe(felt, call_info.entry_point_selector),
+        "calldata": felts(call_info.calldata),
+        "result": felts(call_info.retdata),
+        "execution_resources": {
+            "n_steps": resources.n_steps,
+            "n_memory_holes": resources.n_memory_holes,
+            "builtin_instance_counter": dict(resources.builtin_instance_counter),
+        },
+        "events": [
+            {"order": e.order, "keys": felts(e.keys), "data": felts(e.data)}
+            for e in call_info.events
+        ],
+        "messages": [
+            {
+                "order": m.order,
+                "to_address": "0x" + m.to_address.to_bytes(20, "big").hex(),
+                "payload": felts(m.payload),
+            }
+            for m in call_info.l2_to_l1_messages
+        ],
+        "storage_read_values": felts(call_info.storage_read_values),
+        "accessed_storage_keys": felts(sorted(call_info.accessed_storage_keys)),
+        "internal_calls": list(map(render_call_info, call_info.internal_calls)),
+    }
+
+
+def render_storage_writes(carried_state):
+    """
+    Renders the storage updates of all the contracts touched by the invoke.
+    """
+    return [
+        {"contract_address": hex(address), "key": hex(key), "value": hex(leaf.value)}
+        for (address, contract_state) in carried_state.contract_states.items()
+        for (key, leaf) in contract_state.storage_updates.items()
+    ]
+
+
+async def do_invoke(
+    adapter,
+    root,
+    contract_address,
+    selector,
+    calldata,
+    caller_address,
+    signature,
+    max_fee,
+    block_info,
+    overrides,
+):
+    """
+    Builds the StarknetState out of pathfinder's database at the given root,
+    applies the overrides over it, and runs the invoke on it.
+
+    Returns the dirtied state and the execution info of the invoke.
+    """
     from starkware.starknet.business_logic.state.state import (
         SharedState,
         StateSelector,
@@ -449,21 +889,53 @@ async def do_call(
     # the root tree has to always be height=251
     shared_state = SharedState(PatriciaTree(root=root, height=251), block_info)
     state_selector = StateSelector(
-        contract_addresses={contract_address}, class_hashes=set()
+        contract_addresses={contract_address}
+        | set(o["contract_address"] for o in overrides),
+        class_hashes=set(
+            o["class_hash"] for o in overrides if o["class_hash"] is not None
+        ),
     )
     carried_state = await shared_state.get_filled_carried_state(
         ffc, state_selector=state_selector
     )
 
+    apply_overrides(carried_state, overrides)
+
     state = StarknetState(state=carried_state, general_config=general_config)
-    max_fee = 0
 
     output = await state.invoke_raw(
         contract_address, selector, calldata, caller_address, max_fee, signature
     )
 
-    # this is everything we need, at least so far for the "call".
-    return output.call_info.retdata
+    return (state, output)
+
+
+def apply_overrides(carried_state, overrides):
+    """
+    Layers the overrides over the contract states read through the SqliteAdapter.
+
+    The overridden storage values are seen as pending updates by cairo-lang, so
+    they shadow the values in the storage tree. None of them are written
+    anywhere, as the state will be thrown out after the call.
+    """
+    import dataclasses
+    from starkware.starknet.storage.starknet_storage import StorageLeaf
+
+    for override in overrides:
+        address = override["contract_address"]
+        contract = carried_state.contract_states[address]
+
+        state = contract.state
+        if override["class_hash"] is not None:
+            state = dataclasses.replace(state, contract_hash=override["class_hash"])
+
+        storage_updates = dict(contract.storage_updates)
+        for (key, value) in override["storage"].items():
+            storage_updates[key] = StorageLeaf(value=value)
+
+        carried_state.contract_states[address] = dataclasses.replace(
+            contract, state=state, storage_updates=storage_updates
+        )
 
 
 if __name__ == "__main__":
@@ -1,4 +1,14 @@
-from call import do_loop, loop_inner, EXPECTED_SCHEMA_REVISION, check_cairolang_version
+from call import (
+    do_loop,
+    loop_inner,
+    EXPECTED_SCHEMA_REVISION,
+    check_cairolang_version,
+    handshake,
+    read_frames,
+    use_fast_pedersen,
+    write_frame,
+)
+import pytest
 import sqlite3
 import io
 import json
@@ -367,7 +377,284 @@ def test_no_such_block():
     assert latest == expected
 
 
+def test_storage_override():
+    con = inmemory_with_tables()
+    contract_address = populate_test_contract_with_132_on_3(con)
+
+    output = default_132_on_3_scenario(
+        con,
+        [
+            f'{{ "at_block": 1, "contract_address": {contract_address}, "entry_point_selector": "get_value", "calldata": [132], "overrides": [{{ "contract_address": "{hex(contract_address)}", "storage": {{ "0x84": "0x5" }} }}] }}',
+            # nothing is persisted
+            f'{{ "at_block": 1, "contract_address": {contract_address}, "entry_point_selector": "get_value", "calldata": [132] }}',
+        ],
+    )
+
+    [overridden, original] = output
+
+    assert overridden == {
+        "status": "ok",
+        "output": ["0x" + (5).to_bytes(32, "big").hex()],
+    }
+    assert original == {
+        "status": "ok",
+        "output": ["0x" + (3).to_bytes(32, "big").hex()],
+    }
+
+
+def test_multicall():
+    con = inmemory_with_tables()
+    contract_address = populate_test_contract_with_132_on_3(con)
+
+    output = default_132_on_3_scenario(
+        con,
+        [
+            f'{{ "command": "multicall", "at_block": 1, "calls": [{{ "contract_address": {contract_address}, "entry_point_selector": "get_value", "calldata": [132] }}, {{ "contract_address": {contract_address + 1}, "entry_point_selector": "get_value", "calldata": [132] }}, {{ "contract_address": {contract_address}, "entry_point_selector": "get_value", "calldata": [133] }}] }}',
+        ],
+    )
+
+    assert output == {
+        "status": "ok",
+        "output": [
+            {"status": "ok", "output": ["0x" + (3).to_bytes(32, "big").hex()]},
+            {"status": "error", "kind": "NO_SUCH_CONTRACT"},
+            {"status": "ok", "output": ["0x" + (0).to_bytes(32, "big").hex()]},
+        ],
+    }
+
+
+def test_multicall_no_such_block():
+    con = inmemory_with_tables()
+    contract_address = populate_test_contract_with_132_on_3(con)
+
+    output = default_132_on_3_scenario(
+        con,
+        [
+            f'{{ "command": "multicall", "at_block": 99999999999, "calls": [{{ "contract_address": {contract_address}, "entry_point_selector": "get_value", "calldata": [132] }}] }}',
+        ],
+    )
+
+    assert output == {"status": "error", "kind": "NO_SUCH_BLOCK"}
+
+
+def test_estimate_fee():
+    con = inmemory_with_tables()
+    contract_address = populate_test_contract_with_132_on_3(con)
+
+    output = default_132_on_3_scenario(
+        con,
+        [
+            f'{{ "command": "estimate_fee", "at_block": 1, "contract_address": {contract_address}, "entry_point_selector": "increase_value", "calldata": [132, 4], "signature": [], "max_fee": "0x0", "version": "0x0" }}',
+        ],
+    )
+
+    assert output["status"] == "ok"
+
+    fee = output["output"]
+    gas_consumed = int(fee["gas_consumed"], 16)
+    gas_price = int(fee["gas_price"], 16)
+    overall_fee = int(fee["overall_fee"], 16)
+
+    # the block gas price in the test data is left padded b"0"
+    assert gas_price == int.from_bytes(b"0", "big")
+    assert gas_consumed > 0
+    # the fee is rounded up once, the gas usage before multiplying by the price
+    assert (gas_consumed - 1) * gas_price < overall_fee <= gas_consumed * gas_price
+
+
+def test_estimate_fee_with_zero_gas_price():
+    con = inmemory_with_tables()
+    contract_address = populate_test_contract_with_132_on_3(con)
+
+    con.execute("update starknet_blocks set gas_price = ?", [b"\x00" * 16])
+    con.commit()
+
+    output = default_132_on_3_scenario(
+        con,
+        [
+            f'{{ "command": "estimate_fee", "at_block": 1, "contract_address": {contract_address}, "entry_point_selector": "increase_value", "calldata": [132, 4], "signature": [], "max_fee": "0x0", "version": "0x0" }}',
+        ],
+    )
+
+    assert output["status"] == "ok"
+
+    fee = output["output"]
+
+    # the gas is consumed even if it is free
+    assert int(fee["gas_consumed"], 16) > 0
+    assert int(fee["gas_price"], 16) == 0
+    assert int(fee["overall_fee"], 16) == 0
+
+
+def test_unsupported_transaction_version():
+    con = inmemory_with_tables()
+    contract_address = populate_test_contract_with_132_on_3(con)
+
+    output = default_132_on_3_scenario(
+        con,
+        [
+            f'{{ "command": "estimate_fee", "at_block": 1, "contract_address": {contract_address}, "entry_point_selector": "increase_value", "calldata": [132, 4], "signature": [], "max_fee": "0x0", "version": "0x1" }}',
+            f'{{ "command": "simulate", "at_block": 1, "contract_address": {contract_address}, "entry_point_selector": "increase_value", "calldata": [132, 4], "signature": [], "max_fee": "0x0", "version": "0x1" }}',
+        ],
+    )
+
+    expected = {
+        "status": "failed",
+        "exception": "Unsupported transaction version: 0x1",
+    }
+    assert output == [expected, expected]
+
+
+def test_simulate():
+    con = inmemory_with_tables()
+    contract_address = populate_test_contract_with_132_on_3(con)
+
+    output = default_132_on_3_scenario(
+        con,
+        [
+            f'{{ "command": "simulate", "at_block": 1, "contract_address": {contract_address}, "entry_point_selector": "call_increase_value", "calldata": [{contract_address}, 132, 4], "signature": [], "max_fee": "0x0", "version": "0x0" }}',
+        ],
+    )
+
+    assert output["status"] == "ok"
+
+    trace = output["output"]
+    invocation = trace["function_invocation"]
+
+    assert invocation["contract_address"] == hex(contract_address)
+    assert invocation["result"] == []
+
+    [internal] = invocation["internal_calls"]
+    assert internal["contract_address"] == hex(contract_address)
+    assert internal["calldata"] == [hex(132), hex(4)]
+    assert internal["storage_read_values"] == [hex(3)]
+    assert internal["accessed_storage_keys"] == [hex(132)]
+
+    assert trace["storage_writes"] == [
+        {"contract_address": hex(contract_address), "key": hex(132), "value": hex(4)}
+    ]
+
+
 def test_check_cairolang_version():
     # run this here as well so that we get earlier than CI feedback
     # of another constant that needs to be upgraded
     assert check_cairolang_version()
+
+
+def test_handshake():
+    output = io.BytesIO()
+
+    framing = handshake(io.BytesIO(b"framing length-prefixed\n"), output)
+
+    assert framing == "length-prefixed"
+    assert output.getvalue().splitlines() == [
+        b"pathfinder-ext-py 1 length-prefixed,json-lines pedersen=cairo-lang",
+        b"ok length-prefixed",
+    ]
+
+
+def test_handshake_announces_pedersen():
+    output = io.BytesIO()
+
+    handshake(io.BytesIO(b"framing json-lines\n"), output, "stark_hash")
+
+    assert output.getvalue().splitlines()[0].endswith(b" pedersen=stark_hash")
+
+
+@pytest.fixture
+def original_pedersen():
+    """
+    Restores the functions replaced by `use_fast_pedersen` after the test, and gives the test the
+    original `pedersen_hash` and `compute_hash_on_elements` to compare against.
+    """
+    import starkware.crypto.signature.fast_pedersen_hash as fast_pedersen_hash
+    import starkware.cairo.lang.vm.crypto as crypto
+    import starkware.cairo.common.hash_state as hash_state
+
+    replaced = [
+        (fast_pedersen_hash, "pedersen_hash"),
+        (fast_pedersen_hash, "pedersen_hash_func"),
+        (crypto, "pedersen_hash"),
+        (crypto, "pedersen_hash_func"),
+        (hash_state, "compute_hash_on_elements"),
+    ]
+    originals = [getattr(module, name) for module, name in replaced]
+
+    try:
+        yield (fast_pedersen_hash.pedersen_hash, hash_state.compute_hash_on_elements)
+    finally:
+        for (module, name), original in zip(replaced, originals):
+            setattr(module, name, original)
+
+
+def test_use_fast_pedersen(original_pedersen):
+    pytest.importorskip("stark_hash_rust")
+
+    (original_pedersen_hash, original_compute_hash_on_elements) = original_pedersen
+
+    assert use_fast_pedersen() == "stark_hash"
+
+    from starkware.crypto.signature.fast_pedersen_hash import pedersen_hash
+    from starkware.cairo.common.hash_state import compute_hash_on_elements
+
+    assert pedersen_hash is not original_pedersen_hash
+
+    # the same values as in stark_hash's tests
+    a = 0x03D937C035C878245CAF64531A5756109C53068DA139362728FEB561405371CB
+    b = 0x0208A0A10250E382E1E4BBE2880906C2791BF6275695E02FBBC6AEFF9CD8B31A
+
+    expected = original_pedersen_hash(a, b)
+    assert (
+        expected == 0x030E480BED5FE53FA909CC0F8C4D99B8F9F2C016BE4C41E13A4848797979C662
+    )
+    assert pedersen_hash(a, b) == expected
+
+    for data in [[], [1, 2], [a, b, 3]]:
+        assert compute_hash_on_elements(data) == original_compute_hash_on_elements(data)
+
+
+def test_handshake_unsupported_framing():
+    output = io.BytesIO()
+
+    try:
+        handshake(io.BytesIO(b"framing carrier-pigeon\n"), output)
+        assert False, "should had exited"
+    except SystemExit:
+        pass
+
+    assert output.getvalue().splitlines()[1].startswith(b"unsupported framing")
+
+
+def test_frames_round_trip():
+    buffer = io.BytesIO()
+    write_frame('{"status": "ok"}', buffer)
+    write_frame("\u00e4", buffer)
+    # truncated frames are ignored, as the input was closed in the middle of one
+    buffer.write(b"\x00\x00\x00\x05{")
+    buffer.seek(0)
+
+    assert list(read_frames(buffer)) == ['{"status": "ok"}', "\u00e4"]
+
+
+def test_do_loop_length_prefixed():
+    con = inmemory_with_tables()
+    contract_address = populate_test_contract_with_132_on_3(con)
+
+    commands = io.BytesIO()
+    write_frame(
+        f'{{ "at_block": 1, "contract_address": {contract_address}, '
+        '"entry_point_selector": "get_value", "calldata": [132] }',
+        commands,
+    )
+    commands.seek(0)
+    output = io.BytesIO()
+
+    do_loop(con, read_frames(commands), output, write_frame)
+
+    output.seek(0)
+    [response] = [json.loads(frame) for frame in read_frames(output)]
+    del response["timings"]
+    assert response == {
+        "status": "ok",
+        "output": ["0x" + (3).to_bytes(32, "big").hex()],
+    }